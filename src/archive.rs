@@ -0,0 +1,313 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::decompress::Compression;
+use crate::i18n::Message;
+use crate::inflate::GzipDecoder;
+use crate::zip::Archive;
+use crate::{count_reader, try_count_slice, Counts, WcOptions, BUFFER_SIZE};
+
+/// The size of the headers and data blocks of a tar archive.
+const BLOCK_SIZE: usize = 512;
+
+/// The signature of the local file header a ZIP archive starts with, unless it is empty.
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+/// The signature of the end of central directory record an empty ZIP archive consists of.
+const EMPTY_ZIP_SIGNATURE: &[u8] = b"PK\x05\x06";
+
+/// The path of a member of an archive, and its counts or the error reading or counting it.
+pub type Member = (String, io::Result<Counts>);
+
+/// Returns an error describing a malformed archive.
+fn invalid(problem: Message) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, problem)
+}
+
+/// Returns an error for a tar archive which ends within an entry.
+fn truncated() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        Message::new("truncated-tar-archive"),
+    )
+}
+
+/// Returns the bytes of `field` before the first NUL, as text.
+fn text_field(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Parses the numeric header field `field`, in octal padded with spaces or NULs, or in the big-endian
+/// binary GNU tar uses for large values, flagged by the high bit of its first byte.
+fn numeric_field(field: &[u8]) -> Option<u64> {
+    if let Some((&first, rest)) = field.split_first() {
+        if first & 0x80 != 0 {
+            return rest
+                .iter()
+                .try_fold(u64::from(first & 0x7F), |value, &byte| {
+                    value.checked_mul(256).map(|value| value + u64::from(byte))
+                });
+        }
+    }
+    let digits = text_field(field);
+    let digits = digits.trim_matches(' ');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+/// Returns the value of the `path` record of the pax extended header `data`, if it has one.
+fn pax_path(data: &[u8]) -> Option<String> {
+    // Each record is `<length> <key>=<value>\n`, its length counting the whole record.
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&byte| byte == b' ')?;
+        let length: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record = rest.get(space + 1..length)?.strip_suffix(b"\n")?;
+        if let Some(value) = record.strip_prefix(b"path=") {
+            return Some(String::from_utf8_lossy(value).into_owned());
+        }
+        rest = &rest[length..];
+    }
+    None
+}
+
+/// Counts the regular files of the tar archive `input`, in order, as `options` direct, returning the path
+/// and counts of each, or the error counting it. GNU long names and pax paths are supported; other entries,
+/// such as directories and links, are skipped.
+fn count_tar<R: BufRead>(mut input: R, options: &WcOptions) -> io::Result<Vec<Member>> {
+    let mut members = Vec::new();
+    // The path given for the next entry by a GNU long name or pax extended header, if any.
+    let mut next_path = None;
+    let mut header = [0; BLOCK_SIZE];
+    loop {
+        // Archives are meant to end with two zero blocks, but some writers leave them out.
+        let length = io::copy(
+            &mut (&mut input).take(BLOCK_SIZE as u64),
+            &mut &mut header[..],
+        )?;
+        if length == 0 {
+            break;
+        }
+        if length < BLOCK_SIZE as u64 {
+            return Err(truncated());
+        }
+        if header.iter().all(|&byte| byte == 0) {
+            break;
+        }
+
+        // The checksum is the sum of the header's bytes, with those of the checksum itself as spaces.
+        let checksum: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(index, &byte)| match index {
+                148..=155 => u64::from(b' '),
+                _ => u64::from(byte),
+            })
+            .sum();
+        if numeric_field(&header[148..156]) != Some(checksum) {
+            return Err(invalid(Message::new("invalid-tar-checksum")));
+        }
+        let size = numeric_field(&header[124..136])
+            .ok_or_else(|| invalid(Message::new("invalid-tar-size")))?;
+        let padding = (BLOCK_SIZE as u64 - size % BLOCK_SIZE as u64) % BLOCK_SIZE as u64;
+
+        let mut path = text_field(&header[..100]);
+        if &header[257..262] == b"ustar" {
+            let prefix = text_field(&header[345..500]);
+            if !prefix.is_empty() {
+                path = format!("{}/{}", prefix, path);
+            }
+        }
+
+        let mut data = (&mut input).take(size);
+        match header[156] {
+            b'0' | 0 | b'7' => {
+                let path = next_path.take().unwrap_or(path);
+                let counts = count_reader(&mut data, options).map_err(io::Error::from);
+                members.push((path, counts));
+            }
+            b'L' | b'x' => {
+                let mut contents = Vec::new();
+                data.read_to_end(&mut contents)?;
+                next_path = match header[156] {
+                    b'L' => Some(text_field(&contents)),
+                    _ => pax_path(&contents).or(next_path),
+                };
+            }
+            _ => {}
+        }
+        // Whatever was not counted of the entry's data is skipped, along with the padding of its last block.
+        let remaining = data.limit() + padding;
+        if io::copy(&mut (&mut input).take(remaining), &mut io::sink())? < remaining {
+            return Err(truncated());
+        }
+    }
+    Ok(members)
+}
+
+/// Counts the files of the ZIP archive `data`, in central directory order, as `options` direct, returning
+/// the path and counts of each, or the error reading or counting it. Directories are skipped.
+fn count_zip(data: &[u8], options: &WcOptions) -> io::Result<Vec<Member>> {
+    let archive = Archive::parse(data).map_err(invalid)?;
+    Ok(archive
+        .names()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| {
+            let counts = archive
+                .read(name)
+                .map_err(invalid)
+                .and_then(|contents| try_count_slice(&contents, options).map_err(io::Error::from));
+            (name.to_string(), counts)
+        })
+        .collect())
+}
+
+/// Counts the members of the tar, gzip-compressed tar or ZIP archive `input`, recognized by its leading
+/// bytes, as `options` direct, returning the path and counts of each in the order of the archive. A member
+/// which cannot be read or counted has the error doing so in place of its counts, while one in the archive
+/// as a whole ends the count.
+///
+/// Tar archives are counted as they are read, while ZIP archives, whose directory is at their end, are read
+/// whole first.
+pub fn count_members<R: BufRead>(mut input: R, options: &WcOptions) -> io::Result<Vec<Member>> {
+    let prefix = input.fill_buf()?;
+    if prefix.starts_with(ZIP_SIGNATURE) || prefix.starts_with(EMPTY_ZIP_SIGNATURE) {
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+        return count_zip(&data, options);
+    }
+    match Compression::detect(prefix) {
+        None => count_tar(input, options),
+        Some(Compression::Gzip) => count_tar(
+            BufReader::with_capacity(BUFFER_SIZE, GzipDecoder::new(input)),
+            options,
+        ),
+        Some(compression) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            Message::new("compressed-archive-unsupported").arg("compression", compression.name()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InvalidUtf8;
+
+    /// Returns the header and data blocks of a tar entry of `kind` at `path` holding `data`.
+    fn entry(path: &str, kind: u8, data: &[u8]) -> Vec<u8> {
+        let mut header = [0; BLOCK_SIZE];
+        header[..path.len()].copy_from_slice(path.as_bytes());
+        header[100..108].copy_from_slice(b"0000644\0");
+        header[124..136].copy_from_slice(format!("{:011o}\0", data.len()).as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = header.iter().map(|&byte| u32::from(byte)).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+        let mut entry = header.to_vec();
+        entry.extend_from_slice(data);
+        entry.resize(
+            entry.len() + (BLOCK_SIZE - data.len() % BLOCK_SIZE) % BLOCK_SIZE,
+            0,
+        );
+        entry
+    }
+
+    /// Returns the paths, lines and words of the members of `archive`.
+    fn members(archive: &[u8]) -> Vec<(String, u64, u64)> {
+        count_members(archive, &WcOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|(path, counts)| {
+                let counts = counts.unwrap();
+                (path, counts.lines, counts.words)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_count_tar() {
+        let long_path = format!("data/{}.txt", "x".repeat(120));
+        let pax = b"30 path=data/renamed/notes.md\n";
+        let mut archive = Vec::new();
+        archive.extend(entry("data/", b'5', b""));
+        archive.extend(entry("data/a.txt", b'0', b"one two\nthree\n"));
+        archive.extend(entry("././@LongLink", b'L', long_path.as_bytes()));
+        archive.extend(entry("data/truncated", b'0', b"four\n"));
+        archive.extend(entry("PaxHeaders/notes.md", b'x', pax));
+        archive.extend(entry("data/notes.md", b'0', &[b'w'; 600]));
+        archive.extend(entry("data/link", b'2', b""));
+        archive.extend([0; 2 * BLOCK_SIZE]);
+
+        assert_eq!(
+            members(&archive),
+            vec![
+                ("data/a.txt".to_string(), 2, 3),
+                (long_path, 1, 1),
+                ("data/renamed/notes.md".to_string(), 0, 1),
+            ]
+        );
+
+        let count = |archive: &[u8]| count_members(archive, &WcOptions::default());
+        assert!(count(&archive[..BLOCK_SIZE + 100]).is_err());
+        assert!(count(&archive[..2 * BLOCK_SIZE + 5]).is_err());
+        archive[BLOCK_SIZE + 10] ^= 1;
+        assert!(count(&archive).is_err());
+    }
+
+    #[test]
+    fn test_unreadable_member() {
+        let mut archive = Vec::new();
+        archive.extend(entry("bad.txt", b'0', b"one \xFF\n"));
+        archive.extend(entry("good.txt", b'0', b"two three\n"));
+        let options = WcOptions {
+            chars: true,
+            invalid_utf8: Some(InvalidUtf8::Error),
+            ..WcOptions::default()
+        };
+
+        // The member which cannot be counted has an error of its own, and the next is still counted.
+        let members = count_members(&archive[..], &options).unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].0, "bad.txt");
+        assert_eq!(
+            members[0].1.as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(members[1].0, "good.txt");
+        assert_eq!(members[1].1.as_ref().unwrap().words, 2);
+    }
+
+    #[test]
+    fn test_count_zip() {
+        let mut empty = EMPTY_ZIP_SIGNATURE.to_vec();
+        empty.extend_from_slice(&[0; 18]);
+        assert_eq!(members(&empty), vec![]);
+        assert!(count_members(ZIP_SIGNATURE, &WcOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_numeric_field() {
+        assert_eq!(numeric_field(b"00000001750\0"), Some(1000));
+        assert_eq!(numeric_field(b"     17 \0"), Some(15));
+        assert_eq!(
+            numeric_field(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x00]),
+            Some(256)
+        );
+        assert_eq!(numeric_field(b"9\0"), None);
+    }
+}
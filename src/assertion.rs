@@ -0,0 +1,130 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::str::FromStr;
+
+use wc_rs::i18n::Message;
+use wc_rs::Counts;
+
+/// A count which can be compared against a threshold.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Metric {
+    Bytes,
+    Words,
+    Lines,
+}
+
+impl Metric {
+    /// Returns the name of the receiver, as written in assertions.
+    fn name(self) -> &'static str {
+        match self {
+            Metric::Bytes => "bytes",
+            Metric::Words => "words",
+            Metric::Lines => "lines",
+        }
+    }
+
+    /// Returns the count of the receiver in `counts`.
+    pub fn of(self, counts: &Counts) -> u64 {
+        match self {
+            Metric::Bytes => counts.bytes,
+            Metric::Words => counts.words,
+            Metric::Lines => counts.lines,
+        }
+    }
+}
+
+/// The comparison operators of assertions, longest first so that `<=` is not read as `<`.
+const COMPARISONS: [&str; 6] = ["<=", ">=", "==", "!=", "<", ">"];
+
+/// A threshold that a count must satisfy, such as `lines<=200`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Assertion {
+    /// The count being compared.
+    pub metric: Metric,
+    /// The comparison operator, one of `COMPARISONS`.
+    comparison: &'static str,
+    /// The threshold the count is compared against.
+    limit: u64,
+}
+
+impl Assertion {
+    /// Returns `true` if `counts` satisfy the receiver.
+    pub fn holds(&self, counts: &Counts) -> bool {
+        let value = self.metric.of(counts);
+        match self.comparison {
+            "<=" => value <= self.limit,
+            ">=" => value >= self.limit,
+            "==" => value == self.limit,
+            "!=" => value != self.limit,
+            "<" => value < self.limit,
+            _ => value > self.limit,
+        }
+    }
+}
+
+impl FromStr for Assertion {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Message::new("malformed-assertion").arg("value", s);
+        let (index, comparison) = COMPARISONS
+            .iter()
+            .filter_map(|comparison| Some((s.find(comparison)?, *comparison)))
+            .min_by_key(|&(index, comparison)| (index, usize::MAX - comparison.len()))
+            .ok_or_else(invalid)?;
+
+        let metric = match s[..index].trim() {
+            "bytes" => Metric::Bytes,
+            "words" => Metric::Words,
+            "lines" => Metric::Lines,
+            _ => return Err(invalid()),
+        };
+        let limit = s[index + comparison.len()..]
+            .trim()
+            .parse()
+            .map_err(|_| invalid())?;
+
+        Ok(Assertion {
+            metric,
+            comparison,
+            limit,
+        })
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.metric.name(), self.comparison, self.limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assertion() {
+        let assertion: Assertion = "lines <= 2".parse().unwrap();
+        let mut counts = Counts {
+            lines: 2,
+            ..Counts::default()
+        };
+
+        assert_eq!(assertion.to_string(), "lines<=2");
+        assert!(assertion.holds(&counts));
+        counts.lines = 3;
+        assert!(!assertion.holds(&counts));
+        assert!(!"words>0"
+            .parse::<Assertion>()
+            .unwrap()
+            .holds(&Counts::default()));
+        assert!("pages<1".parse::<Assertion>().is_err());
+        assert!("lines<=many".parse::<Assertion>().is_err());
+    }
+}
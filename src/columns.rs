@@ -0,0 +1,296 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::str::FromStr;
+
+use wc_rs::i18n::Message;
+use wc_rs::line_stats::Extreme;
+use wc_rs::Counts;
+
+/// A column of an output row, being a count, a metric derived from the counts, or the name of the input.
+///
+/// The line statistics are the least and greatest lengths in bytes and words of the lines, and the
+/// numbers of the first lines with them, zero if there are no lines.
+///
+/// The allocated size is the space the file of a row occupies on disk, in bytes, which is less than its
+/// byte count if it is sparse or compressed by the filesystem, and zero for rows of no file.
+///
+/// The shares are the percentages of the total count of all rows that a row accounts for.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Column {
+    Lines,
+    Words,
+    Chars,
+    Bytes,
+    MaxLineLength,
+    Emoji,
+    Graphemes,
+    Urls,
+    Emails,
+    Numbers,
+    Hashtags,
+    Mentions,
+    Messages,
+    Syllables,
+    Spaces,
+    Tabs,
+    Newlines,
+    Nbsp,
+    OtherSpaces,
+    ErrorLines,
+    WarnLines,
+    InfoLines,
+    DebugLines,
+    ShortestLine,
+    ShortestLineAt,
+    LongestLine,
+    LongestLineAt,
+    MeanLineLength,
+    FewestWords,
+    FewestWordsAt,
+    MostWords,
+    MostWordsAt,
+    MeanWordsPerLine,
+    Allocated,
+    BytesPerLine,
+    WordsPerLine,
+    BytesPerWord,
+    LinesShare,
+    WordsShare,
+    BytesShare,
+    File,
+}
+
+/// The name of each column, as given to `--columns` and in templates.
+const NAMES: [(&str, Column); 41] = [
+    ("lines", Column::Lines),
+    ("words", Column::Words),
+    ("chars", Column::Chars),
+    ("bytes", Column::Bytes),
+    ("max_line_length", Column::MaxLineLength),
+    ("emoji", Column::Emoji),
+    ("graphemes", Column::Graphemes),
+    ("urls", Column::Urls),
+    ("emails", Column::Emails),
+    ("numbers", Column::Numbers),
+    ("hashtags", Column::Hashtags),
+    ("mentions", Column::Mentions),
+    ("messages", Column::Messages),
+    ("syllables", Column::Syllables),
+    ("spaces", Column::Spaces),
+    ("tabs", Column::Tabs),
+    ("newlines", Column::Newlines),
+    ("nbsp", Column::Nbsp),
+    ("other_spaces", Column::OtherSpaces),
+    ("error_lines", Column::ErrorLines),
+    ("warn_lines", Column::WarnLines),
+    ("info_lines", Column::InfoLines),
+    ("debug_lines", Column::DebugLines),
+    ("shortest_line", Column::ShortestLine),
+    ("shortest_line_at", Column::ShortestLineAt),
+    ("longest_line", Column::LongestLine),
+    ("longest_line_at", Column::LongestLineAt),
+    ("mean_line_length", Column::MeanLineLength),
+    ("fewest_words", Column::FewestWords),
+    ("fewest_words_at", Column::FewestWordsAt),
+    ("most_words", Column::MostWords),
+    ("most_words_at", Column::MostWordsAt),
+    ("mean_words_per_line", Column::MeanWordsPerLine),
+    ("allocated", Column::Allocated),
+    ("bytes_per_line", Column::BytesPerLine),
+    ("words_per_line", Column::WordsPerLine),
+    ("bytes_per_word", Column::BytesPerWord),
+    ("lines_share", Column::LinesShare),
+    ("words_share", Column::WordsShare),
+    ("bytes_share", Column::BytesShare),
+    ("file", Column::File),
+];
+
+impl FromStr for Column {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|&(_, column)| column)
+            .ok_or_else(|| Message::new("unknown-column").arg("value", s))
+    }
+}
+
+/// Returns `numerator / denominator`, or zero if the denominator is zero.
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Returns `part` as a percentage of `whole`, to `precision` decimal places.
+fn share(part: u64, whole: u64, precision: usize) -> String {
+    format!("{:.*}%", precision, ratio(part, whole) * 100.0)
+}
+
+/// Returns the value of `extreme`, or zero if there is none.
+fn extreme_value(extreme: Option<Extreme>) -> u64 {
+    extreme.map_or(0, |extreme| extreme.value)
+}
+
+/// Returns the number of the line of `extreme`, or zero if there is none.
+fn extreme_line(extreme: Option<Extreme>) -> u64 {
+    extreme.map_or(0, |extreme| extreme.line)
+}
+
+impl Column {
+    /// Returns the name of the receiver.
+    pub fn name(self) -> &'static str {
+        NAMES
+            .iter()
+            .find(|&&(_, column)| column == self)
+            .map(|&(name, _)| name)
+            .expect("Every column is named")
+    }
+
+    /// Returns `true` if the value of the receiver depends on the total counts of all rows.
+    pub fn needs_total(self) -> bool {
+        matches!(
+            self,
+            Column::LinesShare | Column::WordsShare | Column::BytesShare
+        )
+    }
+
+    /// Returns the value of the receiver for the input `name` with `counts`, out of the total counts of all
+    /// rows `total`.
+    pub fn value(self, counts: &Counts, total: &Counts, name: &str) -> String {
+        self.value_with_precision(counts, total, name, None)
+    }
+
+    /// Returns the value of the receiver as `value` does, to `precision` decimal places if it is a mean, ratio
+    /// or share, rather than two for means and ratios and one for shares if it is `None`.
+    pub fn value_with_precision(
+        self,
+        counts: &Counts,
+        total: &Counts,
+        name: &str,
+        precision: Option<usize>,
+    ) -> String {
+        let (decimals, share_decimals) = (precision.unwrap_or(2), precision.unwrap_or(1));
+        let count = match self {
+            Column::Lines => counts.lines,
+            Column::Words => counts.words,
+            Column::Chars => counts.chars,
+            Column::Bytes => counts.bytes,
+            Column::MaxLineLength => counts.max_line_length,
+            Column::Emoji => counts.emoji,
+            Column::Graphemes => counts.graphemes,
+            Column::Urls => counts.tokens.urls,
+            Column::Emails => counts.tokens.emails,
+            Column::Numbers => counts.tokens.numbers,
+            Column::Hashtags => counts.tokens.hashtags,
+            Column::Mentions => counts.tokens.mentions,
+            Column::Messages => counts.messages,
+            Column::Syllables => counts.syllables,
+            Column::Spaces => counts.whitespace.spaces,
+            Column::Tabs => counts.whitespace.tabs,
+            Column::Newlines => counts.whitespace.newlines,
+            Column::Nbsp => counts.whitespace.nbsp,
+            Column::OtherSpaces => counts.whitespace.other,
+            Column::ErrorLines => counts.log_levels.error,
+            Column::WarnLines => counts.log_levels.warn,
+            Column::InfoLines => counts.log_levels.info,
+            Column::DebugLines => counts.log_levels.debug,
+            Column::ShortestLine => extreme_value(counts.line_stats.shortest),
+            Column::ShortestLineAt => extreme_line(counts.line_stats.shortest),
+            Column::LongestLine => extreme_value(counts.line_stats.longest),
+            Column::LongestLineAt => extreme_line(counts.line_stats.longest),
+            Column::MeanLineLength => {
+                return format!("{:.*}", decimals, counts.line_stats.mean_length())
+            }
+            Column::FewestWords => extreme_value(counts.line_stats.fewest_words),
+            Column::FewestWordsAt => extreme_line(counts.line_stats.fewest_words),
+            Column::MostWords => extreme_value(counts.line_stats.most_words),
+            Column::MostWordsAt => extreme_line(counts.line_stats.most_words),
+            Column::MeanWordsPerLine => {
+                return format!("{:.*}", decimals, counts.line_stats.mean_words())
+            }
+            Column::Allocated => counts.allocated,
+            Column::BytesPerLine => {
+                return format!("{:.*}", decimals, ratio(counts.bytes, counts.lines))
+            }
+            Column::WordsPerLine => {
+                return format!("{:.*}", decimals, ratio(counts.words, counts.lines))
+            }
+            Column::BytesPerWord => {
+                return format!("{:.*}", decimals, ratio(counts.bytes, counts.words))
+            }
+            Column::LinesShare => return share(counts.lines, total.lines, share_decimals),
+            Column::WordsShare => return share(counts.words, total.words, share_decimals),
+            Column::BytesShare => return share(counts.bytes, total.bytes, share_decimals),
+            Column::File => return name.to_string(),
+        };
+        count.to_string()
+    }
+
+    /// Formats the value of the receiver for the input `name` with `counts`, out of the total counts of all
+    /// rows `total`, to `precision` decimal places as `value_with_precision` does, padded to the column width.
+    pub fn format(
+        self,
+        counts: &Counts,
+        total: &Counts,
+        name: &str,
+        precision: Option<usize>,
+    ) -> String {
+        let value = self.value_with_precision(counts, total, name, precision);
+        match self {
+            Column::File => value,
+            _ => format!("{:>7}", value),
+        }
+    }
+}
+
+/// Parses `list`, comma-separated column names such as `lines,words,words_per_line,file`.
+pub fn parse_list(list: &str) -> Result<Vec<Column>, Message> {
+    list.split(',').map(|name| name.trim().parse()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns() {
+        let columns = parse_list("lines, words_per_line,bytes_per_word,lines_share,file").unwrap();
+        let counts = Counts {
+            bytes: 10,
+            words: 3,
+            lines: 2,
+            ..Counts::default()
+        };
+        let total = Counts { lines: 8, ..counts };
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| column.format(&counts, &total, "a.txt", None))
+            .collect();
+        let precise: Vec<String> = columns
+            .iter()
+            .map(|column| column.value_with_precision(&counts, &total, "a.txt", Some(3)))
+            .collect();
+
+        assert_eq!(
+            row,
+            vec!["      2", "   1.50", "   3.33", "  25.0%", "a.txt"]
+        );
+        assert_eq!(precise, vec!["2", "1.500", "3.333", "25.000%", "a.txt"]);
+        assert_eq!(
+            Column::WordsPerLine.value_with_precision(&counts, &total, "", Some(0)),
+            "2"
+        );
+        assert!(Column::BytesShare.needs_total());
+        assert!(parse_list("lines,pages").is_err());
+    }
+}
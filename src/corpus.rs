@@ -0,0 +1,158 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The byte order marks recognized at the start of a file, longest first so that the UTF-32 little endian
+/// mark is not taken for the UTF-16 one it begins with.
+const BYTE_ORDER_MARKS: [(&[u8], &str); 5] = [
+    (&[0xFF, 0xFE, 0x00, 0x00], "utf-32le"),
+    (&[0x00, 0x00, 0xFE, 0xFF], "utf-32be"),
+    (&[0xEF, 0xBB, 0xBF], "utf-8"),
+    (&[0xFF, 0xFE], "utf-16le"),
+    (&[0xFE, 0xFF], "utf-16be"),
+];
+
+/// The encoding, byte order mark, line endings and kind of a file.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Profile {
+    /// Whether the file is binary rather than text.
+    pub kind: &'static str,
+    /// The encoding of the file, as far as it can be told from its bytes.
+    pub encoding: &'static str,
+    /// The encoding named by the byte order mark of the file, or `none`.
+    pub bom: &'static str,
+    /// The line endings of the file: `lf`, `crlf`, `cr`, `mixed` or `none`.
+    pub line_endings: &'static str,
+}
+
+impl Profile {
+    /// Returns the profile of a file with `contents`. Files with a NUL byte are binary, unless their byte
+    /// order mark names a UTF-16 or UTF-32 encoding, whose text has them.
+    pub fn of(contents: &[u8]) -> Self {
+        let bom = BYTE_ORDER_MARKS
+            .iter()
+            .find(|(mark, _)| contents.starts_with(mark))
+            .map_or("none", |&(_, encoding)| encoding);
+        let wide = bom.starts_with("utf-16") || bom.starts_with("utf-32");
+
+        let binary = !wide && contents.contains(&0);
+        let encoding = if wide {
+            bom
+        } else if contents.is_ascii() {
+            "ascii"
+        } else if std::str::from_utf8(contents).is_ok() {
+            "utf-8"
+        } else {
+            "other"
+        };
+
+        Profile {
+            kind: if binary { "binary" } else { "text" },
+            encoding,
+            bom,
+            line_endings: if binary || wide {
+                "none"
+            } else {
+                line_endings(contents)
+            },
+        }
+    }
+}
+
+/// Returns the line endings of the 8-bit text `contents`.
+fn line_endings(contents: &[u8]) -> &'static str {
+    let (mut lf, mut crlf, mut cr) = (false, false, false);
+    for (index, &byte) in contents.iter().enumerate() {
+        match byte {
+            b'\n' if index > 0 && contents[index - 1] == b'\r' => crlf = true,
+            b'\n' => lf = true,
+            b'\r' if contents.get(index + 1) != Some(&b'\n') => cr = true,
+            _ => {}
+        }
+    }
+    match (lf, crlf, cr) {
+        (false, false, false) => "none",
+        (true, false, false) => "lf",
+        (false, true, false) => "crlf",
+        (false, false, true) => "cr",
+        _ => "mixed",
+    }
+}
+
+/// The number of files of each kind, encoding, byte order mark and line ending in a corpus.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct CorpusReport {
+    /// The number of files profiled.
+    pub files: u64,
+    /// The number of files with each value of each property, by property and value.
+    counts: BTreeMap<(&'static str, &'static str), u64>,
+}
+
+impl CorpusReport {
+    /// Adds a file of `profile` to the report.
+    pub fn add(&mut self, profile: Profile) {
+        self.files += 1;
+        for property in [
+            ("kind", profile.kind),
+            ("encoding", profile.encoding),
+            ("bom", profile.bom),
+            ("line_endings", profile.line_endings),
+        ] {
+            *self.counts.entry(property).or_default() += 1;
+        }
+    }
+}
+
+impl fmt::Display for CorpusReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>8} files", self.files)?;
+        for ((property, value), count) in &self.counts {
+            writeln!(f, "{:>8} {}={}", count, property, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile() {
+        assert_eq!(
+            Profile::of(b"one\r\ntwo\r\n"),
+            Profile {
+                kind: "text",
+                encoding: "ascii",
+                bom: "none",
+                line_endings: "crlf",
+            }
+        );
+        assert_eq!(
+            Profile::of("\u{FEFF}caf\u{E9}\nold\rmac".as_bytes()),
+            Profile {
+                kind: "text",
+                encoding: "utf-8",
+                bom: "utf-8",
+                line_endings: "mixed",
+            }
+        );
+        assert_eq!(Profile::of(b"caf\xE9\n").encoding, "other");
+        assert_eq!(Profile::of(b"\x7FELF\0\0\n").kind, "binary");
+        assert_eq!(Profile::of(b"\xFF\xFEa\0\n\0").encoding, "utf-16le");
+
+        let mut report = CorpusReport::default();
+        report.add(Profile::of(b"a\n"));
+        report.add(Profile::of(b"b\r\n"));
+        let text = report.to_string();
+        assert!(text.starts_with("       2 files\n"));
+        assert!(text.contains("       1 line_endings=crlf\n"));
+        assert!(text.contains("       2 encoding=ascii\n"));
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::{self, BufRead, BufReader, Read};
+
+use rayon::prelude::*;
+
+use crate::i18n::Message;
+use crate::inflate::GzipDecoder;
+
+/// A compression format recognized by the magic bytes an input starts with.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the format whose magic bytes `prefix` starts with, or `None` if it starts with none.
+    pub fn detect(prefix: &[u8]) -> Option<Self> {
+        if prefix.starts_with(&[0x1F, 0x8B]) {
+            Some(Compression::Gzip)
+        } else if prefix.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if prefix.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else if prefix.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the name of the format.
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+/// The length of the fixed part of a gzip member header, up to and including the length of its extra field.
+const EXTRA_HEADER_LENGTH: usize = 12;
+
+/// Returns an error for a gzip member, following BGZF blocks, which is not one.
+fn not_a_block() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        Message::new("bgzf-no-block-size"),
+    )
+}
+
+/// Returns whether `fixed`, the fixed part of a gzip member header, is that of a member with an extra field.
+fn has_extra_field(fixed: &[u8]) -> bool {
+    fixed[..3] == [0x1F, 0x8B, 8] && fixed[3] & 0x04 != 0
+}
+
+/// Returns the length of the BGZF block, a gzip member as `bgzip` writes them, whose header `header` starts
+/// with, from the `BC` subfield of its extra field. Returns `None` if the header is that of a gzip member
+/// with no such subfield, or is cut short.
+fn block_length(header: &[u8]) -> Option<usize> {
+    let fixed = header.get(..EXTRA_HEADER_LENGTH)?;
+    if !has_extra_field(fixed) {
+        return None;
+    }
+    let extra_length = usize::from(u16::from_le_bytes([fixed[10], fixed[11]]));
+    let mut extra = header.get(EXTRA_HEADER_LENGTH..EXTRA_HEADER_LENGTH + extra_length)?;
+    while let [first, second, low, high, rest @ ..] = extra {
+        let length = usize::from(u16::from_le_bytes([*low, *high]));
+        if [*first, *second] == *b"BC" && length == 2 {
+            let size = rest.get(..2)?;
+            return Some(usize::from(u16::from_le_bytes([size[0], size[1]])) + 1);
+        }
+        extra = rest.get(length..)?;
+    }
+    None
+}
+
+/// A reader decompressing the BGZF blocks read from an underlying reader a batch at a time, the blocks of
+/// each batch in parallel, without verifying their checksums or lengths.
+///
+/// Each block is a gzip member whose header gives its compressed length, so the blocks are found without
+/// decompressing them, unlike the members of other gzip inputs, which are decompressed in turn.
+pub struct BgzfDecoder<R> {
+    /// The reader of the compressed blocks.
+    inner: R,
+    /// The decompressed contents of the last batch of blocks.
+    output: Vec<u8>,
+    /// The length of the part of `output` read so far.
+    position: usize,
+}
+
+impl<R> BgzfDecoder<R>
+where
+    R: BufRead,
+{
+    /// Returns a new reader decompressing the BGZF blocks of `inner`.
+    pub fn new(inner: R) -> Self {
+        BgzfDecoder {
+            inner,
+            output: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Reads the next block, or returns `None` at the end of the input.
+    fn read_block(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.inner.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut block = vec![0; EXTRA_HEADER_LENGTH];
+        self.inner.read_exact(&mut block)?;
+        if !has_extra_field(&block) {
+            return Err(not_a_block());
+        }
+        let extra_length = usize::from(u16::from_le_bytes([block[10], block[11]]));
+        block.resize(EXTRA_HEADER_LENGTH + extra_length, 0);
+        self.inner.read_exact(&mut block[EXTRA_HEADER_LENGTH..])?;
+
+        // A block ends with the CRC-32 and length of its contents, following its compressed data.
+        let length = block_length(&block)
+            .filter(|&length| length >= block.len() + 8)
+            .ok_or_else(not_a_block)?;
+        let start = block.len();
+        block.resize(length, 0);
+        self.inner.read_exact(&mut block[start..])?;
+        Ok(Some(block))
+    }
+
+    /// Decompresses the next batch of blocks in place of the last, returning `false` at the end of the input.
+    fn refill(&mut self) -> io::Result<bool> {
+        let mut blocks = Vec::new();
+        while blocks.len() < 4 * rayon::current_num_threads() {
+            match self.read_block()? {
+                Some(block) => blocks.push(block),
+                None => break,
+            }
+        }
+        let contents: Vec<io::Result<Vec<u8>>> = blocks
+            .par_iter()
+            .map(|block| {
+                let mut contents = Vec::new();
+                GzipDecoder::new(&block[..]).read_to_end(&mut contents)?;
+                Ok(contents)
+            })
+            .collect();
+
+        self.output.clear();
+        self.position = 0;
+        for contents in contents {
+            self.output.extend_from_slice(&contents?);
+        }
+        Ok(!blocks.is_empty())
+    }
+}
+
+impl<R> Read for BgzfDecoder<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Batches of nothing but empty blocks, such as the one ending the input, are passed over.
+        while self.position == self.output.len() {
+            if buf.is_empty() || !self.refill()? {
+                return Ok(0);
+            }
+        }
+        let available = &self.output[self.position..];
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// Returns a reader of the decompressed contents of `input` if it starts with the magic bytes of a
+/// compression format, otherwise of `input` as it is, buffered with a capacity of `capacity`.
+///
+/// Only gzip is decompressed, in parallel if it is in BGZF blocks; inputs in the other formats detected are
+/// reported as unsupported rather than counted compressed.
+pub fn decompress<'a, R>(mut input: R, capacity: usize) -> io::Result<Box<dyn BufRead + 'a>>
+where
+    R: BufRead + 'a,
+{
+    // The magic bytes are at most six long, and the header of a BGZF block eighteen, and seldom split across
+    // reads.
+    let prefix = input.fill_buf()?;
+    match Compression::detect(prefix) {
+        None => Ok(Box::new(input)),
+        Some(Compression::Gzip) if block_length(prefix).is_some() => Ok(Box::new(
+            BufReader::with_capacity(capacity, BgzfDecoder::new(input)),
+        )),
+        Some(Compression::Gzip) => Ok(Box::new(BufReader::with_capacity(
+            capacity,
+            GzipDecoder::new(input),
+        ))),
+        Some(compression) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            Message::new("decompression-unsupported").arg("compression", compression.name()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// `hello hello hello` compressed by gzip in two members, the first with a file name.
+    const MEMBERS: [u8; 56] = [
+        0x1F, 0x8B, 0x08, 0x08, 0x00, 0x00, 0x00, 0x00, 0x02, 0xFF, 0x61, 0x00, 0xCB, 0x48, 0xCD,
+        0xC9, 0xC9, 0x57, 0x00, 0x00, 0xF6, 0xF9, 0x81, 0xED, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x8B,
+        0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xFF, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8,
+        0x00, 0x91, 0x00, 0x40, 0xA6, 0x2D, 0x01, 0x0B, 0x00, 0x00, 0x00,
+    ];
+
+    /// Returns the contents of `input` read through `decompress`.
+    fn read_all(input: &[u8]) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        decompress(input, 16)?.read_to_end(&mut output)?;
+        Ok(output)
+    }
+
+    /// Returns `contents` compressed in a BGZF block of a stored DEFLATE block, with a zero checksum, as
+    /// neither is verified.
+    fn bgzf_block(contents: &[u8]) -> Vec<u8> {
+        let length = contents.len() as u16;
+        let mut block = vec![
+            0x1F, 0x8B, 8, 4, 0, 0, 0, 0, 0, 0xFF, 6, 0, b'B', b'C', 2, 0,
+        ];
+        block.extend_from_slice(&(contents.len() as u16 + 30).to_le_bytes());
+        block.push(1);
+        block.extend_from_slice(&length.to_le_bytes());
+        block.extend_from_slice(&(!length).to_le_bytes());
+        block.extend_from_slice(contents);
+        block.extend_from_slice(&[0; 4]);
+        block.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        block
+    }
+
+    #[test]
+    fn test_bgzf_decoder() {
+        // More blocks than are decompressed in a batch, some empty, ending with the empty block `bgzip` ends
+        // its output with.
+        let text: Vec<u8> = (0..1000)
+            .flat_map(|line| format!("line {}\n", line).into_bytes())
+            .collect();
+        let mut input: Vec<u8> = text.chunks(7).flat_map(bgzf_block).collect();
+        input.extend(bgzf_block(b""));
+        input.extend(bgzf_block(b""));
+        assert_eq!(read_all(&input).unwrap(), text);
+
+        let mut reader = BgzfDecoder::new(&input[..]);
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, text);
+
+        // A block cut short, and a gzip member of no block size following blocks.
+        assert!(read_all(&input[..input.len() - 30]).is_err());
+        let error = read_all(&[&bgzf_block(b"hello ")[..], &MEMBERS].concat()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid BGZF block: no block size");
+        let message = error
+            .get_ref()
+            .and_then(|error| error.downcast_ref::<Message>());
+        assert_eq!(message.map(Message::identifier), Some("bgzf-no-block-size"));
+        assert_eq!(block_length(&MEMBERS), None);
+        assert_eq!(block_length(&bgzf_block(b"hello")), Some(36));
+    }
+
+    #[test]
+    fn test_decompress() {
+        assert_eq!(read_all(&MEMBERS).unwrap(), b"hello hello hello");
+        assert_eq!(read_all(b"plain text\n").unwrap(), b"plain text\n");
+        assert_eq!(read_all(b"").unwrap(), b"");
+        assert!(read_all(&MEMBERS[..40]).is_err());
+        assert_eq!(
+            read_all(b"BZh91AY&SY").unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+        assert_eq!(
+            Compression::detect(&[0x28, 0xB5, 0x2F, 0xFD, 0x04]),
+            Some(Compression::Zstd)
+        );
+    }
+}
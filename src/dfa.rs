@@ -0,0 +1,242 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Table-driven counting of the words, lines and characters of an input in a single pass.
+//!
+//! One deterministic automaton tracks both whether the last byte was part of a word and how far through a
+//! UTF-8 sequence the input is, so that each byte is a single table lookup with no branches. A word is
+//! counted where a byte which is not ASCII whitespace follows one which is or the start of the input, and a
+//! character where a valid UTF-8 sequence completes, as in the SIMD kernels and the decoder. Blocks of the
+//! input which are all ASCII, as most text mostly is, skip counting characters byte by byte, every byte of
+//! them being one.
+
+/// The length of the blocks of input checked for being all ASCII.
+const ASCII_BLOCK: usize = 512;
+
+/// The number of states of the UTF-8 decoder.
+const UTF8_STATES: usize = 8;
+
+/// The state of the UTF-8 decoder between sequences, or after an invalid byte.
+const ACCEPT: u16 = 0;
+
+/// The bit of a transition set if its byte begins a word.
+const WORD_START: u16 = 1 << 4;
+
+/// The bit of a transition set if its byte is a line feed.
+const LINE_FEED: u16 = 1 << 5;
+
+/// The bit of a transition set if its byte completes a character.
+const CHAR_END: u16 = 1 << 6;
+
+/// The mask of the next state of a transition.
+const STATE_MASK: u16 = 0x0F;
+
+/// Returns the UTF-8 decoder state after `byte` from `state`, and whether it completes a character.
+///
+/// The states other than `ACCEPT` are the continuation bytes a sequence still needs: one, two and three of
+/// any value, and those after `E0`, `ED`, `F0` and `F4`, whose first continuation is restricted so that
+/// overlong forms, surrogates and values beyond U+10FFFF are invalid. A byte which does not continue a
+/// sequence ends it unfinished, and is decoded afresh, as `str::from_utf8_lossy` does.
+const fn utf8_transition(state: u16, byte: u8) -> (u16, bool) {
+    let (low, high, next) = match state {
+        1 => (0x80, 0xBF, ACCEPT),
+        2 => (0x80, 0xBF, 1),
+        3 => (0x80, 0xBF, 2),
+        4 => (0xA0, 0xBF, 1),
+        5 => (0x80, 0x9F, 1),
+        6 => (0x90, 0xBF, 2),
+        7 => (0x80, 0x8F, 2),
+        _ => {
+            return match byte {
+                0x00..=0x7F => (ACCEPT, true),
+                0xC2..=0xDF => (1, false),
+                0xE0 => (4, false),
+                0xED => (5, false),
+                0xE1..=0xEF => (2, false),
+                0xF0 => (6, false),
+                0xF1..=0xF3 => (3, false),
+                0xF4 => (7, false),
+                _ => (ACCEPT, false),
+            }
+        }
+    };
+    if byte >= low && byte <= high {
+        (next, next == ACCEPT)
+    } else {
+        utf8_transition(ACCEPT, byte)
+    }
+}
+
+/// Returns the transitions of the automaton, indexed by state and byte. A state is the UTF-8 decoder state
+/// followed by a bit set within a word; a transition is the next state with the bits of what its byte ends
+/// or begins.
+const fn transitions() -> [[u16; 256]; 2 * UTF8_STATES] {
+    let mut table = [[0; 256]; 2 * UTF8_STATES];
+    let mut state = 0;
+    while state < 2 * UTF8_STATES {
+        let mut byte = 0;
+        while byte < 256 {
+            let (utf8, char_end) = utf8_transition((state >> 1) as u16, byte as u8);
+            let is_space = (byte as u8).is_ascii_whitespace();
+            let in_word = state & 1 == 1;
+            let mut transition = utf8 << 1 | !is_space as u16;
+            if !is_space && !in_word {
+                transition |= WORD_START;
+            }
+            if byte == b'\n' as usize {
+                transition |= LINE_FEED;
+            }
+            if char_end {
+                transition |= CHAR_END;
+            }
+            table[state][byte] = transition;
+            byte += 1;
+        }
+        state += 1;
+    }
+    table
+}
+
+/// The transitions of the automaton.
+static TRANSITIONS: [[u16; 256]; 2 * UTF8_STATES] = transitions();
+
+/// Counts the words, lines and characters of an input fed to it in order, in pieces split anywhere.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Scanner {
+    /// The state of the automaton after the input fed so far.
+    state: u16,
+    /// The number of words begun so far.
+    pub words: u64,
+    /// The number of line feeds so far.
+    pub lines: u64,
+    /// The number of valid characters completed so far.
+    pub chars: u64,
+}
+
+impl Scanner {
+    /// Counts `bytes`, which continue the input fed so far.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for block in bytes.chunks(ASCII_BLOCK) {
+            if block.is_ascii() {
+                self.feed_ascii(block);
+            } else {
+                self.feed_bytes(block);
+            }
+        }
+    }
+
+    /// Counts the words and lines of `block`, which is all ASCII, with the automaton. Each of its bytes is a
+    /// character, whether or not it ends a sequence left unfinished, which it leaves invalid.
+    fn feed_ascii(&mut self, block: &[u8]) {
+        let mut state = self.state;
+        let (mut words, mut lines) = (0u64, 0u64);
+        for &byte in block {
+            let transition = TRANSITIONS[usize::from(state)][usize::from(byte)];
+            state = transition & STATE_MASK;
+            words += u64::from(transition & WORD_START != 0);
+            lines += u64::from(transition & LINE_FEED != 0);
+        }
+        self.state = state;
+        self.words += words;
+        self.lines += lines;
+        self.chars += block.len() as u64;
+    }
+
+    /// Counts `bytes` with the automaton, a byte at a time.
+    fn feed_bytes(&mut self, bytes: &[u8]) {
+        let mut state = self.state;
+        let (mut words, mut lines, mut chars) = (0u64, 0u64, 0u64);
+        for &byte in bytes {
+            let transition = TRANSITIONS[usize::from(state)][usize::from(byte)];
+            state = transition & STATE_MASK;
+            words += u64::from(transition & WORD_START != 0);
+            lines += u64::from(transition & LINE_FEED != 0);
+            chars += u64::from(transition & CHAR_END != 0);
+        }
+        self.state = state;
+        self.words += words;
+        self.lines += lines;
+        self.chars += chars;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scanner() {
+        let mut input = "The caf\u{E9}\u{A0}is \u{65E5}\u{672C} \u{1F389}\n\tsecond  line\r\n"
+            .as_bytes()
+            .to_vec();
+        // Overlong, surrogate, truncated and out-of-range sequences, and stray continuation bytes.
+        input.extend_from_slice(
+            b"\xC0\xAF \xED\xA0\x80 \xF0\x9F\x8E! \xF4\x90\x80\x80 \x80\xBFx\xE2\x82",
+        );
+
+        for split in 0..input.len() {
+            let mut scanner = Scanner::default();
+            scanner.feed(&input[..split]);
+            scanner.feed(&input[split..]);
+
+            let (words, lines) = crate::simd::count_words_and_lines(&input);
+            let chars: u64 = input
+                .utf8_chunks()
+                .map(|chunk| chunk.valid().chars().count() as u64)
+                .sum();
+            assert_eq!(
+                (scanner.words, scanner.lines, scanner.chars),
+                (words, lines, chars)
+            );
+        }
+        assert_eq!(Scanner::default().words, 0);
+
+        // Words spanning ASCII blocks, and blocks of the automaton, however the input is split.
+        let input: Vec<u8> = b"words\tspanning blocks\n\xC3\xA9t\xC3\xA9 \xC3"
+            .iter()
+            .copied()
+            .cycle()
+            .take(ASCII_BLOCK * 5)
+            .chain(b"ascii words ".repeat(ASCII_BLOCK / 4))
+            .chain(b"ending\xC3\xA9 here".iter().copied())
+            .collect();
+        let (words, lines) = crate::simd::count_words_and_lines(&input);
+        let chars: u64 = input
+            .utf8_chunks()
+            .map(|chunk| chunk.valid().chars().count() as u64)
+            .sum();
+        for split in [
+            0,
+            1,
+            7,
+            ASCII_BLOCK - 1,
+            ASCII_BLOCK * 5 + 3,
+            input.len() - 9,
+        ] {
+            let mut scanner = Scanner::default();
+            scanner.feed(&input[..split]);
+            scanner.feed(&input[split..]);
+            assert_eq!(
+                (scanner.words, scanner.lines, scanner.chars),
+                (words, lines, chars)
+            );
+        }
+
+        // Every pair of bytes, before the start of a sequence, decodes as the standard library decodes it.
+        for pair in 0..=u16::MAX {
+            let [first, second] = pair.to_be_bytes();
+            let input = [first, second, 0xE2, 0x82, 0xAC];
+            let mut scanner = Scanner::default();
+            scanner.feed(&input);
+            let chars: usize = input
+                .utf8_chunks()
+                .map(|chunk| chunk.valid().chars().count())
+                .sum();
+            assert_eq!(scanner.chars, chars as u64, "{:02X?}", input);
+        }
+    }
+}
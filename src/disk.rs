@@ -0,0 +1,58 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fs::{self, Metadata};
+
+/// The size of the units `st_blocks` counts in, whatever the block size of the filesystem.
+#[cfg(unix)]
+const BLOCK_SIZE: u64 = 512;
+
+/// Returns the space allocated on disk to the file of `metadata`, in bytes.
+#[cfg(unix)]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.blocks().saturating_mul(BLOCK_SIZE)
+}
+
+/// Returns the logical size of the file of `metadata`, as the platform's allocated size is not read.
+#[cfg(not(unix))]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Returns the space allocated on disk to the regular file at `path`, in bytes, or `None` if there is no
+/// such file, as for standard input, streams and the rows of parts of files.
+pub fn allocated(path: &str) -> Option<u64> {
+    if path.is_empty() {
+        return None;
+    }
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    Some(allocated_size(&metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocated() {
+        let path = std::env::temp_dir().join(format!("wc_rs-allocated-{}", std::process::id()));
+        fs::write(&path, vec![b'x'; 64 * 1024]).unwrap();
+        let size = allocated(&path.to_string_lossy());
+        fs::remove_file(&path).unwrap();
+
+        // Filesystems compressing or deduplicating data may allocate less than was written.
+        assert!(size.is_some());
+        assert_eq!(allocated(""), None);
+        assert_eq!(allocated(&std::env::temp_dir().to_string_lossy()), None);
+        assert_eq!(allocated("tcp://localhost:1"), None);
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::i18n::Message;
+use crate::xml::{Event, Reader};
+use crate::zip::Archive;
+
+/// A word processor document format whose text can be counted.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Document {
+    /// An Office Open XML word processing document, as written by Microsoft Word.
+    Docx,
+    /// An OpenDocument text document, as written by LibreOffice Writer.
+    Odt,
+}
+
+/// The elements of a document format which determine the text it contains.
+struct Schema {
+    /// The path of the part holding the body of the document within its archive.
+    part: &'static str,
+    /// The element holding the body of the document.
+    body: &'static [u8],
+    /// Elements whose character data is text, or empty if all character data within the body is.
+    text: &'static [&'static [u8]],
+    /// Elements whose contents are not text, even within the body.
+    skipped: &'static [&'static [u8]],
+    /// Elements standing for a single character of text.
+    characters: &'static [(&'static [u8], u8)],
+    /// Elements whose end separates paragraphs.
+    paragraphs: &'static [&'static [u8]],
+}
+
+impl Document {
+    /// Returns the schema of the receiver.
+    fn schema(self) -> Schema {
+        match self {
+            // Text boxes are repeated in a VML fallback for older readers, and tab stops are defined with the
+            // same element as tabs.
+            Document::Docx => Schema {
+                part: "word/document.xml",
+                body: b"w:body",
+                text: &[b"w:t"],
+                skipped: &[b"mc:Fallback", b"w:pPr"],
+                characters: &[
+                    (b"w:tab", b'\t'),
+                    (b"w:br", b'\n'),
+                    (b"w:cr", b'\n'),
+                    (b"w:noBreakHyphen", b'-'),
+                ],
+                paragraphs: &[b"w:p"],
+            },
+            // Annotations are comments, and tracked changes hold deleted text.
+            Document::Odt => Schema {
+                part: "content.xml",
+                body: b"office:text",
+                text: &[],
+                skipped: &[b"office:annotation", b"text:tracked-changes"],
+                characters: &[
+                    (b"text:s", b' '),
+                    (b"text:tab", b'\t'),
+                    (b"text:line-break", b'\n'),
+                ],
+                paragraphs: &[b"text:p", b"text:h"],
+            },
+        }
+    }
+
+    /// Extracts the text of the body of the document `xml`, the main part of a document of this format.
+    fn extract_part_text(self, xml: &[u8]) -> Vec<u8> {
+        let schema = self.schema();
+        let mut text = Vec::new();
+        let mut body_depth = 0;
+        let mut text_depth = 0;
+        let mut skipped_depth = 0;
+
+        for event in Reader::new(xml) {
+            match event {
+                Event::Start(tag) => {
+                    if let Some(&(_, c)) =
+                        schema.characters.iter().find(|(name, _)| *name == tag.name)
+                    {
+                        // Runs of spaces are a single element with a count.
+                        let count = tag
+                            .attribute(b"text:c")
+                            .and_then(|count| String::from_utf8(count).ok()?.parse().ok())
+                            .unwrap_or(1);
+                        if body_depth > 0 && skipped_depth == 0 {
+                            text.extend(std::iter::repeat_n(c, count));
+                        }
+                    }
+                    if !tag.empty {
+                        if tag.name == schema.body {
+                            body_depth += 1;
+                        }
+                        if schema.text.contains(&tag.name) {
+                            text_depth += 1;
+                        }
+                        if schema.skipped.contains(&tag.name) {
+                            skipped_depth += 1;
+                        }
+                    }
+                }
+                Event::End(name) => {
+                    if name == schema.body {
+                        body_depth -= 1;
+                    }
+                    if schema.text.contains(&name) {
+                        text_depth -= 1;
+                    }
+                    if schema.skipped.contains(&name) {
+                        skipped_depth -= 1;
+                    }
+                    if schema.paragraphs.contains(&name) && body_depth > 0 && skipped_depth == 0 {
+                        text.push(b'\n');
+                    }
+                }
+                Event::Text(data) => {
+                    let is_text = schema.text.is_empty() || text_depth > 0;
+                    if is_text && body_depth > 0 && skipped_depth == 0 {
+                        text.extend_from_slice(&data);
+                    }
+                }
+            }
+        }
+
+        text
+    }
+
+    /// Extracts the text of the body of the document `archive`, a document of this format, with a line
+    /// per paragraph.
+    ///
+    /// Text runs split within a word are joined, so words are counted as a word processor counts them.
+    pub fn extract_text(self, archive: &[u8]) -> Result<Vec<u8>, Message> {
+        let archive = Archive::parse(archive)?;
+        let xml = archive.read(self.schema().part)?;
+        Ok(self.extract_part_text(&xml))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docx() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:pPr><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr><w:r><w:t>Hel</w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>lo</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve">world &amp; </w:t></w:r><w:del><w:r><w:delText>gone</w:delText></w:r></w:del></w:p>
+    <w:p><w:r><mc:AlternateContent><mc:Choice><w:t>Box</w:t></mc:Choice><mc:Fallback><w:t>Box</w:t></mc:Fallback></mc:AlternateContent></w:r></w:p>
+  </w:body>
+</w:document>"#;
+
+        assert_eq!(
+            Document::Docx.extract_part_text(xml),
+            b"Hello\tworld & \nBox\n"
+        );
+    }
+
+    #[test]
+    fn test_odt() {
+        let xml = br#"<office:document-content><office:automatic-styles><style:style style:name="P1"/></office:automatic-styles>
+<office:body><office:text><text:h>Title</text:h><text:p>One<text:s text:c="2"/>two<office:annotation><text:p>Comment</text:p></office:annotation></text:p></office:text></office:body></office:document-content>"#;
+
+        assert_eq!(Document::Odt.extract_part_text(xml), b"Title\nOne  two\n");
+    }
+}
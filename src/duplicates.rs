@@ -0,0 +1,159 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::io::{self, Read};
+
+use wc_rs::Counts;
+
+/// The length and hash of the contents of an input, equal for inputs with the same contents.
+///
+/// The hash is only stable within a run, which is all that grouping the inputs of a run needs.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Digest {
+    /// The length of the contents in bytes.
+    pub length: u64,
+    /// The hash of the contents.
+    pub hash: u64,
+}
+
+/// The hash of the contents of an input, found as it is read in pieces split anywhere.
+#[derive(Clone, Debug, Default)]
+pub struct ContentHash {
+    hasher: DefaultHasher,
+    length: u64,
+}
+
+impl ContentHash {
+    /// Hashes `bytes`, which continue the contents read so far.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.hasher.write(bytes);
+        self.length += bytes.len() as u64;
+    }
+
+    /// Returns the digest of all of the contents read.
+    pub fn digest(&self) -> Digest {
+        Digest {
+            length: self.length,
+            hash: self.hasher.finish(),
+        }
+    }
+}
+
+/// A reader hashing the bytes read through it from another.
+pub struct Hashing<'a, R> {
+    /// The reader being hashed.
+    inner: R,
+    /// The hash of the bytes read so far.
+    hash: Option<&'a mut ContentHash>,
+}
+
+impl<'a, R: Read> Hashing<'a, R> {
+    /// Returns a reader of `inner` adding the bytes it reads to `hash`, if given.
+    pub fn new(inner: R, hash: Option<&'a mut ContentHash>) -> Self {
+        Hashing { inner, hash }
+    }
+}
+
+impl<R: Read> Read for Hashing<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        if let Some(hash) = &mut self.hash {
+            hash.feed(&buf[..length]);
+        }
+        Ok(length)
+    }
+}
+
+/// Returns the groups of the names of `inputs`, each the name, digest and counts of an input, with the
+/// same digest as another, in the order of their first inputs, along with the counts each of the group
+/// has.
+pub fn groups(inputs: &[(String, Digest, Counts)]) -> Vec<(Vec<&str>, Counts)> {
+    let mut groups: Vec<(Vec<&str>, Counts)> = Vec::new();
+    let mut indices: HashMap<Digest, usize> = HashMap::new();
+    for (name, digest, counts) in inputs {
+        match indices.get(digest) {
+            Some(&index) => groups[index].0.push(name),
+            None => {
+                indices.insert(*digest, groups.len());
+                groups.push((vec![name], *counts));
+            }
+        }
+    }
+    groups.retain(|(names, _)| names.len() > 1);
+    groups
+}
+
+/// Renders the groups of duplicate inputs, each a heading with the number of inputs and their counts
+/// followed by their names indented, one to a line.
+pub fn render(groups: &[(Vec<&str>, Counts)]) -> String {
+    let mut text = String::new();
+    for (index, (names, counts)) in groups.iter().enumerate() {
+        text.push_str(&format!(
+            "duplicates {}: {} files of {} lines, {} words, {} bytes\n",
+            index + 1,
+            names.len(),
+            counts.lines,
+            counts.words,
+            counts.bytes
+        ));
+        for name in names {
+            text.push_str(&format!("    {}\n", name));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the digest of `contents` read in two pieces split at `split`.
+    fn digest(contents: &[u8], split: usize) -> Digest {
+        let mut hash = ContentHash::default();
+        let mut reader = Hashing::new(&contents[..split], Some(&mut hash));
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        hash.feed(&contents[split..]);
+        hash.digest()
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(digest(b"same words\n", 0), digest(b"same words\n", 4));
+        assert_ne!(digest(b"same words\n", 0), digest(b"other words\n", 0));
+
+        let counts = |words| Counts {
+            words,
+            lines: 1,
+            bytes: words * 5,
+            ..Counts::default()
+        };
+        let inputs = vec![
+            ("a.txt".to_string(), digest(b"one two\n", 3), counts(2)),
+            ("b.txt".to_string(), digest(b"unique\n", 0), counts(1)),
+            ("c/a.txt".to_string(), digest(b"one two\n", 0), counts(2)),
+            ("empty".to_string(), digest(b"", 0), counts(0)),
+            ("d/a.txt".to_string(), digest(b"one two\n", 8), counts(2)),
+            ("also-empty".to_string(), digest(b"", 0), counts(0)),
+        ];
+
+        let groups = groups(&inputs);
+        assert_eq!(
+            groups,
+            vec![
+                (vec!["a.txt", "c/a.txt", "d/a.txt"], counts(2)),
+                (vec!["empty", "also-empty"], counts(0)),
+            ]
+        );
+        assert_eq!(
+            render(&groups[..1]),
+            "duplicates 1: 3 files of 1 lines, 2 words, 10 bytes\n    a.txt\n    c/a.txt\n    d/a.txt\n"
+        );
+    }
+}
@@ -0,0 +1,410 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::markup::{terminator, trim};
+use crate::xml;
+
+/// HTML elements whose contents are not text.
+const SKIPPED_HTML_ELEMENTS: [&[u8]; 3] = [b"head", b"script", b"style"];
+
+/// HTML elements which separate the text before and after them.
+const BLOCK_HTML_ELEMENTS: [&[u8]; 13] = [
+    b"blockquote",
+    b"br",
+    b"div",
+    b"h1",
+    b"h2",
+    b"h3",
+    b"h4",
+    b"h5",
+    b"h6",
+    b"li",
+    b"p",
+    b"td",
+    b"tr",
+];
+
+/// Returns the lines of `text`, each including its terminator.
+fn lines(text: &[u8]) -> impl Iterator<Item = &[u8]> {
+    text.split_inclusive(|&byte| byte == b'\n')
+}
+
+/// Returns `true` if `line` starts a message of an mbox mailbox.
+fn is_separator(line: &[u8]) -> bool {
+    line.starts_with(b"From ")
+}
+
+/// The header fields of a message or body part, as pairs of names and values.
+type Fields = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Splits `message` into its header fields, with names in lowercase and values unfolded, and its body.
+fn split_headers(message: &[u8]) -> (Fields, &[u8]) {
+    let mut fields = Fields::new();
+    let mut offset = 0;
+    for line in lines(message) {
+        offset += line.len();
+        let content = &line[..line.len() - terminator(line).len()];
+        if content.is_empty() {
+            return (fields, &message[offset..]);
+        }
+
+        match (content[0], fields.last_mut()) {
+            // Continuation lines fold long values.
+            (b' ', Some((_, value))) | (b'\t', Some((_, value))) => {
+                value.push(b' ');
+                value.extend_from_slice(trim(content));
+            }
+            _ => {
+                if let Some(colon) = content.iter().position(|&byte| byte == b':') {
+                    fields.push((
+                        content[..colon].to_ascii_lowercase(),
+                        trim(&content[colon + 1..]).to_vec(),
+                    ));
+                }
+            }
+        }
+    }
+    (fields, &message[message.len()..])
+}
+
+/// Returns the value of the header field `name` in `fields`, which must be in lowercase.
+fn field<'a>(fields: &'a Fields, name: &[u8]) -> &'a [u8] {
+    fields
+        .iter()
+        .find(|(field, _)| field == name)
+        .map_or(&[], |(_, value)| &value[..])
+}
+
+/// Returns the value of the parameter `name`, in lowercase, of the structured header field value `value`.
+fn parameter(value: &[u8], name: &[u8]) -> Option<Vec<u8>> {
+    value
+        .split(|&byte| byte == b';')
+        .skip(1)
+        .find_map(|parameter| {
+            let equals = parameter.iter().position(|&byte| byte == b'=')?;
+            if !trim(&parameter[..equals]).eq_ignore_ascii_case(name) {
+                return None;
+            }
+            let value = trim(&parameter[equals + 1..]);
+            let value = value.strip_prefix(b"\"").unwrap_or(value);
+            Some(value.strip_suffix(b"\"").unwrap_or(value).to_vec())
+        })
+}
+
+/// Returns the structured header field value `value` without its parameters, in lowercase, such as the
+/// media type of a `Content-Type` field.
+fn without_parameters(value: &[u8]) -> Vec<u8> {
+    let end = value
+        .iter()
+        .position(|&byte| byte == b';')
+        .unwrap_or(value.len());
+    trim(&value[..end]).to_ascii_lowercase()
+}
+
+/// Decodes the quoted-printable text `encoded`.
+fn decode_quoted_printable(encoded: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+    while index < encoded.len() {
+        let byte = encoded[index];
+        index += 1;
+        if byte != b'=' {
+            decoded.push(byte);
+            continue;
+        }
+
+        // A soft line break is an `=` at the end of a line, and is removed with the line break.
+        let rest = &encoded[index..];
+        let hex = rest
+            .get(..2)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+        if rest.starts_with(b"\r\n") || rest.starts_with(b"\n") {
+            index += if rest[0] == b'\r' { 2 } else { 1 };
+        } else if let Some(value) = hex {
+            decoded.push(value);
+            index += 2;
+        } else {
+            decoded.push(byte);
+        }
+    }
+    decoded
+}
+
+/// Decodes the base64 text `encoded`, ignoring characters outside the base64 alphabet.
+fn decode_base64(encoded: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut count = 0;
+    for &byte in encoded {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => continue,
+        };
+        buffer = buffer << 6 | u32::from(value);
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            decoded.push((buffer >> count) as u8);
+        }
+    }
+    decoded
+}
+
+/// Appends the text of the HTML document `html` to `text`, without its tags.
+fn push_html_text(html: &[u8], text: &mut Vec<u8>) {
+    let mut skipped_element: Option<&[u8]> = None;
+    let mut rest = html;
+    while let Some(start) = rest.iter().position(|&byte| byte == b'<') {
+        if skipped_element.is_none() {
+            text.extend_from_slice(&xml::unescape(&rest[..start]));
+        }
+        let end = rest[start..]
+            .iter()
+            .position(|&byte| byte == b'>')
+            .map_or(rest.len(), |end| start + end + 1);
+        let tag = &rest[start + 1..end.saturating_sub(1).max(start + 1)];
+        rest = &rest[end..];
+
+        let name_len = tag
+            .iter()
+            .position(|byte| byte.is_ascii_whitespace() || *byte == b'>' || *byte == b'/')
+            .unwrap_or(tag.len());
+        match (tag.first(), skipped_element) {
+            (Some(b'/'), Some(skipped)) if tag[1..].eq_ignore_ascii_case(skipped) => {
+                skipped_element = None;
+            }
+            (Some(b'/'), _) | (Some(b'!'), _) => {}
+            (_, None) => {
+                let name = tag[..name_len].to_ascii_lowercase();
+                skipped_element = SKIPPED_HTML_ELEMENTS
+                    .iter()
+                    .find(|&&element| element == &name[..])
+                    .cloned();
+                if BLOCK_HTML_ELEMENTS.contains(&&name[..]) {
+                    text.push(b' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    if skipped_element.is_none() {
+        text.extend_from_slice(&xml::unescape(rest));
+    }
+}
+
+/// Appends the text of the body of the MIME entity `entity` to `text`.
+///
+/// Of alternative representations only the first plain text one is counted, and attachments and
+/// non-textual parts are skipped entirely.
+fn push_entity_text(entity: &[u8], text: &mut Vec<u8>) {
+    let (fields, body) = split_headers(entity);
+    let content_type = field(&fields, b"content-type");
+    let media_type = without_parameters(content_type);
+    let disposition = without_parameters(field(&fields, b"content-disposition"));
+    if disposition == b"attachment" {
+        return;
+    }
+
+    if media_type.starts_with(b"multipart/") {
+        let boundary = match parameter(content_type, b"boundary") {
+            Some(boundary) => boundary,
+            None => return,
+        };
+        let parts = multipart_parts(body, &boundary);
+        if media_type == b"multipart/alternative" {
+            let preferred = parts
+                .iter()
+                .find(|part| {
+                    let (fields, _) = split_headers(part);
+                    without_parameters(field(&fields, b"content-type")) == b"text/plain"
+                })
+                .or_else(|| parts.first());
+            if let Some(part) = preferred {
+                push_entity_text(part, text);
+            }
+        } else {
+            for part in parts {
+                push_entity_text(part, text);
+            }
+        }
+        return;
+    }
+
+    if media_type == b"message/rfc822" {
+        push_entity_text(body, text);
+        return;
+    }
+
+    // Entities without a content type are plain text.
+    let is_html = media_type == b"text/html";
+    if !(media_type.is_empty() || media_type == b"text/plain" || is_html) {
+        return;
+    }
+    let encoding = field(&fields, b"content-transfer-encoding").to_ascii_lowercase();
+    let decoded = match &encoding[..] {
+        b"base64" => decode_base64(body),
+        b"quoted-printable" => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    };
+    if is_html {
+        push_html_text(&decoded, text);
+    } else {
+        text.extend_from_slice(&decoded);
+    }
+    if !text.ends_with(b"\n") {
+        text.push(b'\n');
+    }
+}
+
+/// Returns the body parts of the multipart body `body`, delimited by `boundary`.
+fn multipart_parts<'a>(body: &'a [u8], boundary: &[u8]) -> Vec<&'a [u8]> {
+    let mut delimiter = b"--".to_vec();
+    delimiter.extend_from_slice(boundary);
+
+    let mut parts = Vec::new();
+    let mut part_start = None;
+    let mut offset = 0;
+    for line in lines(body) {
+        let content = trim(line);
+        if content.starts_with(&delimiter) {
+            if let Some(start) = part_start {
+                parts.push(&body[start..offset]);
+            }
+            if content[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            part_start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    if let Some(start) = part_start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+/// The messages of an mbox mailbox or a single message file.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Mailbox {
+    /// The number of messages.
+    pub messages: u64,
+    /// The text of the bodies of the messages, without their headers or attachments.
+    pub text: Vec<u8>,
+}
+
+impl Mailbox {
+    /// Parses `input`, either an mbox mailbox or a single RFC 5322 message such as an `.eml` file.
+    pub fn parse(input: &[u8]) -> Self {
+        let mut mailbox = Mailbox::default();
+        if !is_separator(input) {
+            mailbox.messages = 1;
+            push_entity_text(input, &mut mailbox.text);
+            return mailbox;
+        }
+
+        // Messages begin with a `From ` line, and lines of their bodies beginning `From ` are quoted.
+        let mut message = Vec::new();
+        let mut previous_blank = true;
+        for line in lines(input) {
+            if is_separator(line) && previous_blank {
+                if mailbox.messages > 0 {
+                    push_entity_text(&message, &mut mailbox.text);
+                }
+                mailbox.messages += 1;
+                message.clear();
+            } else {
+                let quotes = line.iter().take_while(|&&byte| byte == b'>').count();
+                let unquoted = if quotes > 0 && is_separator(&line[quotes..]) {
+                    &line[1..]
+                } else {
+                    line
+                };
+                message.extend_from_slice(unquoted);
+            }
+            previous_blank = trim(line).is_empty();
+        }
+        if mailbox.messages > 0 {
+            push_entity_text(&message, &mut mailbox.text);
+        }
+        mailbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mbox() {
+        let mbox = b"From alice@example.com Mon Jan  1 00:00:00 2024\n\
+Subject: Plain\n\
+ folded\n\
+\n\
+Hello there, Bob.\n\
+>From the archive.\n\
+\n\
+From bob@example.com Mon Jan  1 00:00:00 2024\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\n\
+\n\
+preamble\n\
+--outer\n\
+Content-Type: multipart/alternative; boundary=inner\n\
+\n\
+--inner\n\
+Content-Type: text/html\n\
+\n\
+<p>Hi &amp; bye</p>\n\
+--inner\n\
+Content-Type: text/plain; charset=utf-8\n\
+Content-Transfer-Encoding: quoted-printable\n\
+\n\
+Hi & b=\n\
+ye =3D done\n\
+--inner--\n\
+--outer\n\
+Content-Type: text/plain\n\
+Content-Transfer-Encoding: base64\n\
+\n\
+U2Vjb25kIHBhcnQ=\n\
+--outer\n\
+Content-Type: application/pdf\n\
+Content-Disposition: attachment; filename=\"a.pdf\"\n\
+Content-Transfer-Encoding: base64\n\
+\n\
+JVBERi0xLjQK\n\
+--outer--\n";
+        let mailbox = Mailbox::parse(mbox);
+
+        assert_eq!(mailbox.messages, 2);
+        assert_eq!(
+            String::from_utf8(mailbox.text).unwrap(),
+            "Hello there, Bob.\nFrom the archive.\n\nHi & bye = done\nSecond part\n"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let mut text = Vec::new();
+        push_html_text(
+            b"<html><head><style>p {}</style></head><body><p>One</p><p>two &lt;three&gt;</p></body></html>",
+            &mut text,
+        );
+
+        assert_eq!(
+            String::from_utf8(text)
+                .unwrap()
+                .split_whitespace()
+                .collect::<Vec<_>>(),
+            vec!["One", "two", "<three>"]
+        );
+    }
+}
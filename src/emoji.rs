@@ -0,0 +1,208 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use rayon::prelude::*;
+
+use crate::utf8;
+
+/// Size of the chunks a buffer is split into for parallel scanning.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The role of a character within an emoji sequence.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum EmojiClass {
+    /// The character plays no part in an emoji sequence.
+    Other,
+    /// The character is a pictographic emoji.
+    Pictograph,
+    /// The character is a skin-tone modifier.
+    Modifier,
+    /// The character extends the preceding emoji (variation selector or tag).
+    Extender,
+    /// The character is a zero-width joiner.
+    Joiner,
+    /// The character is a regional indicator, two of which form a flag.
+    RegionalIndicator,
+    /// The character may begin a keycap sequence.
+    KeycapBase,
+    /// The character is the combining enclosing keycap.
+    Keycap,
+}
+
+impl From<char> for EmojiClass {
+    /// Returns the role of `c` within an emoji sequence.
+    fn from(c: char) -> Self {
+        match c as u32 {
+            0x1F3FB..=0x1F3FF => EmojiClass::Modifier,
+            0x1F1E6..=0x1F1FF => EmojiClass::RegionalIndicator,
+            0x200D => EmojiClass::Joiner,
+            0xFE0F | 0xE0020..=0xE007F => EmojiClass::Extender,
+            0x20E3 => EmojiClass::Keycap,
+            0x23 | 0x2A | 0x30..=0x39 => EmojiClass::KeycapBase,
+            0x231A..=0x231B
+            | 0x23E9..=0x23F3
+            | 0x23F8..=0x23FA
+            | 0x2600..=0x27BF
+            | 0x2B50
+            | 0x2B55
+            | 0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F300..=0x1F3FA
+            | 0x1F400..=0x1FAFF => EmojiClass::Pictograph,
+            _ => EmojiClass::Other,
+        }
+    }
+}
+
+/// Returns `true` if `c` is a pictographic emoji, which may be joined to another by a zero-width joiner.
+pub fn is_pictograph(c: char) -> bool {
+    EmojiClass::from(c) == EmojiClass::Pictograph
+}
+
+/// The state of the emoji sequence state machine.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum State {
+    /// Outside of an emoji sequence.
+    Outside = 0,
+    /// Inside of an emoji sequence.
+    InEmoji = 1,
+    /// Immediately after a zero-width joiner which followed an emoji.
+    AfterJoiner = 2,
+    /// Immediately after the first regional indicator of a flag.
+    AfterIndicator = 3,
+    /// After a character which may begin a keycap sequence.
+    AfterKeycapBase = 4,
+}
+
+impl State {
+    /// Returns the state after a character of class `class`, and the number of emoji it began.
+    fn next(self, class: EmojiClass) -> (State, u64) {
+        match (self, class) {
+            (State::AfterJoiner, EmojiClass::Pictograph) => (State::InEmoji, 0),
+            (_, EmojiClass::Pictograph) => (State::InEmoji, 1),
+            (State::InEmoji, EmojiClass::Modifier) => (State::InEmoji, 0),
+            (_, EmojiClass::Modifier) => (State::InEmoji, 1),
+            (State::InEmoji, EmojiClass::Extender) => (State::InEmoji, 0),
+            (State::AfterKeycapBase, EmojiClass::Extender) => (State::AfterKeycapBase, 0),
+            (State::InEmoji, EmojiClass::Joiner) => (State::AfterJoiner, 0),
+            (State::AfterIndicator, EmojiClass::RegionalIndicator) => (State::InEmoji, 0),
+            (_, EmojiClass::RegionalIndicator) => (State::AfterIndicator, 1),
+            (_, EmojiClass::KeycapBase) => (State::AfterKeycapBase, 0),
+            (State::AfterKeycapBase, EmojiClass::Keycap) => (State::InEmoji, 1),
+            _ => (State::Outside, 0),
+        }
+    }
+}
+
+/// Representation of a chunk of text when counting emoji.
+///
+/// An emoji sequence may span chunks, so the chunk is represented by the transition it effects on the
+/// emoji sequence state machine from each possible entry state.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct EmojiFlux {
+    /// The exit state and number of emoji begun, indexed by entry state.
+    transitions: [(State, u64); 5],
+}
+
+impl EmojiFlux {
+    /// Returns a new instance of the receiver encoding a single character of class `class`.
+    fn new(class: EmojiClass) -> Self {
+        EmojiFlux {
+            transitions: [
+                State::Outside.next(class),
+                State::InEmoji.next(class),
+                State::AfterJoiner.next(class),
+                State::AfterIndicator.next(class),
+                State::AfterKeycapBase.next(class),
+            ],
+        }
+    }
+
+    /// Returns a new EmojiFlux spanning the receiver on the left, and `rhs` on the right.
+    fn span(self, rhs: EmojiFlux) -> Self {
+        let mut transitions = self.transitions;
+        for transition in transitions.iter_mut() {
+            let (state, emoji) = rhs.transitions[transition.0 as usize];
+            *transition = (state, transition.1 + emoji);
+        }
+
+        EmojiFlux { transitions }
+    }
+
+    /// Returns the number of emoji in the chunk, assuming it does not continue a preceding sequence.
+    pub fn emoji(&self) -> u64 {
+        self.transitions[State::Outside as usize].1
+    }
+}
+
+/// Takes two optional EmojiFlux instances and returns, where possible, the span of the two.
+pub fn span_opt(lhs: Option<EmojiFlux>, rhs: Option<EmojiFlux>) -> Option<EmojiFlux> {
+    match (lhs, rhs) {
+        (Some(left_flux), Some(right_flux)) => Some(left_flux.span(right_flux)),
+        (flux, None) | (None, flux) => flux,
+    }
+}
+
+/// Computes the emoji flux over the provided input byte string.
+///
+/// Bytes which are not valid UTF-8 end any emoji sequence in progress.
+pub fn emoji_flux_over_byte_string<T>(input: T) -> Option<EmojiFlux>
+where
+    T: AsRef<[u8]>,
+{
+    // Each chunk is scanned on its own, split where no character is, and the fluxes of the chunks spanned.
+    utf8::split_chunks(input.as_ref(), CHUNK_SIZE)
+        .par_iter()
+        .map(|chunk| {
+            chunk.utf8_chunks().fold(None, |flux, piece| {
+                let valid = piece.valid().chars().fold(flux, |flux, c| {
+                    span_opt(flux, Some(EmojiFlux::new(EmojiClass::from(c))))
+                });
+                let invalid = piece
+                    .invalid()
+                    .first()
+                    .map(|_| EmojiFlux::new(EmojiClass::Other));
+                span_opt(valid, invalid)
+            })
+        })
+        .reduce(|| None, span_opt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emoji(input: &str) -> u64 {
+        emoji_flux_over_byte_string(input)
+            .map(|f| f.emoji())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_emoji_sequences() {
+        assert_eq!(emoji("plain text 123"), 0);
+        assert_eq!(emoji("\u{1F600} \u{1F44D}\u{1F3FD}"), 2);
+        assert_eq!(emoji("\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}"), 1);
+        assert_eq!(emoji("\u{1F1EF}\u{1F1F5}\u{1F1FA}\u{1F1F8}"), 2);
+        assert_eq!(emoji("1\u{FE0F}\u{20E3} \u{2764}\u{FE0F}"), 2);
+    }
+
+    #[test]
+    fn test_emoji_flux_span_across_joiner() {
+        let flux_l = emoji_flux_over_byte_string("\u{1F468}\u{200D}");
+        let flux_r = emoji_flux_over_byte_string("\u{1F4BB} done");
+
+        assert_eq!(span_opt(flux_l, flux_r).map(|f| f.emoji()), Some(1));
+
+        // Joined sequences straddle the boundaries of the chunks scanned in parallel.
+        let family = "\u{1F469}\u{200D}\u{1F467} ";
+        let text = family.repeat(CHUNK_SIZE / family.len() * 3);
+        assert_eq!(emoji(&text), (CHUNK_SIZE / family.len() * 3) as u64);
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::str::FromStr;
+
+use crate::i18n::Message;
+
+/// The character encoding of an input.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum Encoding {
+    /// UTF-8, or any ASCII-compatible encoding, counted as it is.
+    #[default]
+    Utf8,
+    /// UTF-16 with the low byte of each unit first, as Windows writes it.
+    Utf16Le,
+    /// UTF-16 with the high byte of each unit first.
+    Utf16Be,
+    /// UTF-16 of the byte order of a byte order mark the input starts with, otherwise UTF-8.
+    Auto,
+}
+
+impl FromStr for Encoding {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "utf8" => Ok(Encoding::Utf8),
+            "utf16le" => Ok(Encoding::Utf16Le),
+            "utf16be" => Ok(Encoding::Utf16Be),
+            "auto" => Ok(Encoding::Auto),
+            _ => Err(Message::new("unknown-encoding").arg("value", s)),
+        }
+    }
+}
+
+/// The byte order mark, as a UTF-16 code unit.
+const BYTE_ORDER_MARK: u16 = 0xFEFF;
+
+/// Appends `c` to `output` in UTF-8.
+fn push_char(output: &mut Vec<u8>, c: char) {
+    output.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+/// Decodes an input in an encoding other than UTF-8, fed to it in order in pieces split anywhere, to UTF-8.
+///
+/// A byte order mark at the start of UTF-16 input is dropped, and unpaired surrogates and a final odd byte
+/// are decoded as replacement characters. Input detected to be UTF-8 is passed through unchanged, byte
+/// order mark and all, as it is counted without a decoder.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Transcoder {
+    /// The encoding of the input, or `Auto` until enough of it has been fed to tell.
+    encoding: Encoding,
+    /// The bytes of a code unit, or of a byte order mark, split across the end of the input fed so far.
+    pending: Vec<u8>,
+    /// A high surrogate awaiting the low surrogate completing its pair.
+    high_surrogate: Option<u16>,
+    /// Whether the next code unit is the first of the input, which is dropped if it is a byte order mark.
+    at_start: bool,
+}
+
+impl Transcoder {
+    /// Returns a decoder of input in `encoding`.
+    pub fn new(encoding: Encoding) -> Self {
+        Transcoder {
+            encoding,
+            pending: Vec::new(),
+            high_surrogate: None,
+            at_start: true,
+        }
+    }
+
+    /// Returns the encoding of the input, or `Auto` if too little of it has been fed to tell.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Decodes `bytes`, which continue the input fed so far, appending the UTF-8 to `output`.
+    pub fn feed(&mut self, bytes: &[u8], output: &mut Vec<u8>) {
+        let mut rest = bytes;
+        if self.encoding == Encoding::Auto {
+            let needed = 2 - self.pending.len();
+            let taken = needed.min(rest.len());
+            self.pending.extend_from_slice(&rest[..taken]);
+            rest = &rest[taken..];
+            match self.pending[..] {
+                [0xFF, 0xFE] => self.encoding = Encoding::Utf16Le,
+                [0xFE, 0xFF] => self.encoding = Encoding::Utf16Be,
+                [_, _] => self.encoding = Encoding::Utf8,
+                _ => return,
+            }
+            // A byte order mark is dropped, while the first bytes of UTF-8 text are passed on.
+            match self.encoding {
+                Encoding::Utf8 => output.append(&mut self.pending),
+                _ => {
+                    self.pending.clear();
+                    self.at_start = false;
+                }
+            }
+        }
+
+        let big_endian = match self.encoding {
+            Encoding::Utf16Le => false,
+            Encoding::Utf16Be => true,
+            _ => return output.extend_from_slice(rest),
+        };
+        if self.pending.len() == 1 {
+            if let Some((&byte, tail)) = rest.split_first() {
+                let first = self.pending[0];
+                self.pending.clear();
+                self.decode_unit([first, byte], big_endian, output);
+                rest = tail;
+            }
+        }
+        let mut units = rest.chunks_exact(2);
+        for unit in &mut units {
+            self.decode_unit([unit[0], unit[1]], big_endian, output);
+        }
+        self.pending.extend_from_slice(units.remainder());
+    }
+
+    /// Decodes the code unit of the bytes `unit`, appending any character it completes to `output`.
+    fn decode_unit(&mut self, unit: [u8; 2], big_endian: bool, output: &mut Vec<u8>) {
+        let unit = if big_endian {
+            u16::from_be_bytes(unit)
+        } else {
+            u16::from_le_bytes(unit)
+        };
+        if std::mem::replace(&mut self.at_start, false) && unit == BYTE_ORDER_MARK {
+            return;
+        }
+
+        if let Some(high) = self.high_surrogate.take() {
+            if let 0xDC00..=0xDFFF = unit {
+                let scalar =
+                    0x10000 + ((u32::from(high) - 0xD800) << 10) + (u32::from(unit) - 0xDC00);
+                push_char(
+                    output,
+                    char::from_u32(scalar).unwrap_or(char::REPLACEMENT_CHARACTER),
+                );
+                return;
+            }
+            push_char(output, char::REPLACEMENT_CHARACTER);
+        }
+        match unit {
+            0xD800..=0xDBFF => self.high_surrogate = Some(unit),
+            _ => push_char(
+                output,
+                char::from_u32(u32::from(unit)).unwrap_or(char::REPLACEMENT_CHARACTER),
+            ),
+        }
+    }
+
+    /// Appends whatever of the input is left undecoded at its end to `output`.
+    pub fn finish(&mut self, output: &mut Vec<u8>) {
+        if self.encoding == Encoding::Auto {
+            output.append(&mut self.pending);
+            return;
+        }
+        if self.high_surrogate.take().is_some() {
+            push_char(output, char::REPLACEMENT_CHARACTER);
+        }
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            push_char(output, char::REPLACEMENT_CHARACTER);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `input` decoded from `encoding`, fed in two pieces split at `split`.
+    fn transcode(input: &[u8], encoding: Encoding, split: usize) -> Vec<u8> {
+        let mut transcoder = Transcoder::new(encoding);
+        let mut output = Vec::new();
+        transcoder.feed(&input[..split], &mut output);
+        transcoder.feed(&input[split..], &mut output);
+        transcoder.finish(&mut output);
+        output
+    }
+
+    #[test]
+    fn test_transcoder() {
+        let text = "caf\u{E9} \u{1F389}\r\n";
+        let units: Vec<u16> = "\u{FEFF}caf\u{E9} \u{1F389}\r\n".encode_utf16().collect();
+        let le: Vec<u8> = units.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+        let be: Vec<u8> = units.iter().flat_map(|unit| unit.to_be_bytes()).collect();
+
+        for split in 0..=le.len() {
+            assert_eq!(transcode(&le, Encoding::Utf16Le, split), text.as_bytes());
+            assert_eq!(transcode(&le, Encoding::Auto, split), text.as_bytes());
+            assert_eq!(transcode(&be, Encoding::Utf16Be, split), text.as_bytes());
+            assert_eq!(transcode(&be, Encoding::Auto, split), text.as_bytes());
+        }
+        assert_eq!(
+            transcode(b"\xEF\xBB\xBFplain", Encoding::Auto, 1),
+            b"\xEF\xBB\xBFplain"
+        );
+        assert_eq!(transcode(b"x", Encoding::Auto, 0), b"x");
+        assert_eq!(transcode(b"", Encoding::Auto, 0), b"");
+    }
+
+    #[test]
+    fn test_transcoder_invalid() {
+        // A lone low surrogate, a high surrogate followed by a letter, and a final odd byte.
+        let input = [0x00, 0xDC, 0x3D, 0xD8, b'a', 0x00, b'b'];
+        assert_eq!(
+            transcode(&input, Encoding::Utf16Le, 3),
+            "\u{FFFD}\u{FFFD}a\u{FFFD}".as_bytes()
+        );
+        assert_eq!(
+            transcode(&[0xD8, 0x3D], Encoding::Utf16Be, 1),
+            "\u{FFFD}".as_bytes()
+        );
+        assert_eq!("utf16le".parse(), Ok(Encoding::Utf16Le));
+        assert!("latin1".parse::<Encoding>().is_err());
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::i18n::Message;
+use crate::xml::{Event, Reader};
+use crate::zip::Archive;
+
+/// XHTML elements whose contents are not text.
+const SKIPPED_ELEMENTS: [&[u8]; 4] = [b"head", b"script", b"style", b"svg"];
+
+/// XHTML elements which separate the text before and after them, as a browser would lay them out.
+const BLOCK_ELEMENTS: [&[u8]; 22] = [
+    b"address",
+    b"aside",
+    b"blockquote",
+    b"br",
+    b"dd",
+    b"div",
+    b"dt",
+    b"figcaption",
+    b"h1",
+    b"h2",
+    b"h3",
+    b"h4",
+    b"h5",
+    b"h6",
+    b"hr",
+    b"li",
+    b"p",
+    b"pre",
+    b"section",
+    b"td",
+    b"th",
+    b"tr",
+];
+
+/// A chapter of an e-book, being a document of its reading order.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Chapter {
+    /// The path of the chapter within the e-book.
+    pub path: String,
+    /// The text of the chapter, with a line per block.
+    pub text: Vec<u8>,
+}
+
+/// Returns the local name of the qualified name `name`, without any namespace prefix.
+fn local_name(name: &[u8]) -> &[u8] {
+    match name.iter().rposition(|&byte| byte == b':') {
+        Some(index) => &name[index + 1..],
+        None => name,
+    }
+}
+
+/// Returns the path within the archive of the target of `href`, relative to the file at `base`.
+fn resolve(base: &str, href: &[u8]) -> String {
+    let href = String::from_utf8_lossy(href);
+    let href = href.split('#').next().unwrap_or_default();
+
+    let mut segments: Vec<&str> = base.split('/').collect();
+    segments.pop();
+    for segment in href.split('/') {
+        match segment {
+            "." | "" => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    // Paths in the package document are URLs, which may be percent-encoded.
+    let path = segments.join("/");
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        let escape = match byte {
+            b'%' => bytes
+                .clone()
+                .take(2)
+                .map(|digit| (digit as char).to_digit(16))
+                .collect::<Option<Vec<u32>>>(),
+            _ => None,
+        };
+        match escape.as_deref() {
+            Some(&[high, low]) => {
+                decoded.push((high << 4 | low) as u8);
+                bytes.nth(1);
+            }
+            _ => decoded.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Extracts the text of the body of the XHTML document `xhtml`, with a line per block.
+fn xhtml_text(xhtml: &[u8]) -> Vec<u8> {
+    let mut text = Vec::new();
+    let mut body_depth = 0;
+    let mut skipped_depth = 0;
+
+    for event in Reader::new(xhtml) {
+        match event {
+            Event::Start(tag) => {
+                let name = local_name(tag.name);
+                if tag.empty {
+                    if BLOCK_ELEMENTS.contains(&name) && body_depth > 0 && skipped_depth == 0 {
+                        text.push(b'\n');
+                    }
+                    continue;
+                }
+                if name == b"body" {
+                    body_depth += 1;
+                }
+                if SKIPPED_ELEMENTS.contains(&name) {
+                    skipped_depth += 1;
+                }
+            }
+            Event::End(name) => {
+                let name = local_name(name);
+                if name == b"body" {
+                    body_depth -= 1;
+                }
+                if SKIPPED_ELEMENTS.contains(&name) {
+                    skipped_depth -= 1;
+                }
+                if BLOCK_ELEMENTS.contains(&name) && body_depth > 0 && skipped_depth == 0 {
+                    text.push(b'\n');
+                }
+            }
+            Event::Text(data) => {
+                if body_depth > 0 && skipped_depth == 0 {
+                    text.extend_from_slice(&data);
+                }
+            }
+        }
+    }
+
+    text
+}
+
+/// Returns the first start tag named `name` in `xml`, ignoring namespace prefixes, as the value of its
+/// attribute `attribute`.
+fn attribute_of_first(xml: &[u8], name: &[u8], attribute: &[u8]) -> Option<Vec<u8>> {
+    Reader::new(xml).find_map(|event| match event {
+        Event::Start(tag) if local_name(tag.name) == name => tag.attribute(attribute),
+        _ => None,
+    })
+}
+
+/// Extracts the chapters of the EPUB e-book `archive` in reading order.
+///
+/// The reading order is the spine of the package document named by the container, and each chapter is
+/// an XHTML document whose body text is extracted.
+pub fn chapters(archive: &[u8]) -> Result<Vec<Chapter>, Message> {
+    let archive = Archive::parse(archive)?;
+    let container = archive.read("META-INF/container.xml")?;
+    let package_path = attribute_of_first(&container, b"rootfile", b"full-path")
+        .map(|path| String::from_utf8_lossy(&path).into_owned())
+        .ok_or_else(|| Message::new("epub-no-package-document"))?;
+    let package = archive.read(&package_path)?;
+
+    let mut manifest = Vec::new();
+    let mut spine = Vec::new();
+    for event in Reader::new(&package) {
+        if let Event::Start(tag) = event {
+            match local_name(tag.name) {
+                b"item" => {
+                    if let (Some(id), Some(href)) = (tag.attribute(b"id"), tag.attribute(b"href")) {
+                        manifest.push((id, href));
+                    }
+                }
+                b"itemref" => spine.extend(tag.attribute(b"idref")),
+                _ => {}
+            }
+        }
+    }
+
+    spine
+        .iter()
+        .map(|idref| {
+            let (_, href) = manifest.iter().find(|(id, _)| id == idref).ok_or_else(|| {
+                Message::new("epub-spine-item-not-in-manifest")
+                    .arg("item", String::from_utf8_lossy(idref))
+            })?;
+            let path = resolve(&package_path, href);
+            let text = xhtml_text(&archive.read(&path)?);
+            Ok(Chapter { path, text })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            resolve("OEBPS/content.opf", b"text/ch%201.xhtml"),
+            "OEBPS/text/ch 1.xhtml"
+        );
+        assert_eq!(
+            resolve("OEBPS/text/nav.xhtml", b"../images/a.png#x"),
+            "OEBPS/images/a.png"
+        );
+        assert_eq!(resolve("content.opf", b"./ch1.xhtml"), "ch1.xhtml");
+    }
+
+    #[test]
+    fn test_xhtml_text() {
+        let xhtml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Ignored</title><style>p { }</style></head>
+<body><h1>Chapter&#160;One</h1><p>It was a <em>dark</em> and st<span>ormy</span> night.<br/>The end.</p></body></html>"#;
+
+        assert_eq!(
+            String::from_utf8(xhtml_text(xhtml)).unwrap(),
+            "Chapter\u{A0}One\nIt was a dark and stormy night.\nThe end.\n"
+        );
+    }
+}
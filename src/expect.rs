@@ -0,0 +1,103 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::str::FromStr;
+
+use wc_rs::i18n::{self, Message};
+use wc_rs::Counts;
+
+use crate::columns::Column;
+
+/// A count that the total of all inputs is expected to equal, such as `lines=12345`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Expectation {
+    /// The column of the count, which is a whole number.
+    pub column: Column,
+    /// The expected count.
+    value: u64,
+}
+
+impl Expectation {
+    /// Returns a line describing how the count of the receiver in `total` differs from that expected, or
+    /// `None` if it does not.
+    pub fn mismatch(&self, total: &Counts) -> Option<String> {
+        let counted: u64 = self
+            .column
+            .value(total, total, "")
+            .parse()
+            .expect("Expected columns are whole numbers");
+        if counted == self.value {
+            return None;
+        }
+        let difference = i128::from(counted) - i128::from(self.value);
+        Some(i18n::message(
+            "count-mismatch",
+            &[
+                ("column", &self.column.name()),
+                ("expected", &self.value),
+                ("counted", &counted),
+                ("difference", &format!("{:+}", difference)),
+            ],
+        ))
+    }
+}
+
+impl FromStr for Expectation {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Message::new("malformed-expectation").arg("value", s);
+        let (name, value) = s.split_once('=').ok_or_else(invalid)?;
+        let column: Column = name.trim().parse()?;
+        match column {
+            Column::BytesPerLine
+            | Column::WordsPerLine
+            | Column::BytesPerWord
+            | Column::MeanLineLength
+            | Column::MeanWordsPerLine
+            | Column::LinesShare
+            | Column::WordsShare
+            | Column::BytesShare
+            | Column::File => return Err(Message::new("not-a-count").arg("column", name.trim())),
+            _ => {}
+        }
+        Ok(Expectation {
+            column,
+            value: value.trim().parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expectation() {
+        let total = Counts {
+            lines: 12340,
+            words: 50000,
+            ..Counts::default()
+        };
+        let lines: Expectation = "lines=12345".parse().unwrap();
+        assert_eq!(lines.column, Column::Lines);
+        assert_eq!(
+            lines.mismatch(&total),
+            Some("lines: expected 12345, counted 12340 (-5)".to_string())
+        );
+        assert_eq!(
+            "words = 50000"
+                .parse::<Expectation>()
+                .unwrap()
+                .mismatch(&total),
+            None
+        );
+        assert!("bytes_per_line=3".parse::<Expectation>().is_err());
+        assert!("lines=many".parse::<Expectation>().is_err());
+        assert!("lines".parse::<Expectation>().is_err());
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::output::json_string;
+
+/// The position of a line of an input.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Line {
+    /// The number of the line, counting from one.
+    pub number: u64,
+    /// The offset of the first byte of the line.
+    pub offset: u64,
+    /// The length of the line in bytes, without its terminator.
+    pub length: u64,
+}
+
+/// Notable positions of an input, found as it is read in pieces split anywhere.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Extremes {
+    /// The first of the longest lines, if the input is not empty.
+    pub longest_line: Option<Line>,
+    /// The offset of the first byte which is not part of a valid UTF-8 sequence, if any.
+    pub first_invalid_utf8: Option<u64>,
+    /// The offset of the first NUL byte, if any.
+    pub first_nul: Option<u64>,
+    /// The number of bytes read so far.
+    offset: u64,
+    /// The number of lines completed so far.
+    lines: u64,
+    /// The line being read, if any of it has been.
+    line: Option<Line>,
+    /// The leading bytes of a UTF-8 sequence which began at the end of the last piece.
+    pending: Vec<u8>,
+}
+
+impl Extremes {
+    /// Finds the notable positions of `bytes`, which continue the input read so far.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.first_nul.is_none() {
+            if let Some(index) = bytes.iter().position(|&byte| byte == 0) {
+                self.first_nul = Some(self.offset + index as u64);
+            }
+        }
+        if self.first_invalid_utf8.is_none() {
+            self.validate(bytes);
+        }
+
+        for piece in bytes.split_inclusive(|&byte| byte == b'\n') {
+            let (number, offset) = (self.lines + 1, self.offset);
+            let line = self.line.get_or_insert(Line {
+                number,
+                offset,
+                length: 0,
+            });
+            self.offset += piece.len() as u64;
+            match piece.strip_suffix(b"\n") {
+                Some(content) => {
+                    line.length += content.len() as u64;
+                    self.end_line();
+                }
+                None => line.length += piece.len() as u64,
+            }
+        }
+    }
+
+    /// Records the end of the line being read, if any.
+    fn end_line(&mut self) {
+        if let Some(line) = self.line.take() {
+            self.lines += 1;
+            if self
+                .longest_line
+                .is_none_or(|longest| line.length > longest.length)
+            {
+                self.longest_line = Some(line);
+            }
+        }
+    }
+
+    /// Checks that `bytes`, following any sequence left incomplete by the last piece, are valid UTF-8.
+    fn validate(&mut self, bytes: &[u8]) {
+        let start = self.offset - self.pending.len() as u64;
+        let joined;
+        let text = if self.pending.is_empty() {
+            bytes
+        } else {
+            joined = [&self.pending[..], bytes].concat();
+            &joined[..]
+        };
+
+        let error = match std::str::from_utf8(text) {
+            Ok(_) => {
+                self.pending.clear();
+                return;
+            }
+            Err(error) => error,
+        };
+        match error.error_len() {
+            Some(_) => {
+                self.first_invalid_utf8 = Some(start + error.valid_up_to() as u64);
+                self.pending.clear();
+            }
+            None => self.pending = text[error.valid_up_to()..].to_vec(),
+        }
+    }
+
+    /// Records the end of the input.
+    pub fn finish(&mut self) {
+        if self.first_invalid_utf8.is_none() && !self.pending.is_empty() {
+            self.first_invalid_utf8 = Some(self.offset - self.pending.len() as u64);
+        }
+        self.pending.clear();
+        self.end_line();
+    }
+}
+
+/// Returns `value` as JSON, `null` if it is `None`.
+fn json_option(value: Option<u64>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
+
+/// The notable positions of a named input, displayed as a line of JSON.
+pub struct Record<'a> {
+    /// The name of the input.
+    pub name: &'a str,
+    /// The notable positions of the input.
+    pub extremes: &'a Extremes,
+}
+
+impl fmt::Display for Record<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let longest_line = match self.extremes.longest_line {
+            Some(line) => format!(
+                "{{\"line\":{},\"offset\":{},\"bytes\":{}}}",
+                line.number, line.offset, line.length
+            ),
+            None => "null".to_string(),
+        };
+        write!(
+            f,
+            "{{\"file\":{},\"longest_line\":{},\"first_invalid_utf8\":{},\"first_nul\":{}}}",
+            json_string(self.name),
+            longest_line,
+            json_option(self.extremes.first_invalid_utf8),
+            json_option(self.extremes.first_nul)
+        )
+    }
+}
+
+/// A reader finding the notable positions of the bytes read through it from another.
+pub struct Scan<'a, R> {
+    /// The reader being scanned.
+    inner: R,
+    /// The notable positions found so far.
+    extremes: Option<&'a mut Extremes>,
+}
+
+impl<'a, R: Read> Scan<'a, R> {
+    /// Returns a reader of `inner` adding the positions it finds to `extremes`, if given.
+    pub fn new(inner: R, extremes: Option<&'a mut Extremes>) -> Self {
+        Scan { inner, extremes }
+    }
+}
+
+impl<R: Read> Read for Scan<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        if let Some(extremes) = &mut self.extremes {
+            extremes.feed(&buf[..length]);
+        }
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extremes() {
+        let mut extremes = Extremes::default();
+        for piece in [&b"ab\nlong"[..], b"est\n\xE2\x80", b"\x94\n\0\xFFx"] {
+            extremes.feed(piece);
+        }
+        extremes.finish();
+
+        let longest = Line {
+            number: 2,
+            offset: 3,
+            length: 7,
+        };
+        assert_eq!(extremes.longest_line, Some(longest));
+        assert_eq!(extremes.first_nul, Some(15));
+        assert_eq!(extremes.first_invalid_utf8, Some(16));
+
+        let mut truncated = Extremes::default();
+        truncated.feed(b"ok\xE2\x80");
+        truncated.finish();
+        assert_eq!(truncated.first_invalid_utf8, Some(2));
+        assert_eq!(
+            Record {
+                name: "a \"b\".txt",
+                extremes: &truncated,
+            }
+            .to_string(),
+            "{\"file\":\"a \\\"b\\\".txt\",\"longest_line\":{\"line\":1,\"offset\":0,\"bytes\":4},\
+             \"first_invalid_utf8\":2,\"first_nul\":null}"
+        );
+    }
+}
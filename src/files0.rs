@@ -0,0 +1,66 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fs;
+use std::io::{self, Read};
+
+use wc_rs::i18n::Message;
+
+/// Reads the list of NUL-terminated file names at `source`, standard input if it is `-`.
+pub fn read_list(source: &str) -> io::Result<Vec<u8>> {
+    if source == "-" {
+        let mut list = Vec::new();
+        io::stdin().lock().read_to_end(&mut list)?;
+        Ok(list)
+    } else {
+        fs::read(source)
+    }
+}
+
+/// Returns the file names of `list`, each terminated by a NUL byte except perhaps the last, as produced by
+/// `find -print0`. Names which are empty or not valid UTF-8 are instead an error naming their position.
+pub fn split_names(list: &[u8]) -> Vec<Result<String, Message>> {
+    let list = list.strip_suffix(b"\0").unwrap_or(list);
+    if list.is_empty() {
+        return Vec::new();
+    }
+    list.split(|&byte| byte == 0)
+        .enumerate()
+        .map(|(index, name)| match std::str::from_utf8(name) {
+            Ok("") => Err(Message::new("empty-file-name").arg("index", index + 1)),
+            Ok(name) => Ok(name.to_string()),
+            Err(_) => Err(Message::new("file-name-not-utf-8").arg("index", index + 1)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_names() {
+        assert_eq!(
+            split_names(b"a.txt\0dir/with space\nand newline\0"),
+            vec![
+                Ok("a.txt".to_string()),
+                Ok("dir/with space\nand newline".to_string())
+            ]
+        );
+        assert_eq!(split_names(b"last"), vec![Ok("last".to_string())]);
+        assert!(split_names(b"").is_empty());
+        assert!(split_names(b"\0").is_empty());
+        assert_eq!(
+            split_names(b"a\0\0\xFF\0"),
+            vec![
+                Ok("a".to_string()),
+                Err(Message::new("empty-file-name").arg("index", 2)),
+                Err(Message::new("file-name-not-utf-8").arg("index", 3))
+            ]
+        );
+    }
+}
@@ -0,0 +1,456 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use wc_rs::i18n::Message;
+
+/// Runs `git` with `args` in the current directory, returning its standard output.
+pub fn run(args: &[&str]) -> Result<Vec<u8>, Message> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|error| Message::new("git-unavailable").arg("error", error))?;
+    if !output.status.success() {
+        return Err(Message::new("git-failed")
+            .arg("command", args.join(" "))
+            .arg("error", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// Runs `git` with `args` in the current directory, writing `input` to its standard input and returning its
+/// standard output.
+fn run_with_input(args: &[&str], input: Vec<u8>) -> Result<Vec<u8>, Message> {
+    let mut child = Command::new("git")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| Message::new("git-unavailable").arg("error", error))?;
+
+    // The input is written from another thread, so git never blocks writing output nobody is reading.
+    let mut stdin = child.stdin.take().expect("git has no standard input");
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child
+        .wait_with_output()
+        .map_err(|error| Message::new("git-unavailable").arg("error", error))?;
+    writer
+        .join()
+        .expect("git input writer panicked")
+        .map_err(|error| Message::new("git-unwritable").arg("error", error))?;
+
+    if !output.status.success() {
+        return Err(Message::new("git-failed")
+            .arg("command", args.join(" "))
+            .arg("error", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// A revision of the history of a repository.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Revision {
+    /// The name of the revision, such as a tag name or commit hash.
+    pub name: String,
+    /// The date of the revision, in strict ISO 8601 format.
+    pub date: String,
+}
+
+/// Parses `listing`, lines of a revision name and date separated by a tab.
+fn parse_revisions(listing: &[u8]) -> Vec<Revision> {
+    String::from_utf8_lossy(listing)
+        .lines()
+        .filter_map(|line| {
+            let (name, date) = line.split_once('\t')?;
+            Some(Revision {
+                name: name.to_string(),
+                date: date.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the tags of the repository, oldest first.
+pub fn tags() -> Result<Vec<Revision>, Message> {
+    run(&[
+        "for-each-ref",
+        "--sort=creatordate",
+        "--format=%(refname:short)%09%(creatordate:iso-strict)",
+        "refs/tags",
+    ])
+    .map(|listing| parse_revisions(&listing))
+}
+
+/// Returns the commits of the first-parent history of `HEAD`, oldest first.
+pub fn commits() -> Result<Vec<Revision>, Message> {
+    run(&["log", "--first-parent", "--reverse", "--format=%H%x09%cI"])
+        .map(|listing| parse_revisions(&listing))
+}
+
+/// Parses `listing`, the NUL-terminated output of `git ls-tree -r -z`, into the object names and paths of
+/// the blobs it lists.
+fn parse_tree(listing: &[u8]) -> Vec<(String, String)> {
+    listing
+        .split(|&byte| byte == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (info, path) = entry.split_once('\t')?;
+            match info.split(' ').collect::<Vec<_>>().as_slice() {
+                [_, "blob", object] => Some((object.to_string(), path.to_string())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Parses `output`, the output of `git cat-file --batch`, into the contents of the objects it holds.
+fn parse_batch(output: &[u8]) -> Result<Vec<Vec<u8>>, Message> {
+    let mut contents = Vec::new();
+    let mut rest = output;
+    while !rest.is_empty() {
+        let header_end = rest
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| Message::new("truncated-git-object"))?;
+        let header = String::from_utf8_lossy(&rest[..header_end]);
+        let size: usize = header
+            .rsplit(' ')
+            .next()
+            .and_then(|size| size.parse().ok())
+            .ok_or_else(|| Message::new("unexpected-git-object-header").arg("header", &header))?;
+        let start = header_end + 1;
+        let content = rest
+            .get(start..start + size)
+            .ok_or_else(|| Message::new("truncated-git-object"))?;
+        contents.push(content.to_vec());
+        rest = rest.get(start + size + 1..).unwrap_or_default();
+    }
+    Ok(contents)
+}
+
+/// Returns the paths and contents of the files of `revision`, limited to `paths` if any are given, read
+/// from the object database without checking the revision out.
+pub fn files_at(revision: &str, paths: &[String]) -> Result<Vec<(String, Vec<u8>)>, Message> {
+    let mut args = vec!["ls-tree", "-r", "-z", revision, "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let blobs = parse_tree(&run(&args)?);
+
+    let contents = read_objects(blobs.iter().map(|(object, _)| object.as_str()))?;
+    Ok(blobs
+        .into_iter()
+        .map(|(_, path)| path)
+        .zip(contents)
+        .collect())
+}
+
+/// Returns the contents of the objects named by `names`, in order.
+fn read_objects<'a>(names: impl Iterator<Item = &'a str>) -> Result<Vec<Vec<u8>>, Message> {
+    let mut input = Vec::new();
+    for name in names {
+        input.extend_from_slice(name.as_bytes());
+        input.push(b'\n');
+    }
+    parse_batch(&run_with_input(&["cat-file", "--batch"], input)?)
+}
+
+/// Returns the path of the top-level directory of the working tree of the current directory.
+pub fn toplevel() -> Result<PathBuf, Message> {
+    let output = run(&["rev-parse", "--show-toplevel"])?;
+    let path = String::from_utf8(output).map_err(|_| Message::new("git-output-not-utf-8"))?;
+    Ok(PathBuf::from(path.trim_end_matches('\n')))
+}
+
+/// Returns the paths of the files added, copied, modified or renamed in the index, relative to the
+/// top-level directory of the working tree, limited to `paths` if any are given.
+pub fn staged_paths(paths: &[String]) -> Result<Vec<String>, Message> {
+    let mut args = vec![
+        "diff",
+        "--cached",
+        "--name-only",
+        "--diff-filter=ACMR",
+        "-z",
+        "--",
+    ];
+    args.extend(paths.iter().map(String::as_str));
+    let listing = run(&args)?;
+    Ok(listing
+        .split(|&byte| byte == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| String::from_utf8_lossy(path).into_owned())
+        .collect())
+}
+
+/// Returns the paths of the files tracked in the index beneath `directory`, relative to it.
+pub fn tracked_paths(directory: &str) -> Result<Vec<String>, Message> {
+    let listing = run(&["-C", directory, "ls-files", "-z"])?;
+    Ok(listing
+        .split(|&byte| byte == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| String::from_utf8_lossy(path).into_owned())
+        .collect())
+}
+
+/// Returns the contents of the files at `paths` as staged in the index, rather than in the worktree.
+pub fn staged_contents(paths: &[String]) -> Result<Vec<Vec<u8>>, Message> {
+    let names: Vec<String> = paths.iter().map(|path| format!(":{}", path)).collect();
+    read_objects(names.iter().map(String::as_str))
+}
+
+/// The lines added to and removed from a file between two revisions.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct FileChurn {
+    /// The path of the file, as of the later revision unless it was deleted.
+    pub path: String,
+    /// The added lines, each with its terminator.
+    pub added: Vec<u8>,
+    /// The removed lines, each with its terminator.
+    pub removed: Vec<u8>,
+    /// Whether the file is binary, so that no lines were compared.
+    pub binary: bool,
+}
+
+/// Returns `name` without the double quotes and C-style escapes git gives names with unusual characters,
+/// such as quotes, control characters and, with `core.quotePath`, bytes beyond ASCII.
+fn unquote(name: &str) -> String {
+    let quoted = match name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+    {
+        Some(quoted) => quoted.as_bytes(),
+        None => return name.to_string(),
+    };
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut rest = quoted;
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let (&escape, tail) = match rest.split_first() {
+            Some(escape) => escape,
+            None => break,
+        };
+        rest = tail;
+        bytes.push(match escape {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0C,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => 0x0B,
+            b'0'..=b'7' => {
+                // Octal escapes are of three digits.
+                let digits = rest
+                    .iter()
+                    .take(2)
+                    .take_while(|digit| matches!(digit, b'0'..=b'7'))
+                    .count();
+                let value = rest[..digits]
+                    .iter()
+                    .fold(u32::from(escape - b'0'), |value, digit| {
+                        value * 8 + u32::from(digit - b'0')
+                    });
+                rest = &rest[digits..];
+                value as u8
+            }
+            escape => escape,
+        });
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Returns `path` without the `a/` or `b/` prefix `diff` is told to give it.
+fn strip_prefix(path: &str) -> String {
+    path.split_once('/')
+        .map_or(path, |(_, path)| path)
+        .to_string()
+}
+
+/// Returns the path named by a `---` or `+++` line of a patch, or `None` if it is `/dev/null`.
+fn patch_path(name: &[u8]) -> Option<String> {
+    let name = String::from_utf8_lossy(name);
+    // Names with spaces are followed by a tab, to tell them from any timestamp.
+    let name = name.trim_end_matches(['\r', '\n']).trim_end_matches('\t');
+    match name {
+        "/dev/null" => None,
+        _ => Some(strip_prefix(&unquote(name))),
+    }
+}
+
+/// Parses the unified diff `patch`, as output by `git diff`, into the churn of each file it changes.
+fn parse_patch(patch: &[u8]) -> Vec<FileChurn> {
+    let mut files: Vec<FileChurn> = Vec::new();
+    let mut in_hunk = false;
+    for line in patch.split_inclusive(|&byte| byte == b'\n') {
+        if let Some(header) = line.strip_prefix(b"diff --git ") {
+            // The header names both paths, which is ambiguous if they contain spaces, so the path is
+            // refined by the `---` and `+++` lines that follow when there are any.
+            let header = String::from_utf8_lossy(header);
+            let header = header.trim_end();
+            let path = match header.rsplit_once(" \"b/") {
+                Some((_, path)) if header.ends_with('"') => {
+                    strip_prefix(&unquote(&format!("\"b/{}", path)))
+                }
+                _ => header
+                    .rsplit_once(" b/")
+                    .map_or_else(|| header.to_string(), |(_, path)| path.to_string()),
+            };
+            files.push(FileChurn {
+                path,
+                ..FileChurn::default()
+            });
+            in_hunk = false;
+            continue;
+        }
+        let file = match files.last_mut() {
+            Some(file) => file,
+            None => continue,
+        };
+
+        if in_hunk {
+            match line.first() {
+                Some(b'+') => {
+                    file.added.extend_from_slice(&line[1..]);
+                    continue;
+                }
+                Some(b'-') => {
+                    file.removed.extend_from_slice(&line[1..]);
+                    continue;
+                }
+                Some(b' ') | Some(b'\\') => continue,
+                _ => in_hunk = false,
+            }
+        }
+
+        if line.starts_with(b"@@") {
+            in_hunk = true;
+        } else if let Some(name) = line.strip_prefix(b"+++ ") {
+            file.path = patch_path(name).unwrap_or_else(|| file.path.clone());
+        } else if let Some(name) = line.strip_prefix(b"--- ") {
+            // A deleted file is named only by the `---` line.
+            if let Some(path) = patch_path(name) {
+                file.path = path;
+            }
+        } else if line.starts_with(b"Binary files ") {
+            file.binary = true;
+        }
+    }
+    files
+}
+
+/// Returns the churn of each file changed by the revision range `range`, such as `v1.0..v2.0`, limited
+/// to `paths` if any are given.
+pub fn diff(range: &str, paths: &[String]) -> Result<Vec<FileChurn>, Message> {
+    let mut args = vec![
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "--unified=0",
+        // Paths are given prefixes whatever `diff.noprefix` or `diff.mnemonicPrefix` say, for them to be
+        // stripped.
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        range,
+        "--",
+    ];
+    args.extend(paths.iter().map(String::as_str));
+    run(&args).map(|patch| parse_patch(&patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_objects() {
+        let listing = b"100644 blob 1111111111111111111111111111111111111111\tREADME.md\0\
+160000 commit 2222222222222222222222222222222222222222\tvendor/lib\0\
+100644 blob 3333333333333333333333333333333333333333\tsrc/a b.rs\0";
+        let batch = b"1111111111111111111111111111111111111111 blob 6\nhello\n\n\
+3333333333333333333333333333333333333333 blob 0\n\n";
+
+        assert_eq!(
+            parse_tree(listing),
+            vec![
+                ("1".repeat(40), "README.md".to_string()),
+                ("3".repeat(40), "src/a b.rs".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_batch(batch).unwrap(),
+            vec![b"hello\n".to_vec(), Vec::new()]
+        );
+        assert_eq!(
+            parse_revisions(b"v1.0\t2019-01-02T03:04:05+00:00\n"),
+            vec![Revision {
+                name: "v1.0".to_string(),
+                date: "2019-01-02T03:04:05+00:00".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_patch() {
+        let patch = b"diff --git a/README.md b/README.md
+index 1111111..2222222 100644
+--- a/README.md
++++ b/README.md
+@@ -1,2 +1,2 @@
+-old title
+--- a rule
++new longer title
++++ plus
+@@ -9,0 +10 @@ context
++tail
+\\ No newline at end of file
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/logo.png b/logo.png
+Binary files a/logo.png and b/logo.png differ
+diff --git a/a b.txt b/a b.txt
+new file mode 100644
+--- /dev/null
++++ b/a b.txt\t
+@@ -0,0 +1 @@
++spaced
+diff --git \"a/q\\\"t\\303\\251.txt\" \"b/q\\\"t\\303\\251.txt\"
+deleted file mode 100644
+--- \"a/q\\\"t\\303\\251.txt\"
++++ /dev/null
+@@ -1 +0,0 @@
+-quoted
+diff --git \"a/tab\\there\" \"b/tab\\there\"
+Binary files \"a/tab\\there\" and \"b/tab\\there\" differ
+";
+        let files = parse_patch(patch);
+
+        assert_eq!(files.len(), 6);
+        assert_eq!(files[0].path, "README.md");
+        assert_eq!(files[0].added, b"new longer title\n++ plus\ntail\n");
+        assert_eq!(files[0].removed, b"old title\n-- a rule\n");
+        assert_eq!(files[1].path, "gone.txt");
+        assert_eq!(files[1].removed, b"bye\n");
+        assert!(files[2].binary);
+        assert_eq!(files[3].path, "a b.txt");
+        assert_eq!(files[3].added, b"spaced\n");
+        assert_eq!(files[4].path, "q\"t\u{E9}.txt");
+        assert_eq!(files[4].removed, b"quoted\n");
+        assert_eq!(files[5].path, "tab\there");
+        assert!(files[5].binary);
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Returns `true` if `pattern` contains wildcards.
+fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Returns `true` if `name` matches `pattern`, in which `*` stands for any run of characters, `?` for any
+/// single character and `[...]` for any character of a set such as `[a-z0-9]`, or not in it as `[!...]`.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches_chars(&pattern, &name)
+}
+
+/// Returns `true` if `name` matches `pattern`, as characters.
+fn matches_chars(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| matches_chars(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && matches_chars(&pattern[1..], &name[1..]),
+        Some('[') => match (class_end(pattern), name.first()) {
+            (Some(end), Some(&c)) => {
+                in_class(&pattern[1..end], c) && matches_chars(&pattern[end + 1..], &name[1..])
+            }
+            (Some(_), None) => false,
+            // An unterminated class is a literal bracket.
+            (None, _) => name.first() == Some(&'[') && matches_chars(&pattern[1..], &name[1..]),
+        },
+        Some(&literal) => {
+            name.first() == Some(&literal) && matches_chars(&pattern[1..], &name[1..])
+        }
+    }
+}
+
+/// Returns the index of the `]` closing the class at the start of `pattern`, if any. A `]` first in the
+/// class, after any `!`, is a member rather than the end.
+fn class_end(pattern: &[char]) -> Option<usize> {
+    let first = if pattern.get(1) == Some(&'!') { 2 } else { 1 };
+    (first + 1..pattern.len()).find(|&index| pattern[index] == ']')
+}
+
+/// Returns `true` if `c` is in `class`, the contents of a `[...]` class.
+fn in_class(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut index = 0;
+    let mut found = false;
+    while index < class.len() {
+        if class.get(index + 1) == Some(&'-') && index + 2 < class.len() {
+            found |= (class[index]..=class[index + 2]).contains(&c);
+            index += 3;
+        } else {
+            found |= class[index] == c;
+            index += 1;
+        }
+    }
+    found != negated
+}
+
+/// Expands the wildcards of `pattern` into the sorted paths that match it, as a Unix shell does. Hidden
+/// files only match components that start with `.`, and a pattern matching nothing is returned unchanged.
+pub fn expand(pattern: &str) -> Vec<String> {
+    if !has_wildcards(pattern) {
+        return vec![pattern.to_string()];
+    }
+
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let component = component.as_os_str();
+        let text = component.to_string_lossy();
+        if !has_wildcards(&text) {
+            for path in &mut paths {
+                path.push(component);
+            }
+            continue;
+        }
+
+        let mut expanded = Vec::new();
+        for path in &paths {
+            let directory = if path.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                path.as_path()
+            };
+            let entries = match fs::read_dir(directory) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            let mut names: Vec<String> = entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|name| !name.starts_with('.') || text.starts_with('.'))
+                .filter(|name| matches(&text, name))
+                .collect();
+            names.sort();
+            expanded.extend(names.into_iter().map(|name| path.join(name)));
+        }
+        paths = expanded;
+    }
+
+    if paths.is_empty() {
+        return vec![pattern.to_string()];
+    }
+    paths
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*.txt", "notes.txt"));
+        assert!(!matches("*.txt", "notes.txt.bak"));
+        assert!(matches("app.log.?", "app.log.1"));
+        assert!(matches("[a-c]*[!0-9]", "beta"));
+        assert!(!matches("[a-c]*[!0-9]", "beta2"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[", "["));
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand("s?c/mai[m-o].rs"), vec!["src/main.rs"]);
+        assert_eq!(expand("Cargo.t*"), vec!["Cargo.toml"]);
+        assert_eq!(expand("src/*.none"), vec!["src/*.none"]);
+        assert_eq!(expand("Cargo.toml"), vec!["Cargo.toml"]);
+    }
+}
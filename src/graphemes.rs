@@ -0,0 +1,396 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Counting of extended grapheme clusters, the characters a reader perceives.
+//!
+//! Clusters are split by the rules of Unicode Standard Annex #29, with the character properties they rely on
+//! approximated by tables of the ranges of the scripts in common use rather than the full character
+//! database: combining marks and vowel signs extend the character before them, emoji joined by zero-width
+//! joiners or followed by modifiers are one cluster, as are pairs of regional indicators, Hangul syllables
+//! spelled in jamo and the conjuncts of the Indic scripts which form them with a virama.
+
+use crate::emoji;
+
+/// The class of a character when splitting grapheme clusters, after its `Grapheme_Cluster_Break` property.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum GraphemeClass {
+    /// The character has no bearing on the clusters around it.
+    Other,
+    /// A carriage return.
+    Cr,
+    /// A line feed.
+    Lf,
+    /// A control or format character, a cluster on its own.
+    Control,
+    /// A combining mark, vowel sign, variation selector or emoji modifier, which extends the cluster before
+    /// it.
+    Extend,
+    /// A zero-width joiner.
+    Zwj,
+    /// A character which joins the cluster after it, as Arabic number signs do.
+    Prepend,
+    /// A regional indicator, two of which form a flag.
+    RegionalIndicator,
+    /// A Hangul leading consonant jamo.
+    L,
+    /// A Hangul vowel jamo.
+    V,
+    /// A Hangul trailing consonant jamo.
+    T,
+    /// A precomposed Hangul syllable of a leading consonant and vowel.
+    Lv,
+    /// A precomposed Hangul syllable of a leading consonant, vowel and trailing consonant.
+    Lvt,
+    /// A pictographic emoji.
+    Pictograph,
+    /// A consonant of an Indic script which forms conjuncts.
+    Consonant,
+    /// A virama joining the consonants of a conjunct.
+    Linker,
+}
+
+/// The Indic scripts which form conjuncts with a virama, by the start of their block: Devanagari, Bengali,
+/// Gujarati, Oriya, Telugu and Malayalam.
+const CONJUNCT_BLOCKS: [u32; 6] = [0x0900, 0x0980, 0x0A80, 0x0B00, 0x0C00, 0x0D00];
+
+/// Returns the class of the character at `offset` within the block of an Indic script laid out after
+/// ISCII, from U+0900 to U+0D7F, which conjoins consonants if `conjoins`.
+fn indic_class(offset: u32, conjoins: bool) -> GraphemeClass {
+    match offset {
+        0x15..=0x39 if conjoins => GraphemeClass::Consonant,
+        0x4D if conjoins => GraphemeClass::Linker,
+        0x00..=0x03 | 0x3A..=0x3C | 0x3E..=0x4D | 0x51..=0x57 | 0x62..=0x63 => {
+            GraphemeClass::Extend
+        }
+        _ => GraphemeClass::Other,
+    }
+}
+
+impl From<char> for GraphemeClass {
+    /// Returns the class of `c` when splitting grapheme clusters.
+    fn from(c: char) -> Self {
+        let code = c as u32;
+        match code {
+            0x0D => GraphemeClass::Cr,
+            0x0A => GraphemeClass::Lf,
+            0x200D => GraphemeClass::Zwj,
+            0x1F1E6..=0x1F1FF => GraphemeClass::RegionalIndicator,
+            0x0600..=0x0605
+            | 0x06DD
+            | 0x070F
+            | 0x0890..=0x0891
+            | 0x08E2
+            | 0x0D4E
+            | 0x110BD
+            | 0x110CD
+            | 0x111C2..=0x111C3 => GraphemeClass::Prepend,
+            0x00..=0x1F
+            | 0x7F..=0x9F
+            | 0xAD
+            | 0x061C
+            | 0x180E
+            | 0x200B
+            | 0x200E..=0x200F
+            | 0x2028..=0x202E
+            | 0x2060..=0x206F
+            | 0xFEFF
+            | 0xFFF0..=0xFFFB
+            | 0x13430..=0x1343F
+            | 0x1BCA0..=0x1BCA3
+            | 0x1D173..=0x1D17A
+            | 0xE0000..=0xE001F
+            | 0xE0080..=0xE00FF
+            | 0xE01F0..=0xE0FFF => GraphemeClass::Control,
+            0x0900..=0x0D7F => {
+                let block = code & !0x7F;
+                indic_class(code & 0x7F, CONJUNCT_BLOCKS.contains(&block))
+            }
+            0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x05BF
+            | 0x05C1..=0x05C2
+            | 0x05C4..=0x05C5
+            | 0x05C7
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0670
+            | 0x06D6..=0x06DC
+            | 0x06DF..=0x06E4
+            | 0x06E7..=0x06E8
+            | 0x06EA..=0x06ED
+            | 0x0711
+            | 0x0730..=0x074A
+            | 0x07A6..=0x07B0
+            | 0x07EB..=0x07F3
+            | 0x07FD
+            | 0x0816..=0x0819
+            | 0x081B..=0x0823
+            | 0x0825..=0x0827
+            | 0x0829..=0x082D
+            | 0x0859..=0x085B
+            | 0x0898..=0x089F
+            | 0x08CA..=0x08E1
+            | 0x08E3..=0x08FF
+            | 0x0D81..=0x0D83
+            | 0x0DCA
+            | 0x0DCF..=0x0DDF
+            | 0x0DF2..=0x0DF3
+            | 0x0E31
+            | 0x0E33..=0x0E3A
+            | 0x0E47..=0x0E4E
+            | 0x0EB1
+            | 0x0EB3..=0x0EBC
+            | 0x0EC8..=0x0ECE
+            | 0x0F18..=0x0F19
+            | 0x0F35
+            | 0x0F37
+            | 0x0F39
+            | 0x0F3E..=0x0F3F
+            | 0x0F71..=0x0F84
+            | 0x0F86..=0x0F87
+            | 0x0F8D..=0x0FBC
+            | 0x0FC6
+            | 0x102B..=0x103E
+            | 0x1056..=0x1059
+            | 0x105E..=0x1060
+            | 0x1062..=0x1064
+            | 0x1067..=0x106D
+            | 0x1071..=0x1074
+            | 0x1082..=0x108D
+            | 0x108F
+            | 0x109A..=0x109D
+            | 0x135D..=0x135F
+            | 0x1712..=0x1715
+            | 0x1732..=0x1734
+            | 0x1752..=0x1753
+            | 0x1772..=0x1773
+            | 0x17B4..=0x17D3
+            | 0x17DD
+            | 0x180B..=0x180D
+            | 0x180F
+            | 0x1885..=0x1886
+            | 0x18A9
+            | 0x1920..=0x193B
+            | 0x1A17..=0x1A1B
+            | 0x1A55..=0x1A7F
+            | 0x1AB0..=0x1AFF
+            | 0x1B00..=0x1B04
+            | 0x1B34..=0x1B44
+            | 0x1B6B..=0x1B73
+            | 0x1B80..=0x1B82
+            | 0x1BA1..=0x1BAD
+            | 0x1BE6..=0x1BF3
+            | 0x1C24..=0x1C37
+            | 0x1CD0..=0x1CD2
+            | 0x1CD4..=0x1CE8
+            | 0x1CED
+            | 0x1CF4
+            | 0x1CF7..=0x1CF9
+            | 0x1DC0..=0x1DFF
+            | 0x200C
+            | 0x20D0..=0x20F0
+            | 0x2CEF..=0x2CF1
+            | 0x2D7F
+            | 0x2DE0..=0x2DFF
+            | 0x302A..=0x302F
+            | 0x3099..=0x309A
+            | 0xA66F..=0xA672
+            | 0xA674..=0xA67D
+            | 0xA69E..=0xA69F
+            | 0xA6F0..=0xA6F1
+            | 0xA802
+            | 0xA806
+            | 0xA80B
+            | 0xA823..=0xA827
+            | 0xA82C
+            | 0xA880..=0xA881
+            | 0xA8B4..=0xA8C5
+            | 0xA8E0..=0xA8F1
+            | 0xA8FF
+            | 0xA926..=0xA92D
+            | 0xA947..=0xA953
+            | 0xA980..=0xA983
+            | 0xA9B3..=0xA9C0
+            | 0xAA29..=0xAA36
+            | 0xAA43
+            | 0xAA4C..=0xAA4D
+            | 0xAAEB..=0xAAEF
+            | 0xAAF5..=0xAAF6
+            | 0xABE3..=0xABEA
+            | 0xABEC..=0xABED
+            | 0xFB1E
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0xFF9E..=0xFF9F
+            | 0x101FD
+            | 0x10A01..=0x10A0F
+            | 0x10A38..=0x10A3F
+            | 0x11000..=0x11002
+            | 0x11038..=0x11046
+            | 0x1107F..=0x11082
+            | 0x110B0..=0x110BA
+            | 0x11100..=0x11102
+            | 0x11127..=0x11134
+            | 0x1D165..=0x1D169
+            | 0x1D16D..=0x1D172
+            | 0x1D17B..=0x1D182
+            | 0x1D185..=0x1D18B
+            | 0x1D1AA..=0x1D1AD
+            | 0x1E000..=0x1E02A
+            | 0x1E8D0..=0x1E8D6
+            | 0x1E944..=0x1E94A
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0020..=0xE007F
+            | 0xE0100..=0xE01EF => GraphemeClass::Extend,
+            0x1100..=0x115F | 0xA960..=0xA97C => GraphemeClass::L,
+            0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => GraphemeClass::V,
+            0x11A8..=0x11FF | 0xD7CB..=0xD7FB => GraphemeClass::T,
+            // The syllables are ordered by leading consonant, vowel and then trailing consonant, if any.
+            0xAC00..=0xD7A3 => match (code - 0xAC00) % 28 {
+                0 => GraphemeClass::Lv,
+                _ => GraphemeClass::Lvt,
+            },
+            _ if emoji::is_pictograph(c) => GraphemeClass::Pictograph,
+            _ => GraphemeClass::Other,
+        }
+    }
+}
+
+/// Returns whether `c` takes no columns of its own when displayed, as a control or format character, a
+/// character extending the cluster before it, or a Hangul vowel or trailing consonant jamo does.
+pub fn is_zero_width(c: char) -> bool {
+    matches!(
+        GraphemeClass::from(c),
+        GraphemeClass::Control
+            | GraphemeClass::Extend
+            | GraphemeClass::Zwj
+            | GraphemeClass::V
+            | GraphemeClass::T
+    )
+}
+
+/// Counts the extended grapheme clusters of a text fed to it in order, in pieces split anywhere.
+///
+/// Whether a character begins a cluster depends on the characters before it back to the start of an emoji
+/// sequence, a run of regional indicators or a conjunct, so the state of each is carried from one piece
+/// into the next.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct GraphemeCounter {
+    /// The clusters begun so far.
+    graphemes: u64,
+    /// The class of the last character, if any.
+    previous: Option<GraphemeClass>,
+    /// Whether the last characters are a pictograph followed by any extending characters.
+    after_pictograph: bool,
+    /// Whether the last character is a zero-width joiner following a pictograph, which joins the next.
+    joining_pictograph: bool,
+    /// Whether the last characters are an odd number of regional indicators, awaiting the second of a flag.
+    odd_indicators: bool,
+    /// Whether the last characters are a consonant followed by nuktas and joiners, and whether they
+    /// include a virama.
+    conjunct: Option<bool>,
+}
+
+impl GraphemeCounter {
+    /// Returns `true` if a character of class `next` does not begin a cluster after the text so far.
+    fn continues(&self, next: GraphemeClass) -> bool {
+        use GraphemeClass::*;
+
+        let previous = match self.previous {
+            Some(previous) => previous,
+            None => return false,
+        };
+        match (previous, next) {
+            (Cr, Lf) => true,
+            (Cr | Lf | Control, _) | (_, Cr | Lf | Control) => false,
+            (L, L | V | Lv | Lvt) | (Lv | V, V | T) | (Lvt | T, T) => true,
+            (_, Extend | Zwj | Linker) | (Prepend, _) => true,
+            (Zwj, Pictograph) => self.joining_pictograph,
+            (RegionalIndicator, RegionalIndicator) => self.odd_indicators,
+            (_, Consonant) => self.conjunct == Some(true),
+            _ => false,
+        }
+    }
+
+    /// Counts the clusters of `text`, which continues the text fed so far.
+    pub fn feed(&mut self, text: &str) {
+        for c in text.chars() {
+            let class = GraphemeClass::from(c);
+            if !self.continues(class) {
+                self.graphemes += 1;
+            }
+
+            let code = c as u32;
+            let is_nukta = (0x0900..=0x0D7F).contains(&code) && code & 0x7F == 0x3C;
+            self.conjunct = match class {
+                GraphemeClass::Consonant => Some(false),
+                GraphemeClass::Linker => self.conjunct.map(|_| true),
+                GraphemeClass::Zwj => self.conjunct,
+                _ if is_nukta => self.conjunct,
+                _ => None,
+            };
+            self.joining_pictograph = self.after_pictograph && class == GraphemeClass::Zwj;
+            self.after_pictograph = match class {
+                GraphemeClass::Pictograph => true,
+                GraphemeClass::Extend => self.after_pictograph,
+                _ => false,
+            };
+            self.odd_indicators = class == GraphemeClass::RegionalIndicator && !self.odd_indicators;
+            self.previous = Some(class);
+        }
+    }
+
+    /// Returns the clusters of all of the text fed.
+    pub fn finish(self) -> u64 {
+        self.graphemes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphemes(text: &str) -> u64 {
+        let mut counter = GraphemeCounter::default();
+        counter.feed(text);
+        counter.finish()
+    }
+
+    #[test]
+    fn test_graphemes() {
+        assert_eq!(graphemes(""), 0);
+        assert_eq!(graphemes("plain text"), 10);
+        assert_eq!(graphemes("cafe\u{301} \r\n\n"), 7);
+        assert_eq!(graphemes("\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}"), 1);
+        assert_eq!(
+            graphemes("\u{1F44D}\u{1F3FD}\u{2764}\u{FE0F} 1\u{FE0F}\u{20E3}"),
+            4
+        );
+        assert_eq!(
+            graphemes("\u{1F1EF}\u{1F1F5}\u{1F1FA}\u{1F1F8}\u{1F1EB}"),
+            3
+        );
+        assert_eq!(graphemes("a\u{200D}\u{1F467}"), 2);
+        assert_eq!(graphemes("\u{1100}\u{1161}\u{11A8}\u{D55C}\u{AD6D}"), 3);
+        assert_eq!(graphemes("\u{928}\u{92E}\u{938}\u{94D}\u{924}\u{947}"), 3);
+        assert_eq!(graphemes("\u{E01}\u{E33}\u{0600}1"), 2);
+    }
+
+    #[test]
+    fn test_graphemes_split() {
+        let text = "e\u{301}\u{1F468}\u{200D}\u{1F4BB}\u{1F1EF}\u{1F1F5}\u{938}\u{94D}\u{924}\r\n";
+        let whole = graphemes(text);
+        for (split, _) in text.char_indices() {
+            let mut counter = GraphemeCounter::default();
+            counter.feed(&text[..split]);
+            counter.feed(&text[split..]);
+            assert_eq!(counter.finish(), whole);
+        }
+        assert_eq!(whole, 5);
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::i18n::Message;
+use crate::markup::{terminator, LineFilter};
+use crate::regex::Regex;
+
+/// A filter keeping only the lines which match a regular expression, or only those which do not, as `grep`
+/// and `grep -v` would.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct GrepFilter {
+    /// The expression lines are matched against, without their terminators.
+    regex: Regex,
+    /// Whether the lines which do not match are kept instead.
+    invert: bool,
+}
+
+impl GrepFilter {
+    /// Returns a new filter keeping the lines matching `pattern`, or those not matching it if `invert` is set.
+    pub fn new(pattern: &str, invert: bool) -> Result<Self, Message> {
+        Ok(GrepFilter {
+            regex: pattern.parse()?,
+            invert,
+        })
+    }
+}
+
+impl LineFilter for GrepFilter {
+    fn filter_line(&mut self, line: &[u8], prose: &mut Vec<u8>) {
+        let text = &line[..line.len() - terminator(line).len()];
+        if self.regex.is_match(&String::from_utf8_lossy(text)) != self.invert {
+            prose.extend_from_slice(line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::MarkupReader;
+    use std::io::Read;
+
+    /// Returns the lines of `input` kept by `filter`.
+    fn filtered(input: &str, filter: GrepFilter) -> String {
+        let mut output = String::new();
+        MarkupReader::with_filter(input.as_bytes(), Box::new(filter))
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_grep_filter() {
+        let input = "# setup\nlet x = 1;\n  # note\nprint(x)";
+
+        assert_eq!(
+            filtered(input, GrepFilter::new("^\\s*#", true).unwrap()),
+            "let x = 1;\nprint(x)"
+        );
+        assert_eq!(
+            filtered(input, GrepFilter::new("#", false).unwrap()),
+            "# setup\n  # note\n"
+        );
+        assert_eq!(
+            filtered("a\r\nb\r\n", GrepFilter::new("a$", false).unwrap()),
+            "a\r\n"
+        );
+        assert!(GrepFilter::new("(", false).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The messages of the command in the language of the user, looked up by identifier in catalogs written in
+//! a subset of the Project Fluent syntax.
+//!
+//! Each message of a catalog is `identifier = value`. A value may continue onto the following lines, which
+//! are indented and lose the indentation they have in common. `{ $name }` within a value stands for the
+//! argument `name`, and `{ "text" }` for the literal text, such as a brace. Lines starting with `#` are
+//! comments.
+//!
+//! The language is that of the first of `LC_ALL`, `LC_MESSAGES` and `LANG` set, as in POSIX. Messages a
+//! catalog lacks, and those of languages without one, are given in English.
+//!
+//! Errors of the library are `Message`s, or I/O errors wrapping them, so that they are given in the language
+//! of the user and can be told apart by identifier rather than by their text.
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::sync::OnceLock;
+
+/// The catalogs of each language, by the ISO 639-1 code of the language, English first.
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("i18n/en.ftl")),
+    ("de", include_str!("i18n/de.ftl")),
+];
+
+/// The messages of a language, by identifier.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Catalog {
+    /// The value of each message, with its placeables unresolved.
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parses the messages of `text`. Lines which are neither messages nor comments are ignored.
+    pub fn parse(text: &str) -> Catalog {
+        let mut messages = HashMap::new();
+        let mut lines = text.lines().peekable();
+        while let Some(line) = lines.next() {
+            if line.starts_with('#') || line.starts_with(char::is_whitespace) {
+                continue;
+            }
+            let (identifier, first) = match line.split_once('=') {
+                Some(message) => message,
+                None => continue,
+            };
+
+            let mut continuation = Vec::new();
+            while let Some(&line) = lines.peek() {
+                if !line.starts_with(' ') && !line.trim().is_empty() {
+                    break;
+                }
+                continuation.push(line);
+                lines.next();
+            }
+            while continuation
+                .last()
+                .is_some_and(|line| line.trim().is_empty())
+            {
+                continuation.pop();
+            }
+            let indent = continuation
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start().len())
+                .min()
+                .unwrap_or(0);
+
+            let first = first.trim();
+            let value: Vec<&str> = (!first.is_empty())
+                .then_some(first)
+                .into_iter()
+                .chain(
+                    continuation
+                        .iter()
+                        .map(|line| line.get(indent..).unwrap_or("")),
+                )
+                .collect();
+            messages.insert(identifier.trim().to_string(), value.join("\n"));
+        }
+        Catalog { messages }
+    }
+
+    /// Returns the message `identifier` with the arguments of `args` in place of its placeables, or `None`
+    /// if the receiver lacks it. Placeables of missing arguments are left as they are.
+    pub fn format(&self, identifier: &str, args: &[(&str, &dyn Display)]) -> Option<String> {
+        let mut value = self.messages.get(identifier)?.as_str();
+        let mut message = String::new();
+        while let Some(start) = value.find('{') {
+            message.push_str(&value[..start]);
+            // A brace within a string literal does not close the placeable.
+            let inner = value[start + 1..].trim_start();
+            let search = match inner
+                .strip_prefix('"')
+                .and_then(|literal| literal.find('"'))
+            {
+                Some(quote) => value.len() - inner.len() + quote + 2,
+                None => start,
+            };
+            let end = match value[search..].find('}') {
+                Some(length) => search + length,
+                None => {
+                    value = &value[start..];
+                    break;
+                }
+            };
+            let placeable = value[start + 1..end].trim();
+            let argument = placeable
+                .strip_prefix('$')
+                .and_then(|name| args.iter().find(|&&(arg, _)| arg == name));
+            match argument {
+                Some((_, argument)) => message.push_str(&argument.to_string()),
+                None if placeable.len() >= 2
+                    && placeable.starts_with('"')
+                    && placeable.ends_with('"') =>
+                {
+                    message.push_str(&placeable[1..placeable.len() - 1])
+                }
+                None => message.push_str(&value[start..=end]),
+            }
+            value = &value[end + 1..];
+        }
+        message.push_str(value);
+        Some(message)
+    }
+}
+
+/// Returns the ISO 639-1 code of the language of `locale`, a POSIX locale name such as `de_DE.UTF-8`, or
+/// `en` for the `C` and `POSIX` locales.
+fn language_of(locale: &str) -> &str {
+    let language = locale.split(['_', '.', '@']).next().unwrap_or_default();
+    match language {
+        "C" | "POSIX" => "en",
+        language => language,
+    }
+}
+
+/// Returns the catalog of the language of the user, or that of English if there is none.
+fn catalog() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        // Tests expect the messages in English, whatever the locale they are run in.
+        let language = if cfg!(test) {
+            "en"
+        } else {
+            language_of(&locale)
+        };
+        let (_, text) = CATALOGS
+            .iter()
+            .find(|&&(code, _)| code == language)
+            .unwrap_or(&CATALOGS[0]);
+        Catalog::parse(text)
+    })
+}
+
+/// Returns the catalog of English, in which every message is given.
+fn english() -> &'static Catalog {
+    static ENGLISH: OnceLock<Catalog> = OnceLock::new();
+    ENGLISH.get_or_init(|| Catalog::parse(CATALOGS[0].1))
+}
+
+/// Returns the message `identifier` in the language of the user, with the arguments of `args` in place of
+/// its placeables, falling back on English and then on the identifier itself.
+pub fn message(identifier: &str, args: &[(&str, &dyn Display)]) -> String {
+    catalog()
+        .format(identifier, args)
+        .or_else(|| english().format(identifier, args))
+        .unwrap_or_else(|| identifier.to_string())
+}
+
+/// A message with the arguments of its placeables, given in the language of the user when displayed.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Message {
+    /// The identifier of the message in the catalogs.
+    identifier: &'static str,
+    /// The name and text of each argument.
+    args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    /// Returns the message `identifier`, without arguments.
+    pub fn new(identifier: &'static str) -> Self {
+        Message {
+            identifier,
+            args: Vec::new(),
+        }
+    }
+
+    /// Returns the receiver with `value` as its argument `name`.
+    pub fn arg(mut self, name: &'static str, value: impl Display) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    /// Returns the identifier of the message in the catalogs.
+    pub fn identifier(&self) -> &'static str {
+        self.identifier
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args: Vec<(&str, &dyn Display)> = self
+            .args
+            .iter()
+            .map(|(name, value)| (*name, value as &dyn Display))
+            .collect();
+        f.write_str(&message(self.identifier, &args))
+    }
+}
+
+impl Error for Message {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let catalog = Catalog::parse(
+            "# A comment.\n\
+             greeting = Hello, { $name }!\n\
+             \n\
+             help =\n    Usage: wc_rs\n\n      -h  print { \"{\" }this{ \"}\" }\n\n\
+             partial = { $name } and { $missing } {\n\
+             continued = First\n  second\n",
+        );
+
+        assert_eq!(
+            catalog.format("greeting", &[("name", &"world")]),
+            Some("Hello, world!".to_string())
+        );
+        assert_eq!(
+            catalog.format("help", &[]),
+            Some("Usage: wc_rs\n\n  -h  print {this}".to_string())
+        );
+        assert_eq!(
+            catalog.format("partial", &[("name", &3)]),
+            Some("3 and { $missing } {".to_string())
+        );
+        assert_eq!(
+            catalog.format("continued", &[]),
+            Some("First\nsecond".to_string())
+        );
+        assert_eq!(catalog.format("absent", &[]), None);
+    }
+
+    #[test]
+    fn test_language_of() {
+        assert_eq!(language_of("de_DE.UTF-8"), "de");
+        assert_eq!(language_of("de"), "de");
+        assert_eq!(language_of("sr_RS@latin"), "sr");
+        assert_eq!(language_of("C.UTF-8"), "en");
+        assert_eq!(language_of("POSIX"), "en");
+    }
+
+    #[test]
+    fn test_catalogs_are_complete() {
+        let english = english();
+        for (code, text) in &CATALOGS[1..] {
+            let catalog = Catalog::parse(text);
+            let mut missing: Vec<&String> = english
+                .messages
+                .keys()
+                .filter(|identifier| !catalog.messages.contains_key(*identifier))
+                .collect();
+            let mut unknown: Vec<&String> = catalog
+                .messages
+                .keys()
+                .filter(|identifier| !english.messages.contains_key(*identifier))
+                .collect();
+            missing.sort();
+            unknown.sort();
+            assert_eq!((code, missing, unknown), (code, vec![], vec![]));
+        }
+        assert_eq!(message("absent", &[]), "absent");
+    }
+
+    #[test]
+    fn test_message() {
+        let message = Message::new("unrecognized-option").arg("option", "--frobnicate");
+        assert_eq!(message.identifier(), "unrecognized-option");
+        assert_eq!(message.to_string(), "unrecognized option '--frobnicate'");
+    }
+}
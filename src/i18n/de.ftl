@@ -0,0 +1,380 @@
+# Die Meldungen von wc_rs auf Deutsch.
+
+## Hilfe
+
+help =
+    Aufruf: wc_rs [OPTION]... [DATEI]...
+            wc_rs [OPTION]... git-diff BEREICH [--] [PFAD]...
+            wc_rs [OPTION]... history [--every tag|commit] [--] [PFAD]...
+            wc_rs [OPTION]... hook [--staged] [--assert ZUSICHERUNG]... [--] [PFAD]...
+            wc_rs [OPTION]... run MANIFEST
+            wc_rs [OPTION]... --jsonrpc
+
+    Gibt die Anzahl der Zeilen, Wörter und Bytes jeder DATEI aus, und eine Gesamtzeile, wenn mehr als eine
+    DATEI angegeben ist. Ohne DATEI, oder wenn DATEI - ist, wird die Standardeingabe gelesen. Eine DATEI der
+    Form tcp://HOST:PORT oder unix:///PFAD ist ein Socket, der verbunden und bis zu seinem Schließen gelesen
+    wird. Eine DATEI der Form kafka://HOST:PORT/THEMA/PARTITION, gefolgt von ?start=OFFSET&end=OFFSET, um
+    einen Bereich zu zählen, steht für die Werte der Datensätze einer Kafka-Partition, einer je Zeile.
+
+    Zählungen:
+      -l, -w, -m, -c, -L         nur die Zeilen, Wörter, Zeichen, Bytes oder die Länge der längsten Zeile
+                                 ausgeben, in Anzeigespalten mit Tabstopps alle 8 Spalten
+          --lines=DEFINITION     Zeilenvorschübe zählen, oder Datensätze einschließlich einer nicht
+                                 abgeschlossenen letzten Zeile: terminators oder records
+          --word-rules REGELN    Wörter nach REGELN trennen: simple oder english
+          --segmenter SEGMENTIERER
+                                 Wörter von Schriften ohne Leerzeichen mit SEGMENTIERER trennen: cjk
+          --unicode-words        Wörter an Unicode-Leerraum trennen, etwa an geschützten und ideografischen
+                                 Leerzeichen, statt an ASCII-Leerraum
+          --invalid-utf8 REGEL   Bytes, die kein gültiges UTF-8 sind, beim Zählen von Zeichen oder
+                                 dekodierten Wörtern als Ersatzzeichen zählen, überspringen, oder mit einem
+                                 Fehler samt Offset des ersten abbrechen: replace, skip oder error
+          --emoji                Emoji zählen
+          --graphemes            Graphemcluster zählen, die Zeichen, wie ein Leser sie wahrnimmt, etwa eine
+                                 Emoji-Folge oder ein Buchstabe mit kombinierenden Akzenten
+          --token-classes        URLs, E-Mail-Adressen und Zahlen zählen
+          --social               Hashtags und Erwähnungen zählen
+          --syllables            die Silben der Wörter schätzen
+          --whitespace-stats     Leerraumzeichen nach Art zählen
+          --log-levels           die Zeilen jeder Protokollstufe zählen: error, warn, info und debug
+          --allocated            den für jede Datei auf der Platte belegten Platz in Bytes zählen
+          --line-stats           die kürzesten und längsten Zeilen in Bytes und Wörtern finden, wo sie
+                                 stehen, und die mittlere Länge und Wortzahl der Zeilen
+
+    Eingaben:
+          --subtitles, --latex, --asciidoc, --rst
+                                 nur den Fließtext von Untertiteln oder ausgezeichnetem Text zählen
+          --ipynb                die Zellen eines Jupyter-Notebooks nach Art zählen
+          --email                die Nachrichten und den Nachrichtentext eines Postfachs zählen
+          --epub                 jedes Kapitel eines E-Books zählen
+          --archive              jede Datei eines tar-, tar.gz- oder zip-Archivs als ARCHIV!PFAD zählen
+          --pdf, --docx, --odt   den Text eines Dokuments zählen
+          --decompress           den entpackten Inhalt von gzip-Eingaben zählen, die an ihren ersten Bytes
+                                 erkannt werden, die Blöcke von bgzip-Eingaben parallel entpacken, und
+                                 andere Eingaben unverändert zählen
+          --encoding KODIERUNG   Eingaben für alles außer der Byte-Anzahl aus KODIERUNG dekodieren: utf8,
+                                 utf16le, utf16be, oder auto für UTF-16 mit Byte-Order-Mark und sonst UTF-8
+          --grep MUSTER          nur die Zeilen zählen, auf die MUSTER passt
+          --grep-v MUSTER        nur die Zeilen zählen, auf die MUSTER nicht passt
+          --last-lines N         nur die letzten N Zeilen jeder Datei zählen
+          --last-bytes N         nur die letzten N Bytes jeder Datei zählen
+          --log-buckets GRÖSSE   die Zeilen eines Protokolls nach ihren führenden Zeitstempeln in Abschnitten
+                                 von einer Minute, Stunde oder einem Tag zählen
+          --timestamp-format FMT die Zeitstempel von --log-buckets mit FMT aus %Y, %m, %b, %d, %H, %M, %S und
+                                 wörtlichem Text lesen, statt mit %Y-%m-%d %H:%M:%S
+          --stop-after bytes=N|lines=N
+                                 nur die ersten N Bytes oder Zeilen jeder Eingabe zählen
+          --concat               alle Dateien als eine durchgehende Eingabe zählen
+          --mmap                 reguläre Dateien in den Speicher abbilden und jede als Ganzes parallel zählen
+          --glob                 Platzhalter in DATEI-Operanden erweitern
+      -r, --recursive            die Dateien unterhalb jedes Verzeichnisses DATEI zählen
+          --include MUSTER       unterhalb von Verzeichnissen nur die Dateien zählen, deren Namen auf MUSTER
+                                 passen
+          --exclude MUSTER       die Dateien und Verzeichnisse unterhalb von Verzeichnissen überspringen,
+                                 deren Namen auf MUSTER passen
+          --respect-gitignore    die von .gitignore-Dateien ignorierten Dateien und Verzeichnisse unterhalb
+                                 von Verzeichnissen überspringen, und versteckte, deren Namen mit einem Punkt
+                                 beginnen
+          --hidden               auch mit --respect-gitignore in versteckte Dateien und Verzeichnisse gehen
+          --file-types ARTEN     unterhalb von Verzeichnissen nur in Dateien der kommagetrennten ARTEN gehen,
+                                 regular, symlink, fifo und socket, standardmäßig regular,symlink
+          --skip-empty           leere Dateien unterhalb von Verzeichnissen überspringen und ihre Anzahl
+                                 melden
+          --newer-than DATUM     unterhalb von Verzeichnissen nur die Dateien zählen, die nach DATUM in UTC
+                                 geändert wurden, etwa 2024-01-01 oder 2024-01-01T12:30:00
+          --newer-than-file REF  unterhalb von Verzeichnissen nur die Dateien zählen, die nach REF geändert
+                                 wurden
+          --git-tracked          unterhalb von Verzeichnissen nur die Dateien zählen, die git verfolgt
+          --dry-run              die Dateien, die gezählt würden, je Zeile eine auflisten, ohne sie zu zählen
+      -v, --verbose              die unterhalb von Verzeichnissen übergangenen Dateien und Verzeichnisse mit
+                                 Grund melden
+          --symlinks REGEL       Verknüpfungen unterhalb von Verzeichnissen überspringen, nur Verknüpfungen
+                                 auf Dateien folgen, oder allen folgen: skip, files oder follow
+          --find-duplicates      nach den Zeilen die Gruppen von Dateien mit gleichem Inhalt samt ihren
+                                 Zählungen auflisten
+          --files0-from=D        die Dateien zählen, deren Namen NUL-terminiert in D stehen, oder in der
+                                 Standardeingabe, wenn D - ist
+          --stdin-name NAME      die Standardeingabe in Zeilen und Fehlern NAME nennen, statt sie ohne Namen
+                                 zu lassen
+          --corpus-report        die Kodierungen und Zeilenenden der Dateien unterhalb jeder DATEI
+                                 zusammenfassen
+
+    Ausgabe:
+          --columns LISTE        die kommagetrennten Spalten von LISTE ausgeben
+          --output FORMAT        Zeilen als text, json, csv oder tsv ausgeben
+          --template DATEI       die Zeilen mit der Vorlage in DATEI darstellen
+          --precision N          Mittelwerte, Verhältnisse und Anteile mit N Nachkommastellen ausgeben
+          --bare                 die eine gewählte Zählung einer Eingabe ohne Auffüllung oder Namen ausgeben
+          --summary-to ZIEL      die Gesamtzeile auf stderr oder in die Datei ZIEL schreiben
+          --report-extremes DATEI
+                                 die bemerkenswerten Stellen jeder Eingabe in DATEI schreiben
+          --window DAUER         jede Sekunde die Zählungen eines Datenstroms über ein gleitendes Fenster
+                                 ausgeben
+          --interval DAUER       alle DAUER die bisherigen Zählungen eines Datenstroms ausgeben
+          --fail-if-empty        mit Status 3 beenden, wenn keine Bytes gezählt wurden
+          --expect ZÄHLUNG=N     mit Status 4 beenden und die Abweichungen auflisten, außer die gesamte
+                                 ZÄHLUNG ist N
+          --rusage               die verbrauchten Ressourcen auf der Standardfehlerausgabe melden
+
+    Ressourcen:
+          --bwlimit RATE         nicht schneller als RATE Bytes pro Sekunde lesen
+          --buffer-size BYTES    Eingaben durch einen Puffer von BYTES lesen, etwa 64K oder 4M, statt 512K
+          --engine VERFAHREN     Wörter, Zeilen und Zeichen mit Vektorbefehlen zählen, oder mit einem
+                                 tabellengesteuerten Automaten für Ziele ohne sie: simd oder dfa
+          --nice                 die Priorität bei der CPU-Zuteilung senken
+          --ionice-idle          die Priorität bei der E/A-Zuteilung auf Leerlauf senken
+          --threads N            mit N Threads statt einem je Kern zählen; WC_RS_THREADS legt den Standard
+                                 fest
+          --sandbox              den Zugriff auf Dateisystem und Netzwerk aufgeben, sobald die Eingabe
+                                 geöffnet ist
+
+          --jsonrpc              JSON-RPC-Zählanfragen, gerahmt wie beim Language Server Protocol, auf der
+                                 Standardein- und -ausgabe bedienen
+      -h, --help                 diese Hilfe ausgeben und beenden
+      -V, --version              die Version ausgeben und beenden
+
+## Aufruffehler
+
+usage-hint = „wc_rs --help“ gibt weitere Informationen.
+unrecognized-option = unbekannte Option „{ $option }“
+extra-operand = zusätzlicher Operand „{ $operand }“
+files0-from-operands = Datei-Operanden können nicht mit --files0-from kombiniert werden.
+recursive-requires-a-file = --recursive erfordert eine DATEI
+unknown-history-sampling = unbekannte Auswahl der Historie „{ $sampling }“
+no-word-rules-specified = keine Wortregeln angegeben
+invalid-word-rules = ungültige Wortregeln
+no-line-definition-specified = keine Zeilendefinition angegeben
+invalid-line-definition = ungültige Zeilendefinition
+no-invalid-utf-8-policy-specified = keine Regel für ungültiges UTF-8 angegeben
+invalid-policy-for-invalid-utf-8 = ungültige Regel für ungültiges UTF-8
+no-segmenter-specified = kein Segmentierer angegeben
+invalid-segmenter = ungültiger Segmentierer
+no-columns-specified = keine Spalten angegeben
+invalid-columns = ungültige Spalten
+no-template-specified = keine Vorlage angegeben
+no-precision-specified = keine Genauigkeit angegeben
+invalid-precision = ungültige Genauigkeit
+no-summary-output-specified = keine Ausgabe für die Gesamtzeile angegeben
+no-expectation-specified = keine Erwartung angegeben
+invalid-expectation = ungültige Erwartung
+no-rate-specified = keine Rate angegeben
+invalid-rate = ungültige Rate
+no-buffer-size-specified = keine Puffergröße angegeben
+invalid-buffer-size = ungültige Puffergröße
+no-engine-specified = kein Verfahren angegeben
+invalid-engine = ungültiges Verfahren
+no-thread-count-specified = keine Anzahl an Threads angegeben
+invalid-thread-count = ungültige Anzahl an Threads
+no-line-count-specified = keine Zeilenanzahl angegeben
+invalid-line-count = ungültige Zeilenanzahl
+no-byte-count-specified = keine Byte-Anzahl angegeben
+invalid-byte-count = ungültige Byte-Anzahl
+no-pattern-specified = kein Muster angegeben
+invalid-pattern = ungültiges Muster
+no-extremes-file-specified = keine Datei für die Extremwerte angegeben
+no-output-format-specified = kein Ausgabeformat angegeben
+invalid-output-format = ungültiges Ausgabeformat
+no-window-specified = kein Fenster angegeben
+invalid-window = ungültiges Fenster
+no-interval-specified = kein Intervall angegeben
+invalid-interval = ungültiges Intervall
+no-limit-specified = keine Grenze angegeben
+invalid-limit = ungültige Grenze
+no-bucket-size-specified = keine Abschnittsgröße angegeben
+invalid-bucket-size = ungültige Abschnittsgröße
+no-timestamp-format-specified = kein Zeitstempelformat angegeben
+invalid-timestamp-format = ungültiges Zeitstempelformat
+no-file-name-list-specified = keine Liste von Dateinamen angegeben
+no-name-specified = kein Name angegeben
+no-file-types-specified = keine Dateiarten angegeben
+invalid-file-types = ungültige Dateiarten
+no-date-specified = kein Datum angegeben
+invalid-date = ungültiges Datum
+no-reference-file-specified = keine Referenzdatei angegeben
+no-symlink-policy-specified = keine Regel für Verknüpfungen angegeben
+invalid-symlink-policy = ungültige Regel für Verknüpfungen
+no-encoding-specified = keine Kodierung angegeben
+invalid-encoding = ungültige Kodierung
+bare-requires-a-single-count = --bare erfordert eine einzelne Zählung, gewählt mit --columns, -l, -w, -m, -c oder -L
+bare-requires-a-single-input = --bare erfordert eine einzelne Eingabe, oder --concat
+bare-with-parts = --bare kann nicht mit --ipynb, --epub, --archive oder --log-buckets verwendet werden, die Eingaben in Teilen zählen
+invalid-wc-rs-threads = ungültiges WC_RS_THREADS
+sandbox-with-subcommands = --sandbox kann nicht mit Unterbefehlen verwendet werden, die git ausführen oder nach und nach Dateien öffnen
+window-and-interval-require-a-single-input = --window und --interval erfordern eine einzelne Eingabe
+no-revision-range-specified = kein Revisionsbereich angegeben
+no-history-sampling-specified = keine Auswahl der Historie angegeben
+no-assertion-specified = keine Zusicherung angegeben
+invalid-assertion = ungültige Zusicherung
+run-requires-a-single-manifest = run erfordert ein einzelnes Manifest
+
+## Ungültige Werte von Optionen
+
+unknown-line-definition = Unbekannte Zeilendefinition '{ $value }'
+unknown-engine = Unbekannte Zählmaschine '{ $value }'
+unknown-invalid-utf-8-policy = Unbekannte Regel für ungültiges UTF-8 '{ $value }'
+unknown-word-rules = Unbekannte Wortregeln '{ $value }'
+unknown-segmenter = Unbekannte Segmentierung '{ $value }'
+unknown-encoding = Unbekannte Kodierung '{ $value }'
+unknown-bucket-size = Unbekannte Größe der Zeitabschnitte '{ $value }'
+unsupported-directive = Nicht unterstützte Direktive '%{ $directive }'
+incomplete-directive = Unvollständige Direktive am Ende des Formats
+unknown-column = Unbekannte Spalte '{ $value }'
+unknown-output-format = Unbekanntes Ausgabeformat '{ $value }'
+malformed-expectation = Ungültige Erwartung '{ $value }'
+not-a-count = '{ $column }' ist keine Zählung
+malformed-assertion = Ungültige Zusicherung '{ $value }'
+unknown-format = Unbekanntes Format '{ $value }'
+unknown-symlink-policy = Unbekannte Regel für Verknüpfungen '{ $value }'
+unknown-file-type = Unbekannte Dateiart '{ $value }'
+malformed-date = Ungültiges Datum '{ $value }'
+malformed-duration = Ungültige Dauer '{ $value }'
+malformed-rate = Ungültige Rate '{ $value }'
+no-threads = mindestens ein Thread ist erforderlich
+not-a-thread-count = '{ $value }' ist keine Anzahl von Threads
+not-a-buffer-size = '{ $value }' ist keine Puffergröße
+pattern-nothing-repeatable = Quantor folgt auf nichts Wiederholbares
+pattern-unbalanced-open = Unausgeglichene '(' im Muster
+pattern-nothing-to-repeat = Nichts zu wiederholen vor '{ $quantifier }'
+pattern-unexpected-end = Unerwartetes Ende des Musters
+pattern-trailing-backslash = Muster endet mit '\'
+pattern-invalid-range = Ungültiger Bereich '{ $first }-{ $last }' im Muster
+pattern-unsupported-escape = Nicht unterstützte Escape-Sequenz '\{ $escape }' im Muster
+pattern-invalid-quantifier = Ungültiger Quantor '{ "{" }{ $bounds }{ "}" }' im Muster
+pattern-too-large = Muster ist zu groß
+pattern-unbalanced-close = Unausgeglichene ')' im Muster
+pattern-unterminated-class = Nicht abgeschlossene Klasse im Muster
+malformed-kafka-partition = Ungültige Kafka-Partition '{ $value }'
+malformed-limit = Ungültige Begrenzung '{ $value }'
+
+## Fehlerhafte Manifeste und Vorlagen
+
+manifest-unsupported-escape = nicht unterstützte Escape-Sequenz
+manifest-unterminated-string = nicht abgeschlossene Zeichenkette
+manifest-expected-separator = ',' oder '{ "}" }' in Inline-Tabelle erwartet
+manifest-invalid-number = ungültige Zahl
+manifest-expected-value = Zeichenkette, Zahl oder Inline-Tabelle erwartet
+manifest-expected-key = Schlüssel erwartet
+manifest-expected = '{ $expected }' erwartet
+manifest-line = Zeile { $line }: { $error }
+manifest-no-path = Eingabe { $input } hat keinen Pfad
+manifest-unknown-table = unbekannte Tabelle '{ $table }'
+manifest-text-after-value = unerwarteter Text nach dem Wert
+manifest-key-outside-input = Schlüssel müssen auf eine Überschrift [[input]] folgen
+manifest-unknown-key = unbekannter oder ungültiger Schlüssel '{ $key }'
+manifest-count-not-a-number = erwartete Zählung '{ $column }' ist keine Zahl
+template-unterminated-expression = Nicht abgeschlossener Ausdruck in der Vorlage
+template-unopened-each = Vorlage hat '{ "{{" }/each{ "}}" }' ohne '{ "{{" }#each rows{ "}}" }'
+template-value-outside-each = Vorlage verwendet '{ "{{" }{ $expression }{ "}}" }' außerhalb von '{ "{{" }#each rows{ "}}" }'
+template-unclosed-each = Vorlage hat '{ "{{" }#each rows{ "}}" }' ohne '{ "{{" }/each{ "}}" }'
+
+## Fehlerhafte Dokumente
+
+invalid-json = Ungültiges JSON bei Byte { $offset }: { $problem }
+json-expected = '{ $expected }' erwartet
+json-trailing-characters = nachfolgende Zeichen
+json-unexpected-character = unerwartetes Zeichen
+json-unexpected-end = unerwartetes Ende der Eingabe
+json-malformed-number = fehlerhafte Zahl
+json-malformed-unicode-escape = fehlerhaftes Unicode-Escape
+json-unterminated-string = nicht abgeschlossene Zeichenkette
+json-unknown-escape = unbekanntes Escape
+json-invalid-utf-8 = Zeichenkette ist kein gültiges UTF-8
+json-expected-array-separator = ',' oder ']' erwartet
+json-expected-object-separator = ',' oder '{ "}" }' erwartet
+invalid-zip-archive = Ungültiges ZIP-Archiv
+zip-no-entry = Kein '{ $name }' im ZIP-Archiv
+invalid-zip-entry = Ungültiger ZIP-Eintrag '{ $name }'
+invalid-compressed-zip-entry = Ungültiger ZIP-Eintrag '{ $name }': { $error }
+unsupported-zip-method = Nicht unterstützte Kompressionsmethode { $method } für ZIP-Eintrag '{ $name }'
+notebook-no-cells = Notebook hat keine Zellen
+epub-no-package-document = EPUB-Container nennt kein Paketdokument
+epub-spine-item-not-in-manifest = EPUB-Spine-Eintrag '{ $item }' ist nicht im Manifest
+not-a-pdf-document = Kein PDF-Dokument
+encrypted-pdf-document = Verschlüsselte PDF-Dokumente werden nicht unterstützt
+pdf-unsupported = PDF-Unterstützung erfordert das Bauen mit dem Feature `pdf`
+
+## Fehler beim Lesen von Eingaben
+
+directory-already-walked = Verzeichnis bereits durchlaufen als { $path }
+invalid-kafka-response = Ungültige Antwort von Kafka: { $problem }
+kafka-offset-out-of-range = Offset außerhalb des Bereichs
+kafka-unknown-partition = unbekanntes Topic oder unbekannte Partition
+kafka-not-leader = der Broker ist nicht der Leader der Partition
+kafka-error-code = Kafka-Fehlercode { $code }
+kafka-truncated = abgeschnitten
+kafka-varint-too-long = Ganzzahl variabler Länge zu lang
+kafka-negative-length = negative Länge
+kafka-unsupported-batch-format = nicht unterstütztes Format des Record-Batches
+kafka-unsupported-codec = nicht unterstützter Kompressionscodec
+kafka-mismatched-correlation = nicht übereinstimmende Korrelationskennung
+kafka-no-topics = keine Topics
+kafka-no-partitions = keine Partitionen
+kafka-no-complete-batch = kein vollständiger Record-Batch
+stream-tail = das Ende eines Datenstroms kann nicht angesteuert werden
+unix-sockets-unsupported = Unix-Domain-Sockets werden auf dieser Plattform nicht unterstützt
+kafka-unsupported = Kafka-Unterstützung erfordert das Bauen mit dem Feature `kafka`
+invalid-deflate-stream = Ungültiger Deflate-Datenstrom: { $problem }
+invalid-gzip-header = Ungültiger gzip-Header: { $problem }
+deflate-over-subscribed-code = überbelegter Code
+deflate-incomplete-code = unvollständiger Code
+deflate-stored-length-mismatch = Länge des unkomprimierten Blocks stimmt nicht überein
+deflate-reserved-block-type = reservierter Blocktyp
+deflate-repeat-without-length = Wiederholung ohne vorherige Länge
+deflate-too-many-code-lengths = zu viele Codelängen
+deflate-invalid-length-symbol = ungültiges Längensymbol
+deflate-invalid-distance-symbol = ungültiges Distanzsymbol
+deflate-distance-too-far-back = Distanz reicht zu weit zurück
+deflate-unsupported-zlib-header = nicht unterstützter zlib-Header
+gzip-unsupported-magic-or-method = nicht unterstützte Signatur oder Methode
+truncated-tar-archive = Abgeschnittenes tar-Archiv
+invalid-tar-checksum = Ungültige Prüfsumme des tar-Headers
+invalid-tar-size = Ungültige tar-Größe
+compressed-archive-unsupported = Mit { $compression } komprimierte Archive werden nicht unterstützt
+bgzf-no-block-size = Ungültiger BGZF-Block: keine Blockgröße
+decompression-unsupported = Dekomprimierung von { $compression } wird nicht unterstützt
+file-too-large-to-map = die Datei ist zu groß, um sie abzubilden
+mmap-unsupported = Speicherabbildung wird auf dieser Plattform nicht unterstützt
+empty-file-name = { $index }: ungültiger Dateiname der Länge null
+file-name-not-utf-8 = { $index }: Dateiname ist kein gültiges UTF-8
+git-unavailable = git kann nicht ausgeführt werden: { $error }
+git-unwritable = an git kann nicht geschrieben werden: { $error }
+git-failed = git { $command } ist fehlgeschlagen: { $error }
+truncated-git-object = Abgeschnittenes git-Objekt
+unexpected-git-object-header = Unerwarteter Header eines git-Objekts '{ $header }'
+git-output-not-utf-8 = Die Ausgabe von git ist kein gültiges UTF-8
+landlock-unavailable = Landlock ist nicht verfügbar: { $error }
+landlock-ruleset-failed = Landlock-Regelsatz kann nicht erstellt werden: { $error }
+no-new-privs-failed = no_new_privs kann nicht gesetzt werden: { $error }
+landlock-restrict-failed = Landlock-Regelsatz kann nicht durchgesetzt werden: { $error }
+landlock-abi-too-old = Landlock-ABI { $abi } kann keinen Netzwerkzugriff verweigern, was ABI 4 erfordert
+pledge-failed = pledge ist fehlgeschlagen: { $error }
+sandbox-unsupported = Sandboxing wird nur unter Linux und OpenBSD unterstützt
+invalid-utf-8 = Ungültiges UTF-8 bei Byte { $offset }
+
+## Status
+
+nice-failed = die Priorität bei der CPU-Zuteilung kann nicht gesenkt werden
+ionice-failed = die Priorität bei der E/A-Zuteilung kann nicht gesenkt werden
+priority-unsupported = die Priorität bei der CPU-Zuteilung wird nur unter Unix gesetzt
+io-priority-unsupported = die Priorität bei der E/A-Zuteilung wird nur unter Linux auf x86-64 und AArch64 gesetzt
+counts-differ = die Zählungen weichen von den erwarteten ab
+count-mismatch = { $column }: erwartet { $expected }, gezählt { $counted } ({ $difference })
+assertion-failed = { $path }: Zusicherung { $assertion } verletzt mit { $value }
+empty-files-skipped = { $count } leere Dateien übersprungen
+resource-usage = max. RSS { $rss } KiB, Benutzer { $user } s, System { $system } s, Threads { $threads }
+resource-usage-failed = die verbrauchten Ressourcen können nicht gelesen werden
+resource-usage-unsupported = die verbrauchten Ressourcen werden nur unter Unix gelesen
+
+## Warum Dateien unterhalb von Verzeichnissen übersprungen werden
+
+skipped = { $path }: übersprungen, { $reason }
+skipped-excluded = ausgeschlossen durch --exclude
+skipped-symlink = eine symbolische Verknüpfung
+skipped-symlink-to-directory = eine symbolische Verknüpfung auf ein Verzeichnis
+skipped-hidden = versteckt
+skipped-gitignored = ignoriert durch .gitignore
+skipped-file-type = nicht von einer Art in --file-types
+skipped-empty = leer
+skipped-not-newer = nicht geändert seit --newer-than
+skipped-not-included = nicht erfasst von --include
+skipped-untracked = nicht von git verfolgt
@@ -0,0 +1,367 @@
+# The messages of wc_rs in English, which every other catalog translates.
+
+## Help
+
+help =
+    Usage: wc_rs [OPTION]... [FILE]...
+           wc_rs [OPTION]... git-diff RANGE [--] [PATH]...
+           wc_rs [OPTION]... history [--every tag|commit] [--] [PATH]...
+           wc_rs [OPTION]... hook [--staged] [--assert ASSERTION]... [--] [PATH]...
+           wc_rs [OPTION]... run MANIFEST
+           wc_rs [OPTION]... --jsonrpc
+
+    Print the line, word and byte counts of each FILE, and a total row if more than one FILE is given.
+    With no FILE, or when FILE is -, read standard input. A FILE of tcp://HOST:PORT or unix:///PATH is a
+    socket connected to and read until it is closed. A FILE of kafka://HOST:PORT/TOPIC/PARTITION, followed
+    by ?start=OFFSET&end=OFFSET to count a range, is the values of the records of a Kafka topic partition,
+    one to a line.
+
+    Counts:
+      -l, -w, -m, -c, -L         print only the lines, words, characters, bytes or longest line length,
+                                 in display columns with tabs stopping every 8 columns
+          --lines=DEFINITION     count line feeds, or records including an unterminated final line:
+                                 terminators or records
+          --word-rules RULES     split words by RULES: simple or english
+          --segmenter SEGMENTER  split words of scripts without spaces with SEGMENTER: cjk
+          --unicode-words        split words on Unicode whitespace, such as no-break and ideographic spaces,
+                                 rather than ASCII whitespace
+          --invalid-utf8 POLICY  count bytes which are not valid UTF-8, when counting characters or decoded
+                                 words, as replacement characters, skip them, or stop with an error giving
+                                 the offset of the first: replace, skip or error
+          --emoji                count emoji
+          --graphemes            count grapheme clusters, the characters a reader perceives, such as an
+                                 emoji sequence or a letter with combining accents
+          --token-classes        count URLs, email addresses and numbers
+          --social               count hashtags and mentions
+          --syllables            estimate the syllables of the words
+          --whitespace-stats     count whitespace characters by kind
+          --log-levels           count the lines logged at each level: error, warn, info and debug
+          --allocated            count the space allocated on disk to each file, in bytes
+          --line-stats           find the shortest and longest lines, in bytes and words, where they are and
+                                 the mean length and words of the lines
+
+    Inputs:
+          --subtitles, --latex, --asciidoc, --rst
+                                 count only the prose of subtitles or marked up text
+          --ipynb                count the cells of a Jupyter notebook by kind
+          --email                count the messages and body text of a mailbox
+          --epub                 count each chapter of an e-book
+          --archive              count each file of a tar, tar.gz or zip archive, as ARCHIVE!PATH
+          --pdf, --docx, --odt   count the text of a document
+          --decompress           count the decompressed contents of gzip inputs, detected by their leading
+                                 bytes, decompressing the blocks of bgzip inputs in parallel, and count other
+                                 inputs as they are
+          --encoding ENCODING    decode inputs from ENCODING for all but the byte count: utf8, utf16le,
+                                 utf16be, or auto for UTF-16 with a byte order mark and UTF-8 otherwise
+          --grep PATTERN         count only the lines matching PATTERN
+          --grep-v PATTERN       count only the lines not matching PATTERN
+          --last-lines N         count only the last N lines of each file
+          --last-bytes N         count only the last N bytes of each file
+          --log-buckets SIZE     count the lines of a log in buckets of a minute, hour or day by their leading
+                                 timestamps
+          --timestamp-format FMT parse the timestamps of --log-buckets with FMT, of %Y, %m, %b, %d, %H, %M, %S
+                                 and literal text, rather than %Y-%m-%d %H:%M:%S
+          --stop-after bytes=N|lines=N
+                                 count only the first N bytes or lines of each input
+          --concat               count all files as one continuous input
+          --mmap                 map regular files into memory and count each whole in parallel
+          --glob                 expand wildcards in FILE operands
+      -r, --recursive            count the files beneath each directory FILE
+          --include PATTERN      count only the files beneath directories whose names match PATTERN
+          --exclude PATTERN      skip the files and directories beneath directories whose names match PATTERN
+          --respect-gitignore    skip the files and directories ignored by .gitignore files beneath directories,
+                                 and hidden ones whose names start with a dot
+          --hidden               walk to hidden files and directories even with --respect-gitignore
+          --file-types TYPES     walk only to the files beneath directories of the comma-separated TYPES,
+                                 regular, symlink, fifo and socket, or regular,symlink by default
+          --skip-empty           skip the empty files beneath directories, reporting how many there were
+          --newer-than DATE      count only the files beneath directories modified after DATE, in UTC, such
+                                 as 2024-01-01 or 2024-01-01T12:30:00
+          --newer-than-file REF  count only the files beneath directories modified after REF was
+          --git-tracked          count only the files beneath directories which git tracks
+          --dry-run              list the files which would be counted, one per line, without counting them
+      -v, --verbose              report the files and directories passed over beneath directories, and why
+          --symlinks POLICY      skip links beneath directories, follow links to files only, or follow all
+                                 links: skip, files or follow
+          --find-duplicates      list the groups of files with identical contents after the rows, with the
+                                 counts each has
+          --files0-from=F        count the files named by the NUL-terminated names in F, or standard input if
+                                 F is -
+          --stdin-name LABEL     name standard input LABEL in rows and errors rather than leaving it blank
+          --corpus-report        summarize the encodings and line endings of the files beneath each FILE
+
+    Output:
+          --columns LIST         print the comma-separated columns of LIST
+          --output FORMAT        print rows as text, json, csv or tsv
+          --template FILE        render the rows with the template in FILE
+          --precision N          print means, ratios and shares to N decimal places
+          --bare                 print the single count selected of one input without padding or name
+          --summary-to TARGET    write the total row to stderr or to the file TARGET
+          --report-extremes FILE write the notable positions of each input to FILE
+          --window DURATION      print the counts of a stream over a sliding window every second
+          --interval DURATION    print the counts of a stream so far every DURATION
+          --fail-if-empty        exit with status 3 if no bytes are counted
+          --expect COUNT=N       exit with status 4, listing the differences, unless the total COUNT is N
+          --rusage               report the resources used to standard error
+
+    Resources:
+          --bwlimit RATE         read no faster than RATE bytes per second
+          --buffer-size BYTES    read inputs through a buffer of BYTES, such as 64K or 4M, rather than 512K
+          --engine ENGINE        count words, lines and characters with vector instructions, or a table-driven
+                                 automaton for targets without them: simd or dfa
+          --nice                 lower the CPU scheduling priority
+          --ionice-idle          lower the I/O scheduling priority to idle
+          --threads N            count with N threads rather than one per core; WC_RS_THREADS sets the
+                                 default
+          --sandbox              give up filesystem and network access once the input is open
+
+          --jsonrpc              serve JSON-RPC count requests framed as by the Language Server Protocol on
+                                 standard input and output
+      -h, --help                 print this help and exit
+      -V, --version              print the version and exit
+
+## Usage errors
+
+usage-hint = Try 'wc_rs --help' for more information.
+unrecognized-option = unrecognized option '{ $option }'
+extra-operand = extra operand '{ $operand }'
+files0-from-operands = File operands cannot be combined with --files0-from.
+recursive-requires-a-file = --recursive requires a FILE
+unknown-history-sampling = unknown history sampling '{ $sampling }'
+no-word-rules-specified = no word rules specified
+invalid-word-rules = invalid word rules
+no-line-definition-specified = no line definition specified
+invalid-line-definition = invalid line definition
+no-invalid-utf-8-policy-specified = no invalid UTF-8 policy specified
+invalid-policy-for-invalid-utf-8 = invalid policy for invalid UTF-8
+no-segmenter-specified = no segmenter specified
+invalid-segmenter = invalid segmenter
+no-columns-specified = no columns specified
+invalid-columns = invalid columns
+no-template-specified = no template specified
+no-precision-specified = no precision specified
+invalid-precision = invalid precision
+no-summary-output-specified = no summary output specified
+no-expectation-specified = no expectation specified
+invalid-expectation = invalid expectation
+no-rate-specified = no rate specified
+invalid-rate = invalid rate
+no-buffer-size-specified = no buffer size specified
+invalid-buffer-size = invalid buffer size
+no-engine-specified = no engine specified
+invalid-engine = invalid engine
+no-thread-count-specified = no thread count specified
+invalid-thread-count = invalid thread count
+no-line-count-specified = no line count specified
+invalid-line-count = invalid line count
+no-byte-count-specified = no byte count specified
+invalid-byte-count = invalid byte count
+no-pattern-specified = no pattern specified
+invalid-pattern = invalid pattern
+no-extremes-file-specified = no extremes file specified
+no-output-format-specified = no output format specified
+invalid-output-format = invalid output format
+no-window-specified = no window specified
+invalid-window = invalid window
+no-interval-specified = no interval specified
+invalid-interval = invalid interval
+no-limit-specified = no limit specified
+invalid-limit = invalid limit
+no-bucket-size-specified = no bucket size specified
+invalid-bucket-size = invalid bucket size
+no-timestamp-format-specified = no timestamp format specified
+invalid-timestamp-format = invalid timestamp format
+no-file-name-list-specified = no file name list specified
+no-name-specified = no name specified
+no-file-types-specified = no file types specified
+invalid-file-types = invalid file types
+no-date-specified = no date specified
+invalid-date = invalid date
+no-reference-file-specified = no reference file specified
+no-symlink-policy-specified = no symlink policy specified
+invalid-symlink-policy = invalid symlink policy
+no-encoding-specified = no encoding specified
+invalid-encoding = invalid encoding
+bare-requires-a-single-count = --bare requires a single count, selected by --columns, -l, -w, -m, -c or -L
+bare-requires-a-single-input = --bare requires a single input, or --concat
+bare-with-parts = --bare cannot be used with --ipynb, --epub, --archive or --log-buckets, which count inputs in parts
+invalid-wc-rs-threads = invalid WC_RS_THREADS
+sandbox-with-subcommands = --sandbox cannot be used with subcommands, which run git or open files as they go
+window-and-interval-require-a-single-input = --window and --interval require a single input
+no-revision-range-specified = no revision range specified
+no-history-sampling-specified = no history sampling specified
+no-assertion-specified = no assertion specified
+invalid-assertion = invalid assertion
+run-requires-a-single-manifest = run requires a single manifest
+
+## Invalid values of options
+
+unknown-line-definition = Unknown line definition '{ $value }'
+unknown-engine = Unknown engine '{ $value }'
+unknown-invalid-utf-8-policy = Unknown invalid UTF-8 policy '{ $value }'
+unknown-word-rules = Unknown word rules '{ $value }'
+unknown-segmenter = Unknown segmenter '{ $value }'
+unknown-encoding = Unknown encoding '{ $value }'
+unknown-bucket-size = Unknown bucket size '{ $value }'
+unsupported-directive = Unsupported directive '%{ $directive }'
+incomplete-directive = Incomplete directive at the end of the format
+unknown-column = Unknown column '{ $value }'
+unknown-output-format = Unknown output format '{ $value }'
+malformed-expectation = Invalid expectation '{ $value }'
+not-a-count = '{ $column }' is not a count
+malformed-assertion = Invalid assertion '{ $value }'
+unknown-format = Unknown format '{ $value }'
+unknown-symlink-policy = Unknown symlink policy '{ $value }'
+unknown-file-type = Unknown file type '{ $value }'
+malformed-date = Invalid date '{ $value }'
+malformed-duration = Invalid duration '{ $value }'
+malformed-rate = Invalid rate '{ $value }'
+no-threads = at least one thread is required
+not-a-thread-count = '{ $value }' is not a number of threads
+not-a-buffer-size = '{ $value }' is not a buffer size
+pattern-nothing-repeatable = Quantifier follows nothing repeatable
+pattern-unbalanced-open = Unbalanced '(' in pattern
+pattern-nothing-to-repeat = Nothing to repeat before '{ $quantifier }'
+pattern-unexpected-end = Unexpected end of pattern
+pattern-trailing-backslash = Pattern ends with '\'
+pattern-invalid-range = Invalid range '{ $first }-{ $last }' in pattern
+pattern-unsupported-escape = Unsupported escape '\{ $escape }' in pattern
+pattern-invalid-quantifier = Invalid quantifier '{ "{" }{ $bounds }{ "}" }' in pattern
+pattern-too-large = Pattern is too large
+pattern-unbalanced-close = Unbalanced ')' in pattern
+pattern-unterminated-class = Unterminated class in pattern
+malformed-kafka-partition = Invalid Kafka partition '{ $value }'
+malformed-limit = Invalid limit '{ $value }'
+
+## Malformed manifests and templates
+
+manifest-unsupported-escape = unsupported escape sequence
+manifest-unterminated-string = unterminated string
+manifest-expected-separator = expected ',' or '{ "}" }' in inline table
+manifest-invalid-number = invalid number
+manifest-expected-value = expected a string, number or inline table
+manifest-expected-key = expected a key
+manifest-expected = expected '{ $expected }'
+manifest-line = line { $line }: { $error }
+manifest-no-path = input { $input } has no path
+manifest-unknown-table = unknown table '{ $table }'
+manifest-text-after-value = unexpected text after value
+manifest-key-outside-input = keys must follow an [[input]] header
+manifest-unknown-key = unknown or invalid key '{ $key }'
+manifest-count-not-a-number = expected count '{ $column }' is not a number
+template-unterminated-expression = Unterminated template expression
+template-unopened-each = Template has '{ "{{" }/each{ "}}" }' without '{ "{{" }#each rows{ "}}" }'
+template-value-outside-each = Template uses '{ "{{" }{ $expression }{ "}}" }' outside '{ "{{" }#each rows{ "}}" }'
+template-unclosed-each = Template has '{ "{{" }#each rows{ "}}" }' without '{ "{{" }/each{ "}}" }'
+
+## Malformed documents
+
+invalid-json = Invalid JSON at byte { $offset }: { $problem }
+json-expected = expected '{ $expected }'
+json-trailing-characters = trailing characters
+json-unexpected-character = unexpected character
+json-unexpected-end = unexpected end of input
+json-malformed-number = malformed number
+json-malformed-unicode-escape = malformed unicode escape
+json-unterminated-string = unterminated string
+json-unknown-escape = unknown escape
+json-invalid-utf-8 = string is not valid UTF-8
+json-expected-array-separator = expected ',' or ']'
+json-expected-object-separator = expected ',' or '{ "}" }'
+invalid-zip-archive = Invalid ZIP archive
+zip-no-entry = No '{ $name }' in ZIP archive
+invalid-zip-entry = Invalid ZIP entry '{ $name }'
+invalid-compressed-zip-entry = Invalid ZIP entry '{ $name }': { $error }
+unsupported-zip-method = Unsupported compression method { $method } for ZIP entry '{ $name }'
+notebook-no-cells = Notebook has no cells
+epub-no-package-document = EPUB container names no package document
+epub-spine-item-not-in-manifest = EPUB spine item '{ $item }' is not in the manifest
+not-a-pdf-document = Not a PDF document
+encrypted-pdf-document = Encrypted PDF documents are not supported
+pdf-unsupported = PDF support requires building with the `pdf` feature
+
+## Errors reading inputs
+
+directory-already-walked = Directory already walked as { $path }
+invalid-kafka-response = Invalid Kafka response: { $problem }
+kafka-offset-out-of-range = offset out of range
+kafka-unknown-partition = unknown topic or partition
+kafka-not-leader = the broker is not the leader of the partition
+kafka-error-code = Kafka error code { $code }
+kafka-truncated = truncated
+kafka-varint-too-long = variable-length integer too long
+kafka-negative-length = negative length
+kafka-unsupported-batch-format = unsupported record batch format
+kafka-unsupported-codec = unsupported compression codec
+kafka-mismatched-correlation = mismatched correlation identifier
+kafka-no-topics = no topics
+kafka-no-partitions = no partitions
+kafka-no-complete-batch = no complete record batch
+stream-tail = the tail of a stream cannot be sought
+unix-sockets-unsupported = Unix domain sockets are not supported on this platform
+kafka-unsupported = Kafka support requires building with the `kafka` feature
+invalid-deflate-stream = Invalid deflate stream: { $problem }
+invalid-gzip-header = Invalid gzip header: { $problem }
+deflate-over-subscribed-code = over-subscribed code
+deflate-incomplete-code = incomplete code
+deflate-stored-length-mismatch = stored block length mismatch
+deflate-reserved-block-type = reserved block type
+deflate-repeat-without-length = repeat without previous length
+deflate-too-many-code-lengths = too many code lengths
+deflate-invalid-length-symbol = invalid length symbol
+deflate-invalid-distance-symbol = invalid distance symbol
+deflate-distance-too-far-back = distance too far back
+deflate-unsupported-zlib-header = unsupported zlib header
+gzip-unsupported-magic-or-method = unsupported magic or method
+truncated-tar-archive = Truncated tar archive
+invalid-tar-checksum = Invalid tar header checksum
+invalid-tar-size = Invalid tar size
+compressed-archive-unsupported = { $compression } compressed archives are not supported
+bgzf-no-block-size = Invalid BGZF block: no block size
+decompression-unsupported = { $compression } decompression is not supported
+file-too-large-to-map = the file is too large to map
+mmap-unsupported = memory mapping is not supported on this platform
+empty-file-name = { $index }: invalid zero-length file name
+file-name-not-utf-8 = { $index }: file name is not valid UTF-8
+git-unavailable = Unable to run git: { $error }
+git-unwritable = Unable to write to git: { $error }
+git-failed = git { $command } failed: { $error }
+truncated-git-object = Truncated git object
+unexpected-git-object-header = Unexpected git object header '{ $header }'
+git-output-not-utf-8 = The output of git is not valid UTF-8
+landlock-unavailable = Landlock is unavailable: { $error }
+landlock-ruleset-failed = Unable to create Landlock ruleset: { $error }
+no-new-privs-failed = Unable to set no_new_privs: { $error }
+landlock-restrict-failed = Unable to enforce Landlock ruleset: { $error }
+landlock-abi-too-old = Landlock ABI { $abi } cannot deny network access, which requires ABI 4
+pledge-failed = pledge failed: { $error }
+sandbox-unsupported = Sandboxing is only supported on Linux and OpenBSD
+invalid-utf-8 = Invalid UTF-8 at byte { $offset }
+
+## Status
+
+nice-failed = unable to lower the scheduling priority
+ionice-failed = unable to lower the I/O priority
+priority-unsupported = scheduling priority is only set on Unix
+io-priority-unsupported = I/O priority is only set on Linux on x86-64 and AArch64
+counts-differ = the counts differ from those expected
+count-mismatch = { $column }: expected { $expected }, counted { $counted } ({ $difference })
+assertion-failed = { $path }: assertion { $assertion } failed with { $value }
+empty-files-skipped = { $count } empty files skipped
+resource-usage = max rss { $rss } KiB, user { $user }s, system { $system }s, threads { $threads }
+resource-usage-failed = unable to read the resources used
+resource-usage-unsupported = resource usage is only read on Unix
+
+## Why files beneath directories are skipped
+
+skipped = { $path }: skipped, { $reason }
+skipped-excluded = excluded by --exclude
+skipped-symlink = a symbolic link
+skipped-symlink-to-directory = a symbolic link to a directory
+skipped-hidden = hidden
+skipped-gitignored = ignored by .gitignore
+skipped-file-type = not of a type in --file-types
+skipped-empty = empty
+skipped-not-newer = not modified since --newer-than
+skipped-not-included = not matched by --include
+skipped-untracked = not tracked by git
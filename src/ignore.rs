@@ -0,0 +1,238 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::glob;
+
+/// A pattern of a `.gitignore` file.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+struct Rule {
+    /// Whether the pattern re-includes the paths it matches, written with a leading `!`.
+    negated: bool,
+    /// Whether the pattern matches only directories, written with a trailing `/`.
+    directory_only: bool,
+    /// Whether the pattern matches paths relative to the directory of its file, as it contains a `/`,
+    /// rather than names at any depth.
+    anchored: bool,
+    /// The components of the pattern, each a wildcard pattern or `**` for any number of components.
+    components: Vec<String>,
+}
+
+impl Rule {
+    /// Parses `line`, or returns `None` if it is blank or a comment.
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end_matches('\r');
+        // Trailing spaces are dropped unless the last is escaped.
+        let line = match line.strip_suffix("\\ ") {
+            Some(escaped) => format!("{} ", escaped),
+            None => line.trim_end_matches(' ').to_string(),
+        };
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line.strip_prefix('\\').unwrap_or(&line)),
+        };
+        let (directory_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(Rule {
+            negated,
+            directory_only,
+            anchored,
+            components: pattern.split('/').map(str::to_string).collect(),
+        })
+    }
+
+    /// Returns `true` if the pattern matches the path with `components`, relative to the directory of its
+    /// file, which is a directory if `is_dir` is set.
+    fn matches(&self, components: &[String], is_dir: bool) -> bool {
+        if self.directory_only && !is_dir {
+            return false;
+        }
+        match (self.anchored, components.last()) {
+            (false, Some(name)) => glob::matches(&self.components[0], name),
+            (false, None) => false,
+            (true, _) => matches_components(&self.components, components),
+        }
+    }
+}
+
+/// Returns `true` if the components of `path` match those of `pattern`. A `**` matches any number of
+/// components, other than a trailing one, which matches everything beneath a directory but not the
+/// directory itself.
+fn matches_components(pattern: &[String], path: &[String]) -> bool {
+    match pattern.first().map(String::as_str) {
+        None => path.is_empty(),
+        Some("**") if pattern.len() == 1 => !path.is_empty(),
+        Some("**") => (0..=path.len()).any(|skip| matches_components(&pattern[1..], &path[skip..])),
+        Some(component) => match path.first() {
+            Some(name) => {
+                glob::matches(component, name) && matches_components(&pattern[1..], &path[1..])
+            }
+            None => false,
+        },
+    }
+}
+
+/// The rules of a `.gitignore` file, or of a repository's `.git/info/exclude`.
+///
+/// Paths are given relative to the root of a walk. The rules apply to those beneath `base`, relative to
+/// the root, whose paths relative to the directory of the file are `prefix` followed by their paths
+/// relative to `base`. For a file beneath the root, `base` is its directory and `prefix` is empty; for one
+/// above the root, `base` is empty and `prefix` is the path of the root relative to its directory.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Gitignore {
+    base: PathBuf,
+    prefix: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl Gitignore {
+    /// Parses `text`, the contents of a file of rules applying as `base` and `prefix` describe.
+    pub fn parse(text: &str, base: PathBuf, prefix: PathBuf) -> Self {
+        Gitignore {
+            base,
+            prefix,
+            rules: text.lines().filter_map(Rule::parse).collect(),
+        }
+    }
+
+    /// Reads the file of rules at `path`, or returns `None` if there is none or it cannot be read.
+    pub fn read(path: &Path, base: PathBuf, prefix: PathBuf) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        Some(Gitignore::parse(&text, base, prefix))
+    }
+
+    /// Returns whether the path `relative` to the root of the walk, a directory if `is_dir` is set, is
+    /// ignored by the last of the rules matching it, or `None` if none do.
+    fn decide(&self, relative: &Path, is_dir: bool) -> Option<bool> {
+        let beneath = relative.strip_prefix(&self.base).ok()?;
+        let components: Vec<String> = self
+            .prefix
+            .join(beneath)
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(&components, is_dir))
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Returns `true` if the path `relative` to the root of a walk, a directory if `is_dir` is set, is ignored
+/// by the files of `rules`, given from the least to the most specific, which take precedence.
+pub fn is_ignored(rules: &[Gitignore], relative: &Path, is_dir: bool) -> bool {
+    rules
+        .iter()
+        .rev()
+        .find_map(|gitignore| gitignore.decide(relative, is_dir))
+        .unwrap_or(false)
+}
+
+/// Returns the rules applying to the walk of `root` from above it: those of the `.gitignore` files of the
+/// directories between it and the root of the repository it is in, and of the repository's
+/// `.git/info/exclude`, from the least to the most specific. Outside a repository there are none.
+pub fn ancestors(root: &Path) -> Vec<Gitignore> {
+    let canonical = match fs::canonicalize(root) {
+        Ok(canonical) => canonical,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut rules = Vec::new();
+    for (depth, directory) in canonical.ancestors().enumerate() {
+        let prefix = canonical
+            .strip_prefix(directory)
+            .expect("Ancestors are prefixes")
+            .to_path_buf();
+        // The root's own file is read as it is walked.
+        if depth > 0 {
+            rules.extend(Gitignore::read(
+                &directory.join(".gitignore"),
+                PathBuf::new(),
+                prefix.clone(),
+            ));
+        }
+        if directory.join(".git").exists() {
+            rules.extend(Gitignore::read(
+                &directory.join(".git").join("info").join("exclude"),
+                PathBuf::new(),
+                prefix,
+            ));
+            rules.reverse();
+            return rules;
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let root = Gitignore::parse(
+            "# Build output\n\
+             /target\n\
+             *.log\n\
+             !keep.log\n\
+             build/\n\
+             docs/**/*.html\n\
+             vendor/**\n\
+             trailing\\ \n",
+            PathBuf::new(),
+            PathBuf::new(),
+        );
+        let nested = Gitignore::parse("!debug.log\n/local\n", PathBuf::from("src"), PathBuf::new());
+        let rules = [root, nested];
+        let ignored = |path: &str, is_dir: bool| is_ignored(&rules, Path::new(path), is_dir);
+
+        assert!(ignored("target", true));
+        assert!(!ignored("src/target", true));
+        assert!(ignored("a/b/trace.log", false));
+        assert!(!ignored("keep.log", false));
+        assert!(!ignored("src/debug.log", false));
+        assert!(ignored("build", true));
+        assert!(!ignored("build", false));
+        assert!(ignored("docs/api/v1/index.html", false));
+        assert!(ignored("docs/index.html", false));
+        assert!(!ignored("vendor", true));
+        assert!(ignored("vendor/lib.rs", false));
+        assert!(ignored("trailing ", false));
+        assert!(ignored("src/local", false));
+        assert!(!ignored("local", false));
+    }
+
+    #[test]
+    fn test_prefix() {
+        // The rules of a file two directories above the root of the walk, `crate/src`.
+        let above = Gitignore::parse(
+            "/crate/src/generated\n",
+            PathBuf::new(),
+            PathBuf::from("crate/src"),
+        );
+        let rules = [above];
+        assert!(is_ignored(&rules, Path::new("generated"), false));
+        assert!(!is_ignored(&rules, Path::new("lib.rs"), false));
+    }
+}
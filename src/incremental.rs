@@ -0,0 +1,343 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::ops::Range;
+
+use crate::{span_opt, Counts, Flux};
+
+/// The greatest size of a block of a buffer, whose flux is stored so that it need not be scanned again
+/// until it is edited.
+const BLOCK_SIZE: usize = 4 * 1024;
+
+#[cfg(test)]
+thread_local! {
+    /// The number of blocks scanned by the thread.
+    static SCANS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// A block of a buffer and its flux.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+struct Block {
+    bytes: Vec<u8>,
+    flux: Option<Flux>,
+}
+
+impl Block {
+    fn new(bytes: Vec<u8>) -> Self {
+        #[cfg(test)]
+        SCANS.with(|scans| scans.set(scans.get() + 1));
+        let flux = Flux::from_chunk(&bytes);
+        Block { bytes, flux }
+    }
+}
+
+/// Splits `bytes` into blocks of nearly equal size no greater than `BLOCK_SIZE`.
+fn blocks_of(bytes: &[u8]) -> impl Iterator<Item = Block> + '_ {
+    let count = bytes.len().div_ceil(BLOCK_SIZE).max(1);
+    let size = bytes.len().div_ceil(count).max(1);
+    bytes.chunks(size).map(|chunk| Block::new(chunk.to_vec()))
+}
+
+/// A treap of blocks, ordered as in the buffer and balanced by the random priorities of its nodes.
+type Tree = Option<Box<Node>>;
+
+/// A node of a tree of blocks, with the length and flux of the blocks of its subtree.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+struct Node {
+    block: Block,
+    /// The priority of the node, no less than those of the nodes below it.
+    priority: u64,
+    /// The length in bytes of the blocks of the subtree.
+    len: usize,
+    /// The flux of the blocks of the subtree.
+    flux: Option<Flux>,
+    left: Tree,
+    right: Tree,
+}
+
+impl Node {
+    /// Returns the receiver with the length and flux of its subtree found again from those of its children.
+    fn update(mut self: Box<Self>) -> Box<Self> {
+        self.len = len(&self.left) + self.block.bytes.len() + len(&self.right);
+        self.flux = span_opt(
+            span_opt(flux(&self.left), self.block.flux),
+            flux(&self.right),
+        );
+        self
+    }
+}
+
+/// Returns the length in bytes of the blocks of `tree`.
+fn len(tree: &Tree) -> usize {
+    tree.as_ref().map_or(0, |node| node.len)
+}
+
+/// Returns the flux of the blocks of `tree`.
+fn flux(tree: &Tree) -> Option<Flux> {
+    tree.as_ref().and_then(|node| node.flux)
+}
+
+/// Returns the tree of the blocks of `left` followed by those of `right`.
+fn merge(left: Tree, right: Tree) -> Tree {
+    match (left, right) {
+        (Some(mut left), Some(mut right)) => {
+            if left.priority >= right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                Some(left.update())
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                Some(right.update())
+            }
+        }
+        (tree, None) | (None, tree) => tree,
+    }
+}
+
+/// Splits `tree` into the blocks ending before `offset`, or at it if `inclusive`, and the rest.
+fn split(tree: Tree, offset: usize, inclusive: bool) -> (Tree, Tree) {
+    let mut node = match tree {
+        Some(node) => node,
+        None => return (None, None),
+    };
+    let end = len(&node.left) + node.block.bytes.len();
+    if end < offset || (inclusive && end == offset) {
+        let (left, right) = split(node.right.take(), offset - end, inclusive);
+        node.right = left;
+        (Some(node.update()), right)
+    } else {
+        let (left, right) = split(node.left.take(), offset, inclusive);
+        node.left = right;
+        (left, Some(node.update()))
+    }
+}
+
+/// Removes the first block of `tree`, returning it and the rest of the tree.
+fn pop_first(tree: Tree) -> (Option<Block>, Tree) {
+    let mut node = match tree {
+        Some(node) => node,
+        None => return (None, None),
+    };
+    match node.left.take() {
+        Some(left) => {
+            let (block, left) = pop_first(Some(left));
+            node.left = left;
+            (block, Some(node.update()))
+        }
+        None => {
+            let Node { block, right, .. } = *node;
+            (Some(block), right)
+        }
+    }
+}
+
+/// Appends the blocks of `tree` to `blocks`, in order.
+fn into_blocks(tree: Tree, blocks: &mut Vec<Block>) {
+    if let Some(node) = tree {
+        let Node {
+            block, left, right, ..
+        } = *node;
+        into_blocks(left, blocks);
+        blocks.push(block);
+        into_blocks(right, blocks);
+    }
+}
+
+/// Appends the bytes of the blocks of `tree` to `bytes`.
+fn extend_bytes(tree: &Tree, bytes: &mut Vec<u8>) {
+    if let Some(node) = tree {
+        extend_bytes(&node.left, bytes);
+        bytes.extend_from_slice(&node.block.bytes);
+        extend_bytes(&node.right, bytes);
+    }
+}
+
+/// The bytes, words and lines of a buffer being edited, such as that of an editor, kept up to date as
+/// ranges of it are replaced.
+///
+/// The buffer is held in blocks in a balanced tree, each node of which stores the length and flux of the
+/// blocks below it. An edit finds the blocks it touches and joins the tree again in time logarithmic in the
+/// number of blocks, rescanning only the blocks it touches, and the counts are those of the root. Words are
+/// split on ASCII whitespace.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct IncrementalCounter {
+    /// The blocks of the buffer, none of them empty.
+    root: Tree,
+    /// The state of the generator of the priorities of new nodes.
+    seed: u64,
+}
+
+impl IncrementalCounter {
+    /// Returns a counter of a buffer holding `text`.
+    pub fn new<T>(text: T) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut counter = IncrementalCounter::default();
+        counter.root = counter.tree_of(text.as_ref());
+        counter
+    }
+
+    /// Returns a tree of the blocks of `bytes`.
+    fn tree_of(&mut self, bytes: &[u8]) -> Tree {
+        blocks_of(bytes).fold(None, |tree, block| {
+            self.seed = self
+                .seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let node = Box::new(Node {
+                priority: self.seed,
+                len: 0,
+                flux: None,
+                left: None,
+                right: None,
+                block,
+            });
+            merge(tree, Some(node.update()))
+        })
+    }
+
+    /// Returns the length of the buffer in bytes.
+    pub fn len(&self) -> usize {
+        len(&self.root)
+    }
+
+    /// Returns `true` if the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the contents of the buffer.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        extend_bytes(&self.root, &mut bytes);
+        bytes
+    }
+
+    /// Returns the counts of the buffer.
+    pub fn counts(&self) -> Counts {
+        let flux = flux(&self.root);
+        Counts {
+            bytes: self.len() as u64,
+            words: flux.map(|flux| flux.words).unwrap_or_default(),
+            lines: flux.map(|flux| flux.lines).unwrap_or_default(),
+            ..Counts::default()
+        }
+    }
+
+    /// Replaces the bytes of the buffer in `range` with `replacement`, rescanning only the blocks the range
+    /// falls within.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or its end is beyond that of the buffer.
+    pub fn replace(&mut self, range: Range<usize>, replacement: &[u8]) {
+        assert!(range.start <= range.end, "Range starts after it ends");
+        assert!(range.end <= self.len(), "Range ends beyond the buffer");
+
+        // Split off the blocks from that holding the start of the range to that holding its end. An
+        // insertion at the end of the buffer extends its last block.
+        let inclusive = range.start < self.len();
+        let (before, rest) = split(self.root.take(), range.start, inclusive);
+        let offset = len(&before);
+        let (touched, after) = split(rest, range.end - offset, false);
+        let (last, mut after) = pop_first(after);
+        let mut blocks = Vec::new();
+        into_blocks(touched, &mut blocks);
+        blocks.extend(last);
+
+        let mut bytes: Vec<u8> = blocks.into_iter().flat_map(|block| block.bytes).collect();
+        bytes.splice(
+            range.start - offset..range.end - offset,
+            replacement.iter().copied(),
+        );
+        // Small blocks are merged with the next, so that repeated deletions do not fragment the buffer.
+        if bytes.len() < BLOCK_SIZE / 2 {
+            let (next, rest) = pop_first(after);
+            bytes.extend(next.into_iter().flat_map(|block| block.bytes));
+            after = rest;
+        }
+        let edited = self.tree_of(&bytes);
+        self.root = merge(merge(before, edited), after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_slice, WcOptions};
+
+    #[test]
+    fn test_incremental_counter() {
+        let mut text: Vec<u8> = "The quick brown fox\njumps over  the lazy dog.\n"
+            .bytes()
+            .cycle()
+            .take(BLOCK_SIZE * 5 + 123)
+            .collect();
+        let mut counter = IncrementalCounter::new(&text);
+        assert_eq!(counter.counts(), count_slice(&text, &WcOptions::default()));
+
+        // Edits of every kind, a pseudo-random sequence of positions and lengths.
+        let mut seed = 12345u64;
+        let mut next = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % (bound + 1)
+        };
+        for edit in 0..300 {
+            let start = next(text.len());
+            let end = start + next((text.len() - start).min(BLOCK_SIZE * 2));
+            let replacement: &[u8] = match edit % 4 {
+                0 => b"",
+                1 => b"x",
+                2 => b" new words\nand a line ",
+                _ => &[b'y'; BLOCK_SIZE + 7],
+            };
+            text.splice(start..end, replacement.iter().copied());
+            counter.replace(start..end, replacement);
+
+            assert_eq!(counter.len(), text.len());
+            assert_eq!(counter.counts(), count_slice(&text, &WcOptions::default()));
+        }
+        assert_eq!(counter.to_vec(), text);
+
+        let mut counter = IncrementalCounter::default();
+        counter.replace(0..0, b"one two");
+        counter.replace(7..7, b" three\n");
+        counter.replace(0..4, b"");
+        assert_eq!(counter.to_vec(), b"two three\n");
+        assert_eq!((counter.counts().lines, counter.counts().words), (1, 2));
+        counter.replace(0..10, b"");
+        assert!(counter.is_empty());
+    }
+
+    #[test]
+    fn test_untouched_blocks_are_not_rescanned() {
+        let scans = || SCANS.with(|scans| scans.get());
+        // Blocks with room for a few more bytes, so that an edit within one leaves it a single block.
+        let size = BLOCK_SIZE - 16;
+        let mut text: Vec<u8> = "one two\nthree ".bytes().cycle().take(size * 64).collect();
+        let mut counter = IncrementalCounter::new(&text);
+        assert_eq!(scans(), 64);
+
+        // Each edit rescans the blocks it falls within, and the blocks they are split into, and no others.
+        let edits: [(Range<usize>, &[u8], usize); 4] = [
+            (size * 10 + 5..size * 10 + 9, b"four", 1),
+            (size * 30..size * 30, b"\n", 1),
+            (size * 20 - 1..size * 22 + 1, b"", 2),
+            (size * 62 - 1..size * 62 - 1, b" end", 1),
+        ];
+        for (range, replacement, rescanned) in edits {
+            let before = scans();
+            text.splice(range.clone(), replacement.iter().copied());
+            counter.replace(range, replacement);
+            assert_eq!(scans() - before, rescanned);
+            assert_eq!(counter.counts(), count_slice(&text, &WcOptions::default()));
+        }
+        assert_eq!(counter.to_vec(), text);
+    }
+}
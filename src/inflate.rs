@@ -0,0 +1,506 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::{self, BufRead, Read};
+
+use crate::i18n::Message;
+
+/// The maximum length of a Huffman code in bits.
+const MAX_BITS: usize = 15;
+
+/// Size of the window of previous output that back-references may reach into.
+const WINDOW_SIZE: usize = 32 * 1024;
+
+/// The base lengths of length symbols 257 through 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+
+/// The number of extra bits following length symbols 257 through 285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// The base distances of distance symbols 0 through 29.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+/// The number of extra bits following distance symbols 0 through 29.
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// The order in which code length code lengths are stored in a dynamic block header.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Returns an error describing malformed compressed data, with `problem` the identifier of a message.
+fn invalid(problem: &'static str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        Message::new("invalid-deflate-stream").arg("problem", Message::new(problem)),
+    )
+}
+
+/// Reads a stream one bit at a time, least significant bit first.
+struct BitReader<R> {
+    /// The underlying reader.
+    inner: R,
+    /// Bits read from the underlying reader and not yet consumed.
+    buffer: u32,
+    /// The number of valid bits in `buffer`.
+    count: u32,
+}
+
+impl<R> BitReader<R>
+where
+    R: BufRead,
+{
+    /// Returns the next `count` bits of the stream.
+    fn bits(&mut self, count: u32) -> io::Result<u32> {
+        while self.count < count {
+            let byte = match self.inner.fill_buf()?.first() {
+                Some(&byte) => byte,
+                None => return Err(io::ErrorKind::UnexpectedEof.into()),
+            };
+            self.inner.consume(1);
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+
+        let value = self.buffer & ((1u64 << count) - 1) as u32;
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Discards the remaining bits of the current byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical Huffman code.
+struct Huffman {
+    /// The number of codes of each length.
+    counts: [u16; MAX_BITS + 1],
+    /// The symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Returns the canonical code with symbol code lengths `lengths`, where zero indicates an unused symbol.
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            counts[usize::from(length)] += 1;
+        }
+        counts[0] = 0;
+
+        // Reject codes which assign more codes of a length than are available.
+        let mut available = 1i32;
+        for &count in &counts[1..] {
+            available = (available << 1) - i32::from(count);
+            if available < 0 {
+                return Err(invalid("deflate-over-subscribed-code"));
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; usize::from(offsets[MAX_BITS + 1])];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                let offset = &mut offsets[usize::from(length)];
+                symbols[usize::from(*offset)] = symbol as u16;
+                *offset += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    /// Decodes the next symbol from `bits`.
+    fn decode<R>(&self, bits: &mut BitReader<R>) -> io::Result<u16>
+    where
+        R: BufRead,
+    {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("deflate-incomplete-code"))
+    }
+}
+
+/// The kind of the block being decompressed.
+enum Block {
+    /// Between blocks.
+    None,
+    /// A stored block, with the number of bytes remaining.
+    Stored(usize),
+    /// A block compressed with the literal/length and distance codes.
+    Compressed(Huffman, Huffman),
+}
+
+/// A reader decompressing a raw DEFLATE (RFC 1951) stream read from an underlying reader.
+pub struct Inflater<R> {
+    /// The compressed stream.
+    bits: BitReader<R>,
+    /// The block being decompressed.
+    block: Block,
+    /// Whether the block being decompressed is the last of the stream.
+    final_block: bool,
+    /// The most recent output, which back-references copy from.
+    window: Vec<u8>,
+    /// The total number of bytes output.
+    total_out: usize,
+    /// The length and distance of the back-reference being copied, if any.
+    copy: (usize, usize),
+}
+
+impl<R> Inflater<R>
+where
+    R: BufRead,
+{
+    /// Returns a new reader decompressing the raw DEFLATE stream `inner`.
+    pub fn new(inner: R) -> Self {
+        Inflater {
+            bits: BitReader {
+                inner,
+                buffer: 0,
+                count: 0,
+            },
+            block: Block::None,
+            final_block: false,
+            window: vec![0; WINDOW_SIZE],
+            total_out: 0,
+            copy: (0, 0),
+        }
+    }
+
+    /// Prepares to decompress another stream following the one just decompressed, read from the same reader.
+    fn restart(&mut self) {
+        self.bits.align();
+        self.block = Block::None;
+        self.final_block = false;
+        self.total_out = 0;
+        self.copy = (0, 0);
+    }
+
+    /// Reads the header of the next block.
+    fn begin_block(&mut self) -> io::Result<()> {
+        self.final_block = self.bits.bits(1)? == 1;
+        self.block = match self.bits.bits(2)? {
+            0 => {
+                self.bits.align();
+                let length = self.bits.bits(16)?;
+                let complement = self.bits.bits(16)?;
+                if length != !complement & 0xFFFF {
+                    return Err(invalid("deflate-stored-length-mismatch"));
+                }
+                Block::Stored(length as usize)
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].iter_mut().for_each(|length| *length = 9);
+                lengths[256..280].iter_mut().for_each(|length| *length = 7);
+                Block::Compressed(Huffman::new(&lengths)?, Huffman::new(&[5; 30])?)
+            }
+            2 => self.dynamic_block()?,
+            _ => return Err(invalid("deflate-reserved-block-type")),
+        };
+        Ok(())
+    }
+
+    /// Reads the code definitions of a dynamic block.
+    fn dynamic_block(&mut self) -> io::Result<Block> {
+        let literal_count = self.bits.bits(5)? as usize + 257;
+        let distance_count = self.bits.bits(5)? as usize + 1;
+        let code_length_count = self.bits.bits(4)? as usize + 4;
+
+        let mut code_lengths = [0u8; 19];
+        for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+            code_lengths[index] = self.bits.bits(3)? as u8;
+        }
+        let code_length_code = Huffman::new(&code_lengths)?;
+
+        let mut lengths = vec![0u8; literal_count + distance_count];
+        let mut index = 0;
+        while index < lengths.len() {
+            let (value, repeat) = match code_length_code.decode(&mut self.bits)? {
+                symbol @ 0..=15 => (symbol as u8, 1),
+                16 if index > 0 => (lengths[index - 1], 3 + self.bits.bits(2)? as usize),
+                17 => (0, 3 + self.bits.bits(3)? as usize),
+                18 => (0, 11 + self.bits.bits(7)? as usize),
+                _ => return Err(invalid("deflate-repeat-without-length")),
+            };
+            if index + repeat > lengths.len() {
+                return Err(invalid("deflate-too-many-code-lengths"));
+            }
+            lengths[index..index + repeat]
+                .iter_mut()
+                .for_each(|length| *length = value);
+            index += repeat;
+        }
+
+        Ok(Block::Compressed(
+            Huffman::new(&lengths[..literal_count])?,
+            Huffman::new(&lengths[literal_count..])?,
+        ))
+    }
+
+    /// Appends `byte` to the output window.
+    fn output(&mut self, byte: u8) {
+        self.window[self.total_out % WINDOW_SIZE] = byte;
+        self.total_out += 1;
+    }
+}
+
+impl<R> Read for Inflater<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            // Continue copying the current back-reference.
+            let (length, distance) = self.copy;
+            if length > 0 {
+                let byte = self.window[(self.total_out - distance) % WINDOW_SIZE];
+                self.output(byte);
+                buf[written] = byte;
+                written += 1;
+                self.copy = (length - 1, distance);
+                continue;
+            }
+
+            let symbol = match &mut self.block {
+                Block::None if self.final_block => break,
+                Block::None => {
+                    self.begin_block()?;
+                    continue;
+                }
+                Block::Stored(0) => {
+                    self.block = Block::None;
+                    continue;
+                }
+                Block::Stored(remaining) => {
+                    *remaining -= 1;
+                    self.bits.bits(8)? as u16
+                }
+                Block::Compressed(literals, _) => literals.decode(&mut self.bits)?,
+            };
+
+            match symbol {
+                0..=255 => {
+                    self.output(symbol as u8);
+                    buf[written] = symbol as u8;
+                    written += 1;
+                }
+                256 => self.block = Block::None,
+                _ => {
+                    let index = usize::from(symbol - 257);
+                    if index >= LENGTH_BASE.len() {
+                        return Err(invalid("deflate-invalid-length-symbol"));
+                    }
+                    let length = usize::from(LENGTH_BASE[index])
+                        + self.bits.bits(u32::from(LENGTH_EXTRA[index]))? as usize;
+
+                    let index = match &self.block {
+                        Block::Compressed(_, distances) => {
+                            usize::from(distances.decode(&mut self.bits)?)
+                        }
+                        _ => unreachable!(),
+                    };
+                    if index >= DISTANCE_BASE.len() {
+                        return Err(invalid("deflate-invalid-distance-symbol"));
+                    }
+                    let distance = usize::from(DISTANCE_BASE[index])
+                        + self.bits.bits(u32::from(DISTANCE_EXTRA[index]))? as usize;
+                    if distance > self.total_out.min(WINDOW_SIZE) {
+                        return Err(invalid("deflate-distance-too-far-back"));
+                    }
+                    self.copy = (length, distance);
+                }
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Decompresses the complete raw DEFLATE stream `input`.
+pub fn inflate(input: &[u8]) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    Inflater::new(input).read_to_end(&mut output)?;
+    Ok(output)
+}
+
+/// Decompresses the complete zlib (RFC 1950) stream `input`, without verifying its checksum.
+#[cfg(feature = "pdf")]
+pub fn zlib_decompress(input: &[u8]) -> io::Result<Vec<u8>> {
+    match input {
+        [method, flags, rest @ ..] if method & 0x0F == 8 && flags & 0x20 == 0 => inflate(rest),
+        _ => Err(invalid("deflate-unsupported-zlib-header")),
+    }
+}
+
+/// Returns an error describing a malformed gzip header, with `problem` the identifier of a message.
+fn invalid_header(problem: &'static str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        Message::new("invalid-gzip-header").arg("problem", Message::new(problem)),
+    )
+}
+
+/// Reads the gzip (RFC 1952) member header at the start of `input`, leaving it at the compressed data.
+fn read_gzip_header<R: BufRead>(input: &mut R) -> io::Result<()> {
+    let mut header = [0; 10];
+    input.read_exact(&mut header)?;
+    let flags = match header {
+        [0x1F, 0x8B, 8, flags, ..] => flags,
+        _ => return Err(invalid_header("gzip-unsupported-magic-or-method")),
+    };
+    if flags & 0x04 != 0 {
+        let mut length = [0; 2];
+        input.read_exact(&mut length)?;
+        let length = u64::from(u16::from_le_bytes(length));
+        if io::copy(&mut input.take(length), &mut io::sink())? < length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+    // The file name and comment are each terminated by a NUL byte.
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            let mut field = Vec::new();
+            input.read_until(0, &mut field)?;
+            if field.last() != Some(&0) {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+    }
+    if flags & 0x02 != 0 {
+        input.read_exact(&mut [0; 2])?;
+    }
+    Ok(())
+}
+
+/// A reader decompressing the gzip (RFC 1952) members read one after another from an underlying reader,
+/// without verifying their checksums or lengths.
+pub struct GzipDecoder<R> {
+    /// The decompressor of the current member, or of the last once it has ended.
+    inflater: Inflater<R>,
+    /// Whether the compressed data of a member is being read, rather than the header of the next.
+    in_member: bool,
+}
+
+impl<R> GzipDecoder<R>
+where
+    R: BufRead,
+{
+    /// Returns a new reader decompressing the gzip members of `inner`.
+    pub fn new(inner: R) -> Self {
+        GzipDecoder {
+            inflater: Inflater::new(inner),
+            in_member: false,
+        }
+    }
+}
+
+impl<R> Read for GzipDecoder<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            if !self.in_member {
+                let input = &mut self.inflater.bits.inner;
+                if input.fill_buf()?.is_empty() {
+                    return Ok(0);
+                }
+                read_gzip_header(input)?;
+                self.inflater.restart();
+                self.in_member = true;
+            }
+
+            let length = self.inflater.read(buf)?;
+            if length > 0 {
+                return Ok(length);
+            }
+            // The member's compressed data ends on a byte boundary before its CRC-32 and length.
+            self.inflater.bits.align();
+            self.inflater.bits.inner.read_exact(&mut [0; 8])?;
+            self.in_member = false;
+        }
+    }
+}
+
+/// Decompresses the gzip (RFC 1952) members of `input`, without verifying their checksums.
+#[cfg(feature = "kafka")]
+pub fn gunzip(input: &[u8]) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    GzipDecoder::new(input).read_to_end(&mut output)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inflate_stored_block() {
+        assert_eq!(
+            inflate(&[0x01, 0x05, 0x00, 0xFA, 0xFF, b'h', b'e', b'l', b'l', b'o']).unwrap(),
+            b"hello"
+        );
+    }
+
+    #[test]
+    fn test_inflate_fixed_block() {
+        let compressed = [
+            0x78, 0xDA, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0x40, 0x90, 0x00, 0x3A, 0x2E,
+            0x06, 0x7D,
+        ];
+
+        assert_eq!(inflate(&compressed[2..]).unwrap(), b"hello hello hello");
+    }
+
+    #[test]
+    fn test_inflate_dynamic_block() {
+        let compressed = [
+            0x78, 0xDA, 0xB5, 0xCB, 0xD1, 0x01, 0x80, 0x10, 0x14, 0x46, 0xE1, 0x55, 0xFE, 0x16,
+            0x68, 0x96, 0x1E, 0x2C, 0x40, 0x11, 0x15, 0x37, 0x84, 0x98, 0xBE, 0xBB, 0x44, 0xCF,
+            0xE7, 0x3B, 0xC2, 0x6A, 0xC4, 0xE2, 0xD6, 0x13, 0x2A, 0x51, 0x0B, 0x30, 0xF4, 0xE2,
+            0x28, 0xFE, 0xCE, 0xA0, 0xAA, 0x13, 0x1E, 0xCE, 0x97, 0x1C, 0x1D, 0x1B, 0xED, 0x33,
+            0xC4, 0x6F, 0x78, 0x91, 0xEC, 0x7C, 0x87, 0x62, 0xD4, 0xDC, 0x63, 0x61, 0x5C, 0xD5,
+            0x9C, 0x86, 0x0E, 0xB8, 0x5C, 0x2C, 0x94, 0xF8, 0xDD, 0xF3, 0xF4, 0x01, 0xB2, 0xEE,
+            0x3F, 0x00,
+        ];
+        let expected = "The quick brown fox jumps over the lazy dog. ".repeat(3)
+            + "Pack my box with five dozen liquor jugs!";
+
+        assert_eq!(inflate(&compressed[2..]).unwrap(), expected.as_bytes());
+    }
+}
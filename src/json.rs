@@ -0,0 +1,341 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+
+use crate::i18n::Message;
+
+/// A JSON value.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// An object, with its members in document order.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the member of an object named `key`, if the receiver is an object and has one.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string the receiver holds, if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns the number the receiver holds, if it is a whole number which is not negative.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(number) if *number >= 0.0 && number.fract() == 0.0 => {
+                Some(*number as u64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of the receiver, if it is an array.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+}
+
+/// Writes `string` as a JSON string literal.
+fn write_string(f: &mut fmt::Formatter, string: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in string.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Value {
+    /// Writes the receiver as compact JSON text.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Number(number) if number.is_finite() => write!(f, "{}", number),
+            Value::Number(_) => f.write_str("null"),
+            Value::String(string) => write_string(f, string),
+            Value::Array(elements) => {
+                f.write_str("[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                f.write_str("]")
+            }
+            Value::Object(members) => {
+                f.write_str("{")?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Parses `input` as a single JSON value, describing the position of the first error on failure.
+pub fn parse(input: &[u8]) -> Result<Value, Message> {
+    let mut parser = Parser { input, position: 0 };
+    let value = parser.value()?;
+    parser.whitespace();
+    if parser.position < input.len() {
+        return Err(parser.error(Message::new("json-trailing-characters")));
+    }
+    Ok(value)
+}
+
+/// A recursive-descent JSON parser.
+struct Parser<'a> {
+    /// The text being parsed.
+    input: &'a [u8],
+    /// The offset of the next byte to be parsed.
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns an error describing `problem`, a message, at the current position.
+    fn error(&self, problem: Message) -> Message {
+        Message::new("invalid-json")
+            .arg("offset", self.position)
+            .arg("problem", problem)
+    }
+
+    /// Returns the next byte without consuming it.
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).cloned()
+    }
+
+    /// Skips any whitespace at the current position.
+    fn whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    /// Consumes `expected`, which must appear at the current position.
+    fn expect(&mut self, expected: &[u8]) -> Result<(), Message> {
+        if self.input[self.position..].starts_with(expected) {
+            self.position += expected.len();
+            Ok(())
+        } else {
+            Err(self.error(
+                Message::new("json-expected").arg("expected", String::from_utf8_lossy(expected)),
+            ))
+        }
+    }
+
+    /// Parses the value at the current position.
+    fn value(&mut self) -> Result<Value, Message> {
+        self.whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect(b"null").map(|_| Value::Null),
+            Some(b't') => self.expect(b"true").map(|_| Value::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|_| Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => self.array(),
+            Some(b'{') => self.object(),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error(Message::new("json-unexpected-character"))),
+            None => Err(self.error(Message::new("json-unexpected-end"))),
+        }
+    }
+
+    /// Parses the number at the current position.
+    fn number(&mut self) -> Result<Value, Message> {
+        let start = self.position;
+        while let Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e') | Some(b'E')
+        | Some(b'0'..=b'9') = self.peek()
+        {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.position])
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .map(Value::Number)
+            .ok_or_else(|| self.error(Message::new("json-malformed-number")))
+    }
+
+    /// Parses the four hexadecimal digits of a `\u` escape at the current position.
+    fn code_unit(&mut self) -> Result<u32, Message> {
+        let digits = self
+            .input
+            .get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error(Message::new("json-malformed-unicode-escape")))?;
+        self.position += 4;
+        Ok(digits)
+    }
+
+    /// Parses the string at the current position.
+    fn string(&mut self) -> Result<String, Message> {
+        self.expect(b"\"")?;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = self
+                .peek()
+                .ok_or_else(|| self.error(Message::new("json-unterminated-string")))?;
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error(Message::new("json-unterminated-string")))?;
+                    self.position += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.code_unit()?;
+                            // A high surrogate is combined with the low surrogate escaped after it.
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.input[self.position..].starts_with(b"\\u")
+                            {
+                                self.position += 2;
+                                let low = self.code_unit()?;
+                                code = 0x10000
+                                    + ((code - 0xD800) << 10)
+                                    + (low.wrapping_sub(0xDC00) & 0x3FF);
+                            }
+                            std::char::from_u32(code).unwrap_or('\u{FFFD}')
+                        }
+                        _ => return Err(self.error(Message::new("json-unknown-escape"))),
+                    };
+                    let mut encoded = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error(Message::new("json-invalid-utf-8")))
+    }
+
+    /// Parses the array at the current position.
+    fn array(&mut self) -> Result<Value, Message> {
+        self.expect(b"[")?;
+        let mut elements = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Value::Array(elements));
+        }
+        loop {
+            elements.push(self.value()?);
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Value::Array(elements));
+                }
+                _ => return Err(self.error(Message::new("json-expected-array-separator"))),
+            }
+        }
+    }
+
+    /// Parses the object at the current position.
+    fn object(&mut self) -> Result<Value, Message> {
+        self.expect(b"{")?;
+        let mut members = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.whitespace();
+            let name = self.string()?;
+            self.whitespace();
+            self.expect(b":")?;
+            members.push((name, self.value()?));
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error(Message::new("json-expected-object-separator"))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let value =
+            parse(br#" {"a": [1, -2.5e1, true, null], "b": "x\"\u00e9\ud83d\ude00"} "#).unwrap();
+
+        assert_eq!(
+            value.get("a"),
+            Some(&Value::Array(vec![
+                Value::Number(1.0),
+                Value::Number(-25.0),
+                Value::Bool(true),
+                Value::Null,
+            ]))
+        );
+        assert_eq!(
+            value.get("b").and_then(Value::as_str),
+            Some("x\"\u{e9}\u{1F600}")
+        );
+        assert!(parse(b"[1, 2").is_err());
+        assert_eq!(
+            value.get("a").unwrap().as_array().unwrap()[0].as_u64(),
+            Some(1)
+        );
+        assert_eq!(
+            value.to_string(),
+            "{\"a\":[1,-25,true,null],\"b\":\"x\\\"\u{e9}\u{1F600}\"}"
+        );
+    }
+}
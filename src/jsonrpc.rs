@@ -0,0 +1,270 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A JSON-RPC 2.0 server counting text for editors, framed as by the Language Server Protocol: each
+//! message is preceded by a `Content-Length` header and a blank line.
+//!
+//! The methods are:
+//!
+//! - `count`, with params `{"text": TEXT}` or `{"path": PATH}`, returns the counts of the text or file.
+//! - `open`, with params `{"uri": URI, "text": TEXT}`, holds a document and returns its counts.
+//! - `edit`, with params `{"uri": URI, "start": OFFSET, "end": OFFSET, "text": TEXT}`, replaces the bytes of
+//!   an open document from `start` up to `end` with the text and returns its counts, recounting only the
+//!   part of the document the edit touches. Words of documents are split on ASCII whitespace.
+//! - `close`, with params `{"uri": URI}`, forgets a document.
+//! - `shutdown` returns null, and the `exit` notification or the end of the input stops the server.
+//!
+//! Counts are objects with `bytes`, `words` and `lines` members.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::incremental::IncrementalCounter;
+use crate::json::{self, Value};
+use crate::{count_path, try_count_slice, Counts, WcOptions};
+
+/// The JSON-RPC error code of a message which is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+
+/// The JSON-RPC error code of a message which is not a valid request.
+const INVALID_REQUEST: i64 = -32600;
+
+/// The JSON-RPC error code of a request for a method which does not exist.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The JSON-RPC error code of a request with invalid parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// The JSON-RPC error code of a request which failed, such as one for a file which cannot be read.
+const SERVER_ERROR: i64 = -32000;
+
+/// An error response, its code and message.
+type Error = (i64, String);
+
+/// Reads the body of the next message from `input`, or returns `None` at the end of the input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without a Content-Length header",
+        )
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Writes `message` to `output`, preceded by its header.
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Returns `counts` as a JSON object.
+fn counts_value(counts: &Counts) -> Value {
+    Value::Object(vec![
+        ("bytes".to_string(), Value::Number(counts.bytes as f64)),
+        ("words".to_string(), Value::Number(counts.words as f64)),
+        ("lines".to_string(), Value::Number(counts.lines as f64)),
+    ])
+}
+
+/// Returns the parameter `name` of `params`, which must be a string.
+fn string_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, Error> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| (INVALID_PARAMS, format!("'{}' must be a string", name)))
+}
+
+/// Returns the parameter `name` of `params`, which must be a whole number.
+fn offset_param(params: &Value, name: &str) -> Result<usize, Error> {
+    params
+        .get(name)
+        .and_then(Value::as_u64)
+        .map(|offset| offset as usize)
+        .ok_or_else(|| (INVALID_PARAMS, format!("'{}' must be a whole number", name)))
+}
+
+/// The state of a server: the options text is counted with and the open documents.
+struct Server {
+    /// The options `count` requests are counted with.
+    options: WcOptions,
+    /// The open documents, by URI.
+    documents: HashMap<String, IncrementalCounter>,
+}
+
+impl Server {
+    /// Returns the result of calling `method` with `params`, or the error to respond with.
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, Error> {
+        match method {
+            "count" => {
+                let counts = match (params.get("text"), params.get("path")) {
+                    (Some(_), _) => try_count_slice(string_param(params, "text")?, &self.options)
+                        .map_err(|error| (SERVER_ERROR, error.to_string()))?,
+                    (None, Some(_)) => {
+                        count_path(Path::new(string_param(params, "path")?), &self.options)
+                            .map_err(|error| (SERVER_ERROR, error.to_string()))?
+                    }
+                    (None, None) => {
+                        return Err((INVALID_PARAMS, "'text' or 'path' is required".to_string()))
+                    }
+                };
+                Ok(counts_value(&counts))
+            }
+            "open" => {
+                let counter = IncrementalCounter::new(string_param(params, "text")?);
+                let counts = counter.counts();
+                self.documents
+                    .insert(string_param(params, "uri")?.to_string(), counter);
+                Ok(counts_value(&counts))
+            }
+            "edit" => {
+                let uri = string_param(params, "uri")?;
+                let (start, end) = (offset_param(params, "start")?, offset_param(params, "end")?);
+                let text = string_param(params, "text")?;
+                let counter = self
+                    .documents
+                    .get_mut(uri)
+                    .ok_or_else(|| (INVALID_PARAMS, format!("'{}' is not open", uri)))?;
+                if start > end || end > counter.len() {
+                    return Err((
+                        INVALID_PARAMS,
+                        "the range is not within the document".to_string(),
+                    ));
+                }
+                counter.replace(start..end, text.as_bytes());
+                Ok(counts_value(&counter.counts()))
+            }
+            "close" => {
+                self.documents.remove(string_param(params, "uri")?);
+                Ok(Value::Null)
+            }
+            "shutdown" => Ok(Value::Null),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+        }
+    }
+}
+
+/// Returns the response to a request with `id`, holding `result` or an error.
+fn response(id: Value, result: Result<Value, Error>) -> Value {
+    let outcome = match result {
+        Ok(result) => ("result".to_string(), result),
+        Err((code, message)) => (
+            "error".to_string(),
+            Value::Object(vec![
+                ("code".to_string(), Value::Number(code as f64)),
+                ("message".to_string(), Value::String(message)),
+            ]),
+        ),
+    };
+    Value::Object(vec![
+        ("jsonrpc".to_string(), Value::String("2.0".to_string())),
+        ("id".to_string(), id),
+        outcome,
+    ])
+}
+
+/// Serves the requests read from `input`, writing the responses to `output`, until the `exit` notification
+/// or the end of the input. Text and files are counted with `options`.
+pub fn serve(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    options: &WcOptions,
+) -> io::Result<()> {
+    let mut server = Server {
+        options: *options,
+        documents: HashMap::new(),
+    };
+    while let Some(body) = read_message(&mut input)? {
+        let request = match json::parse(&body) {
+            Ok(request) => request,
+            Err(error) => {
+                write_message(
+                    &mut output,
+                    &response(Value::Null, Err((PARSE_ERROR, error.to_string()))),
+                )?;
+                continue;
+            }
+        };
+        let method = request.get("method").and_then(Value::as_str);
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        if method == Some("exit") {
+            return Ok(());
+        }
+
+        // Notifications, which have no identifier, are not answered.
+        let result = match method {
+            Some(method) => server.call(method, &params),
+            None => Err((INVALID_REQUEST, "the request has no method".to_string())),
+        };
+        if let Some(id) = request.get("id") {
+            write_message(&mut output, &response(id.clone(), result))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `body` framed as a message.
+    fn message(body: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    #[test]
+    fn test_serve() {
+        let input = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"count","params":{"text":"one two\nthree\n"}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"open","params":{"uri":"a","text":"hello world"}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"edit","params":{"uri":"a","start":5,"end":6,"text":"\n"}}"#,
+            r#"{"jsonrpc":"2.0","id":4,"method":"edit","params":{"uri":"a","start":9,"end":99,"text":""}}"#,
+            r#"{"jsonrpc":"2.0","method":"close","params":{"uri":"a"}}"#,
+            r#"{"jsonrpc":"2.0","id":"x","method":"lint"}"#,
+            r#"{"jsonrpc":"2.0","method":"exit"}"#,
+            r#"{"jsonrpc":"2.0","id":5,"method":"shutdown"}"#,
+        ]
+        .iter()
+        .map(|body| message(body))
+        .collect::<String>();
+
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output, &WcOptions::default()).unwrap();
+        let expected = [
+            r#"{"jsonrpc":"2.0","id":1,"result":{"bytes":14,"words":3,"lines":2}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"result":{"bytes":11,"words":2,"lines":0}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"result":{"bytes":11,"words":2,"lines":1}}"#,
+            r#"{"jsonrpc":"2.0","id":4,"error":{"code":-32602,"message":"the range is not within the document"}}"#,
+            r#"{"jsonrpc":"2.0","id":"x","error":{"code":-32601,"message":"unknown method 'lint'"}}"#,
+        ]
+        .iter()
+        .map(|body| message(body))
+        .collect::<String>();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
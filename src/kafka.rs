@@ -0,0 +1,500 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::borrow::Cow;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::str::FromStr;
+
+use crate::i18n::Message;
+use crate::inflate;
+
+/// The API key and version of the requests to fetch records.
+const FETCH: (i16, i16) = (1, 4);
+
+/// The API key and version of the requests to look up offsets.
+const LIST_OFFSETS: (i16, i16) = (2, 1);
+
+/// The timestamp looking up the earliest offset of a partition.
+const EARLIEST: i64 = -2;
+
+/// The timestamp looking up the latest offset of a partition, that of the next record produced.
+const LATEST: i64 = -1;
+
+/// The client name sent with each request.
+const CLIENT_ID: &str = "wc_rs";
+
+/// The longest the broker waits for records to arrive before responding to a fetch, in milliseconds.
+const MAX_WAIT_MS: i32 = 500;
+
+/// The greatest number of bytes of records requested by each fetch. A larger batch is still returned whole.
+const MAX_FETCH_BYTES: i32 = 4 << 20;
+
+/// The record batch attribute marking control batches, which hold transaction markers rather than records.
+const CONTROL_BATCH: i16 = 0x20;
+
+/// Returns an error describing a malformed response, with `problem` the identifier of a message.
+fn invalid(problem: &'static str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        Message::new("invalid-kafka-response").arg("problem", Message::new(problem)),
+    )
+}
+
+/// Returns an error describing the error `code` returned by the broker.
+fn broker_error(code: i16) -> io::Error {
+    let description = match code {
+        1 => Message::new("kafka-offset-out-of-range"),
+        3 => Message::new("kafka-unknown-partition"),
+        6 => Message::new("kafka-not-leader"),
+        _ => Message::new("kafka-error-code").arg("code", code),
+    };
+    io::Error::other(description)
+}
+
+/// A range of the records of a topic partition, named by a URL of the form
+/// `kafka://HOST:PORT/TOPIC/PARTITION?start=OFFSET&end=OFFSET`.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Partition {
+    /// The address of the broker leading the partition.
+    pub broker: String,
+    /// The name of the topic.
+    pub topic: String,
+    /// The index of the partition.
+    pub partition: i32,
+    /// The offset of the first record of the range, or `None` for the earliest retained.
+    pub start: Option<i64>,
+    /// The offset after the last record of the range, or `None` for the latest when counting begins.
+    pub end: Option<i64>,
+}
+
+impl FromStr for Partition {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Message::new("malformed-kafka-partition").arg("value", s);
+        let rest = s.strip_prefix("kafka://").ok_or_else(invalid)?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut parts = path.splitn(3, '/');
+        let (broker, topic, partition) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(broker), Some(topic), Some(partition))
+                if !broker.is_empty() && !topic.is_empty() =>
+            {
+                (broker, topic, partition.parse().map_err(|_| invalid())?)
+            }
+            _ => return Err(invalid()),
+        };
+
+        let mut range = Partition {
+            broker: broker.to_string(),
+            topic: topic.to_string(),
+            partition,
+            start: None,
+            end: None,
+        };
+        for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+            let (name, value) = parameter.split_once('=').ok_or_else(invalid)?;
+            let offset = Some(value.parse().map_err(|_| invalid())?);
+            match name {
+                "start" => range.start = offset,
+                "end" => range.end = offset,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(range)
+    }
+}
+
+/// Reads the big-endian fields of a response in order.
+struct Decoder<'a> {
+    /// The bytes not yet read.
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Reads the next `length` bytes.
+    fn take(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < length {
+            return Err(invalid("kafka-truncated"));
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Reads the next `N` bytes as an array.
+    fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn i8(&mut self) -> io::Result<i8> {
+        Ok(i8::from_be_bytes(self.array()?))
+    }
+
+    fn i16(&mut self) -> io::Result<i16> {
+        Ok(i16::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> io::Result<i64> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    /// Reads a zig-zag encoded variable-length integer.
+    fn varint(&mut self) -> io::Result<i64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+        Err(invalid("kafka-varint-too-long"))
+    }
+
+    /// Reads the bytes of a field whose length, or -1 if it is null, is given by `length`.
+    fn field(&mut self, length: i64) -> io::Result<Option<&'a [u8]>> {
+        match length {
+            -1 => Ok(None),
+            length if length >= 0 => self.take(length as usize).map(Some),
+            _ => Err(invalid("kafka-negative-length")),
+        }
+    }
+
+    /// Reads a string prefixed with its 16-bit length.
+    fn string(&mut self) -> io::Result<&'a [u8]> {
+        let length = self.i16()?;
+        Ok(self.field(i64::from(length))?.unwrap_or_default())
+    }
+
+    /// Reads the 32-bit length of an array, treating a null array as empty.
+    fn count(&mut self) -> io::Result<usize> {
+        Ok(self.i32()?.max(0) as usize)
+    }
+}
+
+/// Appends `text` to `body` prefixed with its 16-bit length.
+fn put_string(body: &mut Vec<u8>, text: &str) {
+    body.extend_from_slice(&(text.len() as i16).to_be_bytes());
+    body.extend_from_slice(text.as_bytes());
+}
+
+/// Appends the values of the records of the batches in `records` with offsets from `start` up to `end` to
+/// `values`, each followed by a line feed, returning the offset after the last complete batch, if any.
+/// A batch cut short by the end of `records` is ignored, to be fetched again whole.
+fn decode_batches(
+    mut records: &[u8],
+    start: i64,
+    end: i64,
+    values: &mut Vec<u8>,
+) -> io::Result<Option<i64>> {
+    let mut next_offset = None;
+    while records.len() >= 12 {
+        let mut header = Decoder { bytes: records };
+        let base_offset = header.i64()?;
+        let length = header.i32()?.max(0) as usize;
+        if header.bytes.len() < length {
+            break;
+        }
+        let mut batch = Decoder {
+            bytes: &header.bytes[..length],
+        };
+        records = &header.bytes[length..];
+
+        let _leader_epoch = batch.i32()?;
+        if batch.i8()? != 2 {
+            return Err(invalid("kafka-unsupported-batch-format"));
+        }
+        let _crc = batch.i32()?;
+        let attributes = batch.i16()?;
+        let last_offset_delta = batch.i32()?;
+        batch.take(8 + 8 + 8 + 2 + 4)?;
+        let count = batch.count()?;
+        next_offset = Some(base_offset + i64::from(last_offset_delta) + 1);
+        if attributes & CONTROL_BATCH != 0 {
+            continue;
+        }
+
+        let data = match attributes & 0x07 {
+            0 => Cow::Borrowed(batch.bytes),
+            1 => Cow::Owned(inflate::gunzip(batch.bytes)?),
+            _ => return Err(invalid("kafka-unsupported-codec")),
+        };
+        let mut data = Decoder { bytes: &data };
+        for _ in 0..count {
+            let length = data.varint()?;
+            let mut record = Decoder {
+                bytes: data.field(length)?.unwrap_or_default(),
+            };
+            let _attributes = record.i8()?;
+            let _timestamp_delta = record.varint()?;
+            let offset = base_offset + record.varint()?;
+            let key_length = record.varint()?;
+            record.field(key_length)?;
+            let value_length = record.varint()?;
+            let value = record.field(value_length)?.unwrap_or_default();
+            if (start..end).contains(&offset) {
+                values.extend_from_slice(value);
+                values.push(b'\n');
+            }
+        }
+    }
+    Ok(next_offset)
+}
+
+/// A reader of the values of a range of the records of a topic partition, each followed by a line feed so
+/// that records with single-line values are counted as lines.
+///
+/// Records are fetched from the broker named, which must lead the partition. Transaction markers are
+/// skipped, and batches must be uncompressed or compressed with gzip.
+pub struct PartitionReader {
+    /// The connection to the broker.
+    stream: TcpStream,
+    /// The identifier of the last request sent.
+    correlation_id: i32,
+    /// The range of records read.
+    partition: Partition,
+    /// The offset of the next record to fetch.
+    offset: i64,
+    /// The offset after the last record read.
+    end: i64,
+    /// The values fetched and not yet consumed.
+    values: Vec<u8>,
+    /// The position of the first unconsumed byte of `values`.
+    position: usize,
+}
+
+impl PartitionReader {
+    /// Connects to the broker of `partition`, looking up the offsets of the ends of its range not given.
+    pub fn connect(partition: &Partition) -> io::Result<Self> {
+        let mut reader = PartitionReader {
+            stream: TcpStream::connect(&partition.broker)?,
+            correlation_id: 0,
+            partition: partition.clone(),
+            offset: 0,
+            end: 0,
+            values: Vec::new(),
+            position: 0,
+        };
+        reader.offset = match partition.start {
+            Some(start) => start,
+            None => reader.list_offset(EARLIEST)?,
+        };
+        reader.end = match partition.end {
+            Some(end) => end,
+            None => reader.list_offset(LATEST)?,
+        };
+        Ok(reader)
+    }
+
+    /// Sends the request of `api` with `body`, returning the body of the response.
+    fn call(&mut self, api: (i16, i16), body: &[u8]) -> io::Result<Vec<u8>> {
+        self.correlation_id += 1;
+        let mut request = Vec::new();
+        request.extend_from_slice(&api.0.to_be_bytes());
+        request.extend_from_slice(&api.1.to_be_bytes());
+        request.extend_from_slice(&self.correlation_id.to_be_bytes());
+        put_string(&mut request, CLIENT_ID);
+        request.extend_from_slice(body);
+        self.stream
+            .write_all(&(request.len() as i32).to_be_bytes())?;
+        self.stream.write_all(&request)?;
+
+        let mut size = [0; 4];
+        self.stream.read_exact(&mut size)?;
+        let mut response = vec![0; i32::from_be_bytes(size).max(0) as usize];
+        self.stream.read_exact(&mut response)?;
+        if response.get(..4) != Some(&self.correlation_id.to_be_bytes()[..]) {
+            return Err(invalid("kafka-mismatched-correlation"));
+        }
+        response.drain(..4);
+        Ok(response)
+    }
+
+    /// Returns the offset of the partition at `timestamp`, `EARLIEST` or `LATEST`.
+    fn list_offset(&mut self, timestamp: i64) -> io::Result<i64> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&(-1i32).to_be_bytes());
+        body.extend_from_slice(&1i32.to_be_bytes());
+        put_string(&mut body, &self.partition.topic);
+        body.extend_from_slice(&1i32.to_be_bytes());
+        body.extend_from_slice(&self.partition.partition.to_be_bytes());
+        body.extend_from_slice(&timestamp.to_be_bytes());
+
+        let response = self.call(LIST_OFFSETS, &body)?;
+        let mut response = Decoder { bytes: &response };
+        if response.count()? == 0 {
+            return Err(invalid("kafka-no-topics"));
+        }
+        response.string()?;
+        if response.count()? == 0 {
+            return Err(invalid("kafka-no-partitions"));
+        }
+        let _partition = response.i32()?;
+        match response.i16()? {
+            0 => {}
+            code => return Err(broker_error(code)),
+        }
+        let _timestamp = response.i64()?;
+        response.i64()
+    }
+
+    /// Fetches the next records of the range into `values`.
+    fn fetch(&mut self) -> io::Result<()> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&(-1i32).to_be_bytes());
+        body.extend_from_slice(&MAX_WAIT_MS.to_be_bytes());
+        body.extend_from_slice(&1i32.to_be_bytes());
+        body.extend_from_slice(&MAX_FETCH_BYTES.to_be_bytes());
+        body.push(0);
+        body.extend_from_slice(&1i32.to_be_bytes());
+        put_string(&mut body, &self.partition.topic);
+        body.extend_from_slice(&1i32.to_be_bytes());
+        body.extend_from_slice(&self.partition.partition.to_be_bytes());
+        body.extend_from_slice(&self.offset.to_be_bytes());
+        body.extend_from_slice(&MAX_FETCH_BYTES.to_be_bytes());
+
+        let response = self.call(FETCH, &body)?;
+        let mut response = Decoder { bytes: &response };
+        let _throttle_time = response.i32()?;
+        if response.count()? == 0 {
+            return Err(invalid("kafka-no-topics"));
+        }
+        response.string()?;
+        if response.count()? == 0 {
+            return Err(invalid("kafka-no-partitions"));
+        }
+        let _partition = response.i32()?;
+        match response.i16()? {
+            0 => {}
+            code => return Err(broker_error(code)),
+        }
+        let high_watermark = response.i64()?;
+        let _last_stable_offset = response.i64()?;
+        let aborted = response.count()?;
+        response.take(aborted * 16)?;
+        let length = response.i32()?;
+        let records = response.field(i64::from(length))?.unwrap_or_default();
+
+        match decode_batches(records, self.offset, self.end, &mut self.values)? {
+            Some(next_offset) => self.offset = next_offset,
+            // The range ends beyond the records which have been produced.
+            None if high_watermark <= self.offset => self.end = self.offset,
+            None => return Err(invalid("kafka-no-complete-batch")),
+        }
+        Ok(())
+    }
+}
+
+impl Read for PartitionReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.values.len() {
+            if self.offset >= self.end {
+                return Ok(0);
+            }
+            self.values.clear();
+            self.position = 0;
+            self.fetch()?;
+        }
+
+        let length = buf.len().min(self.values.len() - self.position);
+        buf[..length].copy_from_slice(&self.values[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends `value` to `bytes` as a zig-zag encoded variable-length integer.
+    fn put_varint(bytes: &mut Vec<u8>, value: i64) {
+        let mut value = ((value << 1) ^ (value >> 63)) as u64;
+        while value >= 0x80 {
+            bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes.push(value as u8);
+    }
+
+    /// Returns an uncompressed record batch of `values` whose first record has offset `base_offset`.
+    fn batch(base_offset: i64, values: &[&str]) -> Vec<u8> {
+        let mut records = Vec::new();
+        for (delta, value) in values.iter().enumerate() {
+            let mut record = vec![0];
+            put_varint(&mut record, 0);
+            put_varint(&mut record, delta as i64);
+            put_varint(&mut record, -1);
+            put_varint(&mut record, value.len() as i64);
+            record.extend_from_slice(value.as_bytes());
+            put_varint(&mut record, 0);
+            put_varint(&mut records, record.len() as i64);
+            records.extend_from_slice(&record);
+        }
+
+        let mut batch = Vec::new();
+        batch.extend_from_slice(&0i32.to_be_bytes());
+        batch.push(2);
+        batch.extend_from_slice(&0i32.to_be_bytes());
+        batch.extend_from_slice(&0i16.to_be_bytes());
+        batch.extend_from_slice(&(values.len() as i32 - 1).to_be_bytes());
+        batch.extend_from_slice(&[0; 8 + 8 + 8 + 2 + 4]);
+        batch.extend_from_slice(&(values.len() as i32).to_be_bytes());
+        batch.extend_from_slice(&records);
+
+        let mut bytes = base_offset.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&(batch.len() as i32).to_be_bytes());
+        bytes.extend_from_slice(&batch);
+        bytes
+    }
+
+    #[test]
+    fn test_partition() {
+        assert_eq!(
+            "kafka://localhost:9092/logs.app/3?start=10&end=20".parse(),
+            Ok(Partition {
+                broker: "localhost:9092".to_string(),
+                topic: "logs.app".to_string(),
+                partition: 3,
+                start: Some(10),
+                end: Some(20),
+            })
+        );
+        assert_eq!(
+            "kafka://broker:9092/logs/0"
+                .parse::<Partition>()
+                .unwrap()
+                .end,
+            None
+        );
+        assert!("kafka://broker:9092/logs".parse::<Partition>().is_err());
+        assert!("kafka://broker:9092/logs/0?from=1"
+            .parse::<Partition>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_batches() {
+        let mut records = batch(5, &["one two", "three"]);
+        records.extend(batch(7, &["four", "five six"]));
+        let truncated = batch(9, &["seven"]);
+        records.extend_from_slice(&truncated[..truncated.len() - 1]);
+
+        let mut values = Vec::new();
+        let next_offset = decode_batches(&records, 6, 8, &mut values).unwrap();
+        assert_eq!(next_offset, Some(9));
+        assert_eq!(values, b"three\nfour\n");
+        assert_eq!(decode_batches(&[], 0, 1, &mut values).unwrap(), None);
+    }
+}
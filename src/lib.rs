@@ -0,0 +1,1369 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+pub mod archive;
+pub mod decompress;
+mod dfa;
+pub mod document;
+pub mod email;
+mod emoji;
+pub mod encoding;
+pub mod epub;
+mod graphemes;
+pub mod grep;
+pub mod i18n;
+pub mod incremental;
+mod inflate;
+mod json;
+pub mod jsonrpc;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod limit;
+mod line_length;
+pub mod line_stats;
+pub mod log_buckets;
+pub mod log_levels;
+pub mod markup;
+pub mod notebook;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+mod regex;
+pub mod rules;
+pub mod segment;
+mod simd;
+mod syllables;
+pub mod tokens;
+mod utf8;
+pub mod whitespace;
+mod xml;
+mod zip;
+
+use encoding::{Encoding, Transcoder};
+use graphemes::GraphemeCounter;
+use i18n::Message;
+use line_stats::{LineStats, LineStatsCounter};
+use log_levels::{LogLevelCounter, LogLevelCounts};
+use rules::{Classifier, WordRules};
+use segment::Segmenter;
+use syllables::SyllableCounter;
+use tokens::TokenCounts;
+use utf8::Utf8Carry;
+use whitespace::WhitespaceCounts;
+
+/// Size of the I/O buffer when reading from input.
+pub const BUFFER_SIZE: usize = 512 * 1024;
+
+/// Size of the chunks a buffer is split into, each scanned sequentially, for parallel counting.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// What is counted as a line.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum LineDefinition {
+    /// Each line feed is a line, as in the original `wc` utility, so an unterminated final line is not.
+    #[default]
+    Terminators,
+    /// Each record of text ended by a line feed or the end of the input is a line, so an unterminated
+    /// final line is, as most line-oriented tools read it.
+    Records,
+}
+
+impl FromStr for LineDefinition {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "terminators" => Ok(LineDefinition::Terminators),
+            "records" => Ok(LineDefinition::Records),
+            _ => Err(Message::new("unknown-line-definition").arg("value", s)),
+        }
+    }
+}
+
+/// How the words, lines and characters of an input are scanned when words are split on ASCII whitespace.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum Engine {
+    /// Words and lines are counted with vector instructions where the processor has them, in parallel over
+    /// chunks of each buffer, and characters by decoding the input.
+    #[default]
+    Simd,
+    /// Words, lines and characters are counted together by a table-driven automaton, a byte at a time
+    /// without branches, for targets without fast vector instructions.
+    Dfa,
+}
+
+impl FromStr for Engine {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simd" => Ok(Engine::Simd),
+            "dfa" => Ok(Engine::Dfa),
+            _ => Err(Message::new("unknown-engine").arg("value", s)),
+        }
+    }
+}
+
+/// How bytes which are not valid UTF-8 are counted when the input is decoded.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum InvalidUtf8 {
+    /// Each invalid sequence is a replacement character, so a character and part of a word.
+    Replace,
+    /// Invalid sequences are left out, as if the input did not have them.
+    Skip,
+    /// Invalid sequences are an error, reporting the offset of the first. A `Counter` leaves them out, as
+    /// for `Skip`, for its caller to report.
+    Error,
+}
+
+impl FromStr for InvalidUtf8 {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "replace" => Ok(InvalidUtf8::Replace),
+            "skip" => Ok(InvalidUtf8::Skip),
+            "error" => Ok(InvalidUtf8::Error),
+            _ => Err(Message::new("unknown-invalid-utf-8-policy").arg("value", s)),
+        }
+    }
+}
+
+/// Options controlling which counts are taken and how words are split.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct WcOptions {
+    /// The rules used to split words, or `None` to split words on ASCII whitespace.
+    pub word_rules: Option<WordRules>,
+    /// The segmenter used for scripts which do not separate words with spaces, if any.
+    pub segmenter: Option<Segmenter>,
+    /// Whether to decode the input as UTF-8 and split words on Unicode whitespace, such as no-break and
+    /// ideographic spaces, rather than on ASCII whitespace bytes.
+    pub unicode_words: bool,
+    /// Whether to count emoji sequences.
+    pub emoji: bool,
+    /// Whether to count URL, email address and number tokens.
+    pub token_classes: bool,
+    /// Whether to count hashtag and mention tokens.
+    pub social: bool,
+    /// Whether to estimate the syllables of the words.
+    pub syllables: bool,
+    /// Whether to count whitespace characters by kind.
+    pub whitespace: bool,
+    /// Whether to count the characters, decoding the input as UTF-8.
+    pub chars: bool,
+    /// Whether to count the extended grapheme clusters, the characters a reader perceives, decoding the
+    /// input as UTF-8.
+    pub graphemes: bool,
+    /// Whether to find the length of the longest line in display columns, decoding the input as UTF-8.
+    pub max_line_length: bool,
+    /// Whether to count the lines of a log by level.
+    pub log_levels: bool,
+    /// Whether to find the statistics of the lengths and words of the lines.
+    pub line_stats: bool,
+    /// What is counted as a line.
+    pub lines: LineDefinition,
+    /// How words, lines and characters are scanned.
+    pub engine: Engine,
+    /// The character encoding of the input, which all but the byte count are taken of decoded to UTF-8.
+    pub encoding: Encoding,
+    /// How bytes which are not valid UTF-8 are counted when the input is decoded, or `None` to count them
+    /// as part of words but not as characters, as the original `wc` utility does.
+    pub invalid_utf8: Option<InvalidUtf8>,
+}
+
+impl WcOptions {
+    /// Returns the classifier used to split words in decoded text, or `None` if words are split on ASCII
+    /// whitespace bytes.
+    fn classifier(&self) -> Option<Classifier> {
+        if self.word_rules.is_none() && self.segmenter.is_none() {
+            return None;
+        }
+
+        Some(Classifier {
+            word_rules: self.word_rules,
+            segmenter: self.segmenter,
+            unicode_whitespace: self.unicode_words,
+        })
+    }
+}
+
+/// The result of the `wc` operation.
+///
+/// The counts are 64-bit on every target, so that no input a 32-bit build can read overflows them. Totals
+/// over several inputs saturate at `u64::MAX` rather than wrapping, other than the longest line length
+/// which is the longest of any input.
+///
+/// The allocated size is the space on disk of the file counted, in bytes, which counting leaves zero for
+/// the caller to fill in from the file's metadata.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Counts {
+    pub bytes: u64,
+    pub words: u64,
+    pub lines: u64,
+    pub emoji: u64,
+    pub tokens: TokenCounts,
+    pub messages: u64,
+    pub syllables: u64,
+    pub whitespace: WhitespaceCounts,
+    pub chars: u64,
+    pub graphemes: u64,
+    pub max_line_length: u64,
+    pub log_levels: LogLevelCounts,
+    pub line_stats: LineStats,
+    pub allocated: u64,
+}
+
+impl AddAssign for Counts {
+    fn add_assign(&mut self, rhs: Counts) {
+        self.bytes = self.bytes.saturating_add(rhs.bytes);
+        self.words = self.words.saturating_add(rhs.words);
+        self.lines = self.lines.saturating_add(rhs.lines);
+        self.emoji = self.emoji.saturating_add(rhs.emoji);
+        self.tokens += rhs.tokens;
+        self.messages = self.messages.saturating_add(rhs.messages);
+        self.syllables = self.syllables.saturating_add(rhs.syllables);
+        self.whitespace += rhs.whitespace;
+        self.chars = self.chars.saturating_add(rhs.chars);
+        self.graphemes = self.graphemes.saturating_add(rhs.graphemes);
+        self.max_line_length = self.max_line_length.max(rhs.max_line_length);
+        self.log_levels += rhs.log_levels;
+        self.line_stats += rhs.line_stats;
+        self.allocated = self.allocated.saturating_add(rhs.allocated);
+    }
+}
+
+/// The class of a character.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum CharType {
+    /// The character represents a whitespace separator.
+    IsSpace,
+    /// The character does not represent a whitespace separator.
+    NotSpace,
+}
+
+/// Representation of a chunk of text.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Flux {
+    /// The type of the left-most character in the chunk.
+    pub leftmost_char_type: CharType,
+    /// The number of words in the chunk.
+    pub words: u64,
+    /// The number of lines in the chunk.
+    pub lines: u64,
+    /// The type of the right-most character in the chunk.
+    pub rightmost_char_type: CharType,
+}
+
+impl Flux {
+    /// Returns a new instance of the receiver with the provided parameters.
+    pub fn new(
+        leftmost_char_type: CharType,
+        words: u64,
+        lines: u64,
+        rightmost_char_type: CharType,
+    ) -> Self {
+        Flux {
+            leftmost_char_type,
+            words,
+            lines,
+            rightmost_char_type,
+        }
+    }
+
+    /// Returns a new Flux spanning the receiver on the left, and `rhs` on the right.
+    pub fn span(self, rhs: Flux) -> Self {
+        let lines = self.lines + rhs.lines;
+        let words = {
+            // If the span is formed along a non-space to non-space boundary the word count is one less than the sum.
+            if let (CharType::NotSpace, CharType::NotSpace) =
+                (self.rightmost_char_type, rhs.leftmost_char_type)
+            {
+                self.words + rhs.words - 1
+            } else {
+                self.words + rhs.words
+            }
+        };
+
+        Flux::new(
+            self.leftmost_char_type,
+            words,
+            lines,
+            rhs.rightmost_char_type,
+        )
+    }
+}
+
+impl Flux {
+    /// Returns the flux of `chunk` scanned sequentially with vector instructions where the processor has
+    /// them, or `None` if it is empty.
+    fn from_chunk(chunk: &[u8]) -> Option<Self> {
+        let char_type = |byte: u8| {
+            if byte.is_ascii_whitespace() {
+                CharType::IsSpace
+            } else {
+                CharType::NotSpace
+            }
+        };
+        let leftmost_char_type = char_type(*chunk.first()?);
+        let rightmost_char_type = char_type(*chunk.last()?);
+
+        // A word is counted where a non-space follows a space or the start of the chunk.
+        let (words, lines) = simd::count_words_and_lines(chunk);
+        Some(Flux::new(
+            leftmost_char_type,
+            words,
+            lines,
+            rightmost_char_type,
+        ))
+    }
+}
+
+impl From<char> for Flux {
+    /// Creates a new instance of a Flux encoding a single decoded character, a space if it is Unicode
+    /// whitespace.
+    fn from(other: char) -> Self {
+        if other.is_whitespace() {
+            let lines = if other == '\n' { 1 } else { 0 };
+            Flux::new(CharType::IsSpace, 0, lines, CharType::IsSpace)
+        } else {
+            Flux::new(CharType::NotSpace, 1, 0, CharType::NotSpace)
+        }
+    }
+}
+
+impl From<u8> for Flux {
+    /// Creates a new instance of a Flux encoding a single character.
+    fn from(other: u8) -> Self {
+        if other.is_ascii_whitespace() {
+            // A line-feed is considered an ASCII whitespace character by `is_ascii_whitespace`.
+            let lines = if other == b'\n' { 1 } else { 0 };
+            Flux::new(CharType::IsSpace, 0, lines, CharType::IsSpace)
+        } else {
+            Flux::new(CharType::NotSpace, 1, 0, CharType::NotSpace)
+        }
+    }
+}
+
+/// Takes two optional Flux instances and returns, where possible, the span of the two.
+pub fn span_opt(lhs: Option<Flux>, rhs: Option<Flux>) -> Option<Flux> {
+    match (lhs, rhs) {
+        (Some(left_flux), Some(right_flux)) => Some(left_flux.span(right_flux)),
+        (flux, None) | (None, flux) => flux,
+    }
+}
+
+/// Computes the flux over the provided input byte string, scanning chunks of it in parallel.
+pub fn flux_over_byte_string<T>(input: T) -> Option<Flux>
+where
+    T: AsRef<[u8]>,
+{
+    input
+        .as_ref()
+        .par_chunks(CHUNK_SIZE)
+        .map(Flux::from_chunk)
+        .reduce(|| None, span_opt)
+}
+
+/// Appends the replacement character to `text`, in UTF-8.
+fn push_replacement(text: &mut Vec<u8>) {
+    text.extend_from_slice(
+        char::REPLACEMENT_CHARACTER
+            .encode_utf8(&mut [0; 4])
+            .as_bytes(),
+    );
+}
+
+/// The counts of the runs of complete characters of an input, found by decoding it.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+struct DecodedCounts {
+    /// The words and lines, when split by a classifier.
+    rule_flux: Option<rules::RuleFlux>,
+    /// The words and lines, when split on Unicode whitespace without a classifier.
+    unicode_flux: Option<Flux>,
+    /// The emoji sequences, with any split across the end of the runs so far.
+    emoji_flux: Option<emoji::EmojiFlux>,
+    /// The line lengths in columns, with that of the line split across the end of the runs so far.
+    line_length_flux: Option<line_length::LineLengthFlux>,
+    /// The syllables of the words.
+    syllables: SyllableCounter,
+    /// The whitespace characters by kind.
+    whitespace: WhitespaceCounts,
+    /// The number of valid characters.
+    chars: u64,
+    /// The extended grapheme clusters, with any split across the end of the runs so far.
+    graphemes: GraphemeCounter,
+    /// The number of bytes folded so far, when invalid UTF-8 is replaced, skipped or an error.
+    offset: u64,
+    /// The offset of the first invalid UTF-8 sequence folded, if any, when invalid UTF-8 is replaced,
+    /// skipped or an error.
+    invalid_offset: Option<u64>,
+}
+
+impl DecodedCounts {
+    /// Returns `chunk` with its invalid UTF-8 sequences replaced or left out as `policy` directs, noting
+    /// the offset of the first.
+    fn apply_policy<'a>(&mut self, chunk: &'a [u8], policy: InvalidUtf8) -> Cow<'a, [u8]> {
+        let mut position = self.offset;
+        self.offset += chunk.len() as u64;
+        if std::str::from_utf8(chunk).is_ok() {
+            return Cow::Borrowed(chunk);
+        }
+
+        let mut text = Vec::with_capacity(chunk.len());
+        for piece in chunk.utf8_chunks() {
+            text.extend_from_slice(piece.valid().as_bytes());
+            position += piece.valid().len() as u64;
+            if !piece.invalid().is_empty() {
+                self.invalid_offset.get_or_insert(position);
+                if policy == InvalidUtf8::Replace {
+                    push_replacement(&mut text);
+                }
+            }
+            position += piece.invalid().len() as u64;
+        }
+        Cow::Owned(text)
+    }
+
+    /// Folds the counts of `chunk`, a run of complete characters, into the existing.
+    fn fold(&mut self, chunk: &[u8], classifier: Option<Classifier>, options: &WcOptions) {
+        let chunk = match options.invalid_utf8 {
+            Some(policy) => self.apply_policy(chunk, policy),
+            None => Cow::Borrowed(chunk),
+        };
+        let chunk = &chunk[..];
+        if let Some(classifier) = classifier {
+            self.rule_flux = rules::span_opt(
+                self.rule_flux,
+                rules::rule_flux_over_byte_string(chunk, classifier),
+            );
+        } else if options.unicode_words {
+            self.unicode_flux = span_opt(self.unicode_flux, unicode_flux_over_byte_string(chunk));
+        }
+        if options.emoji {
+            self.emoji_flux =
+                emoji::span_opt(self.emoji_flux, emoji::emoji_flux_over_byte_string(chunk));
+        }
+        if options.max_line_length {
+            self.line_length_flux = line_length::span_opt(
+                self.line_length_flux,
+                line_length::line_length_flux_over_byte_string(chunk),
+            );
+        }
+        if options.chars {
+            // Bytes which are not part of a valid sequence are not characters, as in the original `wc`, while
+            // every byte of ASCII is one.
+            self.chars += if chunk.is_ascii() {
+                chunk.len() as u64
+            } else {
+                chunk
+                    .utf8_chunks()
+                    .map(|piece| piece.valid().chars().count() as u64)
+                    .sum::<u64>()
+            };
+        }
+        if options.syllables || options.whitespace || options.graphemes {
+            // Each invalid sequence is decoded as a replacement character, so is a grapheme cluster of its own.
+            let text = String::from_utf8_lossy(chunk);
+            if options.graphemes {
+                self.graphemes.feed(&text);
+            }
+            if options.syllables {
+                self.syllables.feed(&text);
+            }
+            if options.whitespace {
+                self.whitespace.count(&text);
+            }
+        }
+    }
+}
+
+/// Why an input could not be counted.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WcError {
+    /// The input could not be read, or the file at `path` if it is one, because of `source`.
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// The input is not valid UTF-8 at byte `offset`, when it is decoded and invalid UTF-8 is an error.
+    InvalidUtf8 { offset: u64 },
+    /// The limit `limit` on the portion of each input read is neither `bytes=N` nor `lines=N`.
+    InvalidLimit { limit: String },
+}
+
+impl fmt::Display for WcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WcError::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {}", path.display(), source),
+            WcError::Io { path: None, source } => write!(f, "{}", source),
+            WcError::InvalidUtf8 { offset } => {
+                Message::new("invalid-utf-8").arg("offset", offset).fmt(f)
+            }
+            WcError::InvalidLimit { limit } => {
+                Message::new("malformed-limit").arg("value", limit).fmt(f)
+            }
+        }
+    }
+}
+
+impl Error for WcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WcError::Io { source, .. } => Some(source),
+            WcError::InvalidUtf8 { .. } | WcError::InvalidLimit { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for WcError {
+    fn from(source: io::Error) -> Self {
+        WcError::Io { path: None, source }
+    }
+}
+
+/// Errors are converted to I/O errors of the same message, of kind `InvalidData` if the input could not be
+/// decoded and `InvalidInput` if a limit is invalid, for counts read as part of other I/O.
+impl From<WcError> for io::Error {
+    fn from(error: WcError) -> Self {
+        match error {
+            WcError::Io { path: None, source } => source,
+            WcError::Io { ref source, .. } => io::Error::new(source.kind(), error.to_string()),
+            WcError::InvalidUtf8 { .. } => {
+                io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+            }
+            WcError::InvalidLimit { .. } => {
+                io::Error::new(io::ErrorKind::InvalidInput, error.to_string())
+            }
+        }
+    }
+}
+
+/// Counts the bytes, words and lines of an input fed to it in order, in pieces split anywhere, along with
+/// the optional counts its options enable.
+///
+/// Each count carries the state of a word, line, token or character split across the end of one piece
+/// into the next, so the counts of an input are the same however it is split, including into single bytes,
+/// as those of it counted whole.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Counter {
+    /// The counts taken and how words are split.
+    options: WcOptions,
+    /// The classifier words are split with, or `None` if they are split on ASCII whitespace bytes.
+    classifier: Option<Classifier>,
+    /// The options the decoded characters are counted with, which leave characters to the automaton when
+    /// it scans the input.
+    decoded_options: WcOptions,
+    /// Whether the input is decoded as UTF-8, for counts of characters rather than bytes.
+    decode: bool,
+    /// The decoder of the input to UTF-8, if it is in another encoding or that is yet to be detected.
+    transcoder: Option<Transcoder>,
+    /// The input last decoded to UTF-8, kept to reuse its allocation.
+    transcoded: Vec<u8>,
+    /// The number of bytes fed so far, before any decoding to UTF-8.
+    bytes: u64,
+    /// The last byte fed so far, if any.
+    last_byte: Option<u8>,
+    /// The words and lines of the input so far, when split on ASCII whitespace.
+    flux: Option<Flux>,
+    /// The automaton scanning the words, lines and characters of the input, when it is the engine and
+    /// words are split on ASCII whitespace.
+    scanner: Option<dfa::Scanner>,
+    /// The tokens of the input so far, with any split across its end.
+    token_flux: Option<tokens::TokenFlux>,
+    /// The lines of the input so far by level.
+    log_levels: LogLevelCounter,
+    /// The statistics of the lines of the input so far.
+    line_stats: LineStatsCounter,
+    /// Any UTF-8 sequence split across the end of the input fed so far.
+    carry: Utf8Carry,
+    /// The counts of the characters decoded so far.
+    decoded: DecodedCounts,
+}
+
+impl Counter {
+    /// Returns a counter of an input taking the counts `options` enable.
+    pub fn new(options: &WcOptions) -> Self {
+        let classifier = options.classifier();
+        let scanner = (options.engine == Engine::Dfa
+            && classifier.is_none()
+            && !options.unicode_words
+            && options.invalid_utf8.is_none())
+        .then(dfa::Scanner::default);
+        let decoded_options = WcOptions {
+            chars: options.chars && scanner.is_none(),
+            ..*options
+        };
+        Counter {
+            options: *options,
+            classifier,
+            decoded_options,
+            decode: classifier.is_some()
+                || options.unicode_words
+                || options.emoji
+                || options.max_line_length
+                || options.syllables
+                || options.whitespace
+                || options.graphemes
+                || decoded_options.chars,
+            transcoder: (options.encoding != Encoding::Utf8)
+                .then(|| Transcoder::new(options.encoding)),
+            transcoded: Vec::new(),
+            bytes: 0,
+            last_byte: None,
+            flux: None,
+            scanner,
+            token_flux: None,
+            log_levels: LogLevelCounter::default(),
+            line_stats: LineStatsCounter::default(),
+            carry: Utf8Carry::default(),
+            decoded: DecodedCounts::default(),
+        }
+    }
+
+    /// Counts `buffer`, which continues the input fed so far.
+    pub fn feed(&mut self, buffer: &[u8]) {
+        // Update the byte counter from the buffer, before it is decoded.
+        self.bytes += buffer.len() as u64;
+
+        // Decode the buffer to UTF-8 if the input is in another encoding, or pass it on once it is found
+        // not to be.
+        match self.transcoder.take() {
+            None => self.count(buffer),
+            Some(mut transcoder) => {
+                let mut text = std::mem::take(&mut self.transcoded);
+                text.clear();
+                transcoder.feed(buffer, &mut text);
+                self.count(&text);
+                self.transcoded = text;
+                if transcoder.encoding() != Encoding::Utf8 {
+                    self.transcoder = Some(transcoder);
+                }
+            }
+        }
+    }
+
+    /// Counts `buffer`, which continues the input fed so far decoded to UTF-8, other than its bytes.
+    fn count(&mut self, buffer: &[u8]) {
+        let options = &self.options;
+        if buffer.is_empty() {
+            return;
+        }
+        self.last_byte = buffer.last().copied();
+
+        // Fold the flux of the next buffer into the existing, or scan it with the automaton.
+        if let Some(scanner) = &mut self.scanner {
+            scanner.feed(buffer);
+        } else if self.classifier.is_none() && !options.unicode_words {
+            self.flux = span_opt(self.flux, flux_over_byte_string(buffer));
+        }
+
+        // Fold the token flux of the next buffer into the existing.
+        if options.token_classes || options.social {
+            self.token_flux = tokens::span_opt(
+                self.token_flux.take(),
+                tokens::token_flux_over_byte_string(buffer),
+            );
+        }
+
+        // Count the lines of the buffer by level.
+        if options.log_levels {
+            self.log_levels.feed(buffer);
+        }
+
+        // Find the statistics of the lines of the buffer.
+        if options.line_stats {
+            self.line_stats.feed(buffer);
+        }
+
+        // Decode the buffer, carrying any sequence split across its end into the next.
+        if self.decode {
+            let (decoded, classifier) = (&mut self.decoded, self.classifier);
+            let decoded_options = &self.decoded_options;
+            self.carry.feed(buffer, |chunk| {
+                decoded.fold(chunk, classifier, decoded_options)
+            });
+        }
+    }
+
+    /// Returns the number of bytes fed so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the counts of the input fed so far, as if it ended there, leaving the counter to be fed more.
+    pub fn counts(&self) -> Counts {
+        self.clone().finish()
+    }
+
+    /// Returns the offset of the first sequence of the input fed so far which is not valid UTF-8, if any,
+    /// when the input is decoded and invalid UTF-8 is replaced, skipped or an error.
+    pub fn invalid_offset(&self) -> Option<u64> {
+        self.decoded.invalid_offset
+    }
+
+    /// Returns an error giving the offset of the first invalid UTF-8 sequence fed so far, if there is one and
+    /// invalid UTF-8 is an error, counting from `start` as the offset of the input within a larger one.
+    fn check_invalid(&self, start: u64) -> Result<(), WcError> {
+        match self.invalid_offset() {
+            Some(offset) if self.options.invalid_utf8 == Some(InvalidUtf8::Error) => {
+                Err(WcError::InvalidUtf8 {
+                    offset: start + offset,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts whatever of the input is held back at its end, undecoded or in an incomplete sequence.
+    fn flush(&mut self) {
+        if let Some(mut transcoder) = self.transcoder.take() {
+            let mut text = std::mem::take(&mut self.transcoded);
+            text.clear();
+            transcoder.finish(&mut text);
+            self.count(&text);
+        }
+
+        let (decoded, classifier) = (&mut self.decoded, self.classifier);
+        let decoded_options = &self.decoded_options;
+        self.carry
+            .finish(|chunk| decoded.fold(chunk, classifier, decoded_options));
+    }
+
+    /// Returns the counts of all of the input fed.
+    pub fn finish(mut self) -> Counts {
+        self.flush();
+        let options = &self.options;
+        let decoded = self.decoded;
+
+        let (words, lines) = match (decoded.rule_flux, self.scanner) {
+            (Some(rule_flux), _) => (rule_flux.words(), rule_flux.lines),
+            (None, Some(scanner)) => (scanner.words, scanner.lines),
+            (None, None) => decoded
+                .unicode_flux
+                .or(self.flux)
+                .map(|f| (f.words, f.lines))
+                .unwrap_or_default(),
+        };
+        let chars = match self.scanner {
+            Some(scanner) if options.chars => scanner.chars,
+            _ => decoded.chars,
+        };
+        let unterminated = self.last_byte.is_some_and(|byte| byte != b'\n');
+        let lines = lines + u64::from(options.lines == LineDefinition::Records && unterminated);
+
+        Counts {
+            bytes: self.bytes,
+            words,
+            lines,
+            emoji: decoded.emoji_flux.map(|f| f.emoji()).unwrap_or_default(),
+            tokens: self.token_flux.map(|f| f.counts()).unwrap_or_default(),
+            messages: 0,
+            syllables: decoded.syllables.finish(),
+            whitespace: decoded.whitespace,
+            chars,
+            graphemes: decoded.graphemes.finish(),
+            max_line_length: decoded
+                .line_length_flux
+                .map(|f| f.max_line_length())
+                .unwrap_or_default(),
+            log_levels: self.log_levels.finish(),
+            line_stats: self.line_stats.finish(),
+            allocated: 0,
+        }
+    }
+}
+
+/// A flag shared by a count and whatever cancels it, such as another thread, which stops the count once the
+/// buffer being counted when it is set has been.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Returns a token which has not been cancelled.
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Cancels the counts the token, or any of its clones, was passed to.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Computes the flux over the provided input byte string decoded as UTF-8, splitting words on Unicode
+/// whitespace.
+///
+/// Bytes which are not valid UTF-8 are considered to be part of a word.
+pub fn unicode_flux_over_byte_string<T>(input: T) -> Option<Flux>
+where
+    T: AsRef<[u8]>,
+{
+    let word_byte = Flux::new(CharType::NotSpace, 1, 0, CharType::NotSpace);
+    // Each chunk is scanned on its own, split where no character is, and the fluxes of the chunks spanned.
+    utf8::split_chunks(input.as_ref(), CHUNK_SIZE)
+        .par_iter()
+        .map(|chunk| {
+            chunk.utf8_chunks().fold(None, |flux, piece| {
+                let valid = piece
+                    .valid()
+                    .chars()
+                    .fold(flux, |flux, c| span_opt(flux, Some(Flux::from(c))));
+                piece
+                    .invalid()
+                    .iter()
+                    .fold(valid, |flux, _| span_opt(flux, Some(word_byte)))
+            })
+        })
+        .reduce(|| None, span_opt)
+}
+
+/// Counts the bytes, words and lines of `input`, along with the optional counts `options` enable.
+///
+/// Reading stops at the first invalid UTF-8 sequence decoded if invalid UTF-8 is an error, returning an
+/// error giving its offset.
+pub fn count_reader<R>(input: &mut R, options: &WcOptions) -> Result<Counts, WcError>
+where
+    R: BufRead,
+{
+    count_reader_with_progress(input, options, |_| {})
+}
+
+/// Counts `input` as `count_reader` does, calling `progress` with the counter once each buffer read is
+/// counted, so that the bytes counted so far, and the counts of them, can be shown as counting goes on.
+/// Progress is sent elsewhere, such as to a channel, from `progress`.
+pub fn count_reader_with_progress<R, F>(
+    input: &mut R,
+    options: &WcOptions,
+    progress: F,
+) -> Result<Counts, WcError>
+where
+    R: BufRead,
+    F: FnMut(&Counter),
+{
+    count_reader_until(input, options, None, progress)
+}
+
+/// Counts `input` as `count_reader` does until `token` is cancelled, returning the counts of the input read
+/// before it was, which are partial if `token.is_cancelled()`.
+pub fn count_reader_cancellable<R>(
+    input: &mut R,
+    options: &WcOptions,
+    token: &CancellationToken,
+) -> Result<Counts, WcError>
+where
+    R: BufRead,
+{
+    count_reader_until(input, options, Some(token), |_| {})
+}
+
+/// Counts `input` until its end or until `token`, if any, is cancelled, calling `progress` with the counter
+/// once each buffer read is counted.
+fn count_reader_until<R, F>(
+    input: &mut R,
+    options: &WcOptions,
+    token: Option<&CancellationToken>,
+    mut progress: F,
+) -> Result<Counts, WcError>
+where
+    R: BufRead,
+    F: FnMut(&Counter),
+{
+    let mut counter = Counter::new(options);
+    while !token.is_some_and(CancellationToken::is_cancelled) {
+        let buffer = input.fill_buf()?;
+        let length = buffer.len();
+        if length == 0 {
+            break;
+        }
+        counter.feed(buffer);
+        input.consume(length);
+        counter.check_invalid(0)?;
+        progress(&counter);
+    }
+    counter.flush();
+    counter.check_invalid(0)?;
+    Ok(counter.finish())
+}
+
+/// Counts the bytes, words and lines of `input`, a string or byte string held in memory such as an editor
+/// buffer, along with the optional counts `options` enable. The counts are those of a file with the same
+/// contents, other than that invalid UTF-8 is left out rather than an error if it is one; `try_count_slice`
+/// reports it instead.
+pub fn count_slice<T>(input: T, options: &WcOptions) -> Counts
+where
+    T: AsRef<[u8]>,
+{
+    let mut counter = Counter::new(options);
+    counter.feed(input.as_ref());
+    counter.finish()
+}
+
+/// Counts `input` as `count_slice` does, returning an error giving the offset of the first invalid UTF-8
+/// sequence if invalid UTF-8 is an error, so that the counts are those of a file with the same contents.
+pub fn try_count_slice<T>(input: T, options: &WcOptions) -> Result<Counts, WcError>
+where
+    T: AsRef<[u8]>,
+{
+    try_count_slice_at(input, 0, options)
+}
+
+/// Counts `input` as `try_count_slice` does, giving the offset of any invalid UTF-8 from `start`, the offset
+/// of `input` within a larger input it is a part of.
+pub fn try_count_slice_at<T>(input: T, start: u64, options: &WcOptions) -> Result<Counts, WcError>
+where
+    T: AsRef<[u8]>,
+{
+    let mut counter = Counter::new(options);
+    counter.feed(input.as_ref());
+    counter.flush();
+    counter.check_invalid(start)?;
+    Ok(counter.finish())
+}
+
+/// Counts the bytes, words and lines of the file at `path`, along with the optional counts `options` enable.
+/// Errors reading it name `path`.
+pub fn count_path(path: &Path, options: &WcOptions) -> Result<Counts, WcError> {
+    let file = File::open(path).map_err(|error| in_file(path, error))?;
+    count_reader(&mut BufReader::with_capacity(BUFFER_SIZE, file), options)
+        .map_err(|error| in_file(path, error))
+}
+
+/// Returns `error`, met counting the file at `path`, naming the file if it is an I/O error.
+fn in_file(path: &Path, error: impl Into<WcError>) -> WcError {
+    match error.into() {
+        WcError::Io { path: None, source } => WcError::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        },
+        error => error,
+    }
+}
+
+/// Counts the file at `path` as `count_path` does until `token` is cancelled, returning the counts of the
+/// part of it read before it was, which are partial if `token.is_cancelled()`.
+pub fn count_path_cancellable(
+    path: &Path,
+    options: &WcOptions,
+    token: &CancellationToken,
+) -> Result<Counts, WcError> {
+    let file = File::open(path).map_err(|error| in_file(path, error))?;
+    let mut reader = BufReader::with_capacity(BUFFER_SIZE, file);
+    count_reader_cancellable(&mut reader, options, token).map_err(|error| in_file(path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flux_over_byte_string() {
+        assert_eq!(
+            flux_over_byte_string("testing one two three".as_bytes()),
+            Some(Flux::new(CharType::NotSpace, 4, 0, CharType::NotSpace))
+        );
+    }
+
+    #[test]
+    fn test_flux_over_chunks_matches_flux_over_bytes() {
+        // Words and line breaks straddle the boundaries of the chunks.
+        let input: Vec<u8> = "a word\n  spanning\tchunks "
+            .bytes()
+            .cycle()
+            .take(CHUNK_SIZE * 3 + 7)
+            .collect();
+        let bytewise = input
+            .iter()
+            .map(|&byte| Some(Flux::from(byte)))
+            .fold(None, span_opt);
+
+        assert_eq!(flux_over_byte_string(&input), bytewise);
+        assert_eq!(flux_over_byte_string(b""), None);
+    }
+
+    #[test]
+    fn test_span_opt_not_space_to_not_space() {
+        let flux_l = flux_over_byte_string("testing on");
+        let flux_r = flux_over_byte_string("e two three");
+
+        assert_eq!(
+            span_opt(flux_l, flux_r),
+            Some(Flux::new(CharType::NotSpace, 4, 0, CharType::NotSpace))
+        );
+    }
+
+    #[test]
+    fn test_line_definitions() {
+        let records = WcOptions {
+            lines: LineDefinition::Records,
+            ..WcOptions::default()
+        };
+        assert_eq!(count_slice("one\ntwo", &WcOptions::default()).lines, 1);
+        assert_eq!(count_slice("one\ntwo", &records).lines, 2);
+        assert_eq!(count_slice("one\ntwo\n", &records).lines, 2);
+        assert_eq!(count_slice("", &records).lines, 0);
+        assert_eq!("records".parse(), Ok(LineDefinition::Records));
+        let error = "rows".parse::<LineDefinition>().unwrap_err();
+        assert_eq!(error.identifier(), "unknown-line-definition");
+        assert_eq!(error.to_string(), "Unknown line definition 'rows'");
+    }
+
+    #[test]
+    fn test_counts_beyond_4_gib() {
+        let half = Flux::new(CharType::NotSpace, 1 << 31, 1 << 31, CharType::IsSpace);
+        let spanned = half.span(half).span(half);
+        assert_eq!(spanned.words, 3 << 31);
+        assert_eq!(spanned.lines, 3 << 31);
+
+        let mut total = Counts {
+            bytes: u64::from(u32::MAX) + 1,
+            ..Counts::default()
+        };
+        total += total;
+        assert_eq!(total.bytes, 1 << 33);
+        total += Counts {
+            bytes: u64::MAX,
+            ..Counts::default()
+        };
+        assert_eq!(total.bytes, u64::MAX);
+    }
+
+    #[test]
+    fn test_counter_splits() {
+        let input = "2019-06-01 ERROR failed at https://example.com/a?b=1 for @ops #incident\r\n\
+                     The caf\u{E9}\u{A0}na\u{EF}ve  co-op's 3.14 items\tcost $1,000.\r\n\
+                     \u{1F469}\u{200D}\u{1F4BB} \u{1F389}\u{1F3FD} \u{65E5}\u{672C}\u{8A9E}\u{306E}\u{6587}\u{3002}\n\
+                     \n   WARNING syllable-heavy onomatopoeia\u{2003}unterminated";
+        let mut input = input.as_bytes().to_vec();
+        input.extend_from_slice(b" \xE0\x80 \xF0\x9F\x8E \xFF words\xC3");
+
+        let all = WcOptions {
+            emoji: true,
+            token_classes: true,
+            social: true,
+            syllables: true,
+            whitespace: true,
+            chars: true,
+            graphemes: true,
+            max_line_length: true,
+            log_levels: true,
+            line_stats: true,
+            lines: LineDefinition::Records,
+            ..WcOptions::default()
+        };
+        let options = [
+            WcOptions::default(),
+            all,
+            WcOptions {
+                word_rules: Some(WordRules::English),
+                segmenter: Some(Segmenter::Cjk),
+                ..all
+            },
+            WcOptions {
+                unicode_words: true,
+                ..all
+            },
+            WcOptions {
+                engine: Engine::Dfa,
+                ..all
+            },
+            WcOptions {
+                engine: Engine::Dfa,
+                chars: true,
+                ..WcOptions::default()
+            },
+            WcOptions {
+                invalid_utf8: Some(InvalidUtf8::Replace),
+                ..all
+            },
+            WcOptions {
+                unicode_words: true,
+                invalid_utf8: Some(InvalidUtf8::Skip),
+                ..all
+            },
+        ];
+
+        // Pieces of pseudo-random sizes, from single bytes up to a few characters.
+        let mut seed = 2019u64;
+        let mut next = |bound: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) % bound) as usize
+        };
+        for options in &options {
+            let whole = count_slice(&input, options);
+            for _ in 0..50 {
+                let mut counter = Counter::new(options);
+                let mut rest = &input[..];
+                while !rest.is_empty() {
+                    let (piece, remainder) = rest.split_at((1 + next(8)).min(rest.len()));
+                    counter.feed(piece);
+                    counter.feed(b"");
+                    rest = remainder;
+                }
+                assert_eq!(counter.finish(), whole);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unicode_words() {
+        let options = WcOptions {
+            unicode_words: true,
+            ..WcOptions::default()
+        };
+        // No-break, figure and ideographic spaces, a line separator and a vertical tab separate words.
+        let mut text = "one\u{A0}two\u{2007}three\u{3000}four\u{2028}five\x0Bsix "
+            .as_bytes()
+            .to_vec();
+        text.extend_from_slice(b"\xFF\n");
+        let counts = count_slice(&text, &options);
+        assert_eq!((counts.lines, counts.words), (1, 7));
+        assert_eq!(count_slice(&text, &WcOptions::default()).words, 2);
+        assert_eq!(count_slice(b"a\xC2 b", &options).words, 2);
+
+        // Ideographic spaces straddle the boundaries of the chunks counted in parallel.
+        let text = "word\u{3000}".repeat(CHUNK_SIZE / 2);
+        let flux = unicode_flux_over_byte_string(&text).unwrap();
+        assert_eq!((flux.words, flux.lines), (CHUNK_SIZE as u64 / 2, 0));
+
+        let classifier = WcOptions {
+            segmenter: Some(Segmenter::Cjk),
+            ..options
+        };
+        assert_eq!(
+            count_slice("\u{65E5}\u{672C}\u{3000}go\u{A0}on", &classifier).words,
+            4
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let text = b"one \xFF two\xE2\x82 three \xF0\x9F\x8E\n";
+        let counts = |invalid_utf8| {
+            let options = WcOptions {
+                unicode_words: true,
+                chars: true,
+                invalid_utf8,
+                ..WcOptions::default()
+            };
+            let counts = count_slice(text, &options);
+            (counts.chars, counts.words)
+        };
+        assert_eq!(counts(None), (16, 5));
+        assert_eq!(counts(Some(InvalidUtf8::Replace)), (19, 5));
+        assert_eq!(counts(Some(InvalidUtf8::Skip)), (16, 3));
+
+        let options = WcOptions {
+            chars: true,
+            invalid_utf8: Some(InvalidUtf8::Error),
+            ..WcOptions::default()
+        };
+        let error = try_count_slice(text, &options).unwrap_err();
+        assert!(matches!(error, WcError::InvalidUtf8 { offset: 4 }));
+        assert_eq!(error.to_string(), "Invalid UTF-8 at byte 4");
+        let error = try_count_slice_at(b"caf\xC3\xA9\xE2\x82", 10, &options).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid UTF-8 at byte 15");
+        assert_eq!(
+            try_count_slice(b"caf\xC3\xA9\n", &options).unwrap().chars,
+            5
+        );
+        let error = io::Error::from(count_reader(&mut &text[..], &options).unwrap_err());
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Invalid UTF-8 at byte 4");
+        let error = count_reader(&mut &b"caf\xC3\xA9\xE2\x82"[..], &options).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid UTF-8 at byte 5");
+        assert!(count_reader(&mut &b"caf\xC3\xA9\n"[..], &options).is_ok());
+        assert!(count_reader(&mut &text[..], &WcOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_utf16_encoding() {
+        let text = "The caf\u{E9} \u{1F389}\r\nsecond line\nunterminated";
+        let units: Vec<u16> = "\u{FEFF}"
+            .encode_utf16()
+            .chain(text.encode_utf16())
+            .collect();
+        let le: Vec<u8> = units.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+        let be: Vec<u8> = units.iter().flat_map(|unit| unit.to_be_bytes()).collect();
+
+        let options = WcOptions {
+            chars: true,
+            lines: LineDefinition::Records,
+            ..WcOptions::default()
+        };
+        let expected = Counts {
+            bytes: le.len() as u64,
+            ..count_slice(text, &options)
+        };
+        for (input, encoding) in [
+            (&le, Encoding::Utf16Le),
+            (&le, Encoding::Auto),
+            (&be, Encoding::Utf16Be),
+            (&be, Encoding::Auto),
+        ] {
+            let options = WcOptions {
+                encoding,
+                ..options
+            };
+            assert_eq!(count_slice(input, &options), expected);
+
+            let mut counter = Counter::new(&options);
+            for byte in input.chunks(1) {
+                counter.feed(byte);
+            }
+            assert_eq!(counter.finish(), expected);
+        }
+        assert_eq!((expected.chars, expected.words, expected.lines), (36, 6, 3));
+
+        let auto = WcOptions {
+            encoding: Encoding::Auto,
+            ..options
+        };
+        assert_eq!(count_slice(text, &auto), count_slice(text, &options));
+    }
+
+    #[test]
+    fn test_count_slice() {
+        let options = WcOptions {
+            emoji: true,
+            chars: true,
+            max_line_length: true,
+            ..WcOptions::default()
+        };
+        let counts = count_slice("one two\nthree 🎉\n".as_bytes(), &options);
+
+        assert_eq!((counts.lines, counts.words, counts.bytes), (2, 4, 19));
+        assert_eq!(counts.emoji, 1);
+        assert_eq!(counts.chars, 16);
+        assert_eq!(counts.max_line_length, 8);
+        assert_eq!(count_slice(b"caf\xC3\xA9 \xFF", &options).chars, 5);
+        assert_eq!(
+            count_path(Path::new("Cargo.toml"), &options).unwrap().bytes,
+            std::fs::metadata("Cargo.toml").unwrap().len()
+        );
+        let error = count_path(Path::new("missing.toml"), &options).unwrap_err();
+        match &error {
+            WcError::Io { path, source } => {
+                assert_eq!(path.as_deref(), Some(Path::new("missing.toml")));
+                assert_eq!(source.kind(), io::ErrorKind::NotFound);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(error.to_string().starts_with("missing.toml: "));
+
+        // Strings in memory are counted with the same options as files.
+        let options = WcOptions {
+            word_rules: Some(WordRules::English),
+            ..options
+        };
+        let text = std::fs::read_to_string("Cargo.toml").unwrap();
+        assert_eq!(
+            count_slice(&text, &options),
+            count_path(Path::new("Cargo.toml"), &options).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_count_reader_with_progress() {
+        let options = WcOptions {
+            chars: true,
+            ..WcOptions::default()
+        };
+        let input = "one two\nthree 🎉\n".repeat(100);
+        let mut reader = BufReader::with_capacity(64, input.as_bytes());
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let counts = count_reader_with_progress(&mut reader, &options, |counter| {
+            sender.send((counter.bytes(), counter.counts())).unwrap();
+        })
+        .unwrap();
+        drop(sender);
+
+        // Each report is of the counts of the input read so far, however it was split.
+        let reports: Vec<(u64, Counts)> = receiver.iter().collect();
+        assert_eq!(reports.len(), input.len().div_ceil(64));
+        for (bytes, partial) in &reports {
+            let read = &input.as_bytes()[..*bytes as usize];
+            assert_eq!(*partial, count_slice(read, &options));
+        }
+        assert_eq!(reports.last().unwrap().1, counts);
+    }
+
+    #[test]
+    fn test_count_reader_cancellable() {
+        let options = WcOptions::default();
+        let input = "one two\n".repeat(100);
+
+        // The count is cancelled by another holder of the token as its third buffer is read, which is counted.
+        struct Cancelling<'a> {
+            input: &'a [u8],
+            reads: usize,
+            token: CancellationToken,
+        }
+        impl io::Read for Cancelling<'_> {
+            fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+                self.reads += 1;
+                if self.reads == 3 {
+                    self.token.cancel();
+                }
+                self.input.read(buffer)
+            }
+        }
+        let token = CancellationToken::new();
+        let cancelling = Cancelling {
+            input: input.as_bytes(),
+            reads: 0,
+            token: token.clone(),
+        };
+        let mut reader = BufReader::with_capacity(16, cancelling);
+        let counts = count_reader_cancellable(&mut reader, &options, &token).unwrap();
+        assert!(token.is_cancelled());
+        assert_eq!(counts, count_slice(&input[..48], &options));
+
+        let token = CancellationToken::new();
+        let mut reader = BufReader::with_capacity(16, input.as_bytes());
+        let counts = count_reader_cancellable(&mut reader, &options, &token).unwrap();
+        assert!(!token.is_cancelled());
+        assert_eq!(counts, count_slice(&input, &options));
+        assert_eq!(
+            count_path_cancellable(Path::new("Cargo.toml"), &options, &token).unwrap(),
+            count_path(Path::new("Cargo.toml"), &options).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_span_opt_space_to_space() {
+        let flux_l = flux_over_byte_string("testing one ");
+        let flux_r = flux_over_byte_string(" two three");
+
+        assert_eq!(
+            span_opt(flux_l, flux_r),
+            Some(Flux::new(CharType::NotSpace, 4, 0, CharType::NotSpace))
+        );
+    }
+}
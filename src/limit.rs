@@ -0,0 +1,115 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::{self, Read};
+use std::str::FromStr;
+
+use crate::WcError;
+
+/// The leading portion of each input which is read, so that unbounded streams end.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum StopAfter {
+    /// The first given number of bytes.
+    Bytes(u64),
+    /// The first given number of lines, including their terminators.
+    Lines(u64),
+}
+
+impl FromStr for StopAfter {
+    type Err = WcError;
+
+    /// Parses `bytes=N` or `lines=N`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WcError::InvalidLimit {
+            limit: s.to_string(),
+        };
+        let (unit, count) = s.split_once('=').ok_or_else(invalid)?;
+        let count = count.trim().parse().map_err(|_| invalid())?;
+        match unit.trim() {
+            "bytes" => Ok(StopAfter::Bytes(count)),
+            "lines" => Ok(StopAfter::Lines(count)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A reader which ends once a leading portion of another has been read.
+pub struct Limit<R> {
+    /// The reader being limited.
+    inner: R,
+    /// The portion of the reader which remains to be read, or `None` if reading is unlimited.
+    remaining: Option<StopAfter>,
+}
+
+impl<R: Read> Limit<R> {
+    /// Returns a reader of `inner` which ends after `limit`, if given.
+    pub fn new(inner: R, limit: Option<StopAfter>) -> Self {
+        Limit {
+            inner,
+            remaining: limit,
+        }
+    }
+}
+
+impl<R: Read> Read for Limit<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.remaining {
+            None => self.inner.read(buf),
+            Some(StopAfter::Bytes(0)) | Some(StopAfter::Lines(0)) => Ok(0),
+            Some(StopAfter::Bytes(remaining)) => {
+                let limit = (*remaining).min(buf.len() as u64) as usize;
+                let length = self.inner.read(&mut buf[..limit])?;
+                *remaining -= length as u64;
+                Ok(length)
+            }
+            Some(StopAfter::Lines(remaining)) => {
+                // Bytes read beyond the end of the last line are discarded, as reading stops there.
+                let length = self.inner.read(buf)?;
+                let mut kept = length;
+                for (index, _) in buf[..length]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &byte)| byte == b'\n')
+                {
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        kept = index + 1;
+                        break;
+                    }
+                }
+                Ok(kept)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the contents of `input` read through a limit of `limit`.
+    fn limited(input: &str, limit: &str) -> String {
+        let mut output = String::new();
+        Limit::new(input.as_bytes(), Some(limit.parse().unwrap()))
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_limit() {
+        assert_eq!(limited("one\ntwo\nthree\n", "lines=2"), "one\ntwo\n");
+        assert_eq!(limited("one\ntwo", "lines=5"), "one\ntwo");
+        assert_eq!(limited("one\ntwo\n", "bytes=5"), "one\nt");
+        assert_eq!(limited("one", "lines=0"), "");
+        assert!(matches!(
+            "words=3".parse::<StopAfter>(),
+            Err(WcError::InvalidLimit { limit }) if limit == "words=3"
+        ));
+        assert!("bytes".parse::<StopAfter>().is_err());
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The length of the longest line in display columns, as GNU `wc -L` measures it.
+//!
+//! Tabs advance to the next multiple of eight columns, carriage returns and form feeds return to the
+//! first column as line feeds do, the wide characters of East Asian scripts and emoji take two columns,
+//! and control, format and combining characters take none. Bytes which are not valid UTF-8 take none.
+
+use rayon::prelude::*;
+
+use crate::graphemes;
+use crate::utf8;
+
+/// Size of the chunks a buffer is split into for parallel scanning.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of columns from one tab stop to the next.
+const TAB_WIDTH: u64 = 8;
+
+/// Returns the column of the tab stop after `column`.
+fn tab_stop(column: u64) -> u64 {
+    column - column % TAB_WIDTH + TAB_WIDTH
+}
+
+/// Returns the number of columns `c` takes when displayed, other than a tab.
+fn width(c: char) -> u64 {
+    if graphemes::is_zero_width(c) {
+        return 0;
+    }
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x231A..=0x231B
+        | 0x2329..=0x232A
+        | 0x23E9..=0x23EC
+        | 0x23F0
+        | 0x23F3
+        | 0x25FD..=0x25FE
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x267F
+        | 0x2693
+        | 0x26A1
+        | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE
+        | 0x26C4..=0x26C5
+        | 0x26CE
+        | 0x26D4
+        | 0x26EA
+        | 0x26F2..=0x26F3
+        | 0x26F5
+        | 0x26FA
+        | 0x26FD
+        | 0x2705
+        | 0x270A..=0x270B
+        | 0x2728
+        | 0x274C
+        | 0x274E
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27B0
+        | 0x27BF
+        | 0x2B1B..=0x2B1C
+        | 0x2B50
+        | 0x2B55
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xA960..=0xA97F
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE10..=0xFE19
+        | 0xFE30..=0xFE6F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x16FE0..=0x16FE4
+        | 0x17000..=0x18CFF
+        | 0x1B000..=0x1B2FF
+        | 0x1F004
+        | 0x1F0CF
+        | 0x1F18E
+        | 0x1F191..=0x1F19A
+        | 0x1F200..=0x1F251
+        | 0x1F300..=0x1F320
+        | 0x1F32D..=0x1F335
+        | 0x1F337..=0x1F37C
+        | 0x1F37E..=0x1F393
+        | 0x1F3A0..=0x1F3CA
+        | 0x1F3CF..=0x1F3D3
+        | 0x1F3E0..=0x1F3F0
+        | 0x1F3F4
+        | 0x1F3F8..=0x1F43E
+        | 0x1F440
+        | 0x1F442..=0x1F4FC
+        | 0x1F4FF..=0x1F53D
+        | 0x1F54B..=0x1F54E
+        | 0x1F550..=0x1F567
+        | 0x1F57A
+        | 0x1F595..=0x1F596
+        | 0x1F5A4
+        | 0x1F5FB..=0x1F64F
+        | 0x1F680..=0x1F6C5
+        | 0x1F6CC
+        | 0x1F6D0..=0x1F6D2
+        | 0x1F6D5..=0x1F6D7
+        | 0x1F6EB..=0x1F6EC
+        | 0x1F6F4..=0x1F6FC
+        | 0x1F7E0..=0x1F7EB
+        | 0x1F90C..=0x1F93A
+        | 0x1F93C..=0x1F945
+        | 0x1F947..=0x1F9FF
+        | 0x1FA70..=0x1FAFF
+        | 0x20000..=0x2FFFD
+        | 0x30000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// The columns a part of a line takes, which depend on the column it starts at once it has a tab.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+struct Run {
+    /// The width of the part before its first tab, or of the whole part if it has none.
+    lead: u64,
+    /// The width of the part after its first tab, from the tab stop it reaches, if it has one. Later tab
+    /// stops are the same distance apart wherever that stop is.
+    tail: Option<u64>,
+}
+
+impl Run {
+    /// Returns the run of `text`, which has no line breaks.
+    fn of(text: &[u8]) -> Self {
+        let mut run = Run::default();
+        for c in text.utf8_chunks().flat_map(|piece| piece.valid().chars()) {
+            match (c, &mut run.tail) {
+                ('\t', None) => run.tail = Some(0),
+                ('\t', Some(tail)) => *tail = tab_stop(*tail),
+                (c, None) => run.lead += width(c),
+                (c, Some(tail)) => *tail += width(c),
+            }
+        }
+        run
+    }
+
+    /// Returns the column the part ends at when it starts at `column`.
+    fn end(self, column: u64) -> u64 {
+        match self.tail {
+            None => column + self.lead,
+            Some(tail) => tab_stop(column + self.lead) + tail,
+        }
+    }
+
+    /// Returns the run of the receiver followed by `next`.
+    fn then(self, next: Run) -> Self {
+        match (self.tail, next.tail) {
+            (None, _) => Run {
+                lead: self.lead + next.lead,
+                tail: next.tail,
+            },
+            (Some(tail), None) => Run {
+                tail: Some(tail + next.lead),
+                ..self
+            },
+            (Some(tail), Some(next_tail)) => Run {
+                tail: Some(tab_stop(tail + next.lead) + next_tail),
+                ..self
+            },
+        }
+    }
+}
+
+/// Representation of the line lengths of a chunk of text.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct LineLengthFlux {
+    /// The text before the first line break, or the whole chunk if it has none.
+    prefix: Run,
+    /// The length of the longest line lying wholly within the chunk.
+    interior: u64,
+    /// The text after the last line break, or the whole chunk if it has none.
+    suffix: Run,
+    /// Whether the chunk contains a line break.
+    broken: bool,
+}
+
+impl LineLengthFlux {
+    /// Returns the flux of `chunk`, which must not split a UTF-8 sequence for its length to be exact.
+    fn from_chunk(chunk: &[u8]) -> Self {
+        let mut lines = chunk.split(|&byte| matches!(byte, b'\n' | b'\r' | b'\x0C'));
+        let prefix = lines.next().map(Run::of).unwrap_or_default();
+        let mut flux = LineLengthFlux {
+            prefix,
+            interior: 0,
+            suffix: prefix,
+            broken: false,
+        };
+        for line in lines {
+            if flux.broken {
+                flux.interior = flux.interior.max(flux.suffix.end(0));
+            }
+            flux.broken = true;
+            flux.suffix = Run::of(line);
+        }
+        flux
+    }
+
+    /// Returns a new LineLengthFlux spanning the receiver on the left, and `rhs` on the right.
+    fn span(self, rhs: LineLengthFlux) -> Self {
+        // The line spanning the boundary is the suffix of the left continued by the prefix of the right.
+        let joined = self.suffix.then(rhs.prefix);
+        match (self.broken, rhs.broken) {
+            (false, false) => LineLengthFlux {
+                prefix: joined,
+                interior: 0,
+                suffix: joined,
+                broken: false,
+            },
+            (false, true) => LineLengthFlux {
+                prefix: joined,
+                ..rhs
+            },
+            (true, false) => LineLengthFlux {
+                suffix: joined,
+                ..self
+            },
+            (true, true) => LineLengthFlux {
+                prefix: self.prefix,
+                interior: self.interior.max(rhs.interior).max(joined.end(0)),
+                suffix: rhs.suffix,
+                broken: true,
+            },
+        }
+    }
+
+    /// Returns the length of the longest line in the chunk, treating it as the whole input.
+    pub fn max_line_length(&self) -> u64 {
+        self.prefix
+            .end(0)
+            .max(self.interior)
+            .max(self.suffix.end(0))
+    }
+}
+
+/// Takes two optional LineLengthFlux instances and returns, where possible, the span of the two.
+pub fn span_opt(
+    lhs: Option<LineLengthFlux>,
+    rhs: Option<LineLengthFlux>,
+) -> Option<LineLengthFlux> {
+    match (lhs, rhs) {
+        (Some(left_flux), Some(right_flux)) => Some(left_flux.span(right_flux)),
+        (flux, None) | (None, flux) => flux,
+    }
+}
+
+/// Computes the line length flux over the provided input byte string, which must not split a UTF-8
+/// sequence at either end.
+pub fn line_length_flux_over_byte_string<T>(input: T) -> Option<LineLengthFlux>
+where
+    T: AsRef<[u8]>,
+{
+    utf8::split_chunks(input.as_ref(), CHUNK_SIZE)
+        .par_iter()
+        .map(|chunk| Some(LineLengthFlux::from_chunk(chunk)))
+        .reduce(|| None, span_opt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the longest line length of `text` split into fluxes at each of `splits`.
+    fn max_line_length(text: &str, splits: &[usize]) -> u64 {
+        let mut flux = None;
+        let mut start = 0;
+        for &end in splits.iter().chain([text.len()].iter()) {
+            flux = span_opt(flux, line_length_flux_over_byte_string(&text[start..end]));
+            start = end;
+        }
+        flux.map(|f| f.max_line_length()).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_max_line_length() {
+        let text = "one\nthree 🎉 four\r\nfive\n";
+
+        assert_eq!(max_line_length(text, &[]), 13);
+        assert_eq!(max_line_length(text, &[6, 9]), 13);
+        assert_eq!(max_line_length(text, &[2, 4, 5, 18, 20]), 13);
+        assert_eq!(max_line_length("a\nlongest", &[4]), 7);
+        assert_eq!(max_line_length("", &[]), 0);
+    }
+
+    #[test]
+    fn test_display_width() {
+        // As GNU `wc -L` measures them: tabs to the next multiple of eight, two columns for each CJK
+        // character and none for combining marks or zero-width joiners.
+        assert_eq!(max_line_length("a\tb", &[]), 9);
+        assert_eq!(max_line_length("\t\u{65E5}\u{672C}", &[]), 12);
+        assert_eq!(max_line_length("abcdefgh\tx", &[]), 17);
+        assert_eq!(max_line_length("e\u{301}\u{200D}", &[]), 1);
+        assert_eq!(max_line_length("long line\rab", &[]), 9);
+
+        // Tabs split from the text before them still reach the same stops.
+        let text = "ab\t\u{65E5}\tcd\tx";
+        assert_eq!(max_line_length(text, &[]), 25);
+        for split in 1..text.len() {
+            if text.is_char_boundary(split) {
+                assert_eq!(max_line_length(text, &[split]), 25, "split at {}", split);
+            }
+        }
+    }
+}
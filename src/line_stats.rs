@@ -0,0 +1,202 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::ops::AddAssign;
+
+/// The least or greatest value of a statistic of the lines of an input, and where it is first found.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Extreme {
+    /// The value of the statistic.
+    pub value: u64,
+    /// The number of the first line with the value, counting from one.
+    pub line: u64,
+}
+
+impl Extreme {
+    /// Returns the line numbered `line` with `value` if it is beyond `extreme`, as `beyond` decides, or there
+    /// is none yet, otherwise `extreme`.
+    fn update(
+        extreme: Option<Extreme>,
+        value: u64,
+        line: u64,
+        beyond: fn(u64, u64) -> bool,
+    ) -> Option<Self> {
+        match extreme {
+            Some(extreme) if !beyond(value, extreme.value) => Some(extreme),
+            _ => Some(Extreme { value, line }),
+        }
+    }
+}
+
+/// The shortest and longest lines of an input, in bytes without their terminators, and those with the
+/// fewest and most words, split on ASCII whitespace, along with the totals their means are found from.
+///
+/// An unterminated final line counts as a line. The statistics of several inputs together are those of
+/// the inputs read one after the other, so line numbers continue from one input into the next.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct LineStats {
+    /// The number of lines.
+    pub lines: u64,
+    /// The total length of the lines.
+    pub length: u64,
+    /// The total number of words of the lines.
+    pub words: u64,
+    /// The shortest line, if there is any line.
+    pub shortest: Option<Extreme>,
+    /// The longest line, if there is any line.
+    pub longest: Option<Extreme>,
+    /// The line with the fewest words, if there is any line.
+    pub fewest_words: Option<Extreme>,
+    /// The line with the most words, if there is any line.
+    pub most_words: Option<Extreme>,
+}
+
+impl LineStats {
+    /// Returns the mean length of the lines, or zero if there are none.
+    pub fn mean_length(&self) -> f64 {
+        match self.lines {
+            0 => 0.0,
+            lines => self.length as f64 / lines as f64,
+        }
+    }
+
+    /// Returns the mean number of words of the lines, or zero if there are none.
+    pub fn mean_words(&self) -> f64 {
+        match self.lines {
+            0 => 0.0,
+            lines => self.words as f64 / lines as f64,
+        }
+    }
+
+    /// Adds the line numbered `line` with `length` and `words`.
+    fn add_line(&mut self, line: u64, length: u64, words: u64) {
+        self.lines = self.lines.saturating_add(1);
+        self.length = self.length.saturating_add(length);
+        self.words = self.words.saturating_add(words);
+        self.shortest = Extreme::update(self.shortest, length, line, |a, b| a < b);
+        self.longest = Extreme::update(self.longest, length, line, |a, b| a > b);
+        self.fewest_words = Extreme::update(self.fewest_words, words, line, |a, b| a < b);
+        self.most_words = Extreme::update(self.most_words, words, line, |a, b| a > b);
+    }
+}
+
+impl AddAssign for LineStats {
+    fn add_assign(&mut self, rhs: LineStats) {
+        let offset = self.lines;
+        let add =
+            |extreme: Option<Extreme>, beyond: fn(u64, u64) -> bool, into: &mut Option<Extreme>| {
+                if let Some(extreme) = extreme {
+                    *into = Extreme::update(
+                        *into,
+                        extreme.value,
+                        extreme.line.saturating_add(offset),
+                        beyond,
+                    );
+                }
+            };
+        add(rhs.shortest, |a, b| a < b, &mut self.shortest);
+        add(rhs.longest, |a, b| a > b, &mut self.longest);
+        add(rhs.fewest_words, |a, b| a < b, &mut self.fewest_words);
+        add(rhs.most_words, |a, b| a > b, &mut self.most_words);
+        self.lines = self.lines.saturating_add(rhs.lines);
+        self.length = self.length.saturating_add(rhs.length);
+        self.words = self.words.saturating_add(rhs.words);
+    }
+}
+
+/// Finds the statistics of the lines of an input fed to it in order, in pieces split anywhere.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct LineStatsCounter {
+    /// The statistics of the lines completed so far.
+    stats: LineStats,
+    /// The length of the current line so far.
+    length: u64,
+    /// The number of words of the current line so far.
+    words: u64,
+    /// Whether the current line so far ends within a word.
+    in_word: bool,
+}
+
+impl LineStatsCounter {
+    /// Adds the lines of `bytes`, which continue the input fed so far.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for piece in bytes.split_inclusive(|&byte| byte == b'\n') {
+            let (content, terminated) = match piece.strip_suffix(b"\n") {
+                Some(content) => (content, true),
+                None => (piece, false),
+            };
+            for &byte in content {
+                let is_space = byte.is_ascii_whitespace();
+                self.words += u64::from(!is_space && !self.in_word);
+                self.in_word = !is_space;
+            }
+            self.length += content.len() as u64;
+            if terminated {
+                self.end_line();
+            }
+        }
+    }
+
+    /// Adds the current line to the statistics.
+    fn end_line(&mut self) {
+        let line = self.stats.lines + 1;
+        self.stats.add_line(line, self.length, self.words);
+        self.length = 0;
+        self.words = 0;
+        self.in_word = false;
+    }
+
+    /// Returns the statistics of the lines of all of the input fed.
+    pub fn finish(mut self) -> LineStats {
+        if self.length > 0 {
+            self.end_line();
+        }
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_stats() {
+        let input = b"one two\n\nthree four  five\nsix";
+        for split in 0..input.len() {
+            let mut counter = LineStatsCounter::default();
+            counter.feed(&input[..split]);
+            counter.feed(&input[split..]);
+            let stats = counter.finish();
+
+            assert_eq!((stats.lines, stats.length, stats.words), (4, 26, 6));
+            assert_eq!(stats.shortest, Some(Extreme { value: 0, line: 2 }));
+            assert_eq!(stats.longest, Some(Extreme { value: 16, line: 3 }));
+            assert_eq!(stats.fewest_words, Some(Extreme { value: 0, line: 2 }));
+            assert_eq!(stats.most_words, Some(Extreme { value: 3, line: 3 }));
+            assert_eq!(stats.mean_words(), 1.5);
+        }
+        assert_eq!(LineStatsCounter::default().finish(), LineStats::default());
+    }
+
+    #[test]
+    fn test_add_assign() {
+        let stats = |input: &[u8]| {
+            let mut counter = LineStatsCounter::default();
+            counter.feed(input);
+            counter.finish()
+        };
+
+        let mut total = stats(b"a b\nc\n");
+        total += stats(b"d e f\n");
+        assert_eq!(total, stats(b"a b\nc\nd e f\n"));
+        assert_eq!(total.most_words, Some(Extreme { value: 3, line: 3 }));
+
+        let mut total = LineStats::default();
+        total += stats(b"x\n");
+        assert_eq!(total, stats(b"x\n"));
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+use crate::i18n::Message;
+use crate::{try_count_slice_at, Counts, WcOptions, BUFFER_SIZE};
+
+/// The format timestamps are parsed with unless another is given.
+const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The abbreviated English names of the months, in order.
+const MONTH_NAMES: [&[u8; 3]; 12] = [
+    b"JAN", b"FEB", b"MAR", b"APR", b"MAY", b"JUN", b"JUL", b"AUG", b"SEP", b"OCT", b"NOV", b"DEC",
+];
+
+/// The length of the time buckets the lines of a log are counted in.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum BucketSize {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl FromStr for BucketSize {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minute" => Ok(BucketSize::Minute),
+            "hour" => Ok(BucketSize::Hour),
+            "day" => Ok(BucketSize::Day),
+            _ => Err(Message::new("unknown-bucket-size").arg("value", s)),
+        }
+    }
+}
+
+/// A field of a timestamp, as a directive of a format names it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum Field {
+    /// `%Y`, the four-digit year.
+    Year,
+    /// `%m`, the month of the year, from 1.
+    Month,
+    /// `%b`, the abbreviated English name of the month, in any case.
+    MonthName,
+    /// `%d`, the day of the month, which may be padded with a space rather than a zero.
+    Day,
+    /// `%H`, the hour of the day, from 0 to 23.
+    Hour,
+    /// `%M`, the minute of the hour.
+    Minute,
+    /// `%S`, the second of the minute, up to 60 for a leap second.
+    Second,
+}
+
+/// An item of a timestamp format, a field or a byte which must appear as it is.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum Item {
+    Field(Field),
+    Literal(u8),
+}
+
+/// The format of the timestamps leading the lines of a log, written with the `strftime` directives `%Y`,
+/// `%m`, `%b`, `%d`, `%H`, `%M` and `%S`, `%%` for a percent sign, and literal text, such as
+/// `[%d/%b/%Y:%H:%M:%S` for the access logs of a web server.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct TimestampFormat {
+    items: Vec<Item>,
+}
+
+impl FromStr for TimestampFormat {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut items = Vec::new();
+        let mut bytes = s.bytes();
+        while let Some(byte) = bytes.next() {
+            if byte != b'%' {
+                items.push(Item::Literal(byte));
+                continue;
+            }
+            items.push(match bytes.next() {
+                Some(b'Y') => Item::Field(Field::Year),
+                Some(b'm') => Item::Field(Field::Month),
+                Some(b'b') => Item::Field(Field::MonthName),
+                Some(b'd') => Item::Field(Field::Day),
+                Some(b'H') => Item::Field(Field::Hour),
+                Some(b'M') => Item::Field(Field::Minute),
+                Some(b'S') => Item::Field(Field::Second),
+                Some(b'%') => Item::Literal(b'%'),
+                Some(other) => {
+                    return Err(
+                        Message::new("unsupported-directive").arg("directive", other as char)
+                    );
+                }
+                None => return Err(Message::new("incomplete-directive")),
+            });
+        }
+        Ok(TimestampFormat { items })
+    }
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        DEFAULT_FORMAT.parse().expect("The default format is valid")
+    }
+}
+
+/// Parses the number of at most two digits at the start of `input`, which may be padded with a space,
+/// returning it and the rest of the input.
+fn parse_two_digits(input: &[u8]) -> Option<(u32, &[u8])> {
+    let input = match input {
+        [b' ', digit, ..] if digit.is_ascii_digit() => &input[1..],
+        _ => input,
+    };
+    let length = input
+        .iter()
+        .take(2)
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    if length == 0 {
+        return None;
+    }
+    let value = input[..length]
+        .iter()
+        .fold(0, |value, &digit| value * 10 + u32::from(digit - b'0'));
+    Some((value, &input[length..]))
+}
+
+/// The time of a line of a log, to the second. Fields missing from its format are zero, and the year is
+/// `None` if the format has none, as in syslog.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, Default)]
+pub struct Timestamp {
+    pub year: Option<u32>,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl TimestampFormat {
+    /// Returns the timestamp at the start of `line`, or `None` if it does not begin with one in this format.
+    pub fn parse_prefix(&self, line: &[u8]) -> Option<Timestamp> {
+        let mut timestamp = Timestamp::default();
+        let mut rest = line;
+        for item in &self.items {
+            let field = match *item {
+                Item::Literal(byte) => {
+                    rest = rest.strip_prefix(&[byte])?;
+                    continue;
+                }
+                Item::Field(field) => field,
+            };
+            match field {
+                Field::Year => {
+                    let digits = rest
+                        .get(..4)
+                        .filter(|digits| digits.iter().all(u8::is_ascii_digit))?;
+                    let year = digits
+                        .iter()
+                        .fold(0, |year, &digit| year * 10 + u32::from(digit - b'0'));
+                    timestamp.year = Some(year);
+                    rest = &rest[4..];
+                }
+                Field::MonthName => {
+                    let name = rest.get(..3)?.to_ascii_uppercase();
+                    let index = MONTH_NAMES.iter().position(|month| month[..] == name[..])?;
+                    timestamp.month = index as u32 + 1;
+                    rest = &rest[3..];
+                }
+                _ => {
+                    let (value, remainder) = parse_two_digits(rest)?;
+                    let (slot, range) = match field {
+                        Field::Month => (&mut timestamp.month, 1..=12),
+                        Field::Day => (&mut timestamp.day, 1..=31),
+                        Field::Hour => (&mut timestamp.hour, 0..=23),
+                        Field::Minute => (&mut timestamp.minute, 0..=59),
+                        _ => (&mut timestamp.second, 0..=60),
+                    };
+                    if !range.contains(&value) {
+                        return None;
+                    }
+                    *slot = value;
+                    rest = remainder;
+                }
+            }
+        }
+        Some(timestamp)
+    }
+}
+
+impl Timestamp {
+    /// Returns the start of the bucket of `size` the receiver falls in.
+    pub fn truncate(self, size: BucketSize) -> Timestamp {
+        Timestamp {
+            hour: if size == BucketSize::Day {
+                0
+            } else {
+                self.hour
+            },
+            minute: if size == BucketSize::Minute {
+                self.minute
+            } else {
+                0
+            },
+            second: 0,
+            ..self
+        }
+    }
+
+    /// Returns the name of the bucket of `size` starting at the receiver, such as `2019-06-01 12:00` for an
+    /// hour or `2019-06-01` for a day.
+    pub fn label(self, size: BucketSize) -> String {
+        let mut label = match self.year {
+            Some(year) => format!("{:04}-{:02}-{:02}", year, self.month, self.day),
+            None => format!("{:02}-{:02}", self.month, self.day),
+        };
+        if size != BucketSize::Day {
+            label.push_str(&format!(" {:02}:{:02}", self.hour, self.minute));
+        }
+        label
+    }
+}
+
+/// Counts the lines of the log `input` by the bucket of `size` of their leading timestamps, parsed with
+/// `format`, along with the optional counts `options` enable. Lines without a timestamp, such as those of a
+/// stack trace, are counted in the bucket of the line before them, and any before the first timestamp in
+/// the bucket `None`.
+pub fn count_buckets<R>(
+    input: &mut R,
+    size: BucketSize,
+    format: &TimestampFormat,
+    options: &WcOptions,
+) -> io::Result<BTreeMap<Option<Timestamp>, Counts>>
+where
+    R: BufRead,
+{
+    let mut buckets = BTreeMap::new();
+    let mut bucket = None;
+    // Consecutive lines of a bucket are counted together, from the offset of the first of them.
+    let mut pending = Vec::new();
+    let mut start = 0;
+    let mut flush = |bucket, pending: &mut Vec<u8>| -> io::Result<()> {
+        if !pending.is_empty() {
+            *buckets.entry(bucket).or_default() +=
+                try_count_slice_at(&pending[..], start, options)?;
+            start += pending.len() as u64;
+            pending.clear();
+        }
+        Ok(())
+    };
+
+    let mut line = Vec::new();
+    while input.read_until(b'\n', &mut line)? > 0 {
+        if let Some(timestamp) = format.parse_prefix(&line) {
+            let next = Some(timestamp.truncate(size));
+            if next != bucket {
+                flush(bucket, &mut pending)?;
+                bucket = next;
+            }
+        }
+        pending.extend_from_slice(&line);
+        line.clear();
+        if pending.len() >= BUFFER_SIZE {
+            flush(bucket, &mut pending)?;
+        }
+    }
+    flush(bucket, &mut pending)?;
+    Ok(buckets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InvalidUtf8;
+
+    #[test]
+    fn test_parse_prefix() {
+        let format = TimestampFormat::default();
+        assert_eq!(
+            format.parse_prefix(b"2019-06-01 12:34:56 INFO started"),
+            Some(Timestamp {
+                year: Some(2019),
+                month: 6,
+                day: 1,
+                hour: 12,
+                minute: 34,
+                second: 56,
+            })
+        );
+        assert_eq!(format.parse_prefix(b"2019-13-01 12:34:56"), None);
+        assert_eq!(format.parse_prefix(b"    at Main.run"), None);
+
+        let syslog: TimestampFormat = "%b %d %H:%M:%S".parse().unwrap();
+        let timestamp = syslog.parse_prefix(b"Jun  1 09:05:00 host sshd").unwrap();
+        assert_eq!(
+            timestamp.truncate(BucketSize::Hour).label(BucketSize::Hour),
+            "06-01 09:00"
+        );
+
+        let access: TimestampFormat = "[%d/%b/%Y:%H:%M:%S".parse().unwrap();
+        let timestamp = access
+            .parse_prefix(b"[10/Oct/2000:13:55:36 -0700] \"GET /\"")
+            .unwrap();
+        assert_eq!(
+            timestamp.truncate(BucketSize::Day).label(BucketSize::Day),
+            "2000-10-10"
+        );
+
+        assert!("%Y %q".parse::<TimestampFormat>().is_err());
+        assert!("%Y %".parse::<TimestampFormat>().is_err());
+    }
+
+    #[test]
+    fn test_count_buckets() {
+        let log = "preamble\n\
+                   2019-06-01 12:59:59 ERROR failed\n\
+                   \x20   at Main.run\n\
+                   2019-06-01 13:00:00 INFO retrying now\n\
+                   2019-06-01 12:30:00 INFO late\n";
+        let buckets = count_buckets(
+            &mut log.as_bytes(),
+            BucketSize::Hour,
+            &TimestampFormat::default(),
+            &WcOptions::default(),
+        )
+        .unwrap();
+
+        let rows: Vec<(String, u64, u64)> = buckets
+            .iter()
+            .map(|(bucket, counts)| {
+                let label = bucket.map_or(String::new(), |bucket| bucket.label(BucketSize::Hour));
+                (label, counts.lines, counts.words)
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (String::new(), 1, 1),
+                ("2019-06-01 12:00".to_string(), 3, 10),
+                ("2019-06-01 13:00".to_string(), 1, 5),
+            ]
+        );
+
+        // Invalid UTF-8 is reported at its offset in the log rather than in its bucket.
+        let options = WcOptions {
+            chars: true,
+            invalid_utf8: Some(InvalidUtf8::Error),
+            ..WcOptions::default()
+        };
+        let log = b"2019-06-01 12:59:59 ok\n2019-06-01 13:00:00 caf\xE9\n";
+        let error = count_buckets(
+            &mut &log[..],
+            BucketSize::Hour,
+            &TimestampFormat::default(),
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Invalid UTF-8 at byte 46");
+    }
+}
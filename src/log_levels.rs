@@ -0,0 +1,154 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::ops::AddAssign;
+
+/// The length of the longest level token, `CRITICAL`.
+const MAX_TOKEN_LENGTH: usize = 8;
+
+/// The lines of a log, by the level they are logged at.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct LogLevelCounts {
+    /// The number of lines logged at `ERROR`, or at `ERR`, `FATAL`, `CRIT` or `CRITICAL`.
+    pub error: u64,
+    /// The number of lines logged at `WARN` or `WARNING`.
+    pub warn: u64,
+    /// The number of lines logged at `INFO` or `NOTICE`.
+    pub info: u64,
+    /// The number of lines logged at `DEBUG` or `TRACE`.
+    pub debug: u64,
+}
+
+impl AddAssign for LogLevelCounts {
+    fn add_assign(&mut self, rhs: LogLevelCounts) {
+        self.error = self.error.saturating_add(rhs.error);
+        self.warn = self.warn.saturating_add(rhs.warn);
+        self.info = self.info.saturating_add(rhs.info);
+        self.debug = self.debug.saturating_add(rhs.debug);
+    }
+}
+
+/// Counts the lines of a log fed to it in order, in pieces split anywhere, by level.
+///
+/// The level of a line is that of the first level token on it, in any case, such as the `ERROR` of
+/// `12:00:01 ERROR disk full`, the `warn` of `level=warn msg=retrying` or the `Info` of `[Info] started`. A
+/// token is a run of ASCII letters, digits and underscores, so that `ERRORS` and `on_error` are not levels.
+/// Lines without a level token are not counted.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct LogLevelCounter {
+    /// The lines counted so far.
+    counts: LogLevelCounts,
+    /// The first bytes of the current token in upper case.
+    token: [u8; MAX_TOKEN_LENGTH],
+    /// The length of the current token, which may exceed that of the bytes held.
+    length: usize,
+    /// Whether the level of the current line has been found.
+    found: bool,
+}
+
+impl LogLevelCounter {
+    /// Counts the lines of `bytes`, which continue the log fed so far.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            // The rest of a line whose level is known need not be scanned.
+            if self.found {
+                match rest.iter().position(|&byte| byte == b'\n') {
+                    Some(end) => {
+                        self.found = false;
+                        rest = &rest[end + 1..];
+                        continue;
+                    }
+                    None => return,
+                }
+            }
+
+            let byte = rest[0];
+            rest = &rest[1..];
+            if byte.is_ascii_alphanumeric() || byte == b'_' {
+                if self.length < MAX_TOKEN_LENGTH {
+                    self.token[self.length] = byte.to_ascii_uppercase();
+                }
+                self.length += 1;
+            } else {
+                self.end_token();
+                if byte == b'\n' {
+                    self.found = false;
+                }
+            }
+        }
+    }
+
+    /// Counts the current line if the current token, if any, is the first level token on it.
+    fn end_token(&mut self) {
+        let length = std::mem::take(&mut self.length);
+        if self.found || length > MAX_TOKEN_LENGTH {
+            return;
+        }
+
+        let count = match &self.token[..length] {
+            b"ERROR" | b"ERR" | b"FATAL" | b"CRIT" | b"CRITICAL" => &mut self.counts.error,
+            b"WARN" | b"WARNING" => &mut self.counts.warn,
+            b"INFO" | b"NOTICE" => &mut self.counts.info,
+            b"DEBUG" | b"TRACE" => &mut self.counts.debug,
+            _ => return,
+        };
+        *count += 1;
+        self.found = true;
+    }
+
+    /// Returns the lines of all of the log fed, by level.
+    pub fn finish(mut self) -> LogLevelCounts {
+        self.end_token();
+        self.counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_levels() {
+        let log = b"2019-06-01 12:00:00 INFO started\n\
+                   2019-06-01 12:00:01 ERROR disk full, warn later\n\
+                   level=warn msg=\"retrying\"\n\
+                   {\"level\":\"debug\",\"msg\":\"tick\"}\n\
+                   [Critical] on_error ERRORS\n\
+                   continued without a level\n\
+                   TRACE";
+
+        // Every split of the log counts the same.
+        for split in 0..log.len() {
+            let mut counter = LogLevelCounter::default();
+            counter.feed(&log[..split]);
+            counter.feed(&log[split..]);
+            assert_eq!(
+                counter.finish(),
+                LogLevelCounts {
+                    error: 2,
+                    warn: 1,
+                    info: 1,
+                    debug: 2,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_tokens_are_not_levels() {
+        let mut counter = LogLevelCounter::default();
+        counter.feed(b"INFORMATIONAL WARNINGS errored\nCRITICALLY info\n");
+        assert_eq!(
+            counter.finish(),
+            LogLevelCounts {
+                info: 1,
+                ..LogLevelCounts::default()
+            }
+        );
+    }
+}
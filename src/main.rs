@@ -5,198 +5,2425 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use std::collections::HashSet;
 use std::env;
+use std::fmt::Display;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use rayon::prelude::*;
 
-/// Size of the I/O buffer when reading from input.
-const BUFFER_SIZE: usize = (512 * 1024);
-
-/// The result of the `wc` operation.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
-struct Counts {
-    pub bytes: usize,
-    pub words: usize,
-    pub lines: usize,
-}
-
-/// The class of a character.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
-enum CharType {
-    /// The character represents a whitespace separator.
-    IsSpace,
-    /// The character does not represent a whitespace separator.
-    NotSpace,
-}
-
-/// Representation of a chunk of text.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
-struct Flux {
-    /// The type of the left-most character in the chunk.
-    pub leftmost_char_type: CharType,
-    /// The number of words in the chunk.
-    pub words: usize,
-    /// The number of lines in the chunk.
-    pub lines: usize,
-    /// The type of the right-most character in the chunk.
-    pub rightmost_char_type: CharType,
-}
-
-impl Flux {
-    /// Returns a new instance of the receiver with the provided parameters.
-    fn new(
-        leftmost_char_type: CharType,
-        words: usize,
-        lines: usize,
-        rightmost_char_type: CharType,
-    ) -> Self {
-        Flux {
-            leftmost_char_type,
-            words,
-            lines,
-            rightmost_char_type,
+use wc_rs::archive;
+use wc_rs::decompress::decompress;
+use wc_rs::document::Document;
+use wc_rs::email::Mailbox;
+use wc_rs::epub;
+use wc_rs::grep::GrepFilter;
+use wc_rs::i18n::{self, Message};
+use wc_rs::limit::{Limit, StopAfter};
+use wc_rs::log_buckets::{self, BucketSize, TimestampFormat};
+use wc_rs::markup::{Markup, MarkupReader};
+use wc_rs::notebook::Notebook;
+#[cfg(feature = "pdf")]
+use wc_rs::pdf::extract_text as extract_pdf_text;
+use wc_rs::{
+    count_reader, try_count_slice, try_count_slice_at, Counts, WcError, WcOptions, BUFFER_SIZE,
+};
+
+mod assertion;
+mod columns;
+mod corpus;
+mod disk;
+mod duplicates;
+mod expect;
+mod extremes;
+mod files0;
+mod git;
+mod glob;
+mod ignore;
+mod manifest;
+mod mmap;
+mod output;
+mod priority;
+mod rusage;
+mod sandbox;
+mod stream;
+mod tail;
+mod template;
+mod throttle;
+mod walk;
+
+use assertion::Assertion;
+use columns::Column;
+use corpus::{CorpusReport, Profile};
+use duplicates::{ContentHash, Digest, Hashing};
+use expect::Expectation;
+use extremes::{Extremes, Record, Scan};
+use manifest::{Format, Manifest};
+use mmap::Mapping;
+use output::Output;
+use stream::Window;
+use tail::Tail;
+use template::Template;
+use throttle::Throttle;
+use walk::Walk;
+
+/// Time between the snapshots of the counts within the window of a stream, unless an interval is given.
+const SNAPSHOT_PERIOD: Duration = Duration::from_secs(1);
+
+/// Exit status when the command line cannot be parsed.
+const USAGE_STATUS: i32 = 2;
+
+/// The subcommands, named by the first operand, which take options of their own.
+const SUBCOMMANDS: [&str; 4] = ["git-diff", "history", "hook", "run"];
+
+/// Exit status when `--fail-if-empty` is given and no bytes are counted.
+const EMPTY_INPUT_STATUS: i32 = 3;
+
+/// Exit status when the total counts differ from those expected by `--expect`.
+const UNEXPECTED_COUNTS_STATUS: i32 = 4;
+
+/// Options controlling a run of the command.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+struct Options {
+    /// The options controlling which counts are taken and how words are split.
+    pub counting: WcOptions,
+    /// Whether the space allocated on disk to each file is counted.
+    pub allocated: bool,
+    /// The markup language whose prose is counted, if any.
+    pub markup: Option<Markup>,
+    /// Whether the input is a Jupyter notebook whose cells are counted separately.
+    pub notebook: bool,
+    /// Whether the input is an mbox mailbox or email message whose messages and body text are counted.
+    pub email: bool,
+    /// Whether the input is an EPUB e-book whose chapters are counted separately.
+    pub epub: bool,
+    /// Whether the input is a tar, gzip-compressed tar or ZIP archive whose files are counted separately.
+    pub archive: bool,
+    /// Whether the input is a PDF document whose text is counted.
+    pub pdf: bool,
+    /// The word processor format of the input, whose text is counted, if any.
+    pub document: Option<Document>,
+    /// The columns of each output row, or `None` for the columns of the original `wc` utility.
+    pub columns: Option<Vec<Column>>,
+    /// The line, word, character and byte counts and longest line length selected by `-l`, `-w`, `-m`,
+    /// `-c` and `-L`, in that order, or `None` for the line, word and byte counts.
+    pub counters: Option<Vec<Column>>,
+    /// The template the output rows are rendered with, if any.
+    pub template: Option<Template>,
+    /// The decimal places of means, ratios and shares, if not their defaults.
+    pub precision: Option<usize>,
+    /// Whether the value of the single column is output alone, without padding.
+    pub bare: bool,
+    /// Where total rows are written instead of standard output: `stderr`, or the path of a file.
+    pub summary_to: Option<String>,
+    /// Whether to exit with `EMPTY_INPUT_STATUS` if no bytes are counted.
+    pub fail_if_empty: bool,
+    /// The counts the total of all inputs must equal, or the run exits with `UNEXPECTED_COUNTS_STATUS`.
+    pub expect: Vec<Expectation>,
+    /// Whether the resources used by the run are reported to standard error once it finishes.
+    pub rusage: bool,
+    /// The greatest rate files are read at, in bytes per second, or `None` if reading is unthrottled.
+    pub bwlimit: Option<u64>,
+    /// The size of the buffer inputs are read through, or `None` for `BUFFER_SIZE`.
+    pub buffer_size: Option<usize>,
+    /// The number of threads inputs are counted with, or `None` for one per core.
+    pub threads: Option<usize>,
+    /// Whether filesystem and network access are given up once the input is open.
+    pub sandbox: bool,
+    /// Whether all file operands are counted as one continuous input, on a single row, rather than each on
+    /// a row of its own.
+    pub concat: bool,
+    /// Whether compressed inputs are decompressed before they are counted.
+    pub decompress: bool,
+    /// Whether regular files counted whole as plain text are mapped into memory rather than read.
+    pub mmap: bool,
+    /// Whether wildcards in file operands are expanded, for shells such as `cmd.exe` which do not.
+    pub glob: bool,
+    /// Whether the files beneath directory operands are counted.
+    pub recursive: bool,
+    /// The files beneath directories walked to, when counting recursively or reporting on a corpus.
+    pub walk: Walk,
+    /// Whether only the files beneath directories which are tracked by git are counted.
+    pub git_tracked: bool,
+    /// Whether the files which would be counted are listed instead of counted.
+    pub dry_run: bool,
+    /// Whether the files and directories passed over beneath directories are reported, and why.
+    pub verbose: bool,
+    /// Whether the groups of inputs with identical contents are listed once all are counted.
+    pub find_duplicates: bool,
+    /// The final portion of each file which is counted, or `None` if files are counted whole.
+    pub tail: Option<Tail>,
+    /// The filters restricting counting to matching or non-matching lines, applied in turn.
+    pub grep: Vec<GrepFilter>,
+    /// The path of the file the notable positions of each input are written to, if any.
+    pub report_extremes: Option<String>,
+    /// Whether the encodings, byte order marks, line endings and kinds of the files beneath the operands
+    /// are summarized instead of counted.
+    pub corpus_report: bool,
+    /// The format the rows are output in.
+    pub output: Output,
+    /// The length of the sliding window the counts of a stream are snapshotted over, if any.
+    pub window: Option<Duration>,
+    /// The time between the reports of a stream's counts so far, or of the snapshots of its window, if any.
+    pub interval: Option<Duration>,
+    /// The leading portion of each input which is counted, or `None` if inputs are counted to their end.
+    pub stop_after: Option<StopAfter>,
+    /// The size of the time buckets the lines of a log are counted in separately, if any.
+    pub log_buckets: Option<BucketSize>,
+    /// The format of the timestamps leading the lines of a log counted in time buckets.
+    pub timestamp_format: TimestampFormat,
+    /// Whether JSON-RPC requests are served on standard input and output instead of counting files.
+    pub jsonrpc: bool,
+    /// The file of NUL-terminated names of the files counted instead of the operands, `-` for standard
+    /// input, if any.
+    pub files0_from: Option<String>,
+    /// The name standard input is shown with instead of its path, if any.
+    pub stdin_name: Option<String>,
+}
+
+/// The scheduling priorities the run lowers itself to.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+struct Priority {
+    /// Whether the CPU scheduling priority is lowered to the lowest.
+    nice: bool,
+    /// Whether I/O is placed in the idle scheduling class.
+    io_idle: bool,
+}
+
+impl Options {
+    /// Returns the name the input at `path` is shown with, which is `--stdin-name` for standard input.
+    fn input_name<'a>(&'a self, path: &'a str) -> &'a str {
+        match &self.stdin_name {
+            Some(name) if path.is_empty() || path == "-" => name,
+            _ => path,
+        }
+    }
+
+    /// Selects the columns of each output row, enabling the counts they show.
+    fn set_columns(&mut self, columns: Vec<Column>) {
+        self.enable_counts(&columns);
+        self.columns = Some(columns);
+    }
+
+    /// Selects the counters named by the short flags `flags`, such as `lw`, keeping the order of the
+    /// original `wc` utility and enabling the counts they show.
+    fn select_counters(&mut self, flags: &str) {
+        let selected = self.counters.take().unwrap_or_default();
+        let counters: Vec<Column> = [
+            ('l', Column::Lines),
+            ('w', Column::Words),
+            ('m', Column::Chars),
+            ('c', Column::Bytes),
+            ('L', Column::MaxLineLength),
+        ]
+        .iter()
+        .filter(|(flag, counter)| flags.contains(*flag) || selected.contains(counter))
+        .map(|&(_, counter)| counter)
+        .collect();
+        self.enable_counts(&counters);
+        self.counters = Some(counters);
+    }
+
+    /// Sets the template rows are rendered with, enabling the counts its columns need.
+    fn set_template(&mut self, template: Template) {
+        self.enable_counts(&template.columns());
+        self.template = Some(template);
+    }
+
+    /// Returns `true` if files are counted whole and as plain text, not in part, filtered, decompressed or
+    /// extracted from a document, so that a file can be counted from its contents at once or its size.
+    fn counts_whole_text(&self) -> bool {
+        self.markup.is_none()
+            && self.grep.is_empty()
+            && !self.notebook
+            && !self.email
+            && !self.epub
+            && !self.archive
+            && !self.pdf
+            && self.document.is_none()
+            && self.tail.is_none()
+            && self.stop_after.is_none()
+            && self.report_extremes.is_none()
+            && self.log_buckets.is_none()
+            && !self.decompress
+    }
+
+    /// Returns the size of the buffer inputs are read through.
+    fn buffer_size(&self) -> usize {
+        self.buffer_size.unwrap_or(BUFFER_SIZE)
+    }
+
+    /// Adds an expectation of the total counts, enabling the count it checks.
+    fn add_expectation(&mut self, expectation: Expectation) {
+        self.enable_counts(&[expectation.column]);
+        self.expect.push(expectation);
+    }
+
+    /// Enables the counts that `columns` need.
+    fn enable_counts(&mut self, columns: &[Column]) {
+        for column in columns {
+            match column {
+                Column::Emoji => self.counting.emoji = true,
+                Column::Graphemes => self.counting.graphemes = true,
+                Column::Urls | Column::Emails | Column::Numbers => {
+                    self.counting.token_classes = true
+                }
+                Column::Hashtags | Column::Mentions => self.counting.social = true,
+                Column::Messages => self.email = true,
+                Column::Syllables => self.counting.syllables = true,
+                Column::Chars => self.counting.chars = true,
+                Column::MaxLineLength => self.counting.max_line_length = true,
+                Column::Spaces
+                | Column::Tabs
+                | Column::Newlines
+                | Column::Nbsp
+                | Column::OtherSpaces => self.counting.whitespace = true,
+                Column::ErrorLines | Column::WarnLines | Column::InfoLines | Column::DebugLines => {
+                    self.counting.log_levels = true
+                }
+                Column::ShortestLine
+                | Column::ShortestLineAt
+                | Column::LongestLine
+                | Column::LongestLineAt
+                | Column::MeanLineLength
+                | Column::FewestWords
+                | Column::FewestWordsAt
+                | Column::MostWords
+                | Column::MostWordsAt
+                | Column::MeanWordsPerLine => self.counting.line_stats = true,
+                Column::Allocated => self.allocated = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Counts each of `inputs` in parallel, returning their counts, or the error of any with invalid UTF-8 if it
+/// is one, in the order of `inputs` however the counting of each completes.
+fn wc_all(inputs: &[Vec<u8>], options: &Options) -> Vec<Result<Counts, WcError>> {
+    inputs
+        .par_iter()
+        .map(|input| try_count_slice(input, &options.counting))
+        .collect()
+}
+
+/// Parses `value`, a number of threads, which must be at least one.
+fn parse_threads(value: &str) -> Result<usize, Message> {
+    match value.parse() {
+        Ok(0) => Err(Message::new("no-threads")),
+        Ok(threads) => Ok(threads),
+        Err(_) => Err(Message::new("not-a-thread-count").arg("value", value)),
+    }
+}
+
+/// Parses `value`, a buffer size in bytes with an optional binary `K`, `M` or `G` suffix.
+fn parse_buffer_size(value: &str) -> Result<usize, Message> {
+    throttle::parse_size(value)
+        .filter(|&size| size <= usize::MAX as u64)
+        .map(|size| size as usize)
+        .ok_or_else(|| Message::new("not-a-buffer-size").arg("value", value))
+}
+
+/// Fails to extract the text of a PDF document, as PDF support was not built.
+#[cfg(not(feature = "pdf"))]
+fn extract_pdf_text(_document: &[u8]) -> Result<String, Message> {
+    Err(Message::new("pdf-unsupported"))
+}
+
+/// Exits with `USAGE_STATUS` after printing `message`, describing how the command line is invalid.
+fn usage_error(message: &str) -> ! {
+    eprintln!("wc_rs: {}", message);
+    eprintln!("{}", i18n::message("usage-hint", &[]));
+    std::process::exit(USAGE_STATUS);
+}
+
+/// The value of an option or operand, which is a usage error if it is missing or invalid.
+trait OrUsage<T> {
+    /// Returns the value, or exits with a usage error of the message `identifier` and the reason it is
+    /// invalid, if any.
+    fn or_usage(self, identifier: &str) -> T;
+}
+
+impl<T> OrUsage<T> for Option<T> {
+    fn or_usage(self, identifier: &str) -> T {
+        self.unwrap_or_else(|| usage_error(&i18n::message(identifier, &[])))
+    }
+}
+
+impl<T, E: Display> OrUsage<T> for Result<T, E> {
+    fn or_usage(self, identifier: &str) -> T {
+        self.unwrap_or_else(|error| {
+            usage_error(&format!("{}: {}", i18n::message(identifier, &[]), error))
+        })
+    }
+}
+
+/// Starts the threads inputs are counted with, as many as `--threads` or `WC_RS_THREADS` give if either does,
+/// and otherwise leaves them to be started once needed, one per core.
+fn start_threads(options: &Options) {
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("Unable to start the counting threads");
+    }
+}
+
+/// Returns the time the file at `path` was last modified, exiting if it cannot be read.
+fn modified_time(path: &str) -> SystemTime {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or_else(|error| fatal_error(path, error))
+}
+
+/// Returns the identifier of the message describing why `--bare` cannot be given with `options` counting
+/// `inputs` inputs, if it is given and cannot be: the value of a single count is output alone, so only one
+/// count of one input, without the rows of its parts, can be.
+fn bare_error(options: &Options, inputs: usize) -> Option<&'static str> {
+    if !options.bare {
+        return None;
+    }
+    match (options.columns.as_deref(), options.counters.as_deref()) {
+        (Some([column]), _) if *column != Column::File => {}
+        (None, Some([_])) => {}
+        _ => return Some("bare-requires-a-single-count"),
+    }
+    if options.notebook || options.epub || options.archive || options.log_buckets.is_some() {
+        Some("bare-with-parts")
+    } else if inputs > 1 && !options.concat {
+        Some("bare-requires-a-single-input")
+    } else {
+        None
+    }
+}
+
+/// Exits with a failure status after printing `error`, which keeps anything more from being counted, with
+/// the `name` of the file or revisions it concerns.
+fn fatal_error(name: &str, error: impl Display) -> ! {
+    eprintln!("wc_rs: {}: {}", name, error);
+    std::process::exit(1);
+}
+
+fn main() {
+    let mut options = Options {
+        glob: cfg!(windows),
+        ..Options::default()
+    };
+    let mut priority = Priority::default();
+    let mut operands = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--word-rules" {
+            let value = args.next().or_usage("no-word-rules-specified");
+            options.counting.word_rules = Some(value.parse().or_usage("invalid-word-rules"));
+        } else if let Some(value) = arg.strip_prefix("--word-rules=") {
+            options.counting.word_rules = Some(value.parse().or_usage("invalid-word-rules"));
+        } else if arg == "--lines" {
+            let value = args.next().or_usage("no-line-definition-specified");
+            options.counting.lines = value.parse().or_usage("invalid-line-definition");
+        } else if let Some(value) = arg.strip_prefix("--lines=") {
+            options.counting.lines = value.parse().or_usage("invalid-line-definition");
+        } else if arg == "--unicode-words" {
+            options.counting.unicode_words = true;
+        } else if arg == "--invalid-utf8" {
+            let value = args.next().or_usage("no-invalid-utf-8-policy-specified");
+            options.counting.invalid_utf8 =
+                Some(value.parse().or_usage("invalid-policy-for-invalid-utf-8"));
+        } else if let Some(value) = arg.strip_prefix("--invalid-utf8=") {
+            options.counting.invalid_utf8 =
+                Some(value.parse().or_usage("invalid-policy-for-invalid-utf-8"));
+        } else if arg == "--segmenter" {
+            let value = args.next().or_usage("no-segmenter-specified");
+            options.counting.segmenter = Some(value.parse().or_usage("invalid-segmenter"));
+        } else if let Some(value) = arg.strip_prefix("--segmenter=") {
+            options.counting.segmenter = Some(value.parse().or_usage("invalid-segmenter"));
+        } else if arg == "--emoji" {
+            options.counting.emoji = true;
+        } else if arg == "--graphemes" {
+            options.counting.graphemes = true;
+        } else if arg == "--token-classes" {
+            options.counting.token_classes = true;
+        } else if arg == "--social" {
+            options.counting.social = true;
+        } else if arg == "--syllables" {
+            options.counting.syllables = true;
+        } else if arg == "--whitespace-stats" {
+            options.counting.whitespace = true;
+        } else if arg == "--log-levels" {
+            options.counting.log_levels = true;
+        } else if arg == "--allocated" {
+            options.allocated = true;
+        } else if arg == "--line-stats" {
+            options.counting.line_stats = true;
+        } else if arg == "--subtitles" {
+            options.markup = Some(Markup::Subtitles);
+        } else if arg == "--latex" {
+            options.markup = Some(Markup::Latex);
+        } else if arg == "--asciidoc" {
+            options.markup = Some(Markup::AsciiDoc);
+        } else if arg == "--rst" {
+            options.markup = Some(Markup::Rst);
+        } else if arg == "--ipynb" {
+            options.notebook = true;
+        } else if arg == "--columns" {
+            let value = args.next().or_usage("no-columns-specified");
+            options.set_columns(columns::parse_list(&value).or_usage("invalid-columns"));
+        } else if let Some(value) = arg.strip_prefix("--columns=") {
+            options.set_columns(columns::parse_list(value).or_usage("invalid-columns"));
+        } else if arg == "--template" {
+            let path = args.next().or_usage("no-template-specified");
+            options.set_template(read_template(&path));
+        } else if let Some(path) = arg.strip_prefix("--template=") {
+            options.set_template(read_template(path));
+        } else if arg == "--precision" {
+            let value = args.next().or_usage("no-precision-specified");
+            options.precision = Some(value.parse().or_usage("invalid-precision"));
+        } else if let Some(value) = arg.strip_prefix("--precision=") {
+            options.precision = Some(value.parse().or_usage("invalid-precision"));
+        } else if arg == "--summary-to" {
+            options.summary_to = Some(args.next().or_usage("no-summary-output-specified"));
+        } else if let Some(value) = arg.strip_prefix("--summary-to=") {
+            options.summary_to = Some(value.to_string());
+        } else if arg == "--fail-if-empty" {
+            options.fail_if_empty = true;
+        } else if arg == "--expect" {
+            let value = args.next().or_usage("no-expectation-specified");
+            options.add_expectation(value.parse().or_usage("invalid-expectation"));
+        } else if let Some(value) = arg.strip_prefix("--expect=") {
+            options.add_expectation(value.parse().or_usage("invalid-expectation"));
+        } else if arg == "--bwlimit" {
+            let value = args.next().or_usage("no-rate-specified");
+            options.bwlimit = Some(throttle::parse_rate(&value).or_usage("invalid-rate"));
+        } else if let Some(value) = arg.strip_prefix("--bwlimit=") {
+            options.bwlimit = Some(throttle::parse_rate(value).or_usage("invalid-rate"));
+        } else if arg == "--buffer-size" {
+            let value = args.next().or_usage("no-buffer-size-specified");
+            options.buffer_size = Some(parse_buffer_size(&value).or_usage("invalid-buffer-size"));
+        } else if let Some(value) = arg.strip_prefix("--buffer-size=") {
+            options.buffer_size = Some(parse_buffer_size(value).or_usage("invalid-buffer-size"));
+        } else if arg == "--engine" {
+            let value = args.next().or_usage("no-engine-specified");
+            options.counting.engine = value.parse().or_usage("invalid-engine");
+        } else if let Some(value) = arg.strip_prefix("--engine=") {
+            options.counting.engine = value.parse().or_usage("invalid-engine");
+        } else if arg == "--nice" {
+            priority.nice = true;
+        } else if arg == "--ionice-idle" {
+            priority.io_idle = true;
+        } else if arg == "--threads" {
+            let value = args.next().or_usage("no-thread-count-specified");
+            options.threads = Some(parse_threads(&value).or_usage("invalid-thread-count"));
+        } else if let Some(value) = arg.strip_prefix("--threads=") {
+            options.threads = Some(parse_threads(value).or_usage("invalid-thread-count"));
+        } else if arg == "--last-lines" {
+            let value = args.next().or_usage("no-line-count-specified");
+            options.tail = Some(Tail::Lines(value.parse().or_usage("invalid-line-count")));
+        } else if let Some(value) = arg.strip_prefix("--last-lines=") {
+            options.tail = Some(Tail::Lines(value.parse().or_usage("invalid-line-count")));
+        } else if arg == "--last-bytes" {
+            let value = args.next().or_usage("no-byte-count-specified");
+            options.tail = Some(Tail::Bytes(value.parse().or_usage("invalid-byte-count")));
+        } else if let Some(value) = arg.strip_prefix("--last-bytes=") {
+            options.tail = Some(Tail::Bytes(value.parse().or_usage("invalid-byte-count")));
+        } else if arg == "--grep" || arg == "--grep-v" {
+            let pattern = args.next().or_usage("no-pattern-specified");
+            let filter = GrepFilter::new(&pattern, arg == "--grep-v").or_usage("invalid-pattern");
+            options.grep.push(filter);
+        } else if let Some(pattern) = arg.strip_prefix("--grep=") {
+            options
+                .grep
+                .push(GrepFilter::new(pattern, false).or_usage("invalid-pattern"));
+        } else if let Some(pattern) = arg.strip_prefix("--grep-v=") {
+            options
+                .grep
+                .push(GrepFilter::new(pattern, true).or_usage("invalid-pattern"));
+        } else if arg == "--report-extremes" {
+            options.report_extremes = Some(args.next().or_usage("no-extremes-file-specified"));
+        } else if let Some(path) = arg.strip_prefix("--report-extremes=") {
+            options.report_extremes = Some(path.to_string());
+        } else if arg == "--corpus-report" {
+            options.corpus_report = true;
+        } else if arg == "--output" {
+            let value = args.next().or_usage("no-output-format-specified");
+            options.output = value.parse().or_usage("invalid-output-format");
+        } else if let Some(value) = arg.strip_prefix("--output=") {
+            options.output = value.parse().or_usage("invalid-output-format");
+        } else if arg == "--window" {
+            let value = args.next().or_usage("no-window-specified");
+            options.window = Some(stream::parse_duration(&value).or_usage("invalid-window"));
+        } else if let Some(value) = arg.strip_prefix("--window=") {
+            options.window = Some(stream::parse_duration(value).or_usage("invalid-window"));
+        } else if arg == "--interval" {
+            let value = args.next().or_usage("no-interval-specified");
+            options.interval = Some(stream::parse_duration(&value).or_usage("invalid-interval"));
+        } else if let Some(value) = arg.strip_prefix("--interval=") {
+            options.interval = Some(stream::parse_duration(value).or_usage("invalid-interval"));
+        } else if arg == "--stop-after" {
+            let value = args.next().or_usage("no-limit-specified");
+            options.stop_after = Some(value.parse().or_usage("invalid-limit"));
+        } else if let Some(value) = arg.strip_prefix("--stop-after=") {
+            options.stop_after = Some(value.parse().or_usage("invalid-limit"));
+        } else if arg == "--log-buckets" {
+            let value = args.next().or_usage("no-bucket-size-specified");
+            options.log_buckets = Some(value.parse().or_usage("invalid-bucket-size"));
+        } else if let Some(value) = arg.strip_prefix("--log-buckets=") {
+            options.log_buckets = Some(value.parse().or_usage("invalid-bucket-size"));
+        } else if arg == "--timestamp-format" {
+            let value = args.next().or_usage("no-timestamp-format-specified");
+            options.timestamp_format = value.parse().or_usage("invalid-timestamp-format");
+        } else if let Some(value) = arg.strip_prefix("--timestamp-format=") {
+            options.timestamp_format = value.parse().or_usage("invalid-timestamp-format");
+        } else if arg == "--files0-from" {
+            options.files0_from = Some(args.next().or_usage("no-file-name-list-specified"));
+        } else if let Some(source) = arg.strip_prefix("--files0-from=") {
+            options.files0_from = Some(source.to_string());
+        } else if arg == "--stdin-name" {
+            options.stdin_name = Some(args.next().or_usage("no-name-specified"));
+        } else if let Some(name) = arg.strip_prefix("--stdin-name=") {
+            options.stdin_name = Some(name.to_string());
+        } else if arg == "--glob" {
+            options.glob = true;
+        } else if arg == "-r" || arg == "--recursive" {
+            options.recursive = true;
+        } else if arg == "--include" {
+            let pattern = args.next().or_usage("no-pattern-specified");
+            options.walk.include.push(pattern);
+        } else if let Some(pattern) = arg.strip_prefix("--include=") {
+            options.walk.include.push(pattern.to_string());
+        } else if arg == "--exclude" {
+            let pattern = args.next().or_usage("no-pattern-specified");
+            options.walk.exclude.push(pattern);
+        } else if let Some(pattern) = arg.strip_prefix("--exclude=") {
+            options.walk.exclude.push(pattern.to_string());
+        } else if arg == "--find-duplicates" {
+            options.find_duplicates = true;
+        } else if arg == "--respect-gitignore" {
+            options.walk.gitignore = true;
+        } else if arg == "--hidden" {
+            options.walk.hidden = true;
+        } else if arg == "--file-types" {
+            let value = args.next().or_usage("no-file-types-specified");
+            options.walk.file_types = value.parse().or_usage("invalid-file-types");
+        } else if let Some(value) = arg.strip_prefix("--file-types=") {
+            options.walk.file_types = value.parse().or_usage("invalid-file-types");
+        } else if arg == "--skip-empty" {
+            options.walk.skip_empty = true;
+        } else if arg == "--newer-than" {
+            let value = args.next().or_usage("no-date-specified");
+            options.walk.newer_than = Some(walk::parse_time(&value).or_usage("invalid-date"));
+        } else if let Some(value) = arg.strip_prefix("--newer-than=") {
+            options.walk.newer_than = Some(walk::parse_time(value).or_usage("invalid-date"));
+        } else if arg == "--newer-than-file" {
+            let path = args.next().or_usage("no-reference-file-specified");
+            options.walk.newer_than = Some(modified_time(&path));
+        } else if let Some(path) = arg.strip_prefix("--newer-than-file=") {
+            options.walk.newer_than = Some(modified_time(path));
+        } else if arg == "--git-tracked" {
+            options.git_tracked = true;
+        } else if arg == "--dry-run" {
+            options.dry_run = true;
+        } else if arg == "-v" || arg == "--verbose" {
+            options.verbose = true;
+        } else if arg == "--symlinks" {
+            let value = args.next().or_usage("no-symlink-policy-specified");
+            options.walk.symlinks = value.parse().or_usage("invalid-symlink-policy");
+        } else if let Some(value) = arg.strip_prefix("--symlinks=") {
+            options.walk.symlinks = value.parse().or_usage("invalid-symlink-policy");
+        } else if arg == "--decompress" {
+            options.decompress = true;
+        } else if arg == "--encoding" {
+            let value = args.next().or_usage("no-encoding-specified");
+            options.counting.encoding = value.parse().or_usage("invalid-encoding");
+        } else if let Some(value) = arg.strip_prefix("--encoding=") {
+            options.counting.encoding = value.parse().or_usage("invalid-encoding");
+        } else if arg == "--mmap" {
+            options.mmap = true;
+        } else if arg == "--concat" {
+            options.concat = true;
+        } else if arg == "--sandbox" {
+            options.sandbox = true;
+        } else if arg == "--rusage" {
+            options.rusage = true;
+        } else if arg == "--bare" {
+            options.bare = true;
+        } else if arg == "--jsonrpc" {
+            options.jsonrpc = true;
+        } else if arg == "-h" || arg == "--help" {
+            println!("{}", i18n::message("help", &[]));
+            return;
+        } else if arg == "-V" || arg == "--version" {
+            println!("wc_rs {}", env!("CARGO_PKG_VERSION"));
+            return;
+        } else if arg == "--" {
+            operands.extend(args.by_ref());
+        } else if arg.len() > 1
+            && arg.starts_with('-')
+            && arg[1..].chars().all(|c| "lwmcL".contains(c))
+        {
+            options.select_counters(&arg[1..]);
+        } else if arg == "--email" {
+            options.email = true;
+        } else if arg == "--epub" {
+            options.epub = true;
+        } else if arg == "--archive" {
+            options.archive = true;
+        } else if arg == "--pdf" {
+            options.pdf = true;
+        } else if arg == "--docx" {
+            options.document = Some(Document::Docx);
+        } else if arg == "--odt" {
+            options.document = Some(Document::Odt);
+        } else if arg.starts_with('-')
+            && arg != "-"
+            && !SUBCOMMANDS.contains(&operands.first().map_or("", String::as_str))
+        {
+            // Options not recognized here are left to subcommands, which parse their own.
+            usage_error(&i18n::message("unrecognized-option", &[("option", &arg)]));
+        } else {
+            operands.push(arg);
         }
     }
 
-    /// Returns a new Flux spanning the receiver on the left, and `rhs` on the right.
-    fn span(self, rhs: Flux) -> Self {
-        let lines = self.lines + rhs.lines;
-        let words = {
-            // If the span is formed along a non-space to non-space boundary the word count is one less than the sum.
-            if let (CharType::NotSpace, CharType::NotSpace) =
-                (self.rightmost_char_type, rhs.leftmost_char_type)
-            {
-                self.words + rhs.words - 1
+    if let Some(identifier) = bare_error(&options, 1) {
+        usage_error(&i18n::message(identifier, &[]));
+    }
+
+    // Priorities are lowered before the counting threads are started, so that they inherit them.
+    if priority.nice {
+        priority::set_niceness(priority::LOWEST_NICENESS)
+            .unwrap_or_else(|error| fatal_error(&i18n::message("nice-failed", &[]), error));
+    }
+    if priority.io_idle {
+        priority::set_io_idle()
+            .unwrap_or_else(|error| fatal_error(&i18n::message("ionice-failed", &[]), error));
+    }
+    options.threads = options.threads.or_else(|| {
+        env::var("WC_RS_THREADS")
+            .ok()
+            .map(|value| parse_threads(&value).or_usage("invalid-wc-rs-threads"))
+    });
+    // Threads started before the sandbox is entered would keep the access it gives up, so they are started
+    // once it is entered instead.
+    if !options.sandbox {
+        start_threads(&options);
+    }
+
+    if options.jsonrpc {
+        let (stdin, stdout) = (io::stdin(), io::stdout());
+        wc_rs::jsonrpc::serve(stdin.lock(), stdout.lock(), &options.counting)
+            .expect("Unable to serve requests");
+        return;
+    }
+
+    // Subcommands are named by the first operand.
+    let mut failed = false;
+    let subcommand = operands.first().map(String::as_str);
+    if options.sandbox && subcommand.is_some_and(|name| SUBCOMMANDS.contains(&name)) {
+        usage_error(&i18n::message("sandbox-with-subcommands", &[]));
+    }
+    let mut report = Report::new(&options);
+    match subcommand {
+        Some("git-diff") => git_diff(&operands[1..], &options),
+        Some("history") => history(&operands[1..], &mut report),
+        Some("hook") => hook(&operands[1..], &mut report),
+        Some("run") => failed = run(&operands[1..], &mut report),
+        _ => {
+            if let Some(source) = &options.files0_from {
+                if let Some(operand) = operands.first() {
+                    let extra = i18n::message("extra-operand", &[("operand", operand)]);
+                    eprintln!("wc_rs: {}", extra);
+                    eprintln!("{}", i18n::message("files0-from-operands", &[]));
+                    std::process::exit(USAGE_STATUS);
+                }
+                match files0::read_list(source) {
+                    Ok(list) => {
+                        for name in files0::split_names(&list) {
+                            match name {
+                                Ok(name) => operands.push(name),
+                                Err(error) => {
+                                    eprintln!("wc_rs: {}:{}", source, error);
+                                    failed = true;
+                                }
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        eprintln!("wc_rs: {}: {}", source, error);
+                        failed = true;
+                    }
+                }
+            } else if options.glob {
+                operands = operands
+                    .iter()
+                    .flat_map(|operand| glob::expand(operand))
+                    .collect();
+            }
+            if options.corpus_report {
+                if operands.is_empty() {
+                    operands.push(".".to_string());
+                }
+                failed |= corpus_report(&operands, &options.walk);
+            } else if options.recursive && operands.is_empty() {
+                eprintln!("wc_rs: {}", i18n::message("recursive-requires-a-file", &[]));
+                std::process::exit(USAGE_STATUS);
             } else {
-                self.words + rhs.words
+                // Standard input is counted if no file is named, on a row without a name like that of `wc`.
+                if operands.is_empty() && options.files0_from.is_none() {
+                    operands.push(String::new());
+                }
+                if options.recursive {
+                    let (files, walk_failed) = walk_operands(&operands, &mut report);
+                    operands = files;
+                    failed |= walk_failed;
+                }
+                if let Some(identifier) = bare_error(&options, operands.len()) {
+                    usage_error(&i18n::message(identifier, &[]));
+                }
+                if options.dry_run {
+                    for operand in &operands {
+                        match options.input_name(operand) {
+                            "" => println!("-"),
+                            name => println!("{}", name),
+                        }
+                    }
+                    std::process::exit(if failed { 1 } else { 0 });
+                }
+                failed |= if options.window.is_some() || options.interval.is_some() {
+                    watch(&operands, &mut report)
+                } else {
+                    count_files(&operands, &mut report)
+                };
             }
-        };
+        }
+    }
+    report.finish();
 
-        Flux::new(
-            self.leftmost_char_type,
-            words,
-            lines,
-            rhs.rightmost_char_type,
-        )
+    if options.fail_if_empty && report.total.bytes == 0 {
+        std::process::exit(EMPTY_INPUT_STATUS);
+    }
+    let mismatches: Vec<String> = options
+        .expect
+        .iter()
+        .filter_map(|expectation| expectation.mismatch(&report.total))
+        .collect();
+    if !mismatches.is_empty() {
+        eprintln!("wc_rs: {}", i18n::message("counts-differ", &[]));
+        for mismatch in mismatches {
+            eprintln!("  {}", mismatch);
+        }
+        std::process::exit(UNEXPECTED_COUNTS_STATUS);
+    }
+    if failed {
+        std::process::exit(1);
     }
 }
 
-impl From<u8> for Flux {
-    /// Creates a new instance of a Flux encoding a single character.
-    fn from(other: u8) -> Self {
-        if other.is_ascii_whitespace() {
-            // A line-feed is considered an ASCII whitespace character by `is_ascii_whitespace`.
-            let lines = if other == ('\n' as u8) { 1 } else { 0 };
-            Flux::new(CharType::IsSpace, 0, lines, CharType::IsSpace)
+/// Reads and parses the report template at `path`.
+fn read_template(path: &str) -> Template {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|error| fatal_error(path, error));
+    Template::parse(&text).unwrap_or_else(|error| fatal_error(path, error))
+}
+
+/// The output rows of a counting run, printed as they are counted unless they are rendered with a template
+/// or in a structured format once all are counted.
+struct Report<'a> {
+    /// The options of the run.
+    options: &'a Options,
+    /// Whether rows are held back until all are counted, for the template or for columns that depend on
+    /// the total counts.
+    held: bool,
+    /// The rows held back, each the name of an input and its counts.
+    rows: Vec<(String, Counts)>,
+    /// The sum of the counts of each input, leaving out the rows of their parts.
+    total: Counts,
+    /// The total row held back to follow the rows, if any.
+    summary: Option<Counts>,
+    /// The notable positions of each input, if they are reported.
+    extremes: Vec<(String, Extremes)>,
+    /// The digest of the contents of each input and its counts, if duplicates are found.
+    digests: Vec<(String, Digest, Counts)>,
+    /// The name of each input which could not be counted and why, shown in JSON output.
+    errors: Vec<(String, io::Error)>,
+    /// The number of empty files skipped beneath directories.
+    empty_files: u64,
+}
+
+impl<'a> Report<'a> {
+    /// Returns an empty report of a run with `options`.
+    fn new(options: &'a Options) -> Self {
+        let needs_total = options
+            .columns
+            .iter()
+            .flatten()
+            .any(|column| column.needs_total());
+        Report {
+            options,
+            held: options.template.is_some() || options.output != Output::Text || needs_total,
+            rows: Vec::new(),
+            total: Counts::default(),
+            summary: None,
+            extremes: Vec::new(),
+            digests: Vec::new(),
+            errors: Vec::new(),
+            empty_files: 0,
+        }
+    }
+
+    /// Returns an empty report of a run with `options` holding back all of its rows, for them to be added
+    /// to another with `absorb`.
+    fn buffer(options: &'a Options) -> Self {
+        Report {
+            held: true,
+            ..Report::new(options)
+        }
+    }
+
+    /// Outputs the rows held back by `buffer`, and adds its notable positions, digests and errors, returning
+    /// `true` if it has any errors.
+    fn absorb(&mut self, buffer: Report) -> bool {
+        for (name, counts) in buffer.rows {
+            self.row(name, counts);
+        }
+        self.extremes.extend(buffer.extremes);
+        self.digests.extend(buffer.digests);
+        let failed = !buffer.errors.is_empty();
+        self.errors.extend(buffer.errors);
+        failed
+    }
+
+    /// Outputs the row of `counts` for the input `name`.
+    fn row(&mut self, name: String, mut counts: Counts) {
+        measure_allocated(&name, &mut counts, self.options);
+        if self.held {
+            self.rows.push((name, counts));
+        } else {
+            println!("{}", format_row(&counts, &counts, self.options, &name));
+        }
+    }
+
+    /// Reports that the input `name` could not be counted because of `error`, on standard error and in JSON
+    /// output.
+    fn error(&mut self, name: &str, error: io::Error) {
+        eprintln!("wc_rs: {}: {}", name, error);
+        self.errors.push((name.to_string(), error));
+    }
+
+    /// Adds the counts of the input `name` as a whole to the total, once whatever rows it is shown in are
+    /// output.
+    fn tally(&mut self, name: &str, mut counts: Counts) {
+        measure_allocated(name, &mut counts, self.options);
+        self.total += counts;
+    }
+
+    /// Outputs the total row of `counts`, after any rows held back. Templates and JSON show their own
+    /// totals, and delimited formats show it as a last record.
+    fn total_row(&mut self, counts: Counts) {
+        if self.held {
+            self.summary = Some(counts);
         } else {
-            Flux::new(CharType::NotSpace, 1, 0, CharType::NotSpace)
+            print_summary(
+                &format_row(&counts, &counts, self.options, "total"),
+                self.options,
+            );
+        }
+    }
+
+    /// Outputs any rows held back followed by the groups of duplicate inputs, and reports the resources used
+    /// by the run if requested.
+    fn finish(&mut self) {
+        match &self.options.template {
+            Some(template) => print!(
+                "{}",
+                template.render(&self.rows, &self.total, self.options.precision)
+            ),
+            None if self.options.output == Output::Json => {
+                let columns = row_columns(self.options);
+                print!(
+                    "{}",
+                    output::render_json(
+                        &self.rows,
+                        &self.total,
+                        &columns,
+                        &self.errors,
+                        self.options.walk.skip_empty.then_some(self.empty_files),
+                        self.options.precision
+                    )
+                );
+            }
+            None if self.options.output != Output::Text => {
+                let columns = row_columns(self.options);
+                let (output, precision) = (self.options.output, self.options.precision);
+                print!(
+                    "{}",
+                    output::render_delimited(&self.rows, &self.total, &columns, output, precision)
+                );
+                if let Some(summary) = self.summary.take() {
+                    let record = output::render_record(
+                        "total",
+                        &summary,
+                        &self.total,
+                        &columns,
+                        output,
+                        precision,
+                    );
+                    print_summary(&record, self.options);
+                }
+            }
+            None => {
+                for (name, counts) in &self.rows {
+                    println!("{}", format_row(counts, &self.total, self.options, name));
+                }
+                if let Some(summary) = self.summary.take() {
+                    let row = format_row(&summary, &summary, self.options, "total");
+                    print_summary(&row, self.options);
+                }
+            }
+        }
+        self.rows.clear();
+        if self.options.walk.skip_empty && self.options.output != Output::Json {
+            // As with duplicates, delimited output and templates are left parseable.
+            let skipped = i18n::message("empty-files-skipped", &[("count", &self.empty_files)]);
+            if self.options.output == Output::Text && self.options.template.is_none() {
+                println!("{}", skipped);
+            } else {
+                eprintln!("wc_rs: {}", skipped);
+            }
+        }
+        if self.options.find_duplicates {
+            // Structured output is left parseable by listing the groups on standard error instead.
+            let groups = duplicates::render(&duplicates::groups(&self.digests));
+            if self.options.output == Output::Text && self.options.template.is_none() {
+                print!("{}", groups);
+            } else {
+                eprint!("{}", groups);
+            }
+            self.digests.clear();
+        }
+        if let Some(path) = &self.options.report_extremes {
+            let mut output = String::new();
+            for (name, extremes) in self.extremes.drain(..) {
+                let record = Record {
+                    name: &name,
+                    extremes: &extremes,
+                };
+                output.push_str(&format!("{}\n", record));
+            }
+            std::fs::write(path, output).unwrap_or_else(|error| fatal_error(path, error));
+        }
+        if self.options.rusage {
+            match rusage::current() {
+                Ok(usage) => eprintln!("{}", usage),
+                Err(error) => self.error(&i18n::message("resource-usage-failed", &[]), error),
+            }
+        }
+    }
+}
+
+/// Opens the file at `path`, standard input if `path` is empty or `-`, a stream socket if `path` is a
+/// `tcp://host:port` or `unix:///path` address or names a Unix domain socket, or a Kafka topic partition if
+/// it is a `kafka://` URL, positioned at the start of `tail` if given.
+fn open_file(path: &str, tail: Option<Tail>) -> io::Result<Box<dyn Read>> {
+    let stream: Option<Box<dyn Read>> = if path.is_empty() || path == "-" {
+        Some(Box::new(io::stdin().lock()))
+    } else if let Some(address) = path.strip_prefix("tcp://") {
+        Some(Box::new(TcpStream::connect(address)?))
+    } else if let Some(socket) = path.strip_prefix("unix://") {
+        Some(connect_unix(socket)?)
+    } else if path.starts_with("kafka://") {
+        Some(open_kafka(path)?)
+    } else if is_socket(path) {
+        Some(connect_unix(path)?)
+    } else {
+        None
+    };
+    if let Some(stream) = stream {
+        if tail.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                Message::new("stream-tail"),
+            ));
+        }
+        return Ok(stream);
+    }
+
+    let mut file = File::open(path)?;
+    if let Some(tail) = tail {
+        tail::seek_to_tail(&mut file, tail)?;
+    }
+    Ok(Box::new(file))
+}
+
+/// Returns whether `path` names a Unix domain socket.
+#[cfg(unix)]
+fn is_socket(path: &str) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket())
+}
+
+/// Returns `false`, as the platform has no Unix domain sockets.
+#[cfg(not(unix))]
+fn is_socket(_path: &str) -> bool {
+    false
+}
+
+/// Connects to the Unix domain stream socket at `path`.
+#[cfg(unix)]
+fn connect_unix(path: &str) -> io::Result<Box<dyn Read>> {
+    Ok(Box::new(std::os::unix::net::UnixStream::connect(path)?))
+}
+
+/// Fails to connect to a Unix domain socket, as the platform has none.
+#[cfg(not(unix))]
+fn connect_unix(_path: &str) -> io::Result<Box<dyn Read>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        Message::new("unix-sockets-unsupported"),
+    ))
+}
+
+/// Opens a reader of the values of the records of the Kafka topic partition named by `url`.
+#[cfg(feature = "kafka")]
+fn open_kafka(url: &str) -> io::Result<Box<dyn Read>> {
+    let partition: wc_rs::kafka::Partition = url
+        .parse()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    Ok(Box::new(wc_rs::kafka::PartitionReader::connect(
+        &partition,
+    )?))
+}
+
+/// Fails to open a Kafka topic partition, as support was not built.
+#[cfg(not(feature = "kafka"))]
+fn open_kafka(_url: &str) -> io::Result<Box<dyn Read>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        Message::new("kafka-unsupported"),
+    ))
+}
+
+/// Counts the files at `paths`, each on a row of its own followed by a total row if there are several,
+/// or as one continuous input with `--concat`. Files which cannot be opened, read or parsed are reported
+/// and skipped, returning `true` if any were.
+fn count_files(paths: &[String], report: &mut Report) -> bool {
+    let options = report.options;
+    let mut failed = false;
+
+    // Files are counted in parallel unless they are opened up front or read at a limited rate, with their
+    // rows output in the order of the files however counting completes. Each is opened only once it is
+    // counted, so that long lists of files are not all held open at once.
+    if !options.sandbox && !options.concat && options.bwlimit.is_none() {
+        let counted: Vec<(Report, io::Result<Counts>)> = paths
+            .par_iter()
+            .map(|path| {
+                let mut rows = Report::buffer(options);
+                let counts = count_from_size(path, &mut rows)
+                    .or_else(|| count_mapped(path, &mut rows))
+                    .unwrap_or_else(|| {
+                        open_file(path, options.tail)
+                            .and_then(|file| count_input(options.input_name(path), file, &mut rows))
+                    });
+                (rows, counts)
+            })
+            .collect();
+
+        let mut total = Counts::default();
+        for (path, (rows, counts)) in paths.iter().zip(counted) {
+            let path = options.input_name(path);
+            failed |= report.absorb(rows);
+            match counts {
+                Ok(counts) => {
+                    report.tally(path, counts);
+                    total += counts;
+                }
+                Err(error) => {
+                    report.error(path, error);
+                    failed = true;
+                }
+            }
+        }
+        if paths.len() > 1 {
+            report.total_row(total);
+        }
+        return failed;
+    }
+
+    // Every file is opened before any is counted otherwise, so that the sandbox can be entered in between.
+    // The members of archives which cannot be read are reported as they are met, failing the run.
+    let errors = report.errors.len();
+    let mut files = Vec::new();
+    for path in paths {
+        match open_file(path, options.tail) {
+            Ok(file) => files.push((options.input_name(path), file)),
+            Err(error) => {
+                report.error(options.input_name(path), error);
+                failed = true;
+            }
+        }
+    }
+    if options.sandbox {
+        sandbox::enter().unwrap_or_else(|error| fatal_error("--sandbox", error));
+        start_threads(options);
+    }
+
+    // Words and lines spanning the end of one file and the start of the next are counted once.
+    if options.concat {
+        let names: Vec<&str> = paths.iter().map(|path| options.input_name(path)).collect();
+        let name = names.join(" ");
+        let input = files.into_iter().fold(
+            Box::new(io::empty()) as Box<dyn Read>,
+            |input, (_, file)| Box::new(input.chain(file)),
+        );
+        match count_input(&name, input, report) {
+            Ok(counts) => report.tally(&name, counts),
+            Err(error) => {
+                report.error(&name, error);
+                failed = true;
+            }
+        }
+        return failed || report.errors.len() > errors;
+    }
+
+    let mut total = Counts::default();
+    for (path, file) in files {
+        match count_input(path, file, report) {
+            Ok(counts) => {
+                report.tally(path, counts);
+                total += counts;
+            }
+            Err(error) => {
+                report.error(path, error);
+                failed = true;
+            }
+        }
+    }
+    if paths.len() > 1 {
+        report.total_row(total);
+    }
+    failed || report.errors.len() > errors
+}
+
+/// Sets the space allocated on disk to the file at `path` in `counts`, if it is counted and not yet set.
+fn measure_allocated(path: &str, counts: &mut Counts, options: &Options) {
+    if options.allocated && counts.allocated == 0 {
+        counts.allocated = disk::allocated(path).unwrap_or(0);
+    }
+}
+
+/// Counts the regular file at `path` from its size, without reading it, if only its byte count is output,
+/// adding its row to `report` and returning its counts. Returns `None` if it is to be read instead, as other
+/// counts are output, `path` names standard input, the file is not a regular one, or its size is zero, as
+/// that of files generated as they are read such as those of `/proc` is.
+fn count_from_size(path: &str, report: &mut Report) -> Option<io::Result<Counts>> {
+    let options = report.options;
+    let bytes_only = row_columns(options).iter().all(|column| {
+        matches!(
+            column,
+            Column::Bytes | Column::BytesShare | Column::Allocated | Column::File
+        )
+    });
+    let expects_bytes_only = options
+        .expect
+        .iter()
+        .all(|expectation| expectation.column == Column::Bytes);
+    if !bytes_only
+        || !expects_bytes_only
+        || options.template.is_some()
+        || options.find_duplicates
+        || !options.counts_whole_text()
+        || path.is_empty()
+        || path == "-"
+    {
+        return None;
+    }
+
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return None;
+    }
+    let mut counts = Counts {
+        bytes: metadata.len(),
+        ..Counts::default()
+    };
+    measure_allocated(path, &mut counts, options);
+    report.row(path.to_string(), counts);
+    Some(Ok(counts))
+}
+
+/// Counts the regular file at `path` mapped into memory, all of it at once, adding its row to `report` and
+/// returning its counts or the error mapping it. Returns `None` if it is to be read instead, as `--mmap` is
+/// not given, `path` names standard input, the file is not a regular one, or the options count only part of
+/// it or transform it.
+fn count_mapped(path: &str, report: &mut Report) -> Option<io::Result<Counts>> {
+    let options = report.options;
+    if !options.mmap || !options.counts_whole_text() || path.is_empty() || path == "-" {
+        return None;
+    }
+
+    // Files which cannot be opened are left to be reported when they are read, and FIFOs, which block
+    // until written to, are opened only once.
+    if !std::fs::metadata(path).ok()?.is_file() {
+        return None;
+    }
+    let file = File::open(path).ok()?;
+    Some(Mapping::new(&file).and_then(|mapping| {
+        let mut counts = count_reader(&mut &mapping[..], &options.counting)?;
+        measure_allocated(path, &mut counts, options);
+        report.row(path.to_string(), counts);
+        if options.find_duplicates {
+            let mut hash = ContentHash::default();
+            hash.feed(&mapping);
+            report
+                .digests
+                .push((path.to_string(), hash.digest(), counts));
+        }
+        Ok(counts)
+    }))
+}
+
+/// Counts the stream at the single path in `paths` as its lines arrive, printing periodically either the
+/// counts of the lines which arrived within the window, if any, or the counts so far, and then its row once
+/// it ends. Returns `true` if the stream could not be read.
+fn watch(paths: &[String], report: &mut Report) -> bool {
+    let options = report.options;
+    let path = match paths {
+        [path] => path.clone(),
+        _ => usage_error(&i18n::message(
+            "window-and-interval-require-a-single-input",
+            &[],
+        )),
+    };
+    let name = options.input_name(&path).to_string();
+    let period = options.interval.unwrap_or(SNAPSHOT_PERIOD);
+
+    // Lines are read on a thread of their own, so that snapshots are printed while waiting for them.
+    let (sender, receiver) = mpsc::channel();
+    let (tail, stop_after) = (options.tail, options.stop_after);
+    let buffer_size = options.buffer_size();
+    thread::spawn(move || {
+        let mut reader = match open_file(&path, tail) {
+            Ok(file) => BufReader::with_capacity(buffer_size, Limit::new(file, stop_after)),
+            Err(error) => return sender.send(Err(error)),
+        };
+        loop {
+            let mut line = Vec::new();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => return Ok(()),
+                Ok(_) => sender.send(Ok(line))?,
+                Err(error) => return sender.send(Err(error)),
+            }
+        }
+    });
+
+    let mut failed = false;
+    let mut window = options
+        .window
+        .map(|length| Window::new(length, Instant::now()));
+    let mut next_snapshot = Instant::now() + period;
+    let mut total = Counts::default();
+    loop {
+        let timeout = next_snapshot.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(line)) => match try_count_slice_at(&line, total.bytes, &options.counting) {
+                Ok(counts) => {
+                    if let Some(window) = &mut window {
+                        window.add(Instant::now(), counts);
+                    }
+                    total += counts;
+                }
+                Err(error) => {
+                    report.error(&name, error.into());
+                    failed = true;
+                    break;
+                }
+            },
+            Ok(Err(error)) => {
+                report.error(&name, error);
+                failed = true;
+                break;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let now = Instant::now();
+        if now >= next_snapshot {
+            match &mut window {
+                Some(window) => println!("{}", window.snapshot(now)),
+                None => println!("{}", format_row(&total, &total, options, &name)),
+            }
+            next_snapshot = now + period;
+        }
+    }
+    report.tally(&name, total);
+    report.row(name, total);
+    failed
+}
+
+/// Returns the files beneath those of `operands` which are directories, walked to as the options of
+/// `report` direct, in place of the directories, and whether any could not be read, which are reported to
+/// `report`. Other operands, such as sockets and standard input, are returned as they are. The files and
+/// directories passed over are reported on standard error too if the options are verbose, with why. Empty
+/// files skipped are counted in `report`.
+fn walk_operands(operands: &[String], report: &mut Report) -> (Vec<String>, bool) {
+    let options = report.options;
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    let mut skipped = Vec::new();
+    for operand in operands {
+        if !Path::new(operand).is_dir() {
+            files.push(operand.clone());
+            continue;
+        }
+        let mut paths = Vec::new();
+        options
+            .walk
+            .walk_noting_skipped(Path::new(operand), &mut paths, &mut errors, &mut skipped);
+        if options.git_tracked {
+            let tracked: HashSet<PathBuf> = git::tracked_paths(operand)
+                .unwrap_or_else(|error| fatal_error(operand, error))
+                .iter()
+                .map(|path| Path::new(operand).join(path))
+                .collect();
+            let (kept, untracked) = paths.into_iter().partition(|path| tracked.contains(path));
+            paths = kept;
+            skipped.extend(
+                untracked
+                    .into_iter()
+                    .map(|path: PathBuf| (path, "skipped-untracked")),
+            );
+        }
+        files.extend(paths.iter().map(|path| path.to_string_lossy().into_owned()));
+    }
+    if options.verbose {
+        for (path, reason) in &skipped {
+            let reason = i18n::message(reason, &[]);
+            let args: [(&str, &dyn Display); 2] = [("path", &path.display()), ("reason", &reason)];
+            eprintln!("wc_rs: {}", i18n::message("skipped", &args));
+        }
+    }
+    report.empty_files += skipped
+        .iter()
+        .filter(|&&(_, reason)| reason == walk::EMPTY)
+        .count() as u64;
+    let failed = !errors.is_empty();
+    for (path, error) in errors {
+        report.error(&path.to_string_lossy(), error);
+    }
+    (files, failed)
+}
+
+/// Prints the number of files beneath `paths`, walked to as `walk` directs, of each kind, encoding, byte
+/// order mark and line ending. Files which cannot be read are reported and skipped, returning `true` if any
+/// were.
+fn corpus_report(paths: &[String], walk: &Walk) -> bool {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        walk.walk(Path::new(path), &mut files, &mut errors);
+    }
+    let mut failed = !errors.is_empty();
+    for (path, error) in errors {
+        eprintln!("wc_rs: {}: {}", path.display(), error);
+    }
+
+    let mut report = CorpusReport::default();
+    for file in files {
+        match std::fs::read(&file) {
+            Ok(contents) => report.add(Profile::of(&contents)),
+            Err(error) => {
+                eprintln!("wc_rs: {}: {}", file.display(), error);
+                failed = true;
+            }
         }
     }
+    print!("{}", report);
+    failed
 }
 
-/// Takes two optional Flux instances and returns, where possible, the span of the two.
-fn span_opt(lhs: Option<Flux>, rhs: Option<Flux>) -> Option<Flux> {
-    lhs.map_or(rhs, |left_flux| {
-        rhs.map(|right_flux| left_flux.span(right_flux))
-    })
+/// Counts `input`, named `target_path`, as the options of `report` direct, adding its rows and any notable
+/// positions to `report` and returning the counts of the input as a whole, or the error reading or parsing
+/// it.
+fn count_input(target_path: &str, input: impl Read, report: &mut Report) -> io::Result<Counts> {
+    if report.options.decompress {
+        let buffer_size = report.options.buffer_size();
+        let input = decompress(BufReader::with_capacity(buffer_size, input), buffer_size)?;
+        return count_decompressed(target_path, input, report);
+    }
+    count_decompressed(target_path, input, report)
 }
 
-/// Computes the flux over the provided input byte string.
-fn flux_over_byte_string<T>(input: T) -> Option<Flux>
-where
-    T: AsRef<[u8]>,
-{
-    input
+/// Counts `input`, named `target_path` and decompressed if it was compressed, as `count_input` does.
+fn count_decompressed(
+    target_path: &str,
+    input: impl Read,
+    report: &mut Report,
+) -> io::Result<Counts> {
+    let mut extremes = report
+        .options
+        .report_extremes
         .as_ref()
-        .par_iter()
-        .cloned()
-        .map(Flux::from)
-        .fold(|| None, |acc, next| span_opt(acc, Some(next)))
-        .reduce(|| None, |acc, next| span_opt(acc, next))
+        .map(|_| Extremes::default());
+    let mut hash = report.options.find_duplicates.then(ContentHash::default);
+    let input = Hashing::new(Limit::new(input, report.options.stop_after), hash.as_mut());
+    let mut counts = count_contents(target_path, Scan::new(input, extremes.as_mut()), report)?;
+    measure_allocated(target_path, &mut counts, report.options);
+    if let Some(mut extremes) = extremes {
+        extremes.finish();
+        report.extremes.push((target_path.to_string(), extremes));
+    }
+    if let Some(hash) = hash {
+        report
+            .digests
+            .push((target_path.to_string(), hash.digest(), counts));
+    }
+    Ok(counts)
 }
 
-fn wc<T>(input: &mut T) -> std::io::Result<Counts>
-where
-    T: BufRead,
-{
-    let mut bytes = 0;
-    let mut flux = None;
+/// Counts `input`, named `target_path`, as the options of `report` direct, adding its rows to `report` and
+/// returning the counts of the input as a whole, or the error reading or parsing it.
+fn count_contents(target_path: &str, input: impl Read, report: &mut Report) -> io::Result<Counts> {
+    let options = report.options;
+    let mut reader =
+        BufReader::with_capacity(options.buffer_size(), Throttle::new(input, options.bwlimit));
+
+    // Count the code, markdown and outputs of a notebook separately.
+    if options.notebook {
+        let mut document = Vec::new();
+        reader.read_to_end(&mut document)?;
+        let notebook = Notebook::parse(&document).map_err(invalid_data)?;
+
+        let mut total = Counts::default();
+        for (section, text) in &[
+            ("code", notebook.code),
+            ("markdown", notebook.markdown),
+            ("outputs", notebook.outputs),
+        ] {
+            let counts = try_count_slice(text, &options.counting)?;
+            report.row(format!("{}:{}", target_path, section), counts);
+            total += counts;
+        }
+        return Ok(total);
+    }
+
+    // Count the messages of a mailbox and the words of their bodies.
+    if options.email {
+        let mut document = Vec::new();
+        reader.read_to_end(&mut document)?;
+        let mailbox = Mailbox::parse(&document);
+        let mut counts = try_count_slice(&mailbox.text, &options.counting)?;
+        counts.messages = mailbox.messages;
+        report.row(target_path.to_string(), counts);
+        return Ok(counts);
+    }
+
+    // Count each chapter of an e-book, in reading order, and the book as a whole, which alone is totalled.
+    if options.epub {
+        let mut document = Vec::new();
+        reader.read_to_end(&mut document)?;
+        let chapters = epub::chapters(&document).map_err(invalid_data)?;
+
+        let mut book = Vec::new();
+        for chapter in &chapters {
+            let counts = try_count_slice(&chapter.text, &options.counting)?;
+            report.row(format!("{}:{}", target_path, chapter.path), counts);
+            book.extend_from_slice(&chapter.text);
+        }
+        let counts = try_count_slice(&book, &options.counting)?;
+        report.row(target_path.to_string(), counts);
+        return Ok(counts);
+    }
 
-    'buffer_loop: loop {
-        let buffer = input.fill_buf()?;
-        let length = buffer.len();
-        if length == 0 {
-            break 'buffer_loop;
+    // Count each file of an archive, in the order of the archive, and the archive as a whole. Only the
+    // counts returned for the archive are totalled, its rows being for display. Members which cannot be
+    // read are reported and left out of the archive's counts.
+    if options.archive {
+        let mut total = Counts::default();
+        for (path, counted) in archive::count_members(reader, &options.counting)? {
+            let name = format!("{}!{}", target_path, path);
+            match counted {
+                Ok(counts) => {
+                    report.row(name, counts);
+                    total += counts;
+                }
+                Err(error) => report.error(&name, error),
+            }
         }
+        report.row(target_path.to_string(), total);
+        return Ok(total);
+    }
 
-        // Update the byte counter from the buffer.
-        bytes = bytes + length;
+    // Count the text extracted from a PDF or word processor document.
+    if options.pdf || options.document.is_some() {
+        let mut document = Vec::new();
+        reader.read_to_end(&mut document)?;
+        let text = match options.document {
+            Some(format) => format.extract_text(&document),
+            None => extract_pdf_text(&document).map(String::into_bytes),
+        }
+        .map_err(invalid_data)?;
+        let counts = try_count_slice(&text, &options.counting)?;
+        report.row(target_path.to_string(), counts);
+        return Ok(counts);
+    }
 
-        // Fold the flux of the next buffer into the existing.
-        flux = span_opt(flux, flux_over_byte_string(&buffer));
+    // Count the bytes, words and lines in the specified file, keeping only the lines selected by any
+    // patterns, or in its prose if it is marked up.
+    let mut reader: Box<dyn BufRead + '_> = Box::new(reader);
+    for filter in &options.grep {
+        reader = Box::new(MarkupReader::with_filter(reader, Box::new(filter.clone())));
+    }
+    if let Some(markup) = options.markup {
+        reader = Box::new(MarkupReader::new(reader, markup));
+    }
 
-        // Mark the buffer as consumed.
-        input.consume(length);
+    // Count the lines of a log in each time bucket, in order, and the log as a whole, which alone is
+    // totalled.
+    if let Some(size) = options.log_buckets {
+        let buckets = log_buckets::count_buckets(
+            &mut reader,
+            size,
+            &options.timestamp_format,
+            &options.counting,
+        )?;
+        let mut total = Counts::default();
+        for (bucket, counts) in buckets {
+            let label = bucket.map_or("untimestamped".to_string(), |bucket| bucket.label(size));
+            report.row(format!("{}:{}", target_path, label), counts);
+            total += counts;
+        }
+        report.row(target_path.to_string(), total);
+        return Ok(total);
     }
 
-    Ok(Counts {
-        bytes,
-        words: flux.map(|f| f.words).unwrap_or_default(),
-        lines: flux.map(|f| f.lines).unwrap_or_default(),
-    })
+    let counts = count_reader(&mut reader, &options.counting)?;
+
+    report.row(target_path.to_string(), counts);
+    Ok(counts)
 }
 
-fn main() {
-    let target_path = env::args().nth(1).expect("No file path specified");
-    let target_file = File::open(&target_path).expect("Unable to open file");
-    let mut reader = BufReader::with_capacity(BUFFER_SIZE, target_file);
-
-    // Count the bytes, words and lines in the specified file.
-    let counts = wc(&mut reader).expect("Error reading file");
-
-    // Display the results in the format of the original `wc` utility.
-    println!(
-        "{lines:>8} {words:>7} {bytes:7} {file}",
-        bytes = counts.bytes,
-        words = counts.words,
-        lines = counts.lines,
-        file = target_path
+/// Returns an error for input whose contents could not be parsed, described by `error`.
+fn invalid_data(error: Message) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Prints the lines and words added and removed in each file changed by the git revision range and
+/// optional paths in `operands`, followed by their totals.
+fn git_diff(operands: &[String], options: &Options) {
+    let range = operands.first().or_usage("no-revision-range-specified");
+    let paths = match operands.get(1).map(String::as_str) {
+        Some("--") => &operands[2..],
+        _ => &operands[1..],
+    };
+    let files = git::diff(range, paths).unwrap_or_else(|error| fatal_error(range, error));
+
+    let mut failed = false;
+    let mut totals = [0; 4];
+    for file in &files {
+        if file.binary {
+            println!("{:>8} {:>7} {:>8} {:>7} {}", "-", "-", "-", "-", file.path);
+            continue;
+        }
+        let counted = try_count_slice(&file.added, &options.counting).and_then(|added| {
+            try_count_slice(&file.removed, &options.counting).map(|removed| (added, removed))
+        });
+        let (added, removed) = match counted {
+            Ok(counted) => counted,
+            Err(error) => {
+                eprintln!("wc_rs: {}: {}", file.path, error);
+                failed = true;
+                continue;
+            }
+        };
+        let churn = [added.lines, added.words, removed.lines, removed.words];
+        for (total, count) in totals.iter_mut().zip(&churn) {
+            *total += count;
+        }
+        println!(
+            "{:>8} {:>7} {:>8} {:>7} {}",
+            churn[0], churn[1], churn[2], churn[3], file.path
+        );
+    }
+    let total = format!(
+        "{:>8} {:>7} {:>8} {:>7} total",
+        totals[0], totals[1], totals[2], totals[3]
     );
+    print_summary(&total, options);
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Prints the total row `row` to standard output, or wherever `--summary-to` directs it.
+fn print_summary(row: &str, options: &Options) {
+    match options.summary_to.as_deref() {
+        None => println!("{}", row),
+        Some("stderr") => eprintln!("{}", row),
+        Some(path) => {
+            File::create(path)
+                .and_then(|mut file| writeln!(file, "{}", row))
+                .unwrap_or_else(|error| fatal_error(path, error));
+        }
+    }
+}
+
+/// Prints the counts of the files, limited to the paths in `operands` if any are given, at each tag of the
+/// repository, or each commit with `--every commit`, oldest first.
+fn history(operands: &[String], report: &mut Report) {
+    let options = report.options;
+    let mut every = "tag";
+    let mut paths = Vec::new();
+    let mut operands = operands.iter();
+    while let Some(operand) = operands.next() {
+        if operand == "--every" {
+            every = operands.next().or_usage("no-history-sampling-specified");
+        } else if let Some(value) = operand.strip_prefix("--every=") {
+            every = value;
+        } else if operand != "--" {
+            paths.push(operand.clone());
+        }
+    }
+
+    let revisions = match every {
+        "tag" => git::tags(),
+        "commit" => git::commits(),
+        _ => usage_error(&i18n::message(
+            "unknown-history-sampling",
+            &[("sampling", &every)],
+        )),
+    }
+    .unwrap_or_else(|error| fatal_error("git", error));
+
+    for revision in revisions {
+        let files = git::files_at(&revision.name, &paths)
+            .unwrap_or_else(|error| fatal_error(&revision.name, error));
+        let (names, contents): (Vec<String>, Vec<Vec<u8>>) = files.into_iter().unzip();
+        let mut counts = Counts::default();
+        for (path, file_counts) in names.iter().zip(wc_all(&contents, options)) {
+            counts += file_counts
+                .unwrap_or_else(|error| fatal_error(&format!("{}:{}", revision.name, path), error));
+        }
+        let name = format!("{} {}", revision.date, revision.name);
+        report.tally(&name, counts);
+        report.row(name, counts);
+    }
+}
+
+/// Prints the counts of each file changed in the index, limited to the paths in `operands` if any are
+/// given, and exits with a failure status if any violates an `--assert` threshold.
+///
+/// With `--staged` the contents staged in the index are counted, as they are what will be committed,
+/// rather than the contents of the worktree.
+fn hook(operands: &[String], report: &mut Report) {
+    let options = report.options;
+    let mut staged = false;
+    let mut assertions: Vec<Assertion> = Vec::new();
+    let mut paths = Vec::new();
+    let mut operands = operands.iter();
+    while let Some(operand) = operands.next() {
+        if operand == "--staged" {
+            staged = true;
+        } else if operand == "--assert" {
+            let value = operands.next().or_usage("no-assertion-specified");
+            assertions.push(value.parse().or_usage("invalid-assertion"));
+        } else if let Some(value) = operand.strip_prefix("--assert=") {
+            assertions.push(value.parse().or_usage("invalid-assertion"));
+        } else if operand != "--" {
+            paths.push(operand.clone());
+        }
+    }
+
+    let changed = git::staged_paths(&paths).unwrap_or_else(|error| fatal_error("git", error));
+    let contents = if staged {
+        git::staged_contents(&changed).unwrap_or_else(|error| fatal_error("git", error))
+    } else {
+        // The paths are relative to the top of the working tree, wherever in it the hook is run from.
+        let toplevel = git::toplevel().unwrap_or_else(|error| fatal_error("git", error));
+        changed
+            .iter()
+            .map(|path| {
+                std::fs::read(toplevel.join(path)).unwrap_or_else(|error| fatal_error(path, error))
+            })
+            .collect()
+    };
+
+    let mut violated = false;
+    for (path, counts) in changed.iter().zip(wc_all(&contents, options)) {
+        let counts = counts.unwrap_or_else(|error| fatal_error(path, error));
+        report.tally(path, counts);
+        report.row(path.clone(), counts);
+        for assertion in assertions
+            .iter()
+            .filter(|assertion| !assertion.holds(&counts))
+        {
+            let args: [(&str, &dyn Display); 3] = [
+                ("path", path),
+                ("assertion", assertion),
+                ("value", &assertion.metric.of(&counts)),
+            ];
+            eprintln!("{}", i18n::message("assertion-failed", &args));
+            violated = true;
+        }
+    }
+    if violated {
+        report.finish();
+        std::process::exit(1);
+    }
+}
+
+/// Counts the inputs listed by the manifest at the single path in `operands`, each with the options the
+/// manifest gives it, followed by their total. Inputs which cannot be opened, read or parsed are reported
+/// and skipped, returning `true` if any were, and the run exits with `UNEXPECTED_COUNTS_STATUS` after
+/// listing the differences if any input does not have the counts the manifest expects of it.
+fn run(operands: &[String], report: &mut Report) -> bool {
+    let path = match operands {
+        [path] => path,
+        _ => usage_error(&i18n::message("run-requires-a-single-manifest", &[])),
+    };
+    let text = std::fs::read_to_string(path).unwrap_or_else(|error| fatal_error(path, error));
+    let manifest = Manifest::parse(&text).unwrap_or_else(|error| fatal_error(path, error));
+    let directory = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+    let mut failed = false;
+    let mut mismatches = Vec::new();
+    let mut total = Counts::default();
+    for entry in &manifest.inputs {
+        let mut options = report.options.clone();
+        match entry.format {
+            Some(Format::Text) => {}
+            Some(Format::Markup(markup)) => options.markup = Some(markup),
+            Some(Format::Notebook) => options.notebook = true,
+            Some(Format::Email) => options.email = true,
+            Some(Format::Epub) => options.epub = true,
+            Some(Format::Pdf) => options.pdf = true,
+            Some(Format::Document(document)) => options.document = Some(document),
+            None => {}
+        }
+        if entry.word_rules.is_some() {
+            options.counting.word_rules = entry.word_rules;
+        }
+        for &expectation in &entry.expect {
+            options.add_expectation(expectation);
+        }
+
+        let location = directory.join(&entry.path);
+        let mut rows = Report::buffer(&options);
+        let counted = open_file(&location.to_string_lossy(), options.tail)
+            .and_then(|file| count_input(&entry.path, file, &mut rows));
+        failed |= report.absorb(rows);
+        match counted {
+            Ok(counts) => {
+                report.tally(&entry.path, counts);
+                total += counts;
+                for mismatch in entry
+                    .expect
+                    .iter()
+                    .filter_map(|expectation| expectation.mismatch(&counts))
+                {
+                    mismatches.push(format!("{}: {}", entry.path, mismatch));
+                }
+            }
+            Err(error) => {
+                report.error(&entry.path, error);
+                failed = true;
+            }
+        }
+    }
+    if manifest.inputs.len() > 1 {
+        report.total_row(total);
+    }
+
+    if !mismatches.is_empty() {
+        report.finish();
+        eprintln!("wc_rs: {}", i18n::message("counts-differ", &[]));
+        for mismatch in mismatches {
+            eprintln!("  {}", mismatch);
+        }
+        std::process::exit(UNEXPECTED_COUNTS_STATUS);
+    }
+    failed
+}
+
+/// The columns of the statistics of the lines, in the order `--line-stats` outputs them.
+const LINE_STATS_COLUMNS: [Column; 10] = [
+    Column::ShortestLine,
+    Column::ShortestLineAt,
+    Column::LongestLine,
+    Column::LongestLineAt,
+    Column::MeanLineLength,
+    Column::FewestWords,
+    Column::FewestWordsAt,
+    Column::MostWords,
+    Column::MostWordsAt,
+    Column::MeanWordsPerLine,
+];
+
+/// Returns the selected columns, or the counts of the original `wc` utility followed by any optional counts
+/// enabled.
+fn row_columns(options: &Options) -> Vec<Column> {
+    if let Some(columns) = &options.columns {
+        return columns.clone();
+    }
+
+    let mut columns = options
+        .counters
+        .clone()
+        .unwrap_or_else(|| vec![Column::Lines, Column::Words, Column::Bytes]);
+    let optional: [(bool, &[Column]); 10] = [
+        (options.counting.emoji, &[Column::Emoji]),
+        (options.counting.graphemes, &[Column::Graphemes]),
+        (
+            options.counting.token_classes,
+            &[Column::Urls, Column::Emails, Column::Numbers],
+        ),
+        (
+            options.counting.social,
+            &[Column::Hashtags, Column::Mentions],
+        ),
+        (options.email, &[Column::Messages]),
+        (options.counting.syllables, &[Column::Syllables]),
+        (
+            options.counting.whitespace,
+            &[
+                Column::Spaces,
+                Column::Tabs,
+                Column::Newlines,
+                Column::Nbsp,
+                Column::OtherSpaces,
+            ],
+        ),
+        (
+            options.counting.log_levels,
+            &[
+                Column::ErrorLines,
+                Column::WarnLines,
+                Column::InfoLines,
+                Column::DebugLines,
+            ],
+        ),
+        (options.counting.line_stats, &LINE_STATS_COLUMNS),
+        (options.allocated, &[Column::Allocated]),
+    ];
+    for (enabled, optional) in optional {
+        if enabled {
+            columns.extend_from_slice(optional);
+        }
+    }
+    columns
+}
+
+/// Formats `counts` for the input `name`, out of the total counts of all rows `total`, in the selected
+/// columns, or in the columns of the original `wc` utility followed by any optional counts and the name.
+fn format_row(counts: &Counts, total: &Counts, options: &Options, name: &str) -> String {
+    if let Some(columns) = &options.columns {
+        if options.bare {
+            return columns[0].value_with_precision(counts, total, name, options.precision);
+        }
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| column.format(counts, total, name, options.precision))
+            .collect();
+        return row.join(" ");
+    }
+
+    let counters =
+        options
+            .counters
+            .as_deref()
+            .unwrap_or(&[Column::Lines, Column::Words, Column::Bytes]);
+    if options.bare {
+        return counters[0].value_with_precision(counts, total, name, options.precision);
+    }
+    // The first column is one wider, as in the original `wc` utility.
+    let mut row = String::new();
+    for (index, counter) in counters.iter().enumerate() {
+        let value = counter.value_with_precision(counts, total, name, options.precision);
+        match index {
+            0 => row.push_str(&format!("{:>8}", value)),
+            _ => row.push_str(&format!(" {:>7}", value)),
+        }
+    }
+    if options.counting.emoji {
+        row.push_str(&format!(" {emoji:7}", emoji = counts.emoji));
+    }
+    if options.counting.graphemes {
+        row.push_str(&format!(" {graphemes:7}", graphemes = counts.graphemes));
+    }
+    if options.counting.token_classes {
+        row.push_str(&format!(
+            " {urls:7} {emails:7} {numbers:7}",
+            urls = counts.tokens.urls,
+            emails = counts.tokens.emails,
+            numbers = counts.tokens.numbers,
+        ));
+    }
+    if options.counting.social {
+        row.push_str(&format!(
+            " {hashtags:7} {mentions:7}",
+            hashtags = counts.tokens.hashtags,
+            mentions = counts.tokens.mentions,
+        ));
+    }
+    if options.email {
+        row.push_str(&format!(" {messages:7}", messages = counts.messages));
+    }
+    if options.counting.syllables {
+        row.push_str(&format!(" {syllables:7}", syllables = counts.syllables));
+    }
+    if options.counting.whitespace {
+        let whitespace = &counts.whitespace;
+        row.push_str(&format!(
+            " {spaces:7} {tabs:7} {newlines:7} {nbsp:7} {other:7}",
+            spaces = whitespace.spaces,
+            tabs = whitespace.tabs,
+            newlines = whitespace.newlines,
+            nbsp = whitespace.nbsp,
+            other = whitespace.other,
+        ));
+    }
+    if options.counting.log_levels {
+        let log_levels = &counts.log_levels;
+        row.push_str(&format!(
+            " {error:7} {warn:7} {info:7} {debug:7}",
+            error = log_levels.error,
+            warn = log_levels.warn,
+            info = log_levels.info,
+            debug = log_levels.debug,
+        ));
+    }
+    if options.counting.line_stats {
+        for column in &LINE_STATS_COLUMNS {
+            let value = column.value_with_precision(counts, total, name, options.precision);
+            row.push_str(&format!(" {:>7}", value));
+        }
+    }
+    if options.allocated {
+        row.push_str(&format!(" {:7}", counts.allocated));
+    }
+    if !name.is_empty() {
+        row.push(' ');
+        row.push_str(name);
+    }
+    row
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Returns a ZIP archive storing `files`, each with its name and contents.
+    fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        for (name, contents) in files {
+            let offset = data.len() as u32;
+            data.extend_from_slice(b"PK\x03\x04\x14\0");
+            data.extend_from_slice(&[0; 20]);
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(contents);
+
+            directory.extend_from_slice(b"PK\x01\x02\x14\0\x14\0");
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+
+        let directory_offset = data.len() as u32;
+        data.extend_from_slice(&directory);
+        data.extend_from_slice(b"PK\x05\x06\0\0\0\0");
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&directory_offset.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
     #[test]
-    fn test_flux_over_byte_string() {
+    fn test_wc_all_preserves_input_order() {
+        // Larger inputs first, so that later inputs finish counting before earlier ones.
+        let inputs: Vec<Vec<u8>> = (0..32)
+            .rev()
+            .map(|words| "word ".repeat(words * 200).into_bytes())
+            .collect();
+        let options = Options::default();
+        let words: Vec<u64> = wc_all(&inputs, &options)
+            .iter()
+            .map(|counts| counts.as_ref().unwrap().words)
+            .collect();
+
         assert_eq!(
-            flux_over_byte_string("testing one two three".as_bytes()),
-            Some(Flux::new(CharType::NotSpace, 4, 0, CharType::NotSpace))
+            words,
+            (0..32).rev().map(|words| words * 200).collect::<Vec<_>>()
         );
     }
 
     #[test]
-    fn test_span_opt_not_space_to_not_space() {
-        let flux_l = flux_over_byte_string("testing on");
-        let flux_r = flux_over_byte_string("e two three");
+    fn test_count_from_size() {
+        let path = env::temp_dir().join(format!("wc_rs-count-from-size-{}", std::process::id()));
+        std::fs::write(&path, "one two\nthree\n").unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let mut options = Options::default();
+        let mut report = Report::buffer(&options);
+        assert!(count_from_size(&path, &mut report).is_none());
+
+        options.select_counters("c");
+        let mut report = Report::buffer(&options);
+        let counts = count_from_size(&path, &mut report).unwrap().unwrap();
+        assert_eq!((counts.bytes, counts.lines), (14, 0));
+        assert_eq!(report.rows, vec![(path.clone(), counts)]);
+
+        options.stop_after = Some(StopAfter::Bytes(3));
+        assert!(count_from_size(&path, &mut Report::buffer(&options)).is_none());
+        options.stop_after = None;
+        assert!(count_from_size("-", &mut Report::buffer(&options)).is_none());
+        assert!(count_from_size("", &mut Report::buffer(&options)).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_count_files_preserves_input_order() {
+        let directory = env::temp_dir().join(format!("wc_rs-count-files-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut paths: Vec<String> = (0..16)
+            .rev()
+            .map(|words| {
+                let path = directory.join(format!("{}.txt", words));
+                std::fs::write(&path, "word ".repeat(words * 1000)).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect();
+        paths.insert(1, directory.join("missing").to_string_lossy().into_owned());
+
+        let options = Options::default();
+        let mut report = Report::buffer(&options);
+        let failed = count_files(&paths, &mut report);
+        std::fs::remove_dir_all(&directory).unwrap();
 
+        assert!(failed);
+        let words: Vec<u64> = report.rows.iter().map(|(_, counts)| counts.words).collect();
         assert_eq!(
-            span_opt(flux_l, flux_r),
-            Some(Flux::new(CharType::NotSpace, 4, 0, CharType::NotSpace))
+            words,
+            (0..16).rev().map(|words| words * 1000).collect::<Vec<_>>()
+        );
+        assert_eq!(report.summary.map(|total| total.words), Some(120_000));
+    }
+
+    #[test]
+    fn test_total_counts_each_input_once() {
+        let path = env::temp_dir().join(format!("wc_rs-total-{}.zip", std::process::id()));
+        let archive = zip_archive(&[("a.txt", b"one\ntwo\n"), ("b.txt", b"three\n")]);
+        std::fs::write(&path, archive).unwrap();
+        let paths = vec![path.to_string_lossy().into_owned()];
+
+        let mut options = Options {
+            archive: true,
+            ..Options::default()
+        };
+        options.add_expectation("lines=3".parse().unwrap());
+        let mut report = Report::buffer(&options);
+        let failed = count_files(&paths, &mut report);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!failed);
+        assert_eq!(report.rows.len(), 3);
+        assert_eq!(report.total.lines, 3);
+        assert_eq!(options.expect[0].mismatch(&report.total), None);
+    }
+
+    #[test]
+    fn test_unreadable_archive_member_is_reported() {
+        let path = env::temp_dir().join(format!("wc_rs-unreadable-{}.zip", std::process::id()));
+        let mut archive = zip_archive(&[("a.txt", b"one\ntwo\n"), ("b.txt", b"three\n")]);
+        // The local header of the second member, after the 30 byte header, name and data of the first.
+        archive[30 + 5 + 8] = 0;
+        std::fs::write(&path, archive).unwrap();
+        let paths = vec![path.to_string_lossy().into_owned()];
+
+        let options = Options {
+            archive: true,
+            ..Options::default()
+        };
+        let mut report = Report::buffer(&options);
+        let failed = count_files(&paths, &mut report);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(failed);
+        let errors: Vec<&str> = report
+            .errors
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(errors, vec![format!("{}!b.txt", paths[0])]);
+        assert_eq!(report.total.lines, 2);
+    }
+
+    #[test]
+    fn test_archive_row_is_not_totalled() {
+        let path = env::temp_dir().join(format!("wc_rs-archive-{}.zip", std::process::id()));
+        let archive = zip_archive(&[("a.txt", b"one\ntwo\nthree\n"), ("b.txt", b"four\n")]);
+        std::fs::write(&path, archive).unwrap();
+        let paths = vec![path.to_string_lossy().into_owned()];
+
+        let mut options = Options {
+            archive: true,
+            ..Options::default()
+        };
+        options.set_columns(columns::parse_list("lines,lines_share,file").unwrap());
+        let mut report = Report::buffer(&options);
+        let failed = count_files(&paths, &mut report);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!failed);
+        let shares: Vec<String> = report
+            .rows
+            .iter()
+            .map(|(name, counts)| Column::LinesShare.value(counts, &report.total, name))
+            .collect();
+        assert_eq!(shares, vec!["75.0%", "25.0%", "100.0%"]);
+        assert_eq!(report.total.lines, 4);
+    }
+
+    #[test]
+    fn test_log_bucket_rows_are_not_totalled() {
+        let path = env::temp_dir().join(format!("wc_rs-log-buckets-{}.log", std::process::id()));
+        let log = "2019-06-01 12:59:59 ERROR failed\n2019-06-01 13:00:00 INFO retrying\n";
+        std::fs::write(&path, log).unwrap();
+        let paths = vec![path.to_string_lossy().into_owned()];
+
+        let options = Options {
+            log_buckets: Some(BucketSize::Hour),
+            ..Options::default()
+        };
+        let mut report = Report::buffer(&options);
+        let failed = count_files(&paths, &mut report);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!failed);
+        let lines: Vec<u64> = report.rows.iter().map(|(_, counts)| counts.lines).collect();
+        assert_eq!(lines, vec![1, 1, 2]);
+        assert_eq!(report.total.lines, 2);
+    }
+
+    #[test]
+    fn test_structured_output_totals_inputs() {
+        let directory = env::temp_dir().join(format!("wc_rs-output-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let archive = directory.join("a.zip");
+        std::fs::write(
+            &archive,
+            zip_archive(&[("a.txt", b"one two\n"), ("b.txt", b"three\n")]),
+        )
+        .unwrap();
+        let log = directory.join("b.log");
+        let text = "2019-06-01 12:59:59 ERROR failed\n2019-06-01 13:00:00 INFO retrying\n";
+        std::fs::write(&log, text).unwrap();
+        let archive = vec![archive.to_string_lossy().into_owned()];
+        let log = vec![log.to_string_lossy().into_owned()];
+
+        let mut options = Options {
+            archive: true,
+            output: Output::Json,
+            ..Options::default()
+        };
+        let mut report = Report::buffer(&options);
+        assert!(!count_files(&archive, &mut report));
+        let json = output::render_json(
+            &report.rows,
+            &report.total,
+            &row_columns(&options),
+            &[],
+            None,
+            None,
+        );
+        assert!(json.ends_with("\"total\": {\"lines\":2,\"words\":3,\"bytes\":14}\n}\n"));
+
+        options.archive = false;
+        options.log_buckets = Some(BucketSize::Hour);
+        options.output = Output::Csv;
+        options.set_columns(columns::parse_list("lines,lines_share").unwrap());
+        let mut report = Report::buffer(&options);
+        assert!(!count_files(&log, &mut report));
+        let csv = output::render_delimited(
+            &report.rows,
+            &report.total,
+            &row_columns(&options),
+            options.output,
+            None,
         );
+        std::fs::remove_dir_all(&directory).unwrap();
+        let shares: Vec<&str> = csv
+            .lines()
+            .map(|record| record.rsplit(',').next().unwrap())
+            .collect();
+        assert_eq!(shares, vec!["lines_share", "50.0", "50.0", "100.0"]);
+    }
+
+    #[test]
+    fn test_delimited_summary_to() {
+        let directory = env::temp_dir().join(format!("wc_rs-summary-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let paths: Vec<String> = [("a.txt", "one two\n"), ("b.txt", "three\n")]
+            .iter()
+            .map(|(name, text)| {
+                let path = directory.join(name);
+                std::fs::write(&path, text).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect();
+        let summary = directory.join("summary.csv");
+
+        let options = Options {
+            output: Output::Csv,
+            summary_to: Some(summary.to_string_lossy().into_owned()),
+            ..Options::default()
+        };
+        let mut report = Report::new(&options);
+        assert!(!count_files(&paths, &mut report));
+        report.finish();
+        let written = std::fs::read_to_string(&summary).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(written, "total,2,3,14\n");
     }
 
     #[test]
-    fn test_span_opt_space_to_space() {
-        let flux_l = flux_over_byte_string("testing one ");
-        let flux_r = flux_over_byte_string(" two three");
+    fn test_git_tracked() {
+        let directory = env::temp_dir().join(format!("wc_rs-git-{}", std::process::id()));
+        let subdirectory = directory.join("sub");
+        std::fs::create_dir_all(&subdirectory).unwrap();
+        std::fs::write(subdirectory.join("tracked.txt"), "one two\n").unwrap();
+        std::fs::write(subdirectory.join("untracked.txt"), "three\n").unwrap();
+        for args in [&["init", "-q"][..], &["add", "sub/tracked.txt"]] {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&directory)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let operand = subdirectory.to_string_lossy().into_owned();
+        let tracked = git::tracked_paths(&operand);
+        let options = Options {
+            recursive: true,
+            git_tracked: true,
+            ..Options::default()
+        };
+        let mut report = Report::buffer(&options);
+        let (files, failed) = walk_operands(&[operand], &mut report);
+        let counted = count_files(&files, &mut report);
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Paths are listed relative to the subdirectory, not the root of the repository.
+        assert_eq!(tracked, Ok(vec!["tracked.txt".to_string()]));
+        assert!(!failed && !counted);
+        let tracked_file = subdirectory.join("tracked.txt");
+        assert_eq!(files, vec![tracked_file.to_string_lossy().into_owned()]);
+        assert_eq!(report.total.words, 2);
+    }
+
+    #[test]
+    fn test_epub_chapter_shares() {
+        let path = env::temp_dir().join(format!("wc_rs-epub-{}.epub", std::process::id()));
+        let book = zip_archive(&[
+            (
+                "META-INF/container.xml",
+                b"<container><rootfiles><rootfile full-path=\"book.opf\"/></rootfiles></container>",
+            ),
+            (
+                "book.opf",
+                b"<package><manifest><item id=\"a\" href=\"a.xhtml\"/><item id=\"b\" href=\"b.xhtml\"/>\
+                  </manifest><spine><itemref idref=\"a\"/><itemref idref=\"b\"/></spine></package>",
+            ),
+            ("a.xhtml", b"<html><body><p>one two three</p></body></html>"),
+            ("b.xhtml", b"<html><body><p>four</p></body></html>"),
+        ]);
+        std::fs::write(&path, book).unwrap();
+        let paths = vec![path.to_string_lossy().into_owned()];
+
+        let mut options = Options {
+            epub: true,
+            ..Options::default()
+        };
+        options.set_columns(columns::parse_list("words,words_share").unwrap());
+        let mut report = Report::buffer(&options);
+        let failed = count_files(&paths, &mut report);
+        std::fs::remove_file(&path).unwrap();
 
+        assert!(!failed);
+        let shares: Vec<String> = report
+            .rows
+            .iter()
+            .map(|(name, counts)| Column::WordsShare.value(counts, &report.total, name))
+            .collect();
+        assert_eq!(shares, vec!["75.0%", "25.0%", "100.0%"]);
+        assert_eq!(report.total.words, 4);
+    }
+
+    #[test]
+    fn test_bare_error() {
+        let mut options = Options {
+            bare: true,
+            counters: Some(vec![Column::Words]),
+            ..Options::default()
+        };
+        assert_eq!(bare_error(&options, 1), None);
+        assert_eq!(
+            bare_error(&options, 2),
+            Some("bare-requires-a-single-input")
+        );
+        options.concat = true;
+        assert_eq!(bare_error(&options, 2), None);
+        options.archive = true;
+        assert_eq!(bare_error(&options, 1), Some("bare-with-parts"));
+        options.counters = Some(vec![Column::Words, Column::Lines]);
         assert_eq!(
-            span_opt(flux_l, flux_r),
-            Some(Flux::new(CharType::NotSpace, 4, 0, CharType::NotSpace))
+            bare_error(&options, 1),
+            Some("bare-requires-a-single-count")
         );
+        options.bare = false;
+        assert_eq!(bare_error(&options, 2), None);
+    }
+
+    #[test]
+    fn test_input_name() {
+        let mut options = Options::default();
+        assert_eq!(options.input_name("-"), "-");
+        options.stdin_name = Some("upload.csv".to_string());
+        assert_eq!(options.input_name(""), "upload.csv");
+        assert_eq!(options.input_name("-"), "upload.csv");
+        assert_eq!(options.input_name("./-"), "./-");
+    }
+
+    #[test]
+    fn test_parse_threads() {
+        assert_eq!(parse_threads("4"), Ok(4));
+        assert!(parse_threads("0").is_err());
+        assert!(parse_threads("-1").is_err());
+        assert!(parse_threads("all").is_err());
+    }
+
+    #[test]
+    fn test_parse_buffer_size() {
+        assert_eq!(parse_buffer_size("4096"), Ok(4096));
+        assert_eq!(parse_buffer_size("64K"), Ok(64 << 10));
+        assert_eq!(parse_buffer_size("4M"), Ok(4 << 20));
+        assert!(parse_buffer_size("0").is_err());
+        assert!(parse_buffer_size("4X").is_err());
     }
 }
@@ -0,0 +1,319 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+use wc_rs::document::Document;
+use wc_rs::i18n::Message;
+use wc_rs::markup::Markup;
+use wc_rs::rules::WordRules;
+
+use crate::expect::Expectation;
+
+/// The format of an input listed by a manifest, as its `format` key names it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Format {
+    /// Plain text, counted as it is.
+    Text,
+    /// Marked up text, whose prose is counted.
+    Markup(Markup),
+    /// A Jupyter notebook, whose cells are counted by kind.
+    Notebook,
+    /// An mbox mailbox or email message.
+    Email,
+    /// An EPUB e-book, whose chapters are counted separately.
+    Epub,
+    /// A PDF document.
+    Pdf,
+    /// A word processor document.
+    Document(Document),
+}
+
+impl FromStr for Format {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "subtitles" => Ok(Format::Markup(Markup::Subtitles)),
+            "latex" => Ok(Format::Markup(Markup::Latex)),
+            "asciidoc" => Ok(Format::Markup(Markup::AsciiDoc)),
+            "rst" => Ok(Format::Markup(Markup::Rst)),
+            "ipynb" => Ok(Format::Notebook),
+            "email" => Ok(Format::Email),
+            "epub" => Ok(Format::Epub),
+            "pdf" => Ok(Format::Pdf),
+            "docx" => Ok(Format::Document(Document::Docx)),
+            "odt" => Ok(Format::Document(Document::Odt)),
+            _ => Err(Message::new("unknown-format").arg("value", s)),
+        }
+    }
+}
+
+/// An input listed by a manifest, with the options it is counted with and the counts it is expected to have.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Entry {
+    /// The path of the input, relative to the directory of the manifest unless absolute.
+    pub path: String,
+    /// The format of the input, or `None` to count it as the command line directs.
+    pub format: Option<Format>,
+    /// The rules used to split the words of the input, or `None` to split them as the command line directs.
+    pub word_rules: Option<WordRules>,
+    /// The counts the input is expected to have.
+    pub expect: Vec<Expectation>,
+}
+
+/// A value of a key of a manifest.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+enum Value {
+    String(String),
+    Integer(u64),
+    Table(Vec<(String, Value)>),
+}
+
+/// Parses the value at the start of `chars`, a string, a whole number or an inline table of either.
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<Value, Message> {
+    skip_spaces(chars);
+    match chars.peek() {
+        Some('"') => {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => return Ok(Value::String(string)),
+                    Some('\\') => match chars.next() {
+                        Some('n') => string.push('\n'),
+                        Some('t') => string.push('\t'),
+                        Some(c @ '"') | Some(c @ '\\') => string.push(c),
+                        _ => return Err(Message::new("manifest-unsupported-escape")),
+                    },
+                    Some(c) => string.push(c),
+                    None => return Err(Message::new("manifest-unterminated-string")),
+                }
+            }
+        }
+        Some('{') => {
+            chars.next();
+            let mut table = Vec::new();
+            loop {
+                skip_spaces(chars);
+                if chars.peek() == Some(&'}') && table.is_empty() {
+                    chars.next();
+                    return Ok(Value::Table(table));
+                }
+                let key = parse_key(chars)?;
+                expect_char(chars, '=')?;
+                table.push((key, parse_value(chars)?));
+                skip_spaces(chars);
+                match chars.next() {
+                    Some(',') => {}
+                    Some('}') => return Ok(Value::Table(table)),
+                    _ => return Err(Message::new("manifest-expected-separator")),
+                }
+            }
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let mut digits = String::new();
+            while let Some(&c) = chars.peek() {
+                if !c.is_ascii_digit() && c != '_' {
+                    break;
+                }
+                if c != '_' {
+                    digits.push(c);
+                }
+                chars.next();
+            }
+            digits
+                .parse()
+                .map(Value::Integer)
+                .map_err(|_| Message::new("manifest-invalid-number"))
+        }
+        _ => Err(Message::new("manifest-expected-value")),
+    }
+}
+
+/// Parses the bare or dotted key at the start of `chars`.
+fn parse_key(chars: &mut Peekable<Chars>) -> Result<String, Message> {
+    skip_spaces(chars);
+    let mut key = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_alphanumeric() && !matches!(c, '_' | '-' | '.') {
+            break;
+        }
+        key.push(c);
+        chars.next();
+    }
+    if key.is_empty() {
+        return Err(Message::new("manifest-expected-key"));
+    }
+    Ok(key)
+}
+
+/// Skips the spaces and tabs at the start of `chars`.
+fn skip_spaces(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|&c| c == ' ' || c == '\t').is_some() {}
+}
+
+/// Consumes `expected` at the start of `chars`, after any spaces.
+fn expect_char(chars: &mut Peekable<Chars>, expected: char) -> Result<(), Message> {
+    skip_spaces(chars);
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        _ => Err(Message::new("manifest-expected").arg("expected", expected)),
+    }
+}
+
+/// The inputs of a batch run, read from a manifest written in a subset of TOML, such as:
+///
+/// ```toml
+/// [[input]]
+/// path = "exports/orders.csv"
+/// expect = { lines = 12345 }
+///
+/// [[input]]
+/// path = "docs/guide.adoc"
+/// format = "asciidoc"
+/// word_rules = "english"
+/// expect.words = 5000
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Manifest {
+    /// The inputs, in the order listed.
+    pub inputs: Vec<Entry>,
+}
+
+impl Manifest {
+    /// Parses `text`, returning a description of the first error and its line number if it is invalid.
+    pub fn parse(text: &str) -> Result<Self, Message> {
+        let mut manifest = Manifest::default();
+        for (index, line) in text.lines().enumerate() {
+            manifest.parse_line(line).map_err(|error| {
+                Message::new("manifest-line")
+                    .arg("line", index + 1)
+                    .arg("error", error)
+            })?;
+        }
+        match manifest
+            .inputs
+            .iter()
+            .position(|entry| entry.path.is_empty())
+        {
+            Some(index) => Err(Message::new("manifest-no-path").arg("input", index + 1)),
+            None => Ok(manifest),
+        }
+    }
+
+    /// Parses `line`, a table header or a key and its value, adding it to the receiver.
+    fn parse_line(&mut self, line: &str) -> Result<(), Message> {
+        let mut chars = line.chars().peekable();
+        skip_spaces(&mut chars);
+        match chars.peek() {
+            None | Some('#') => return Ok(()),
+            Some('[') => {
+                if line.trim() != "[[input]]" {
+                    return Err(Message::new("manifest-unknown-table").arg("table", line.trim()));
+                }
+                self.inputs.push(Entry {
+                    path: String::new(),
+                    format: None,
+                    word_rules: None,
+                    expect: Vec::new(),
+                });
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let key = parse_key(&mut chars)?;
+        expect_char(&mut chars, '=')?;
+        let value = parse_value(&mut chars)?;
+        skip_spaces(&mut chars);
+        if chars.next().is_some_and(|c| c != '#') {
+            return Err(Message::new("manifest-text-after-value"));
+        }
+
+        let entry = self
+            .inputs
+            .last_mut()
+            .ok_or_else(|| Message::new("manifest-key-outside-input"))?;
+        match (key.as_str(), value) {
+            ("path", Value::String(path)) => entry.path = path,
+            ("format", Value::String(format)) => entry.format = Some(format.parse()?),
+            ("word_rules", Value::String(rules)) => entry.word_rules = Some(rules.parse()?),
+            ("expect", Value::Table(counts)) => {
+                for (name, value) in counts {
+                    entry.expect.push(expectation(&name, value)?);
+                }
+            }
+            (key, value) => match key.strip_prefix("expect.") {
+                Some(name) => entry.expect.push(expectation(name, value)?),
+                None => return Err(Message::new("manifest-unknown-key").arg("key", key)),
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Returns the expectation that the count `name` of an input is `value`.
+fn expectation(name: &str, value: Value) -> Result<Expectation, Message> {
+    match value {
+        Value::Integer(count) => format!("{}={}", name, count).parse(),
+        _ => Err(Message::new("manifest-count-not-a-number").arg("column", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let manifest = Manifest::parse(
+            "# Nightly export\n\
+             [[input]]\n\
+             path = \"exports/orders.csv\"  # the main table\n\
+             expect = { lines = 12_345, bytes = 1000 }\n\
+             \n\
+             [[input]]\n\
+             path = \"docs/guide \\\"v2\\\".adoc\"\n\
+             format = \"asciidoc\"\n\
+             word_rules = \"english\"\n\
+             expect.words = 5000\n",
+        )
+        .unwrap();
+
+        assert_eq!(manifest.inputs.len(), 2);
+        assert_eq!(manifest.inputs[0].path, "exports/orders.csv");
+        assert_eq!(
+            manifest.inputs[0].expect,
+            vec![
+                "lines=12345".parse().unwrap(),
+                "bytes=1000".parse().unwrap()
+            ]
+        );
+        assert_eq!(manifest.inputs[1].path, "docs/guide \"v2\".adoc");
+        assert_eq!(
+            manifest.inputs[1].format,
+            Some(Format::Markup(Markup::AsciiDoc))
+        );
+        assert_eq!(manifest.inputs[1].word_rules, Some(WordRules::English));
+        assert_eq!(
+            manifest.inputs[1].expect,
+            vec!["words=5000".parse().unwrap()]
+        );
+
+        assert_eq!(
+            Manifest::parse("path = \"a\"\n").map_err(|error| error.to_string()),
+            Err("line 1: keys must follow an [[input]] header".to_string())
+        );
+        assert!(Manifest::parse("[[input]]\nformat = \"text\"\n").is_err());
+        assert!(Manifest::parse("[[input]]\npath = \"a\"\nformat = \"xml\"\n").is_err());
+        assert!(Manifest::parse("[[input]]\npath = \"a\"\nexpect.pages = 3\n").is_err());
+        assert!(Manifest::parse("[[input]]\npath = \"a\" b\n").is_err());
+    }
+}
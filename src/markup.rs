@@ -0,0 +1,247 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::{BufRead, Read};
+
+mod asciidoc;
+mod latex;
+mod rst;
+mod subtitles;
+
+use self::asciidoc::AsciiDocFilter;
+use self::latex::LatexFilter;
+use self::rst::RstFilter;
+use self::subtitles::SubtitleFilter;
+
+/// Size of the batch of prose extracted before it is made available to the reader.
+const PROSE_BATCH_SIZE: usize = 64 * 1024;
+
+/// A markup language whose prose is extracted before counting.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Markup {
+    /// SubRip (SRT) and WebVTT subtitles.
+    Subtitles,
+    /// LaTeX documents.
+    Latex,
+    /// AsciiDoc documents.
+    AsciiDoc,
+    /// reStructuredText documents.
+    Rst,
+}
+
+impl Markup {
+    /// Returns a new filter extracting the prose of the receiver.
+    fn filter(self) -> Box<dyn LineFilter> {
+        match self {
+            Markup::Subtitles => Box::new(SubtitleFilter::default()),
+            Markup::Latex => Box::new(LatexFilter::default()),
+            Markup::AsciiDoc => Box::new(AsciiDocFilter::default()),
+            Markup::Rst => Box::new(RstFilter::default()),
+        }
+    }
+}
+
+/// Extracts the prose from a markup language one line at a time.
+pub trait LineFilter {
+    /// Appends the prose of `line`, including its line terminator if any, to `prose`.
+    fn filter_line(&mut self, line: &[u8], prose: &mut Vec<u8>);
+
+    /// Appends any prose held back by the filter at the end of the input to `prose`.
+    fn finish(&mut self, _prose: &mut Vec<u8>) {}
+}
+
+/// Returns `line` without leading and trailing ASCII whitespace.
+pub fn trim(line: &[u8]) -> &[u8] {
+    let start = line
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(line.len());
+    let end = line
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(start, |index| index + 1);
+    &line[start..end]
+}
+
+/// Returns the line terminator at the end of `line`, if any.
+pub fn terminator(line: &[u8]) -> &[u8] {
+    let length = line
+        .iter()
+        .rev()
+        .take_while(|&&byte| byte == b'\n' || byte == b'\r')
+        .count();
+    &line[line.len() - length..]
+}
+
+/// Returns the number of leading spaces and tabs in `line`.
+pub fn indentation(line: &[u8]) -> usize {
+    line.iter()
+        .take_while(|&&byte| byte == b' ' || byte == b'\t')
+        .count()
+}
+
+/// Returns the byte `line` consists of, ignoring surrounding whitespace, if it repeats a single byte.
+pub fn repeated_byte(line: &[u8]) -> Option<u8> {
+    let text = trim(line);
+    let first = *text.first()?;
+    if text.iter().all(|&byte| byte == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+/// A reader yielding only the prose of a markup language read from an underlying reader.
+pub struct MarkupReader<R> {
+    /// The underlying reader.
+    inner: R,
+    /// The filter extracting prose from each line.
+    filter: Box<dyn LineFilter>,
+    /// The most recent line read from the underlying reader.
+    line: Vec<u8>,
+    /// The prose extracted and not yet consumed.
+    prose: Vec<u8>,
+    /// The position of the first unconsumed byte of prose.
+    position: usize,
+    /// Whether the underlying reader has been exhausted.
+    finished: bool,
+}
+
+impl<R> MarkupReader<R>
+where
+    R: BufRead,
+{
+    /// Returns a new reader yielding the prose of `markup` read from `inner`.
+    pub fn new(inner: R, markup: Markup) -> Self {
+        MarkupReader::with_filter(inner, markup.filter())
+    }
+
+    /// Returns a new reader yielding the lines of `inner` as transformed by `filter`.
+    pub fn with_filter(inner: R, filter: Box<dyn LineFilter>) -> Self {
+        MarkupReader {
+            inner,
+            filter,
+            line: Vec::new(),
+            prose: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+}
+
+impl<R> Read for MarkupReader<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl<R> BufRead for MarkupReader<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.position < self.prose.len() {
+            return Ok(&self.prose[self.position..]);
+        }
+
+        self.prose.clear();
+        self.position = 0;
+
+        // Extract prose a batch of lines at a time so it is counted in reasonably sized buffers.
+        while self.prose.len() < PROSE_BATCH_SIZE && !self.finished {
+            self.line.clear();
+            if self.inner.read_until(b'\n', &mut self.line)? == 0 {
+                self.filter.finish(&mut self.prose);
+                self.finished = true;
+            } else {
+                self.filter.filter_line(&self.line, &mut self.prose);
+            }
+        }
+
+        Ok(&self.prose[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.prose.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prose(input: &str, markup: Markup) -> String {
+        let mut prose = String::new();
+        MarkupReader::new(input.as_bytes(), markup)
+            .read_to_string(&mut prose)
+            .unwrap();
+        prose
+    }
+
+    #[test]
+    fn test_srt_subtitles() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,000\r\n<i>Hello</i> there.\r\n\r\n\
+                   2\r\n00:00:03,000 --> 00:00:04,000 X1:40\r\n{\\an8}General Kenobi!\r\n12 parsecs\r\n";
+
+        assert_eq!(
+            prose(srt, Markup::Subtitles),
+            "Hello there.\r\nGeneral Kenobi!\r\n12 parsecs\r\n"
+        );
+    }
+
+    #[test]
+    fn test_vtt_subtitles() {
+        let vtt = "WEBVTT - Example\nKind: captions\n\nNOTE a comment\nspanning lines\n\n\
+                   intro\n00:01.000 --> 00:02.000 align:start\nWelcome back\n";
+
+        assert_eq!(prose(vtt, Markup::Subtitles), "Welcome back\n");
+    }
+
+    #[test]
+    fn test_latex() {
+        let tex = "\\documentclass{article}\n\\usepackage{amsmath}\n\\begin{document}\n\
+                   \\section{Intro} % not counted\n\
+                   We show $x^2$ is \\emph{positive}~\\cite{knuth} (see Eq.~\\ref{eq:1}).\n\
+                   \\begin{equation}\n  x^2 \\geq 0 \\label{eq:1}\n\\end{equation}\n\
+                   It costs 5\\% \\[ y \\] less.\n\\end{document}\nignored\n";
+
+        assert_eq!(
+            prose(tex, Markup::Latex),
+            "\n Intro \nWe show  is  positive (see Eq. ).\n\n\n\nIt costs 5%  less.\n"
+        );
+    }
+
+    #[test]
+    fn test_asciidoc() {
+        let adoc = "= Guide\n:toc: left\n\n// a comment\n[source,rust]\n----\nfn main() {}\n----\n\
+                    == Usage\n* Run it\n.Example title\nimage::shot.png[Screenshot]\n====\nInside.\n====\n";
+
+        assert_eq!(
+            prose(adoc, Markup::AsciiDoc),
+            "Guide\n\nUsage\nRun it\nExample title\nInside.\n"
+        );
+    }
+
+    #[test]
+    fn test_rst() {
+        let rst = "Title\n=====\n\n.. _target:\n\nSee :ref:`install` for this::\n\n    $ make\n\n\
+                   .. code-block:: rust\n   :linenos:\n\n   fn main() {}\n\n\
+                   .. note:: Mind the gap.\n   :class: x\n\n   Really.\n";
+
+        assert_eq!(
+            prose(rst, Markup::Rst),
+            "Title\n\nSee `install` for this:\nMind the gap.\n\nReally.\n"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{repeated_byte, terminator, trim, LineFilter};
+
+/// Extracts the prose from AsciiDoc documents.
+///
+/// Comments, listing, literal and passthrough blocks, attribute entries, block attributes and anchors,
+/// and block macros such as `include::` and `image::` are skipped. Section, list and block title markers
+/// are removed, as are the delimiters of blocks whose contents are prose.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct AsciiDocFilter {
+    /// The delimiter line closing the skipped block the filter is within, if any.
+    closing_delimiter: Option<Vec<u8>>,
+}
+
+impl LineFilter for AsciiDocFilter {
+    fn filter_line(&mut self, line: &[u8], prose: &mut Vec<u8>) {
+        let text = trim(line);
+
+        if let Some(delimiter) = &self.closing_delimiter {
+            if text == &delimiter[..] {
+                self.closing_delimiter = None;
+            }
+            return;
+        }
+
+        // Comment, listing, literal and passthrough blocks are skipped, other delimiters are dropped.
+        match repeated_byte(text) {
+            Some(b'/') | Some(b'-') | Some(b'.') | Some(b'+') if text.len() >= 4 => {
+                self.closing_delimiter = Some(text.to_vec());
+                return;
+            }
+            Some(b'=') | Some(b'*') | Some(b'_') if text.len() >= 4 => return,
+            Some(b'-') if text.len() == 2 => return,
+            _ => {}
+        }
+
+        let is_comment = text.starts_with(b"//");
+        let is_attribute_entry = text.starts_with(b":") && text[1..].contains(&b':');
+        let is_block_attribute_list = text.starts_with(b"[") && text.ends_with(b"]");
+        let is_block_macro = text
+            .windows(2)
+            .position(|window| window == b"::")
+            .is_some_and(|index| {
+                index > 0
+                    && text[..index].iter().all(u8::is_ascii_alphanumeric)
+                    && text.ends_with(b"]")
+            });
+        let is_table_delimiter = text.starts_with(b"|===");
+        if is_comment
+            || is_attribute_entry
+            || is_block_attribute_list
+            || is_block_macro
+            || is_table_delimiter
+        {
+            return;
+        }
+
+        // Section titles, list items and block titles are prose once their markers are removed.
+        let marker = text
+            .iter()
+            .take_while(|&&byte| byte == b'=' || byte == b'*' || byte == b'-' || byte == b'.')
+            .count();
+        let body = match text.get(marker) {
+            Some(b' ') if marker > 0 => &text[marker + 1..],
+            Some(_) if marker == 1 && text[0] == b'.' => &text[1..],
+            _ => text,
+        };
+
+        prose.extend_from_slice(body);
+        prose.extend_from_slice(terminator(line));
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{terminator, trim, LineFilter};
+
+/// Commands whose arguments are not part of the prose.
+const SKIPPED_ARGUMENT_COMMANDS: [&[u8]; 24] = [
+    b"begin",
+    b"bibliography",
+    b"bibliographystyle",
+    b"cite",
+    b"citep",
+    b"citet",
+    b"documentclass",
+    b"end",
+    b"eqref",
+    b"hspace",
+    b"include",
+    b"includegraphics",
+    b"input",
+    b"label",
+    b"newcommand",
+    b"newenvironment",
+    b"nocite",
+    b"pageref",
+    b"ref",
+    b"renewcommand",
+    b"setlength",
+    b"url",
+    b"usepackage",
+    b"vspace",
+];
+
+/// Environments whose contents are typeset in math mode.
+const MATH_ENVIRONMENTS: [&[u8]; 9] = [
+    b"align",
+    b"alignat",
+    b"displaymath",
+    b"eqnarray",
+    b"equation",
+    b"flalign",
+    b"gather",
+    b"math",
+    b"multline",
+];
+
+/// Returns the name of the command at the start of `text`, which follows a backslash.
+///
+/// A command name is either a run of ASCII letters, optionally followed by `*`, or a single other byte.
+fn command_name(text: &[u8]) -> &[u8] {
+    let letters = text
+        .iter()
+        .take_while(|byte| byte.is_ascii_alphabetic())
+        .count();
+    match (letters, text.get(letters)) {
+        (0, Some(_)) => &text[..1],
+        (_, Some(b'*')) => &text[..letters + 1],
+        _ => &text[..letters],
+    }
+}
+
+/// Returns the contents of the braced group at the start of `text`, if it is closed on the same line.
+fn braced_argument(text: &[u8]) -> Option<&[u8]> {
+    let end = text.iter().position(|&byte| byte == b'}')?;
+    match text.first() {
+        Some(b'{') => Some(&text[1..end]),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `environment` is typeset in math mode.
+fn is_math_environment(environment: &[u8]) -> bool {
+    let name = environment.strip_suffix(b"*").unwrap_or(environment);
+    MATH_ENVIRONMENTS.contains(&name)
+}
+
+/// Extracts the prose from LaTeX documents in the manner of `texcount`.
+///
+/// The preamble, comments, inline and display math, math environments and the arguments of
+/// referencing and structural commands are skipped. Other commands are removed but their arguments,
+/// such as the text of `\emph{...}` or `\section{...}`, are kept.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct LatexFilter {
+    /// Whether the filter is within the preamble of the document.
+    in_preamble: bool,
+    /// Whether the end of the document has been reached.
+    ended: bool,
+    /// The delimiter ending the math mode the filter is within, if any.
+    math_end: Option<Vec<u8>>,
+    /// Whether the filter is expecting the skipped arguments of a command.
+    expecting_arguments: bool,
+    /// The closing bracket and nesting depth of the skipped argument the filter is within, if any.
+    argument: Option<(u8, usize)>,
+}
+
+impl LatexFilter {
+    /// Appends the prose of `text`, a line without its terminator, to `prose`.
+    fn scan(&mut self, text: &[u8], prose: &mut Vec<u8>) {
+        let mut index = 0;
+        while index < text.len() {
+            let byte = text[index];
+
+            // Comments run to the end of the line, in every mode.
+            if byte == b'%' {
+                return;
+            }
+
+            if let Some(math_end) = &self.math_end {
+                if text[index..].starts_with(math_end) {
+                    index += math_end.len();
+                    self.math_end = None;
+                } else {
+                    index += if byte == b'\\' { 2 } else { 1 };
+                }
+                continue;
+            }
+
+            if let Some((closing, depth)) = self.argument {
+                let opening = if closing == b'}' { b'{' } else { b'[' };
+                self.argument = match byte {
+                    b'\\' => {
+                        index += 1;
+                        Some((closing, depth))
+                    }
+                    _ if byte == opening => Some((closing, depth + 1)),
+                    _ if byte == closing && depth == 1 => None,
+                    _ if byte == closing => Some((closing, depth - 1)),
+                    _ => Some((closing, depth)),
+                };
+                index += 1;
+                continue;
+            }
+
+            if self.expecting_arguments {
+                match byte {
+                    b'{' => self.argument = Some((b'}', 1)),
+                    b'[' => self.argument = Some((b']', 1)),
+                    b' ' | b'\t' => {}
+                    _ => self.expecting_arguments = false,
+                }
+                if self.expecting_arguments {
+                    index += 1;
+                    continue;
+                }
+            }
+
+            match byte {
+                b'\\' => index += self.command(&text[index + 1..], prose),
+                b'$' if text.get(index + 1) == Some(&b'$') => {
+                    self.math_end = Some(b"$$".to_vec());
+                    index += 2;
+                }
+                b'$' => {
+                    self.math_end = Some(b"$".to_vec());
+                    index += 1;
+                }
+                b'{' | b'}' => index += 1,
+                b'~' => {
+                    prose.push(b' ');
+                    index += 1;
+                }
+                _ => {
+                    prose.push(byte);
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Interprets the command at the start of `text`, which follows a backslash, returning the number of
+    /// bytes consumed including the backslash.
+    fn command(&mut self, text: &[u8], prose: &mut Vec<u8>) -> usize {
+        let name = command_name(text);
+        let mut consumed = 1 + name.len();
+
+        match name {
+            b"" => {}
+            b"(" => self.math_end = Some(b"\\)".to_vec()),
+            b"[" => self.math_end = Some(b"\\]".to_vec()),
+            b"\\" | b" " | b"," | b";" => prose.push(b' '),
+            [symbol] if !symbol.is_ascii_alphabetic() => prose.push(*symbol),
+            b"begin" | b"end" => match braced_argument(&text[name.len()..]) {
+                Some(environment) if name == b"end" && environment == b"document" => {
+                    self.ended = true;
+                    consumed = text.len() + 1;
+                }
+                Some(environment) if name == b"begin" && is_math_environment(environment) => {
+                    let mut math_end = b"\\end{".to_vec();
+                    math_end.extend_from_slice(environment);
+                    math_end.push(b'}');
+                    self.math_end = Some(math_end);
+                    consumed += environment.len() + 2;
+                }
+                _ => self.expecting_arguments = true,
+            },
+            _ if SKIPPED_ARGUMENT_COMMANDS.contains(&name.strip_suffix(b"*").unwrap_or(name)) => {
+                self.expecting_arguments = true;
+            }
+            _ => prose.push(b' '),
+        }
+
+        consumed
+    }
+}
+
+impl LineFilter for LatexFilter {
+    fn filter_line(&mut self, line: &[u8], prose: &mut Vec<u8>) {
+        if self.ended {
+            return;
+        }
+
+        let mut text = line;
+        if self.in_preamble {
+            let marker = b"\\begin{document}";
+            match text
+                .windows(marker.len())
+                .position(|window| window == marker)
+            {
+                Some(index) => {
+                    self.in_preamble = false;
+                    text = &text[index + marker.len()..];
+                }
+                None => return,
+            }
+        } else if trim(text).starts_with(b"\\documentclass") {
+            self.in_preamble = true;
+            return;
+        }
+
+        // Arguments are only expected on the line of their command.
+        self.expecting_arguments = false;
+
+        let terminator = terminator(text);
+        self.scan(&text[..text.len() - terminator.len()], prose);
+        if !self.ended {
+            prose.extend_from_slice(terminator);
+        }
+    }
+}
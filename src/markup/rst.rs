@@ -0,0 +1,136 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{indentation, repeated_byte, terminator, trim, LineFilter};
+
+/// Directives whose contents are prose.
+const ADMONITIONS: [&[u8]; 14] = [
+    b"admonition",
+    b"attention",
+    b"caution",
+    b"danger",
+    b"error",
+    b"hint",
+    b"important",
+    b"note",
+    b"seealso",
+    b"sidebar",
+    b"tip",
+    b"topic",
+    b"versionadded",
+    b"warning",
+];
+
+/// Appends `text` to `prose` with the names of interpreted text roles, such as `:ref:`, removed.
+fn push_without_roles(text: &[u8], prose: &mut Vec<u8>) {
+    let mut index = 0;
+    while index < text.len() {
+        if text[index] == b':' {
+            let name = text[index + 1..]
+                .iter()
+                .take_while(|&&byte| byte.is_ascii_alphanumeric() || b"-_.+".contains(&byte))
+                .count();
+            let end = index + 1 + name;
+            if name > 0 && text.get(end) == Some(&b':') && text.get(end + 1) == Some(&b'`') {
+                index = end + 1;
+                continue;
+            }
+        }
+        prose.push(text[index]);
+        index += 1;
+    }
+}
+
+/// Returns `text` without a leading field name such as `:param value:`, unless it names a role.
+fn without_field_name(text: &[u8]) -> &[u8] {
+    if !text.starts_with(b":") {
+        return text;
+    }
+    match text[1..]
+        .iter()
+        .position(|&byte| byte == b':' || byte == b'`')
+    {
+        Some(index) if text[index + 1] == b':' && text.get(index + 2) != Some(&b'`') => {
+            trim(&text[index + 2..])
+        }
+        _ => text,
+    }
+}
+
+/// Extracts the prose from reStructuredText documents.
+///
+/// Comments, hyperlink targets, substitution definitions, literal blocks and the bodies of directives
+/// other than admonitions are skipped. Section adornments, field names and role names are removed.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct RstFilter {
+    /// The indentation of the line introducing the skipped block the filter is within, if any.
+    skipped_indentation: Option<usize>,
+    /// Whether the filter is within the options following an admonition.
+    in_options: bool,
+}
+
+impl LineFilter for RstFilter {
+    fn filter_line(&mut self, line: &[u8], prose: &mut Vec<u8>) {
+        let text = trim(line);
+        let indent = indentation(line);
+
+        // A skipped block continues until the first non-blank line indented no further than its start.
+        if let Some(limit) = self.skipped_indentation {
+            if text.is_empty() || indent > limit {
+                return;
+            }
+            self.skipped_indentation = None;
+        }
+
+        // Admonition options, such as `:class:`, run until the first line which is not a field.
+        if self.in_options && text.starts_with(b":") && without_field_name(text) != text {
+            return;
+        }
+        self.in_options = false;
+
+        let mut body = text;
+        if let Some(explicit) = text
+            .strip_prefix(b"..")
+            .filter(|rest| rest.is_empty() || rest[0] == b' ')
+        {
+            let explicit = trim(explicit);
+            let directive = explicit.windows(2).position(|window| window == b"::");
+            body = match (explicit.first(), directive) {
+                // Footnotes and citations are prose.
+                (Some(b'['), _) if explicit.contains(&b']') => {
+                    let end = explicit
+                        .iter()
+                        .position(|&byte| byte == b']')
+                        .unwrap_or_default();
+                    trim(&explicit[end + 1..])
+                }
+                (_, Some(index)) if ADMONITIONS.contains(&trim(&explicit[..index])) => {
+                    self.in_options = true;
+                    trim(&explicit[index + 2..])
+                }
+                _ => {
+                    self.skipped_indentation = Some(indent);
+                    return;
+                }
+            };
+        } else if text.ends_with(b"::") {
+            // The paragraph introduces a literal block, and the `::` is either removed or becomes a colon.
+            self.skipped_indentation = Some(indent);
+            body = text.strip_suffix(b"::").map(trim).unwrap_or_default();
+            if !body.is_empty() && !text.ends_with(b" ::") {
+                body = &text[..text.len() - 1];
+            }
+        } else if repeated_byte(text).is_some_and(|byte| byte.is_ascii_punctuation())
+            && text.len() >= 2
+        {
+            return;
+        }
+
+        push_without_roles(without_field_name(body), prose);
+        prose.extend_from_slice(terminator(line));
+    }
+}
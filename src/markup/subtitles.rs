@@ -0,0 +1,99 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{trim, LineFilter};
+
+/// Appends `text` to `prose` with `<...>` tags and `{...}` override blocks removed.
+fn push_without_tags(text: &[u8], prose: &mut Vec<u8>) {
+    let mut closing = None;
+    for &byte in text {
+        match (closing, byte) {
+            (None, b'<') => closing = Some(b'>'),
+            (None, b'{') => closing = Some(b'}'),
+            (None, _) => prose.push(byte),
+            (Some(end), _) if byte == end => closing = None,
+            (Some(_), _) => {}
+        }
+    }
+}
+
+/// Returns `true` if `line` is a subtitle timing line, which may include cue settings.
+fn is_timing(line: &[u8]) -> bool {
+    line.windows(3).any(|window| window == b"-->")
+}
+
+/// Extracts the dialogue from SubRip and WebVTT subtitles.
+///
+/// Sequence numbers, cue identifiers, timings and cue settings, the WebVTT header and `NOTE`, `STYLE` and
+/// `REGION` blocks are skipped, as are formatting tags within the dialogue.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct SubtitleFilter {
+    /// Whether the next line is the first of a block.
+    at_block_start: bool,
+    /// Whether the remainder of the current block is skipped.
+    skipping_block: bool,
+    /// The first line of the current block, held back until it is known not to identify a cue.
+    pending: Option<Vec<u8>>,
+}
+
+impl Default for SubtitleFilter {
+    fn default() -> Self {
+        SubtitleFilter {
+            at_block_start: true,
+            skipping_block: false,
+            pending: None,
+        }
+    }
+}
+
+impl LineFilter for SubtitleFilter {
+    fn filter_line(&mut self, line: &[u8], prose: &mut Vec<u8>) {
+        let text = trim(line.strip_prefix("\u{FEFF}".as_bytes()).unwrap_or(line));
+        let at_block_start = self.at_block_start;
+        self.at_block_start = text.is_empty();
+
+        if text.is_empty() {
+            self.skipping_block = false;
+            self.finish(prose);
+            return;
+        }
+
+        if at_block_start {
+            let is_header = [&b"WEBVTT"[..], b"NOTE", b"STYLE", b"REGION"]
+                .iter()
+                .any(|keyword| {
+                    text.starts_with(keyword)
+                        && text.get(keyword.len()).is_none_or(u8::is_ascii_whitespace)
+                });
+            if is_header {
+                self.skipping_block = true;
+            } else if !is_timing(text) {
+                self.pending = Some(line.to_vec());
+                return;
+            }
+        }
+
+        if self.skipping_block {
+            return;
+        }
+
+        // A timing line reveals the line before it to have been a cue identifier.
+        if is_timing(text) {
+            self.pending = None;
+            return;
+        }
+
+        self.finish(prose);
+        push_without_tags(line, prose);
+    }
+
+    fn finish(&mut self, prose: &mut Vec<u8>) {
+        if let Some(line) = self.pending.take() {
+            push_without_tags(&line, prose);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+
+use wc_rs::i18n::Message;
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::{c_int, c_long, c_void};
+
+    /// `PROT_READ` of `<sys/mman.h>`.
+    pub const PROT_READ: c_int = 1;
+
+    /// `MAP_PRIVATE` of `<sys/mman.h>`.
+    pub const MAP_PRIVATE: c_int = 2;
+
+    /// `MAP_FAILED` of `<sys/mman.h>`.
+    pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    extern "C" {
+        pub fn mmap(
+            address: *mut c_void,
+            length: usize,
+            protection: c_int,
+            flags: c_int,
+            descriptor: c_int,
+            offset: c_long,
+        ) -> *mut c_void;
+        pub fn munmap(address: *mut c_void, length: usize) -> c_int;
+    }
+}
+
+/// The contents of a file mapped into memory, read-only, for as long as the mapping lives.
+///
+/// Truncating the file while it is mapped makes reading the truncated pages fault, as with any mapping.
+pub struct Mapping {
+    /// The address of the mapping, or null if the file is empty.
+    address: *mut u8,
+    /// The length of the mapping, that of the file when it was mapped.
+    length: usize,
+}
+
+// SAFETY: the mapping is read-only and owned by the receiver, so it can be read from any thread.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    /// Maps the whole of `file` into memory.
+    #[cfg(unix)]
+    pub fn new(file: &File) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let length = file.metadata()?.len();
+        let length = if length <= usize::MAX as u64 {
+            length as usize
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                Message::new("file-too-large-to-map"),
+            ));
+        };
+        // A mapping cannot be empty.
+        if length == 0 {
+            return Ok(Mapping {
+                address: std::ptr::null_mut(),
+                length,
+            });
+        }
+
+        // SAFETY: the arguments describe a new read-only mapping of the whole of an open file.
+        let address = unsafe {
+            sys::mmap(
+                std::ptr::null_mut(),
+                length,
+                sys::PROT_READ,
+                sys::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if address == sys::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping {
+            address: address as *mut u8,
+            length,
+        })
+    }
+
+    /// Fails to map `file`, as memory mapping is only supported on Unix.
+    #[cfg(not(unix))]
+    pub fn new(_file: &File) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            Message::new("mmap-unsupported"),
+        ))
+    }
+}
+
+impl Deref for Mapping {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        if self.address.is_null() {
+            return &[];
+        }
+        // SAFETY: the mapping is `length` bytes long and lives as long as the receiver.
+        unsafe { std::slice::from_raw_parts(self.address, self.length) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if !self.address.is_null() {
+            // SAFETY: the mapping was created by `mmap` with this address and length, and is unmapped once.
+            unsafe { sys::munmap(self.address as *mut _, self.length) };
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping() {
+        let path = std::env::temp_dir().join(format!("wc_rs-mapping-{}", std::process::id()));
+        std::fs::write(&path, b"mapped contents\n").unwrap();
+        let mapping = Mapping::new(&File::open(&path).unwrap()).unwrap();
+        assert_eq!(&mapping[..6], b"mapped");
+        assert_eq!(mapping.len(), 16);
+        drop(mapping);
+
+        std::fs::write(&path, b"").unwrap();
+        let empty = Mapping::new(&File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(empty.is_empty());
+    }
+}
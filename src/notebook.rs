@@ -0,0 +1,110 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::i18n::Message;
+use crate::json::{self, Value};
+
+/// The text of a Jupyter notebook, separated by origin.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Notebook {
+    /// The source of the code cells.
+    pub code: Vec<u8>,
+    /// The source of the markdown cells.
+    pub markdown: Vec<u8>,
+    /// The plain text of the outputs of the code cells.
+    pub outputs: Vec<u8>,
+}
+
+/// Appends the multiline string `value` to `text`, ending it with a line terminator.
+///
+/// Notebooks store multiline strings either as a single string or as an array of lines.
+fn push_multiline(value: Option<&Value>, text: &mut Vec<u8>) {
+    let start = text.len();
+    match value {
+        Some(Value::String(string)) => text.extend_from_slice(string.as_bytes()),
+        Some(Value::Array(lines)) => {
+            for line in lines.iter().filter_map(Value::as_str) {
+                text.extend_from_slice(line.as_bytes());
+            }
+        }
+        _ => {}
+    }
+    if text.len() > start && !text.ends_with(b"\n") {
+        text.push(b'\n');
+    }
+}
+
+/// Appends the plain text of the cell output `output` to `text`.
+fn push_output(output: &Value, text: &mut Vec<u8>) {
+    match output.get("output_type").and_then(Value::as_str) {
+        Some("stream") => push_multiline(output.get("text"), text),
+        Some("execute_result") | Some("display_data") => push_multiline(
+            output.get("data").and_then(|data| data.get("text/plain")),
+            text,
+        ),
+        Some("error") => push_multiline(output.get("traceback"), text),
+        _ => {}
+    }
+}
+
+impl Notebook {
+    /// Parses the notebook document `input`.
+    pub fn parse(input: &[u8]) -> Result<Self, Message> {
+        let document = json::parse(input)?;
+        let cells = document
+            .get("cells")
+            .and_then(Value::as_array)
+            .ok_or_else(|| Message::new("notebook-no-cells"))?;
+
+        let mut notebook = Notebook::default();
+        for cell in cells {
+            let source = cell.get("source");
+            match cell.get("cell_type").and_then(Value::as_str) {
+                Some("code") => {
+                    push_multiline(source, &mut notebook.code);
+                    let outputs = cell
+                        .get("outputs")
+                        .and_then(Value::as_array)
+                        .unwrap_or_default();
+                    for output in outputs {
+                        push_output(output, &mut notebook.outputs);
+                    }
+                }
+                Some("markdown") => push_multiline(source, &mut notebook.markdown),
+                _ => {}
+            }
+        }
+
+        Ok(notebook)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook_parse() {
+        let document = br##"{"cells": [
+            {"cell_type": "markdown", "source": ["# Title\n", "Some prose"]},
+            {"cell_type": "code", "source": "print(1 + 1)", "outputs": [
+                {"output_type": "stream", "name": "stdout", "text": ["2\n"]},
+                {"output_type": "display_data", "data": {"image/png": "iVBOR", "text/plain": "<Figure>"}}
+            ]},
+            {"cell_type": "raw", "source": "ignored"}
+        ], "nbformat": 4}"##;
+
+        assert_eq!(
+            Notebook::parse(document),
+            Ok(Notebook {
+                code: b"print(1 + 1)\n".to_vec(),
+                markdown: b"# Title\nSome prose\n".to_vec(),
+                outputs: b"2\n<Figure>\n".to_vec(),
+            })
+        );
+    }
+}
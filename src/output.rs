@@ -0,0 +1,308 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io;
+use std::str::FromStr;
+
+use wc_rs::i18n::Message;
+use wc_rs::Counts;
+
+use crate::columns::Column;
+
+/// The format the rows of counts are output in.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum Output {
+    /// Fixed-width columns, as in the original `wc` utility.
+    #[default]
+    Text,
+    /// A JSON object holding an array of the rows and the total.
+    Json,
+    /// Comma-separated values with a header record, quoted as in RFC 4180.
+    Csv,
+    /// Tab-separated values with a header record, escaping tabs, line breaks and backslashes.
+    Tsv,
+}
+
+impl FromStr for Output {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            "csv" => Ok(Output::Csv),
+            "tsv" => Ok(Output::Tsv),
+            _ => Err(Message::new("unknown-output-format").arg("value", s)),
+        }
+    }
+}
+
+/// Returns `text` as a JSON string literal.
+pub fn json_string(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Returns the value of `column` for the input `name` with `counts`, out of the total counts of all rows
+/// `total`, to `precision` decimal places as `Column::value_with_precision` does, with shares as
+/// percentages without a sign.
+fn plain_value(
+    column: Column,
+    counts: &Counts,
+    total: &Counts,
+    name: &str,
+    precision: Option<usize>,
+) -> String {
+    let value = column.value_with_precision(counts, total, name, precision);
+    match value.strip_suffix('%') {
+        Some(number) if column != Column::File => number.to_string(),
+        _ => value,
+    }
+}
+
+/// Returns the JSON object of the values of `columns` for the input `name` with `counts`, out of the total
+/// counts of all rows `total`, led by the name unless it is `None`. Shares are percentages without a sign,
+/// and derived values are to `precision` decimal places.
+fn json_object(
+    columns: &[Column],
+    counts: &Counts,
+    total: &Counts,
+    name: Option<&str>,
+    precision: Option<usize>,
+) -> String {
+    let mut members = Vec::new();
+    if let Some(name) = name {
+        members.push(format!("\"file\":{}", json_string(name)));
+    }
+    for &column in columns.iter().filter(|&&column| column != Column::File) {
+        let value = plain_value(column, counts, total, name.unwrap_or_default(), precision);
+        members.push(format!("{}:{}", json_string(column.name()), value));
+    }
+    format!("{{{}}}", members.join(","))
+}
+
+/// Returns the JSON object of the input `name` which could not be counted because of `error`, with its
+/// operating system error number, or `null` if it has none, and its message.
+fn json_error(name: &str, error: &io::Error) -> String {
+    let errno = match error.raw_os_error() {
+        Some(errno) => errno.to_string(),
+        None => "null".to_string(),
+    };
+    format!(
+        "{{\"file\":{},\"errno\":{},\"message\":{}}}",
+        json_string(name),
+        errno,
+        json_string(&error.to_string())
+    )
+}
+
+/// Renders `rows`, each the name of an input and its counts, and their `total` as a JSON object with the
+/// values of `columns`, one row to a line. The inputs which could not be counted, each the name of one
+/// and why, follow in an array of errors, if there are any, and then the number of `empty_files` skipped,
+/// if they are. Derived values are to `precision` decimal places.
+pub fn render_json(
+    rows: &[(String, Counts)],
+    total: &Counts,
+    columns: &[Column],
+    errors: &[(String, io::Error)],
+    empty_files: Option<u64>,
+    precision: Option<usize>,
+) -> String {
+    let rows: Vec<String> = rows
+        .iter()
+        .map(|(name, counts)| {
+            let object = json_object(columns, counts, total, Some(name), precision);
+            format!("    {}", object)
+        })
+        .collect();
+    let mut text = format!(
+        "{{\n  \"files\": [\n{}\n  ],\n  \"total\": {}",
+        rows.join(",\n"),
+        json_object(columns, total, total, None, precision)
+    );
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors
+            .iter()
+            .map(|(name, error)| format!("    {}", json_error(name, error)))
+            .collect();
+        text.push_str(&format!(",\n  \"errors\": [\n{}\n  ]", errors.join(",\n")));
+    }
+    if let Some(empty_files) = empty_files {
+        text.push_str(&format!(",\n  \"empty_files\": {}", empty_files));
+    }
+    text.push_str("\n}\n");
+    text
+}
+
+/// Returns `field` quoted or escaped as needed to be a field of a record of the `output` format.
+fn delimited_field(field: &str, output: Output) -> String {
+    match output {
+        Output::Tsv => field
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r"),
+        _ if field.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        }
+        _ => field.to_string(),
+    }
+}
+
+/// Returns `columns` as delimited records give them, the name of the input first.
+fn delimited_columns(columns: &[Column]) -> Vec<Column> {
+    std::iter::once(Column::File)
+        .chain(
+            columns
+                .iter()
+                .copied()
+                .filter(|&column| column != Column::File),
+        )
+        .collect()
+}
+
+/// Renders `rows`, each the name of an input and its counts, as records of the values of `columns` in the
+/// delimited `output` format, led by a header record. The name of each input is the first field. Shares
+/// are out of `total`, and derived values are to `precision` decimal places.
+pub fn render_delimited(
+    rows: &[(String, Counts)],
+    total: &Counts,
+    columns: &[Column],
+    output: Output,
+    precision: Option<usize>,
+) -> String {
+    let delimiter = if output == Output::Tsv { "\t" } else { "," };
+    let header: Vec<&str> = delimited_columns(columns)
+        .iter()
+        .map(|column| column.name())
+        .collect();
+    let mut text = format!("{}\n", header.join(delimiter));
+    for (name, counts) in rows {
+        text.push_str(&render_record(
+            name, counts, total, columns, output, precision,
+        ));
+        text.push('\n');
+    }
+    text
+}
+
+/// Renders the record of the input `name` with `counts` as `render_delimited` does, without a line
+/// terminator.
+pub fn render_record(
+    name: &str,
+    counts: &Counts,
+    total: &Counts,
+    columns: &[Column],
+    output: Output,
+    precision: Option<usize>,
+) -> String {
+    let delimiter = if output == Output::Tsv { "\t" } else { "," };
+    let record: Vec<String> = delimited_columns(columns)
+        .iter()
+        .map(|&column| {
+            let value = plain_value(column, counts, total, name, precision);
+            delimited_field(&value, output)
+        })
+        .collect();
+    record.join(delimiter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_json() {
+        let counts = |lines, words| Counts {
+            lines,
+            words,
+            bytes: words * 4,
+            ..Counts::default()
+        };
+        let rows = vec![
+            ("a.txt".to_string(), counts(1, 2)),
+            ("say \"hi\"\n".to_string(), counts(3, 6)),
+        ];
+        let columns = [
+            Column::Lines,
+            Column::Words,
+            Column::WordsShare,
+            Column::File,
+        ];
+
+        assert_eq!(
+            render_json(&rows, &counts(4, 8), &columns, &[], None, None),
+            "{\n  \"files\": [\n\
+             \x20   {\"file\":\"a.txt\",\"lines\":1,\"words\":2,\"words_share\":25.0},\n\
+             \x20   {\"file\":\"say \\\"hi\\\"\\n\",\"lines\":3,\"words\":6,\"words_share\":75.0}\n  ],\n\
+             \x20 \"total\": {\"lines\":4,\"words\":8,\"words_share\":100.0}\n}\n"
+        );
+        let errors = [
+            ("gone".to_string(), io::Error::from_raw_os_error(2)),
+            (
+                "bad".to_string(),
+                io::Error::other("Invalid UTF-8 at byte 3"),
+            ),
+        ];
+        assert_eq!(
+            render_json(&rows[..1], &counts(1, 2), &columns[..1], &errors, Some(3), None),
+            format!(
+                "{{\n  \"files\": [\n    {{\"file\":\"a.txt\",\"lines\":1}}\n  ],\n\
+                 \x20 \"total\": {{\"lines\":1}},\n  \"errors\": [\n\
+                 \x20   {{\"file\":\"gone\",\"errno\":2,\"message\":{}}},\n\
+                 \x20   {{\"file\":\"bad\",\"errno\":null,\"message\":\"Invalid UTF-8 at byte 3\"}}\n  ],\n\
+                 \x20 \"empty_files\": 3\n}}\n",
+                json_string(&errors[0].1.to_string())
+            )
+        );
+        assert_eq!(
+            render_delimited(&rows, &counts(4, 8), &columns, Output::Csv, None),
+            "file,lines,words,words_share\n\
+             a.txt,1,2,25.0\n\
+             \"say \"\"hi\"\"\n\",3,6,75.0\n"
+        );
+        assert_eq!(
+            render_record(
+                "total",
+                &counts(4, 8),
+                &counts(4, 8),
+                &columns,
+                Output::Csv,
+                None
+            ),
+            "total,4,8,100.0"
+        );
+        assert_eq!(
+            render_delimited(&rows[1..], &counts(3, 6), &columns[..1], Output::Tsv, None),
+            "file\tlines\nsay \"hi\"\\n\t3\n"
+        );
+        assert_eq!(
+            render_delimited(
+                &rows[..1],
+                &counts(4, 8),
+                &columns[2..3],
+                Output::Csv,
+                Some(0)
+            ),
+            "file,words_share\na.txt,25\n"
+        );
+        assert_eq!("json".parse(), Ok(Output::Json));
+        assert!("xml".parse::<Output>().is_err());
+    }
+}
@@ -0,0 +1,663 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+
+use crate::i18n::Message;
+use crate::inflate;
+
+/// Horizontal adjustment in a `TJ` array, in thousandths of a text space unit, beyond which a word gap is
+/// assumed.
+const WORD_GAP: f64 = -200.0;
+
+/// Stream types, without whitespace, whose contents are never page text.
+const NON_TEXT_STREAMS: [&[u8]; 8] = [
+    b"/Type/EmbeddedFile",
+    b"/Type/Metadata",
+    b"/Type/ObjStm",
+    b"/Type/XRef",
+    b"/Subtype/CIDFontType0C",
+    b"/Subtype/Image",
+    b"/Subtype/OpenType",
+    b"/Subtype/Type1C",
+];
+
+/// Returns `true` if `byte` is PDF whitespace.
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b'\x0C' | b'\0')
+}
+
+/// Returns `true` if `byte` is a PDF delimiter.
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+/// Returns the offset of the first occurrence of `needle` in `haystack` at or after `from`.
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|index| from + index)
+}
+
+/// Returns the length of the literal string, including its parentheses, at the start of `text`.
+fn literal_string_len(text: &[u8]) -> usize {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < text.len() {
+        match text[index] {
+            b'\\' => index += 1,
+            b'(' => depth += 1,
+            b')' if depth == 1 => return index + 1,
+            b')' => depth -= 1,
+            _ => {}
+        }
+        index += 1;
+    }
+    text.len()
+}
+
+/// Returns the length of the dictionary, including its delimiters, at the start of `text`.
+fn dictionary_len(text: &[u8]) -> usize {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < text.len() {
+        match (text[index], text.get(index + 1)) {
+            (b'<', Some(b'<')) => {
+                depth += 1;
+                index += 1;
+            }
+            (b'>', Some(b'>')) if depth == 1 => return index + 2,
+            (b'>', Some(b'>')) => {
+                depth -= 1;
+                index += 1;
+            }
+            (b'(', _) => index += literal_string_len(&text[index..]) - 1,
+            (b'<', _) => {
+                index += text[index..]
+                    .iter()
+                    .position(|&byte| byte == b'>')
+                    .unwrap_or(0)
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    text.len()
+}
+
+/// Returns `dictionary` with all whitespace removed, so entries can be matched regardless of spacing.
+fn compact(dictionary: &[u8]) -> Vec<u8> {
+    dictionary
+        .iter()
+        .cloned()
+        .filter(|&byte| !is_whitespace(byte))
+        .collect()
+}
+
+/// Returns the position just past the entry key `key` in `dictionary`.
+fn after_key(dictionary: &[u8], key: &[u8]) -> Option<usize> {
+    let mut from = 0;
+    while let Some(index) = find(dictionary, key, from) {
+        let end = index + key.len();
+        match dictionary.get(end) {
+            Some(&byte) if !is_whitespace(byte) && !is_delimiter(byte) => from = end,
+            _ => return Some(end),
+        }
+    }
+    None
+}
+
+/// Parses the unsigned integers at the start of `text`, separated by whitespace, returning at most `count`.
+fn integers(text: &[u8], count: usize) -> Vec<u32> {
+    text.split(|&byte| is_whitespace(byte))
+        .filter(|word| !word.is_empty())
+        .take(count)
+        .map_while(|word| std::str::from_utf8(word).ok()?.parse().ok())
+        .collect()
+}
+
+/// Returns the value of the integer entry `key` in `dictionary`.
+fn integer_entry(dictionary: &[u8], key: &[u8]) -> Option<u32> {
+    let start = after_key(dictionary, key)?;
+    integers(&dictionary[start..], 1).first().cloned()
+}
+
+/// Returns the object number of the indirect reference entry `key` in `dictionary`.
+fn reference_entry(dictionary: &[u8], key: &[u8]) -> Option<u32> {
+    let start = after_key(dictionary, key)?;
+    let rest = &dictionary[start..];
+    let numbers = integers(rest, 2);
+    let reference = rest.iter().position(|&byte| byte == b'R')?;
+    if numbers.len() == 2 && integers(&rest[..reference], 3).len() == 2 {
+        Some(numbers[0])
+    } else {
+        None
+    }
+}
+
+/// An indirect object of a PDF document.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+struct Object {
+    /// The object, or its dictionary if it is a stream.
+    body: Vec<u8>,
+    /// The decoded data of the object if it is a stream, and can be decoded.
+    stream: Option<Vec<u8>>,
+}
+
+/// Decodes the data of a stream with dictionary `dictionary`, if it is unfiltered or uses `FlateDecode`.
+fn decode_stream(dictionary: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let compact = compact(dictionary);
+    if find(&compact, b"/Filter", 0).is_none() {
+        Some(data.to_vec())
+    } else if find(&compact, b"/Filter/FlateDecode", 0).is_some()
+        || find(&compact, b"/Filter[/FlateDecode]", 0).is_some()
+    {
+        inflate::zlib_decompress(data).ok()
+    } else {
+        None
+    }
+}
+
+/// Indexes the indirect objects of `document`, including those within object streams.
+fn objects(document: &[u8]) -> HashMap<u32, Object> {
+    let mut objects = HashMap::new();
+    let mut from = 0;
+    while let Some(index) = find(document, b" obj", from) {
+        from = index + 4;
+
+        // The keyword is preceded by the object and generation numbers.
+        let header_start = document[..index]
+            .iter()
+            .rposition(|&byte| !(byte.is_ascii_digit() || byte == b' '))
+            .map_or(0, |position| position + 1);
+        let number = match integers(&document[header_start..index], 2).as_slice() {
+            &[number, _] => number,
+            _ => continue,
+        };
+
+        let end = find(document, b"endobj", from).unwrap_or(document.len());
+        let body = &document[from..end];
+        let mut object = Object {
+            body: body.to_vec(),
+            stream: None,
+        };
+
+        let body_start = body
+            .iter()
+            .position(|&byte| !is_whitespace(byte))
+            .unwrap_or(0);
+        if body[body_start..].starts_with(b"<<") {
+            let dictionary = &body[body_start..body_start + dictionary_len(&body[body_start..])];
+            let after = &body[body_start + dictionary.len()..];
+            let keyword = after
+                .iter()
+                .position(|&byte| !is_whitespace(byte))
+                .unwrap_or(0);
+            if after[keyword..].starts_with(b"stream") {
+                let mut data = &after[keyword + 6..];
+                data = data.strip_prefix(b"\r").unwrap_or(data);
+                data = data.strip_prefix(b"\n").unwrap_or(data);
+                let data_end = find(data, b"endstream", 0).unwrap_or(data.len());
+                object.body = dictionary.to_vec();
+                object.stream = decode_stream(dictionary, &data[..data_end]);
+            }
+        }
+
+        objects.insert(number, object);
+        from = end;
+    }
+
+    // Objects may also be compressed into object streams, which begin with their numbers and offsets.
+    let mut compressed = Vec::new();
+    for object in objects.values() {
+        let (stream, first) = match (&object.stream, integer_entry(&object.body, b"/First")) {
+            (Some(stream), Some(first))
+                if find(&compact(&object.body), b"/Type/ObjStm", 0).is_some() =>
+            {
+                (stream, first as usize)
+            }
+            _ => continue,
+        };
+        let count = integer_entry(&object.body, b"/N").unwrap_or(0) as usize;
+        let header = integers(&stream[..first.min(stream.len())], count * 2);
+        for (index, pair) in header.chunks(2).enumerate() {
+            let start = first + pair[1] as usize;
+            let end = header
+                .get(index * 2 + 3)
+                .map_or(stream.len(), |&offset| first + offset as usize);
+            if let Some(body) = stream.get(start..end) {
+                compressed.push((pair[0], body.to_vec()));
+            }
+        }
+    }
+    for (number, body) in compressed {
+        objects
+            .entry(number)
+            .or_insert(Object { body, stream: None });
+    }
+
+    objects
+}
+
+/// A mapping from character codes to Unicode text, as specified by a font's `ToUnicode` CMap.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+struct CMap {
+    /// The number of bytes in each character code.
+    code_len: usize,
+    /// The text of each mapped character code.
+    mappings: HashMap<u32, String>,
+}
+
+/// Returns the bytes of the hexadecimal string `hex`, which excludes its angle brackets.
+fn hex_bytes(hex: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .iter()
+        .filter_map(|&byte| (byte as char).to_digit(16).map(|digit| digit as u8))
+        .collect();
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).cloned().unwrap_or(0))
+        .collect()
+}
+
+/// Returns the big-endian integer formed by `bytes`.
+fn code(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |code, &byte| code << 8 | u32::from(byte))
+}
+
+/// Decodes the UTF-16BE text `bytes`.
+fn utf16_text(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| u16::from(pair[0]) << 8 | u16::from(pair.get(1).cloned().unwrap_or(0)))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+impl CMap {
+    /// Parses the `bfchar` and `bfrange` mappings of the CMap program `program`.
+    fn parse(program: &[u8]) -> Self {
+        let mut cmap = CMap::default();
+        let mut operands: Vec<Vec<u8>> = Vec::new();
+        let mut in_array = false;
+        let mut array: Vec<Vec<u8>> = Vec::new();
+        let mut section = &b""[..];
+
+        let mut index = 0;
+        while index < program.len() {
+            let byte = program[index];
+            if byte == b'<' {
+                let end = find(program, b">", index).unwrap_or(program.len());
+                let bytes = hex_bytes(&program[index + 1..end]);
+                if in_array {
+                    array.push(bytes);
+                } else {
+                    operands.push(bytes);
+                }
+                index = end + 1;
+            } else if byte == b'[' {
+                in_array = true;
+                index += 1;
+            } else if byte == b']' {
+                in_array = false;
+                index += 1;
+            } else if byte.is_ascii_alphabetic() {
+                let length = program[index..]
+                    .iter()
+                    .take_while(|byte| byte.is_ascii_alphanumeric())
+                    .count();
+                section = &program[index..index + length];
+                operands.clear();
+                index += length;
+            } else {
+                index += 1;
+            }
+
+            match (section, operands.len(), in_array) {
+                (b"beginbfchar", 2, _) => {
+                    cmap.code_len = operands[0].len();
+                    cmap.mappings
+                        .insert(code(&operands[0]), utf16_text(&operands[1]));
+                    operands.clear();
+                }
+                (b"beginbfrange", 3, false) => {
+                    cmap.code_len = operands[0].len();
+                    let (low, high) = (code(&operands[0]), code(&operands[1]));
+                    let mut destination = operands[2].clone();
+                    for source in low..=high.min(low + 0xFFFF) {
+                        cmap.mappings.insert(source, utf16_text(&destination));
+                        if let Some(last) = destination.last_mut() {
+                            *last = last.wrapping_add(1);
+                        }
+                    }
+                    operands.clear();
+                }
+                (b"beginbfrange", 2, false) if !array.is_empty() => {
+                    cmap.code_len = operands[0].len();
+                    let low = code(&operands[0]);
+                    for (offset, destination) in array.drain(..).enumerate() {
+                        cmap.mappings
+                            .insert(low + offset as u32, utf16_text(&destination));
+                    }
+                    operands.clear();
+                }
+                _ => {}
+            }
+        }
+
+        cmap
+    }
+
+    /// Appends the text of the character codes `bytes` to `text`.
+    fn decode(&self, bytes: &[u8], text: &mut String) {
+        for code_bytes in bytes.chunks(self.code_len.max(1)) {
+            if let Some(mapped) = self.mappings.get(&code(code_bytes)) {
+                text.push_str(mapped);
+            }
+        }
+    }
+}
+
+/// Returns the `ToUnicode` CMaps of the fonts of `objects`, keyed by the resource names fonts are
+/// selected by in content streams.
+///
+/// Resource names are only unique within a page, so where two pages use one name for different fonts,
+/// either CMap may be chosen.
+fn font_cmaps(objects: &HashMap<u32, Object>) -> HashMap<Vec<u8>, CMap> {
+    let mut cmaps = HashMap::new();
+    for object in objects.values() {
+        let start = match after_key(&object.body, b"/Font") {
+            Some(start) => start,
+            None => continue,
+        };
+
+        // The font resource dictionary may be direct or indirect.
+        let rest = &object.body[start..];
+        let offset = rest
+            .iter()
+            .position(|&byte| !is_whitespace(byte))
+            .unwrap_or(0);
+        let fonts = if rest[offset..].starts_with(b"<<") {
+            rest[offset..offset + dictionary_len(&rest[offset..])].to_vec()
+        } else {
+            match reference_entry(&object.body, b"/Font").and_then(|number| objects.get(&number)) {
+                Some(fonts) => fonts.body.clone(),
+                None => continue,
+            }
+        };
+
+        let mut from = 0;
+        while let Some(index) = find(&fonts, b"/", from) {
+            let length = fonts[index + 1..]
+                .iter()
+                .take_while(|&&byte| !is_whitespace(byte) && !is_delimiter(byte))
+                .count();
+            let name = &fonts[index..index + 1 + length];
+            from = index + 1 + length;
+
+            let cmap = reference_entry(&fonts, name)
+                .and_then(|number| objects.get(&number))
+                .and_then(|font| reference_entry(&font.body, b"/ToUnicode"))
+                .and_then(|number| objects.get(&number))
+                .and_then(|cmap| cmap.stream.as_ref());
+            if let Some(program) = cmap {
+                cmaps.insert(name[1..].to_vec(), CMap::parse(program));
+            }
+        }
+    }
+    cmaps
+}
+
+/// An operand of a content stream operator.
+#[derive(Clone, PartialEq, Debug)]
+enum Operand {
+    String(Vec<u8>),
+    Number(f64),
+    Name(Vec<u8>),
+    Array(Vec<Operand>),
+    Other,
+}
+
+/// Decodes the string escapes of the literal string `literal`, which excludes its parentheses.
+fn literal_bytes(literal: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut index = 0;
+    while index < literal.len() {
+        let byte = literal[index];
+        index += 1;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match literal.get(index) {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'b') => bytes.push(8),
+            Some(b'f') => bytes.push(12),
+            Some(b'0'..=b'7') => {
+                let digits = literal[index..]
+                    .iter()
+                    .take(3)
+                    .take_while(|byte| (b'0'..=b'7').contains(byte))
+                    .count();
+                let value = literal[index..index + digits]
+                    .iter()
+                    .fold(0u32, |value, &digit| value * 8 + u32::from(digit - b'0'));
+                bytes.push(value as u8);
+                index += digits - 1;
+            }
+            // An escaped line break continues the string on the next line.
+            Some(b'\r') | Some(b'\n') => {}
+            Some(&other) => bytes.push(other),
+            None => {}
+        }
+        index += 1;
+    }
+    bytes
+}
+
+/// Extracts the text shown by the content stream `content` and appends it to `text`.
+fn extract_content(content: &[u8], cmaps: &HashMap<Vec<u8>, CMap>, text: &mut String) {
+    let mut operands: Vec<Operand> = Vec::new();
+    let mut arrays: Vec<Vec<Operand>> = Vec::new();
+    let mut cmap = None;
+
+    // Appends the text of a shown string using the current font.
+    let show = |bytes: &[u8], cmap: Option<&CMap>, text: &mut String| match cmap {
+        Some(cmap) => cmap.decode(bytes, text),
+        None => text.extend(
+            bytes
+                .iter()
+                .filter(|&&byte| byte >= b' ')
+                .map(|&byte| byte as char),
+        ),
+    };
+
+    let mut index = 0;
+    while index < content.len() {
+        let byte = content[index];
+        let operand = if is_whitespace(byte) {
+            index += 1;
+            continue;
+        } else if byte == b'%' {
+            index += content[index..]
+                .iter()
+                .position(|&byte| byte == b'\n' || byte == b'\r')
+                .unwrap_or(content.len() - index);
+            continue;
+        } else if byte == b'(' {
+            let length = literal_string_len(&content[index..]);
+            let literal = &content[index + 1..index + length.max(2) - 1];
+            index += length;
+            Operand::String(literal_bytes(literal))
+        } else if content[index..].starts_with(b"<<") {
+            index += dictionary_len(&content[index..]);
+            Operand::Other
+        } else if byte == b'<' {
+            let end = find(content, b">", index).unwrap_or(content.len());
+            let hex = hex_bytes(&content[index + 1..end]);
+            index = end + 1;
+            Operand::String(hex)
+        } else if byte == b'[' {
+            arrays.push(Vec::new());
+            index += 1;
+            continue;
+        } else if byte == b']' {
+            index += 1;
+            match arrays.pop() {
+                Some(array) => Operand::Array(array),
+                None => continue,
+            }
+        } else {
+            let length = content[index..]
+                .iter()
+                .skip(1)
+                .take_while(|&&byte| !is_whitespace(byte) && !is_delimiter(byte))
+                .count()
+                + 1;
+            let token = &content[index..index + length];
+            index += length;
+
+            if token[0] == b'/' {
+                Operand::Name(token[1..].to_vec())
+            } else if let Some(number) = std::str::from_utf8(token)
+                .ok()
+                .and_then(|token| token.parse().ok())
+            {
+                Operand::Number(number)
+            } else {
+                // The token is an operator, which consumes the operands preceding it.
+                match token {
+                    b"Tf" => {
+                        let name = operands.iter().rev().find_map(|operand| match operand {
+                            Operand::Name(name) => Some(name),
+                            _ => None,
+                        });
+                        cmap = name.and_then(|name| cmaps.get(name));
+                    }
+                    b"Tj" | b"'" | b"\"" => {
+                        if token != b"Tj" {
+                            text.push('\n');
+                        }
+                        if let Some(Operand::String(bytes)) = operands.last() {
+                            show(bytes, cmap, text);
+                        }
+                    }
+                    b"TJ" => {
+                        if let Some(Operand::Array(elements)) = operands.last() {
+                            for element in elements {
+                                match element {
+                                    Operand::String(bytes) => show(bytes, cmap, text),
+                                    Operand::Number(adjustment) if *adjustment < WORD_GAP => {
+                                        text.push(' ')
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                    b"Td" | b"TD" => match operands.as_slice() {
+                        [.., Operand::Number(_), Operand::Number(ty)] if *ty == 0.0 => {
+                            text.push(' ')
+                        }
+                        _ => text.push('\n'),
+                    },
+                    b"T*" | b"ET" => text.push('\n'),
+                    b"Tm" => text.push(' '),
+                    b"BI" => {
+                        // Inline image data is binary and ends at the first `EI` operator.
+                        index = find(content, b"EI", index).map_or(content.len(), |end| end + 2);
+                    }
+                    _ => {}
+                }
+                operands.clear();
+                continue;
+            }
+        };
+
+        match arrays.last_mut() {
+            Some(array) => array.push(operand),
+            None => operands.push(operand),
+        }
+    }
+}
+
+/// Extracts the text of the PDF document `document`.
+///
+/// Text is taken from the text-showing operators of every unencrypted content stream that is either
+/// unfiltered or compressed with `FlateDecode`, mapped through the `ToUnicode` CMap of the selected font
+/// where there is one and otherwise treated as Latin-1.
+pub fn extract_text(document: &[u8]) -> Result<String, Message> {
+    if !document.starts_with(b"%PDF-") {
+        return Err(Message::new("not-a-pdf-document"));
+    }
+    if find(document, b"/Encrypt", 0).is_some() {
+        return Err(Message::new("encrypted-pdf-document"));
+    }
+
+    let objects = objects(document);
+    let cmaps = font_cmaps(&objects);
+
+    let mut numbers: Vec<&u32> = objects.keys().collect();
+    numbers.sort();
+
+    let mut text = String::new();
+    for number in numbers {
+        let object = &objects[number];
+        let content = match &object.stream {
+            Some(content) => content,
+            None => continue,
+        };
+        let dictionary = compact(&object.body);
+        // Embedded Type 1 and TrueType font programs are identified by the lengths of their sections.
+        let is_text = !NON_TEXT_STREAMS
+            .iter()
+            .any(|entry| after_key(&dictionary, entry).is_some())
+            && after_key(&object.body, b"/Length1").is_none()
+            && find(content, b"begincmap", 0).is_none();
+        if is_text {
+            extract_content(content, &cmaps, &mut text);
+        }
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_text() {
+        let document = b"%PDF-1.4\n\
+            1 0 obj << /Type /Page /Resources << /Font << /F1 2 0 R /F2 3 0 R >> >> /Contents 4 0 R >> endobj\n\
+            2 0 obj << /Type /Font /Subtype /Type1 /BaseFont /Helvetica >> endobj\n\
+            3 0 obj << /Type /Font /Subtype /Type0 /ToUnicode 5 0 R >> endobj\n\
+            4 0 obj << /Length 99 >>\nstream\n\
+            BT /F1 12 Tf 72 712 Td (Hello, \\(PDF\\)) Tj 0 -14 Td [(wor) 10 (ld) -300 (again)] TJ ET\n\
+            BT /F2 12 Tf <00010002> Tj ET\n\
+            endstream\nendobj\n\
+            5 0 obj << /Length 99 >>\nstream\n\
+            begincmap 1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+            1 beginbfchar <0001> <0048> endbfchar 1 beginbfrange <0002> <0002> <0069> endbfrange endcmap\n\
+            endstream\nendobj\n\
+            trailer << /Root 1 0 R >>\n%%EOF\n";
+
+        assert_eq!(
+            extract_text(document),
+            Ok("\nHello, (PDF)\nworld again\nHi\n".to_string())
+        );
+    }
+}
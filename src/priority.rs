@@ -0,0 +1,117 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use wc_rs::i18n;
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::c_int;
+    #[cfg(target_os = "linux")]
+    use std::os::raw::c_long;
+
+    /// `PRIO_PROCESS` of `<sys/resource.h>`.
+    pub const PRIO_PROCESS: c_int = 0;
+
+    /// `id_t` of `<sys/types.h>`.
+    #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+    pub type Id = i64;
+    #[cfg(not(any(target_os = "freebsd", target_os = "dragonfly")))]
+    pub type Id = u32;
+
+    /// `IOPRIO_WHO_PROCESS` of `<linux/ioprio.h>`.
+    #[cfg(target_os = "linux")]
+    pub const IOPRIO_WHO_PROCESS: c_long = 1;
+
+    /// The `who` of `ioprio_set` naming the calling thread.
+    #[cfg(target_os = "linux")]
+    pub const CALLING_THREAD: c_long = 0;
+
+    /// The `ioprio_set` I/O priority of the idle class, `IOPRIO_PRIO_VALUE(IOPRIO_CLASS_IDLE, 0)`.
+    #[cfg(target_os = "linux")]
+    pub const IOPRIO_IDLE: c_long = 3 << 13;
+
+    /// The number of the `ioprio_set` system call, which has no C library wrapper.
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    pub const SYS_IOPRIO_SET: Option<c_long> = Some(251);
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    pub const SYS_IOPRIO_SET: Option<c_long> = Some(30);
+    #[cfg(all(
+        target_os = "linux",
+        not(any(target_arch = "x86_64", target_arch = "aarch64"))
+    ))]
+    pub const SYS_IOPRIO_SET: Option<c_long> = None;
+
+    extern "C" {
+        pub fn setpriority(which: c_int, who: Id, priority: c_int) -> c_int;
+        #[cfg(target_os = "linux")]
+        pub fn syscall(number: c_long, ...) -> c_long;
+    }
+}
+
+/// The niceness of `--nice`, the lowest scheduling priority.
+pub const LOWEST_NICENESS: i32 = 19;
+
+/// Sets the niceness of the calling thread to `niceness`. Priorities belong to threads on Linux and to
+/// processes elsewhere, and are inherited by the threads a thread creates, so this is called before the
+/// counting threads are started.
+#[cfg(unix)]
+pub fn set_niceness(niceness: i32) -> Result<(), String> {
+    // SAFETY: `setpriority` takes no pointers.
+    match unsafe { sys::setpriority(sys::PRIO_PROCESS, 0, niceness) } {
+        0 => Ok(()),
+        _ => Err(std::io::Error::last_os_error().to_string()),
+    }
+}
+
+/// Places the calling thread in the idle I/O scheduling class, so that its I/O is only served when no
+/// other process needs the disk.
+#[cfg(target_os = "linux")]
+pub fn set_io_idle() -> Result<(), String> {
+    let number =
+        sys::SYS_IOPRIO_SET.ok_or_else(|| i18n::message("io-priority-unsupported", &[]))?;
+    // SAFETY: `ioprio_set` takes three integers and no pointers.
+    match unsafe {
+        sys::syscall(
+            number,
+            sys::IOPRIO_WHO_PROCESS,
+            sys::CALLING_THREAD,
+            sys::IOPRIO_IDLE,
+        )
+    } {
+        0 => Ok(()),
+        _ => Err(std::io::Error::last_os_error().to_string()),
+    }
+}
+
+/// Fails to set the niceness of the calling thread, as priorities are only set on Unix.
+#[cfg(not(unix))]
+pub fn set_niceness(_niceness: i32) -> Result<(), String> {
+    Err(i18n::message("priority-unsupported", &[]))
+}
+
+/// Fails to set the I/O priority of the calling thread, as I/O priorities are only set on Linux.
+#[cfg(not(target_os = "linux"))]
+pub fn set_io_idle() -> Result<(), String> {
+    Err(i18n::message("io-priority-unsupported", &[]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_priority() {
+        // The priority is lowered on a thread of its own, leaving the other tests unaffected.
+        std::thread::spawn(|| {
+            assert_eq!(set_niceness(LOWEST_NICENESS), Ok(()));
+            assert_eq!(set_io_idle().is_ok(), cfg!(target_os = "linux"));
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -0,0 +1,576 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::str::FromStr;
+
+use crate::i18n::Message;
+
+/// A member of a character class.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum ClassItem {
+    /// The characters from the first through the second.
+    Range(char, char),
+    /// The decimal digits, `\d`.
+    Digit,
+    /// The word characters, `\w`: letters, digits and underscores.
+    Word,
+    /// The whitespace characters, `\s`.
+    Space,
+}
+
+impl ClassItem {
+    /// Returns `true` if `c` is a member of the receiver.
+    fn contains(self, c: char) -> bool {
+        match self {
+            ClassItem::Range(first, last) => (first..=last).contains(&c),
+            ClassItem::Digit => c.is_ascii_digit(),
+            ClassItem::Word => c.is_alphanumeric() || c == '_',
+            ClassItem::Space => c.is_whitespace(),
+        }
+    }
+}
+
+/// A set of characters, such as `[a-z_]` or `\S`.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+struct Class {
+    /// Whether the class matches the characters that are not members.
+    negated: bool,
+    /// The members of the class.
+    items: Vec<ClassItem>,
+}
+
+impl Class {
+    /// Returns `true` if the receiver matches `c`.
+    fn matches(&self, c: char) -> bool {
+        self.items.iter().any(|item| item.contains(c)) != self.negated
+    }
+}
+
+/// A condition on the position between two characters, which matches no characters.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum Assertion {
+    /// The start of the text, `^`.
+    Start,
+    /// The end of the text, `$`.
+    End,
+    /// A boundary between a word character and another character or either end of the text, `\b`.
+    WordBoundary,
+    /// A position which is not a word boundary, `\B`.
+    NotWordBoundary,
+}
+
+impl Assertion {
+    /// Returns `true` if the receiver holds between `previous` and `current`, the characters either side of
+    /// a position, or `None` at the ends of the text.
+    fn holds(self, previous: Option<char>, current: Option<char>) -> bool {
+        let is_word = |c: Option<char>| c.is_some_and(|c| ClassItem::Word.contains(c));
+        match self {
+            Assertion::Start => previous.is_none(),
+            Assertion::End => current.is_none(),
+            Assertion::WordBoundary => is_word(previous) != is_word(current),
+            Assertion::NotWordBoundary => is_word(previous) == is_word(current),
+        }
+    }
+}
+
+/// A node of a parsed pattern.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+enum Node {
+    /// A literal character.
+    Char(char),
+    /// Any character, `.`.
+    Any,
+    /// A character of a class.
+    Class(Class),
+    /// A condition on the position, such as `^` or `\b`.
+    Assert(Assertion),
+    /// Alternative sequences, `(a|b)`.
+    Group(Vec<Vec<Node>>),
+    /// A node repeated between a minimum and an optional maximum number of times, greedily.
+    Repeat(Box<Node>, usize, Option<usize>),
+}
+
+/// An instruction of a compiled pattern.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+enum Inst {
+    /// Matches the character, continuing with the next instruction.
+    Char(char),
+    /// Matches any character, continuing with the next instruction.
+    Any,
+    /// Matches a character of the class, continuing with the next instruction.
+    Class(Class),
+    /// Continues with the next instruction if the assertion holds.
+    Assert(Assertion),
+    /// Continues with both instructions.
+    Split(usize, usize),
+    /// Continues with the instruction.
+    Jump(usize),
+    /// The pattern has matched.
+    Match,
+}
+
+/// The most instructions a pattern may compile to, bounding the work of matching each character.
+const MAX_PROGRAM_LEN: usize = 10_000;
+
+/// A regular expression in a common subset of the POSIX extended and Perl syntaxes: literals, `.`, classes
+/// such as `[^a-z]`, the escapes `\d`, `\w` and `\s` and their negations, `\t`, `\n` and `\r`, the
+/// anchors `^`, `$`, `\b` and `\B`, groups, alternation and the quantifiers `*`, `+`, `?` and `{m,n}`.
+/// Other escaped letters and digits are errors, rather than matching themselves.
+///
+/// The pattern is compiled to the instructions of an automaton, which is simulated over the text with all
+/// of its states at once, so matching takes time proportional to the lengths of the text and the pattern
+/// whatever the pattern, and no stack beyond that of the states.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Regex {
+    /// The instructions of the pattern, starting with the first.
+    program: Vec<Inst>,
+}
+
+/// Parses a pattern, one character at a time.
+struct Parser {
+    /// The characters of the pattern.
+    chars: Vec<char>,
+    /// The index of the next character.
+    position: usize,
+}
+
+impl Parser {
+    /// Returns the next character without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Consumes and returns the next character.
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    /// Parses alternatives separated by `|`, up to the end of the pattern or of the enclosing group.
+    fn alternatives(&mut self) -> Result<Vec<Vec<Node>>, Message> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.position += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(alternatives)
+    }
+
+    /// Parses a sequence of quantified atoms.
+    fn sequence(&mut self) -> Result<Vec<Node>, Message> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(nodes)
+    }
+
+    /// Parses any quantifiers following `atom`.
+    fn quantified(&mut self, mut atom: Node) -> Result<Node, Message> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') if self.chars[self.position..].contains(&'}') => {
+                    self.position += 1;
+                    let end = self.position
+                        + self.chars[self.position..]
+                            .iter()
+                            .position(|&c| c == '}')
+                            .unwrap_or(0);
+                    let bounds: String = self.chars[self.position..end].iter().collect();
+                    self.position = end;
+                    parse_bounds(&bounds)?
+                }
+                _ => return Ok(atom),
+            };
+            if matches!(atom, Node::Assert(_) | Node::Repeat(..)) {
+                return Err(Message::new("pattern-nothing-repeatable"));
+            }
+            self.position += 1;
+            atom = Node::Repeat(Box::new(atom), min, max);
+        }
+    }
+
+    /// Parses a single character, class, anchor or group.
+    fn atom(&mut self) -> Result<Node, Message> {
+        match self.next() {
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Assert(Assertion::Start)),
+            Some('$') => Ok(Node::Assert(Assertion::End)),
+            Some('(') => {
+                let alternatives = self.alternatives()?;
+                match self.next() {
+                    Some(')') => Ok(Node::Group(alternatives)),
+                    _ => Err(Message::new("pattern-unbalanced-open")),
+                }
+            }
+            Some('[') => self.class().map(Node::Class),
+            Some('\\') => self.escape(),
+            Some(c @ ('*' | '+' | '?')) => {
+                Err(Message::new("pattern-nothing-to-repeat").arg("quantifier", c))
+            }
+            Some(c) => Ok(Node::Char(c)),
+            None => Err(Message::new("pattern-unexpected-end")),
+        }
+    }
+
+    /// Parses the escape after a `\`.
+    fn escape(&mut self) -> Result<Node, Message> {
+        let shorthand = |item, negated| {
+            Ok(Node::Class(Class {
+                negated,
+                items: vec![item],
+            }))
+        };
+        match self.next() {
+            Some('d') => shorthand(ClassItem::Digit, false),
+            Some('D') => shorthand(ClassItem::Digit, true),
+            Some('w') => shorthand(ClassItem::Word, false),
+            Some('W') => shorthand(ClassItem::Word, true),
+            Some('s') => shorthand(ClassItem::Space, false),
+            Some('S') => shorthand(ClassItem::Space, true),
+            Some('b') => Ok(Node::Assert(Assertion::WordBoundary)),
+            Some('B') => Ok(Node::Assert(Assertion::NotWordBoundary)),
+            Some(c) => escaped_char(c).map(Node::Char),
+            None => Err(Message::new("pattern-trailing-backslash")),
+        }
+    }
+
+    /// Parses the rest of a class after its `[`. A `]` first in the class, after any `^`, is a member.
+    fn class(&mut self) -> Result<Class, Message> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.position += 1;
+        }
+
+        let mut items = Vec::new();
+        loop {
+            let first = match self.next() {
+                Some(']') if !items.is_empty() => break,
+                Some('\\') => match self.next() {
+                    Some('d') => {
+                        items.push(ClassItem::Digit);
+                        continue;
+                    }
+                    Some('w') => {
+                        items.push(ClassItem::Word);
+                        continue;
+                    }
+                    Some('s') => {
+                        items.push(ClassItem::Space);
+                        continue;
+                    }
+                    Some(c) => escaped_char(c)?,
+                    None => return Err(Message::new("pattern-unterminated-class")),
+                },
+                Some(c) => c,
+                None => return Err(Message::new("pattern-unterminated-class")),
+            };
+
+            let is_range = self.peek() == Some('-')
+                && self.chars.get(self.position + 1).is_some_and(|&c| c != ']');
+            if is_range {
+                let last = self.chars[self.position + 1];
+                self.position += 2;
+                if last < first {
+                    return Err(Message::new("pattern-invalid-range")
+                        .arg("first", first)
+                        .arg("last", last));
+                }
+                items.push(ClassItem::Range(first, last));
+            } else {
+                items.push(ClassItem::Range(first, first));
+            }
+        }
+        Ok(Class { negated, items })
+    }
+}
+
+/// Returns the character the escape `\` followed by `c` stands for: a control character for `t`, `n` and
+/// `r`, or `c` itself if it is not a letter or digit, as escaped punctuation is.
+fn escaped_char(c: char) -> Result<char, Message> {
+    match c {
+        't' => Ok('\t'),
+        'n' => Ok('\n'),
+        'r' => Ok('\r'),
+        c if c.is_alphanumeric() => {
+            Err(Message::new("pattern-unsupported-escape").arg("escape", c))
+        }
+        c => Ok(c),
+    }
+}
+
+/// Parses `bounds`, the contents of a `{m}`, `{m,}` or `{m,n}` quantifier.
+fn parse_bounds(bounds: &str) -> Result<(usize, Option<usize>), Message> {
+    let invalid = || Message::new("pattern-invalid-quantifier").arg("bounds", bounds);
+    let parse = |count: &str| count.trim().parse::<usize>().map_err(|_| invalid());
+    match bounds.split_once(',') {
+        None => parse(bounds).map(|count| (count, Some(count))),
+        Some((min, max)) if max.trim().is_empty() => Ok((parse(min)?, None)),
+        Some((min, max)) => {
+            let (min, max) = (parse(min)?, parse(max)?);
+            if max < min {
+                return Err(invalid());
+            }
+            Ok((min, Some(max)))
+        }
+    }
+}
+
+/// Compiles parsed nodes to the instructions of a program.
+#[derive(Default)]
+struct Compiler {
+    /// The instructions compiled so far.
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    /// Appends `inst`, returning its index.
+    fn push(&mut self, inst: Inst) -> Result<usize, Message> {
+        if self.program.len() == MAX_PROGRAM_LEN {
+            return Err(Message::new("pattern-too-large"));
+        }
+        self.program.push(inst);
+        Ok(self.program.len() - 1)
+    }
+
+    /// Compiles `alternatives`, each tried in turn.
+    fn alternatives(&mut self, alternatives: &[Vec<Node>]) -> Result<(), Message> {
+        let mut jumps = Vec::new();
+        for (index, sequence) in alternatives.iter().enumerate() {
+            if index + 1 == alternatives.len() {
+                self.sequence(sequence)?;
+                break;
+            }
+            let split = self.push(Inst::Split(0, 0))?;
+            self.sequence(sequence)?;
+            jumps.push(self.push(Inst::Jump(0))?);
+            self.program[split] = Inst::Split(split + 1, self.program.len());
+        }
+        let end = self.program.len();
+        for jump in jumps {
+            self.program[jump] = Inst::Jump(end);
+        }
+        Ok(())
+    }
+
+    /// Compiles `nodes`, one after another.
+    fn sequence(&mut self, nodes: &[Node]) -> Result<(), Message> {
+        nodes.iter().try_for_each(|node| self.node(node))
+    }
+
+    /// Compiles `node`.
+    fn node(&mut self, node: &Node) -> Result<(), Message> {
+        match node {
+            Node::Char(c) => self.push(Inst::Char(*c)).map(drop),
+            Node::Any => self.push(Inst::Any).map(drop),
+            Node::Class(class) => self.push(Inst::Class(class.clone())).map(drop),
+            Node::Assert(assertion) => self.push(Inst::Assert(*assertion)).map(drop),
+            Node::Group(alternatives) => self.alternatives(alternatives),
+            Node::Repeat(node, min, max) => {
+                for _ in 0..*min {
+                    self.node(node)?;
+                }
+                match max {
+                    // Any further repetitions loop back to a choice between another and going on.
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.node(node)?;
+                        self.push(Inst::Jump(split))?;
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+                    // Each optional repetition may be skipped to the end of them all.
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.push(Inst::Split(0, 0))?);
+                            self.node(node)?;
+                        }
+                        let end = self.program.len();
+                        for split in splits {
+                            self.program[split] = Inst::Split(split + 1, end);
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A set of the instructions the automaton is at, in the order they were added, each at most once.
+struct Threads {
+    /// The instructions of the set, in order.
+    list: Vec<usize>,
+    /// Whether each instruction of the program is in the set.
+    present: Vec<bool>,
+}
+
+impl Threads {
+    /// Returns an empty set of the instructions of a program of `len` instructions.
+    fn new(len: usize) -> Self {
+        Threads {
+            list: Vec::with_capacity(len),
+            present: vec![false; len],
+        }
+    }
+
+    /// Empties the set.
+    fn clear(&mut self) {
+        for &pc in &self.list {
+            self.present[pc] = false;
+        }
+        self.list.clear();
+    }
+}
+
+impl Regex {
+    /// Adds `pc` to `threads`, following the jumps, splits and assertions holding between `previous` and
+    /// `current` from it to the instructions which match characters. Returns `true` if the pattern matches.
+    fn add(
+        &self,
+        threads: &mut Threads,
+        stack: &mut Vec<usize>,
+        pc: usize,
+        previous: Option<char>,
+        current: Option<char>,
+    ) -> bool {
+        stack.push(pc);
+        while let Some(pc) = stack.pop() {
+            if threads.present[pc] {
+                continue;
+            }
+            threads.present[pc] = true;
+            threads.list.push(pc);
+            match &self.program[pc] {
+                Inst::Match => return true,
+                Inst::Jump(target) => stack.push(*target),
+                Inst::Split(first, second) => {
+                    stack.push(*second);
+                    stack.push(*first);
+                }
+                Inst::Assert(assertion) if assertion.holds(previous, current) => stack.push(pc + 1),
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if the receiver matches anywhere in `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        let mut current_threads = Threads::new(self.program.len());
+        let mut next_threads = Threads::new(self.program.len());
+        let mut stack = Vec::new();
+        let mut chars = text.chars();
+        let mut previous = None;
+        let mut current = chars.next();
+        loop {
+            // A match may start at any position, so each begins with the first instruction.
+            if self.add(&mut current_threads, &mut stack, 0, previous, current) {
+                return true;
+            }
+            let c = match current {
+                Some(c) => c,
+                None => return false,
+            };
+            let following = chars.next();
+            for index in 0..current_threads.list.len() {
+                let pc = current_threads.list[index];
+                let matched = match &self.program[pc] {
+                    Inst::Char(expected) => *expected == c,
+                    Inst::Any => true,
+                    Inst::Class(class) => class.matches(c),
+                    _ => false,
+                };
+                if matched && self.add(&mut next_threads, &mut stack, pc + 1, current, following) {
+                    return true;
+                }
+            }
+            std::mem::swap(&mut current_threads, &mut next_threads);
+            next_threads.clear();
+            previous = current;
+            current = following;
+        }
+    }
+}
+
+impl FromStr for Regex {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s.chars().collect(),
+            position: 0,
+        };
+        let alternatives = parser.alternatives()?;
+        if parser.peek().is_some() {
+            return Err(Message::new("pattern-unbalanced-close"));
+        }
+
+        let mut compiler = Compiler::default();
+        compiler.alternatives(&alternatives)?;
+        compiler.push(Inst::Match)?;
+        Ok(Regex {
+            program: compiler.program,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `true` if `pattern` matches `text`.
+    fn is_match(pattern: &str, text: &str) -> bool {
+        pattern.parse::<Regex>().unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_regex() {
+        assert!(is_match("^\\s*#", "  # comment"));
+        assert!(!is_match("^\\s*#", "code # trailing"));
+        assert!(is_match("colou?r", "the color"));
+        assert!(is_match("\\b(ERROR|WARN):", "2024 WARN: disk"));
+        assert!(!is_match("\\b(ERROR|WARN):", "PREWARN: disk"));
+        assert!(is_match("\\bfoo\\b", "a foo.") && !is_match("\\bfoo\\b", "foobar"));
+        assert!(is_match("\\Boo", "foo") && !is_match("\\Bfoo", "foo"));
+        assert!(is_match("a\\.b\\t[\\n\\]]", "a.b\t]"));
+        assert!(is_match("^[a-c]+\\d{2,3}$", "abc123"));
+        assert!(!is_match("^[a-c]+\\d{2,3}$", "abc1234"));
+        assert!(is_match("[^x]y", "zy"));
+        assert!(!is_match("a.c", "äbc") && is_match("ä.c", "äbc"));
+        assert!(is_match("(a*)*b", "aaab"));
+        assert!(is_match("", "anything"));
+
+        assert!("(a".parse::<Regex>().is_err());
+        assert!("a)".parse::<Regex>().is_err());
+        assert!("*a".parse::<Regex>().is_err());
+        assert!("[z-a]".parse::<Regex>().is_err());
+        assert!("\\q".parse::<Regex>().is_err());
+        assert!("[\\D]".parse::<Regex>().is_err());
+        assert!("\\b+".parse::<Regex>().is_err());
+        assert!("(a{100}){200}".parse::<Regex>().is_err());
+    }
+
+    #[test]
+    fn test_regex_long_and_nested() {
+        // Neither a long line nor nested quantifiers take more than time linear in the text.
+        let mut long = "a".repeat(200_000);
+        assert!(!is_match("a.*b", &long));
+        long.push('b');
+        assert!(is_match("a.*b", &long));
+        assert!(!is_match("(a+)+b", &"a".repeat(29)));
+        assert!(is_match("(a|aa)*$", &"a".repeat(1000)));
+    }
+}
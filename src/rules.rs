@@ -0,0 +1,261 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::str::FromStr;
+
+use rayon::prelude::*;
+
+use crate::i18n::Message;
+use crate::segment::Segmenter;
+use crate::utf8;
+
+/// Size of the chunks a buffer is split into for parallel scanning.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A set of rules governing which characters form words.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum WordRules {
+    /// Words are runs of alphanumeric characters, all other characters separate words.
+    Simple,
+    /// As `Simple`, except that a single apostrophe or hyphen between two alphanumeric characters
+    /// joins them, so contractions and hyphenated compounds count as one word. Dashes separate words.
+    English,
+}
+
+impl FromStr for WordRules {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simple" => Ok(WordRules::Simple),
+            "english" => Ok(WordRules::English),
+            _ => Err(Message::new("unknown-word-rules").arg("value", s)),
+        }
+    }
+}
+
+/// The class of a character when counting words.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum CharClass {
+    /// The character separates words.
+    Separator,
+    /// The character is part of a word.
+    Word,
+    /// The character is part of a word only when it appears singly between two word characters.
+    Joiner,
+    /// The character is a word on its own.
+    Standalone,
+}
+
+impl WordRules {
+    /// Returns the class of `c` under the receiver.
+    fn classify(self, c: char) -> CharClass {
+        match (self, c) {
+            (_, c) if c.is_alphanumeric() => CharClass::Word,
+            (WordRules::English, '\'') | (WordRules::English, '\u{2019}') => CharClass::Joiner,
+            (WordRules::English, '-') | (WordRules::English, '\u{2010}') => CharClass::Joiner,
+            _ => CharClass::Separator,
+        }
+    }
+}
+
+/// The rules used to classify characters when counting words.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Classifier {
+    /// The rules used to split words, or `None` to split words on ASCII whitespace.
+    pub word_rules: Option<WordRules>,
+    /// The segmenter used for scripts which do not separate words with spaces, if any.
+    pub segmenter: Option<Segmenter>,
+    /// Whether words are split on Unicode whitespace rather than ASCII whitespace, without word rules.
+    pub unicode_whitespace: bool,
+}
+
+impl Classifier {
+    /// Returns the class of `c` under the receiver.
+    fn classify(self, c: char) -> CharClass {
+        if let Some(class) = self.segmenter.and_then(|segmenter| segmenter.classify(c)) {
+            return class;
+        }
+
+        match self.word_rules {
+            Some(rules) => rules.classify(c),
+            None if self.unicode_whitespace && c.is_whitespace() => CharClass::Separator,
+            None if c.is_ascii_whitespace() => CharClass::Separator,
+            None => CharClass::Word,
+        }
+    }
+}
+
+/// The state of the word-boundary state machine.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum State {
+    /// Outside of a word.
+    Outside = 0,
+    /// Inside of a word.
+    InWord = 1,
+    /// Immediately after a joiner which followed a word character.
+    AfterJoiner = 2,
+}
+
+impl State {
+    /// Returns the state after a character of class `class`, and the number of words it began.
+    fn next(self, class: CharClass) -> (State, u64) {
+        match (self, class) {
+            (State::Outside, CharClass::Word) => (State::InWord, 1),
+            (_, CharClass::Word) => (State::InWord, 0),
+            (_, CharClass::Standalone) => (State::Outside, 1),
+            (State::InWord, CharClass::Joiner) => (State::AfterJoiner, 0),
+            _ => (State::Outside, 0),
+        }
+    }
+}
+
+/// Representation of a chunk of text under a set of word rules.
+///
+/// Whether the first word of a chunk continues a word from the preceding text depends on more than the
+/// character immediately before it, so the chunk is represented by the transition it effects on the
+/// word-boundary state machine from each possible entry state.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct RuleFlux {
+    /// The exit state and number of words begun, indexed by entry state.
+    transitions: [(State, u64); 3],
+    /// The number of lines in the chunk.
+    pub lines: u64,
+}
+
+impl RuleFlux {
+    /// Returns a new instance of the receiver encoding a single character of class `class`.
+    fn new(class: CharClass, lines: u64) -> Self {
+        RuleFlux {
+            transitions: [
+                State::Outside.next(class),
+                State::InWord.next(class),
+                State::AfterJoiner.next(class),
+            ],
+            lines,
+        }
+    }
+
+    /// Returns a new instance of the receiver encoding the character `c` under `classifier`.
+    fn from_char(c: char, classifier: Classifier) -> Self {
+        let lines = if c == '\n' { 1 } else { 0 };
+        RuleFlux::new(classifier.classify(c), lines)
+    }
+
+    /// Returns a new RuleFlux spanning the receiver on the left, and `rhs` on the right.
+    fn span(self, rhs: RuleFlux) -> Self {
+        let mut transitions = self.transitions;
+        for transition in transitions.iter_mut() {
+            let (state, words) = rhs.transitions[transition.0 as usize];
+            *transition = (state, transition.1 + words);
+        }
+
+        RuleFlux {
+            transitions,
+            lines: self.lines + rhs.lines,
+        }
+    }
+
+    /// Returns the number of words in the chunk, assuming it is not preceded by part of a word.
+    pub fn words(&self) -> u64 {
+        self.transitions[State::Outside as usize].1
+    }
+}
+
+/// Takes two optional RuleFlux instances and returns, where possible, the span of the two.
+pub fn span_opt(lhs: Option<RuleFlux>, rhs: Option<RuleFlux>) -> Option<RuleFlux> {
+    match (lhs, rhs) {
+        (Some(left_flux), Some(right_flux)) => Some(left_flux.span(right_flux)),
+        (flux, None) | (None, flux) => flux,
+    }
+}
+
+/// Computes the flux over the provided input byte string under `classifier`.
+///
+/// Bytes which are not valid UTF-8 are considered to be part of a word.
+pub fn rule_flux_over_byte_string<T>(input: T, classifier: Classifier) -> Option<RuleFlux>
+where
+    T: AsRef<[u8]>,
+{
+    let word_byte = RuleFlux::new(CharClass::Word, 0);
+    // Each chunk is scanned on its own, split where no character is, and the fluxes of the chunks spanned.
+    utf8::split_chunks(input.as_ref(), CHUNK_SIZE)
+        .par_iter()
+        .map(|chunk| {
+            chunk.utf8_chunks().fold(None, |flux, piece| {
+                let valid = piece.valid().chars().fold(flux, |flux, c| {
+                    span_opt(flux, Some(RuleFlux::from_char(c, classifier)))
+                });
+                piece
+                    .invalid()
+                    .iter()
+                    .fold(valid, |flux, _| span_opt(flux, Some(word_byte)))
+            })
+        })
+        .reduce(|| None, span_opt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str, word_rules: WordRules) -> u64 {
+        let classifier = Classifier {
+            word_rules: Some(word_rules),
+            segmenter: None,
+            ..Classifier::default()
+        };
+        rule_flux_over_byte_string(input, classifier)
+            .map(|f| f.words())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_simple_word_rules() {
+        assert_eq!(words("it's a well-known fact", WordRules::Simple), 6);
+        assert_eq!(words("one\u{2014}two -- three", WordRules::Simple), 3);
+    }
+
+    #[test]
+    fn test_english_word_rules() {
+        assert_eq!(words("it's a well-known fact", WordRules::English), 4);
+        assert_eq!(words("one\u{2014}two -- three", WordRules::English), 3);
+        assert_eq!(
+            words("'quoted' rock--roll don\u{2019}t", WordRules::English),
+            4
+        );
+    }
+
+    #[test]
+    fn test_rule_flux_span_across_joiner() {
+        let classifier = Classifier {
+            word_rules: Some(WordRules::English),
+            segmenter: None,
+            ..Classifier::default()
+        };
+        let flux_l = rule_flux_over_byte_string("a well-", classifier);
+        let flux_r = rule_flux_over_byte_string("known fact", classifier);
+
+        assert_eq!(span_opt(flux_l, flux_r).map(|f| f.words()), Some(3));
+
+        // Hyphenated words straddle the boundaries of the chunks scanned in parallel.
+        let text = "well-known ".repeat(CHUNK_SIZE / 5);
+        assert_eq!(words(&text, WordRules::English), CHUNK_SIZE as u64 / 5);
+    }
+
+    #[test]
+    fn test_cjk_segmenter() {
+        let classifier = Classifier {
+            word_rules: None,
+            segmenter: Some(Segmenter::Cjk),
+            ..Classifier::default()
+        };
+        let flux = rule_flux_over_byte_string("我爱北京。 rust語 ひらがな", classifier);
+
+        assert_eq!(flux.map(|f| f.words()), Some(10));
+    }
+}
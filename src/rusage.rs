@@ -0,0 +1,128 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use wc_rs::i18n;
+
+/// The resources used by the process so far.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Usage {
+    /// The peak resident set size, in kibibytes.
+    pub max_rss: u64,
+    /// The CPU time spent in user mode.
+    pub user: Duration,
+    /// The CPU time spent in the kernel on behalf of the process.
+    pub system: Duration,
+    /// The number of threads counting, including the main thread.
+    pub threads: usize,
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let usage = i18n::message(
+            "resource-usage",
+            &[
+                ("rss", &self.max_rss),
+                ("user", &format!("{:.3}", self.user.as_secs_f64())),
+                ("system", &format!("{:.3}", self.system.as_secs_f64())),
+                ("threads", &self.threads),
+            ],
+        );
+        f.write_str(&usage)
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::{c_int, c_long};
+
+    /// `RUSAGE_SELF` of `<sys/resource.h>`.
+    pub const RUSAGE_SELF: c_int = 0;
+
+    /// `suseconds_t` of `<sys/types.h>`.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub type Suseconds = i32;
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    pub type Suseconds = c_long;
+
+    /// `struct timeval` of `<sys/time.h>`.
+    #[repr(C)]
+    #[derive(Copy, Clone, Default)]
+    pub struct Timeval {
+        pub tv_sec: c_long,
+        pub tv_usec: Suseconds,
+    }
+
+    /// `struct rusage` of `<sys/resource.h>`, of which only the times and peak resident set size are read.
+    #[repr(C)]
+    #[derive(Copy, Clone, Default)]
+    pub struct Rusage {
+        pub ru_utime: Timeval,
+        pub ru_stime: Timeval,
+        pub ru_maxrss: c_long,
+        pub ru_other: [c_long; 13],
+    }
+
+    /// The bytes of the units `ru_maxrss` is in, which are bytes on Apple platforms and kibibytes elsewhere.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const MAXRSS_UNIT: u64 = 1;
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    pub const MAXRSS_UNIT: u64 = 1024;
+
+    extern "C" {
+        pub fn getrusage(who: c_int, usage: *mut Rusage) -> c_int;
+    }
+}
+
+/// Returns `time` as a duration.
+#[cfg(unix)]
+fn duration(time: sys::Timeval) -> Duration {
+    Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+}
+
+/// Returns the resources used by the process so far.
+#[cfg(unix)]
+pub fn current() -> io::Result<Usage> {
+    let mut usage = sys::Rusage::default();
+    // SAFETY: `usage` is a valid, writable `struct rusage` for the duration of the call.
+    if unsafe { sys::getrusage(sys::RUSAGE_SELF, &mut usage) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Usage {
+        max_rss: usage.ru_maxrss as u64 * sys::MAXRSS_UNIT / 1024,
+        user: duration(usage.ru_utime),
+        system: duration(usage.ru_stime),
+        threads: rayon::current_num_threads() + 1,
+    })
+}
+
+/// Fails to return the resources used by the process, as they are only read on Unix.
+#[cfg(not(unix))]
+pub fn current() -> io::Result<Usage> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        i18n::message("resource-usage-unsupported", &[]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_current() {
+        let usage = current().unwrap();
+
+        assert!(usage.max_rss > 0);
+        assert!(usage.threads > 1);
+        assert!(usage.to_string().starts_with("max rss "));
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use wc_rs::i18n::Message;
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::os::raw::{c_int, c_long};
+
+    /// `PR_SET_NO_NEW_PRIVS` of `<linux/prctl.h>`.
+    pub const PR_SET_NO_NEW_PRIVS: c_int = 38;
+
+    /// The numbers of the Landlock system calls, which are the same on every architecture and have no C
+    /// library wrappers.
+    pub const SYS_LANDLOCK_CREATE_RULESET: c_long = 444;
+    pub const SYS_LANDLOCK_RESTRICT_SELF: c_long = 446;
+
+    /// `LANDLOCK_CREATE_RULESET_VERSION` of `<linux/landlock.h>`.
+    pub const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1;
+
+    /// `struct landlock_ruleset_attr` of `<linux/landlock.h>`, as of Landlock ABI 4.
+    #[repr(C)]
+    pub struct RulesetAttr {
+        pub handled_access_fs: u64,
+        pub handled_access_net: u64,
+    }
+
+    extern "C" {
+        pub fn prctl(option: c_int, ...) -> c_int;
+        pub fn syscall(number: c_long, ...) -> c_long;
+        pub fn close(fd: c_int) -> c_int;
+    }
+}
+
+#[cfg(target_os = "openbsd")]
+mod sys {
+    use std::os::raw::{c_char, c_int};
+
+    extern "C" {
+        pub fn pledge(promises: *const c_char, execpromises: *const c_char) -> c_int;
+    }
+}
+
+/// Returns the filesystem access rights Landlock ABI `abi` can deny.
+#[cfg(target_os = "linux")]
+fn handled_access_fs(abi: i64) -> u64 {
+    match abi {
+        // EXECUTE through TRUNCATE.
+        4 => (1 << 15) - 1,
+        // IOCTL_DEV.
+        _ => (1 << 16) - 1,
+    }
+}
+
+/// Returns the message `identifier`, of an operation which failed, with the last OS error as its argument.
+#[cfg(target_os = "linux")]
+fn last_error(identifier: &'static str) -> Message {
+    Message::new(identifier).arg("error", std::io::Error::last_os_error())
+}
+
+/// Denies the calling thread, and any thread it creates afterwards, all further filesystem access and TCP binds
+/// and connections. Files already open remain usable. Kernels older than Landlock ABI 4, which cannot deny
+/// network access, refuse to enter it.
+#[cfg(target_os = "linux")]
+pub fn enter() -> Result<(), Message> {
+    // SAFETY: querying the ABI version passes no ruleset.
+    let abi = unsafe {
+        sys::syscall(
+            sys::SYS_LANDLOCK_CREATE_RULESET,
+            std::ptr::null::<sys::RulesetAttr>(),
+            0usize,
+            sys::LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    if abi < 1 {
+        return Err(last_error("landlock-unavailable"));
+    }
+    if abi < 4 {
+        return Err(Message::new("landlock-abi-too-old").arg("abi", abi));
+    }
+
+    // A ruleset handling every right and granting none denies all of them.
+    let attr = sys::RulesetAttr {
+        handled_access_fs: handled_access_fs(abi),
+        handled_access_net: 0b11,
+    };
+    let size = std::mem::size_of::<sys::RulesetAttr>();
+    // SAFETY: `attr` is a valid ruleset attribute of `size` bytes for the duration of the call.
+    let ruleset = unsafe { sys::syscall(sys::SYS_LANDLOCK_CREATE_RULESET, &attr, size, 0u32) };
+    if ruleset < 0 {
+        return Err(last_error("landlock-ruleset-failed"));
+    }
+
+    // SAFETY: these calls take no pointers, and `ruleset` is a descriptor owned here.
+    unsafe {
+        let result = if sys::prctl(sys::PR_SET_NO_NEW_PRIVS, 1u64, 0u64, 0u64, 0u64) != 0 {
+            Err(last_error("no-new-privs-failed"))
+        } else if sys::syscall(sys::SYS_LANDLOCK_RESTRICT_SELF, ruleset as i32, 0u32) != 0 {
+            Err(last_error("landlock-restrict-failed"))
+        } else {
+            Ok(())
+        };
+        sys::close(ruleset as i32);
+        result
+    }
+}
+
+/// Restricts the process to operations on the files it already has open, through `pledge("stdio")`.
+#[cfg(target_os = "openbsd")]
+pub fn enter() -> Result<(), Message> {
+    // SAFETY: the promises are a valid NUL-terminated string, and null leaves the exec promises unchanged.
+    match unsafe { sys::pledge(b"stdio\0".as_ptr().cast(), std::ptr::null()) } {
+        0 => Ok(()),
+        _ => Err(Message::new("pledge-failed").arg("error", std::io::Error::last_os_error())),
+    }
+}
+
+/// Fails to sandbox the process, as sandboxing is only supported on Linux and OpenBSD.
+#[cfg(not(any(target_os = "linux", target_os = "openbsd")))]
+pub fn enter() -> Result<(), Message> {
+    Err(Message::new("sandbox-unsupported"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_enter() {
+        // The sandbox is entered on a thread of its own, leaving the other tests unaffected. Kernels without
+        // Landlock, or too old a version of it, refuse to enter it rather than running unsandboxed.
+        std::thread::spawn(|| {
+            let open = std::fs::File::open("Cargo.toml").unwrap();
+            if enter().is_ok() {
+                assert!(std::fs::File::open("Cargo.toml").is_err());
+                assert!(open.metadata().is_ok());
+            }
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::str::FromStr;
+
+use crate::i18n::Message;
+use crate::rules::CharClass;
+
+/// A segmenter for scripts which do not separate words with spaces.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Segmenter {
+    /// Each Han ideograph and kana character is a word, and CJK punctuation separates words. This is the
+    /// convention word processors use to count Chinese and Japanese text.
+    Cjk,
+}
+
+impl FromStr for Segmenter {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cjk" => Ok(Segmenter::Cjk),
+            _ => Err(Message::new("unknown-segmenter").arg("value", s)),
+        }
+    }
+}
+
+impl Segmenter {
+    /// Returns the class of `c` under the receiver, or `None` if the receiver does not segment `c`.
+    pub fn classify(self, c: char) -> Option<CharClass> {
+        match (self, c as u32) {
+            // CJK Symbols and Punctuation, including the ideographic space.
+            (Segmenter::Cjk, 0x3000..=0x303F) => Some(CharClass::Separator),
+            // Hiragana, Katakana and Katakana Phonetic Extensions.
+            (Segmenter::Cjk, 0x3040..=0x30FF) | (Segmenter::Cjk, 0x31F0..=0x31FF) => {
+                Some(CharClass::Standalone)
+            }
+            // CJK Unified Ideographs, Extension A and Compatibility Ideographs.
+            (Segmenter::Cjk, 0x3400..=0x4DBF)
+            | (Segmenter::Cjk, 0x4E00..=0x9FFF)
+            | (Segmenter::Cjk, 0xF900..=0xFAFF) => Some(CharClass::Standalone),
+            // Halfwidth Katakana.
+            (Segmenter::Cjk, 0xFF66..=0xFF9F) => Some(CharClass::Standalone),
+            // CJK Unified Ideographs Extensions B through F and Compatibility Ideographs Supplement.
+            (Segmenter::Cjk, 0x20000..=0x2FA1F) => Some(CharClass::Standalone),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Vectorized counting of the words and lines of a chunk, with a scalar fallback.
+//!
+//! A word is counted where a byte which is not ASCII whitespace, as `u8::is_ascii_whitespace` defines it,
+//! follows one which is or the start of the chunk.
+
+/// Returns the number of words and lines in `chunk` scanned a byte at a time, and whether its last byte is
+/// whitespace. Words are counted as though the chunk follows whitespace if `after_space` is set.
+fn scalar(chunk: &[u8], mut after_space: bool) -> (u64, u64, bool) {
+    let (mut words, mut lines) = (0, 0);
+    for &byte in chunk {
+        let is_space = byte.is_ascii_whitespace();
+        words += u64::from(after_space && !is_space);
+        lines += u64::from(byte == b'\n');
+        after_space = is_space;
+    }
+    (words, lines, after_space)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    /// Returns the number of words and lines in `chunk`, scanning 16 bytes at a time.
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn sse2(chunk: &[u8]) -> (u64, u64) {
+        let splat = |byte: u8| _mm_set1_epi8(byte as i8);
+        let (space, tab, line_feed, form_feed, carriage_return) = (
+            splat(b' '),
+            splat(b'\t'),
+            splat(b'\n'),
+            splat(0x0C),
+            splat(b'\r'),
+        );
+
+        let (mut words, mut lines) = (0, 0);
+        let mut after_space = 1;
+        let mut blocks = chunk.chunks_exact(16);
+        for block in &mut blocks {
+            let bytes = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+            let newlines = _mm_cmpeq_epi8(bytes, line_feed);
+            let spaces = _mm_or_si128(
+                _mm_or_si128(_mm_cmpeq_epi8(bytes, space), _mm_cmpeq_epi8(bytes, tab)),
+                _mm_or_si128(
+                    newlines,
+                    _mm_or_si128(
+                        _mm_cmpeq_epi8(bytes, form_feed),
+                        _mm_cmpeq_epi8(bytes, carriage_return),
+                    ),
+                ),
+            );
+            let spaces = _mm_movemask_epi8(spaces) as u32;
+            let starts = !spaces & ((spaces << 1) | after_space) & 0xFFFF;
+            words += u64::from(starts.count_ones());
+            lines += u64::from((_mm_movemask_epi8(newlines) as u32).count_ones());
+            after_space = spaces >> 15;
+        }
+
+        let (tail_words, tail_lines, _) = super::scalar(blocks.remainder(), after_space != 0);
+        (words + tail_words, lines + tail_lines)
+    }
+
+    /// Returns the number of words and lines in `chunk`, scanning 32 bytes at a time.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn avx2(chunk: &[u8]) -> (u64, u64) {
+        let splat = |byte: u8| _mm256_set1_epi8(byte as i8);
+        let (space, tab, line_feed, form_feed, carriage_return) = (
+            splat(b' '),
+            splat(b'\t'),
+            splat(b'\n'),
+            splat(0x0C),
+            splat(b'\r'),
+        );
+
+        let (mut words, mut lines) = (0, 0);
+        let mut after_space = 1;
+        let mut blocks = chunk.chunks_exact(32);
+        for block in &mut blocks {
+            let bytes = _mm256_loadu_si256(block.as_ptr() as *const __m256i);
+            let newlines = _mm256_cmpeq_epi8(bytes, line_feed);
+            let spaces = _mm256_or_si256(
+                _mm256_or_si256(
+                    _mm256_cmpeq_epi8(bytes, space),
+                    _mm256_cmpeq_epi8(bytes, tab),
+                ),
+                _mm256_or_si256(
+                    newlines,
+                    _mm256_or_si256(
+                        _mm256_cmpeq_epi8(bytes, form_feed),
+                        _mm256_cmpeq_epi8(bytes, carriage_return),
+                    ),
+                ),
+            );
+            let spaces = _mm256_movemask_epi8(spaces) as u32;
+            let starts = !spaces & ((spaces << 1) | after_space);
+            words += u64::from(starts.count_ones());
+            lines += u64::from((_mm256_movemask_epi8(newlines) as u32).count_ones());
+            after_space = spaces >> 31;
+        }
+
+        let (tail_words, tail_lines, _) = super::scalar(blocks.remainder(), after_space != 0);
+        (words + tail_words, lines + tail_lines)
+    }
+}
+
+/// Returns the number of words and lines in `chunk`, using the widest vector instructions the processor
+/// supports.
+pub fn count_words_and_lines(chunk: &[u8]) -> (u64, u64) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: the processor supports AVX2, as was just detected.
+            return unsafe { x86::avx2(chunk) };
+        }
+        // SAFETY: SSE2 is part of the x86-64 baseline.
+        unsafe { x86::sse2(chunk) }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        let (words, lines, _) = scalar(chunk, true);
+        (words, lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_words_and_lines() {
+        // Every whitespace byte and a vertical tab, which is not one, straddling the vector boundaries.
+        let input: Vec<u8> = b"ab \t\x0B c\nd\x0Cef\r\n  ghij klm\n"
+            .iter()
+            .cycle()
+            .take(1000)
+            .copied()
+            .collect();
+        for start in 0..40 {
+            let chunk = &input[start..];
+            let (words, lines, _) = scalar(chunk, true);
+            assert_eq!(count_words_and_lines(chunk), (words, lines));
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                assert_eq!(unsafe { x86::sse2(chunk) }, (words, lines));
+                if is_x86_feature_detected!("avx2") {
+                    assert_eq!(unsafe { x86::avx2(chunk) }, (words, lines));
+                }
+            }
+        }
+        assert_eq!(count_words_and_lines(b""), (0, 0));
+        assert_eq!(count_words_and_lines(b"one two\n"), (2, 1));
+    }
+}
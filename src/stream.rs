@@ -0,0 +1,157 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use wc_rs::i18n::Message;
+use wc_rs::Counts;
+
+/// Parses `duration`, a whole number with a unit of `ms`, `s`, `m` or `h` such as `60s`.
+pub fn parse_duration(duration: &str) -> Result<Duration, Message> {
+    let invalid = || Message::new("malformed-duration").arg("value", duration);
+    let duration = duration.trim();
+    let split = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let count: u64 = duration[..split].parse().map_err(|_| invalid())?;
+    let duration = match &duration[split..] {
+        "ms" => Duration::from_millis(count),
+        "s" => Duration::from_secs(count),
+        "m" => Duration::from_secs(count.saturating_mul(60)),
+        "h" => Duration::from_secs(count.saturating_mul(60 * 60)),
+        _ => return Err(invalid()),
+    };
+    if duration.is_zero() {
+        return Err(invalid());
+    }
+    Ok(duration)
+}
+
+/// The counts of the lines of a stream which arrived within a sliding window of time.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Window {
+    /// The length of the window.
+    length: Duration,
+    /// When the window was opened, before which no lines arrived.
+    opened: Instant,
+    /// The counts of each line within the window and when it arrived, oldest first.
+    lines: VecDeque<(Instant, Counts)>,
+}
+
+impl Window {
+    /// Returns an empty window of `length` opened at `now`.
+    pub fn new(length: Duration, now: Instant) -> Self {
+        Window {
+            length,
+            opened: now,
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Adds a line with `counts` which arrived at `at`.
+    pub fn add(&mut self, at: Instant, counts: Counts) {
+        self.lines.push_back((at, counts));
+    }
+
+    /// Returns the counts of the lines which arrived within the window ending at `now`, forgetting those
+    /// which arrived before it.
+    pub fn snapshot(&mut self, now: Instant) -> Snapshot {
+        while let Some(&(at, _)) = self.lines.front() {
+            if now.duration_since(at) < self.length {
+                break;
+            }
+            self.lines.pop_front();
+        }
+
+        let mut counts = Counts::default();
+        for (_, line) in &self.lines {
+            counts += *line;
+        }
+        Snapshot {
+            counts,
+            // Until the window has been open for its whole length, rates are over the time it has been.
+            span: self.length.min(now.duration_since(self.opened)),
+        }
+    }
+}
+
+/// The counts of the lines within a window, and the time they arrived over.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Snapshot {
+    /// The counts of the lines.
+    pub counts: Counts,
+    /// The time the lines arrived over.
+    pub span: Duration,
+}
+
+impl Snapshot {
+    /// Returns `count` per second of the span of the snapshot.
+    fn rate(&self, count: u64) -> f64 {
+        match self.span.as_secs_f64() {
+            seconds if seconds > 0.0 => count as f64 / seconds,
+            _ => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>8} {:>7} {:>7} {:>9.2} lines/s {:>9.2} words/s",
+            self.counts.lines,
+            self.counts.words,
+            self.counts.bytes,
+            self.rate(self.counts.lines),
+            self.rate(self.counts.words)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert!(parse_duration("60").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    #[test]
+    fn test_window() {
+        let start = Instant::now();
+        let line = Counts {
+            lines: 1,
+            words: 4,
+            bytes: 20,
+            ..Counts::default()
+        };
+        let mut window = Window::new(Duration::from_secs(10), start);
+        window.add(start + Duration::from_secs(1), line);
+        window.add(start + Duration::from_secs(2), line);
+
+        let snapshot = window.snapshot(start + Duration::from_secs(5));
+        assert_eq!(snapshot.counts.lines, 2);
+        assert_eq!(snapshot.span, Duration::from_secs(5));
+
+        window.add(start + Duration::from_secs(11), line);
+        window.add(start + Duration::from_secs(12), line);
+        let snapshot = window.snapshot(start + Duration::from_secs(15));
+        assert_eq!((snapshot.counts.lines, snapshot.counts.words), (2, 8));
+        assert_eq!(
+            snapshot.to_string(),
+            "       2       8      40      0.20 lines/s      0.80 words/s"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// Returns `true` if the lower case letter `c` is a vowel, counting `y` as one.
+fn is_vowel(c: char) -> bool {
+    "aeiouyàáâäèéêëìíîïòóôöùúûü".contains(c)
+}
+
+/// Returns `true` if `c` is an apostrophe, which joins the letters of a contraction into one word.
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '\u{2019}'
+}
+
+/// Estimates the syllables of the words of a text fed to it in order, in pieces split anywhere.
+///
+/// Each run of letters is a word, whose syllables are its groups of consecutive vowels, less a silent final
+/// `e` other than in an `-le` ending after a consonant, and at least one. A `y` beginning a word is a
+/// consonant. The heuristic is tuned for English and is usually within a syllable per word.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct SyllableCounter {
+    /// The syllables of the words completed so far.
+    syllables: u64,
+    /// The number of letters of the current word.
+    letters: u64,
+    /// The number of vowel groups of the current word.
+    groups: u64,
+    /// Whether the last letter of the current word is a vowel.
+    after_vowel: bool,
+    /// The last three letters of the current word in lower case, the latest last.
+    tail: [char; 3],
+}
+
+impl SyllableCounter {
+    /// Counts the syllables of `text`, which continues the text fed so far.
+    pub fn feed(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_alphabetic() {
+                let c = c.to_lowercase().next().unwrap_or(c);
+                let vowel = is_vowel(c) && !(c == 'y' && self.letters == 0);
+                if vowel && !self.after_vowel {
+                    self.groups += 1;
+                }
+                self.after_vowel = vowel;
+                self.letters += 1;
+                self.tail = [self.tail[1], self.tail[2], c];
+            } else if !(is_apostrophe(c) && self.letters > 0) {
+                self.end_word();
+            }
+        }
+    }
+
+    /// Adds the syllables of the current word, if any, to the count.
+    fn end_word(&mut self) {
+        if self.letters == 0 {
+            return;
+        }
+
+        let [third_last, second_last, last] = self.tail;
+        let le_ending = second_last == 'l' && third_last != '\0' && !is_vowel(third_last);
+        let silent_e = last == 'e' && self.groups > 1 && !le_ending;
+        self.syllables += (self.groups - silent_e as u64).max(1);
+        *self = SyllableCounter {
+            syllables: self.syllables,
+            ..SyllableCounter::default()
+        };
+    }
+
+    /// Returns the syllables of all of the text fed.
+    pub fn finish(mut self) -> u64 {
+        self.end_word();
+        self.syllables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the estimated syllables of `text`.
+    fn syllables(text: &str) -> u64 {
+        let mut counter = SyllableCounter::default();
+        counter.feed(text);
+        counter.finish()
+    }
+
+    #[test]
+    fn test_syllables() {
+        assert_eq!(syllables("The table is beautiful."), 7);
+        assert_eq!(syllables("make running syllable"), 6);
+        assert_eq!(syllables("Yes, don't rhythm"), 3);
+        assert_eq!(syllables(""), 0);
+
+        let mut counter = SyllableCounter::default();
+        counter.feed("beau");
+        counter.feed("tiful ta");
+        counter.feed("ble");
+        assert_eq!(counter.finish(), 5);
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Size of the blocks read backwards from the end of the input when searching for lines.
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// The final portion of each input which is counted.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Tail {
+    /// The last given number of lines.
+    Lines(u64),
+    /// The last given number of bytes.
+    Bytes(u64),
+}
+
+/// Seeks `input` to the start of its `tail`, found by reading backwards from the end so that only the tail
+/// itself is read however large the input is.
+pub fn seek_to_tail<R: Read + Seek>(input: &mut R, tail: Tail) -> io::Result<()> {
+    let length = input.seek(SeekFrom::End(0))?;
+    let start = match tail {
+        Tail::Bytes(count) => length.saturating_sub(count),
+        Tail::Lines(count) => lines_start(input, length, count)?,
+    };
+    input.seek(SeekFrom::Start(start))?;
+    Ok(())
+}
+
+/// Returns the offset of the start of the last `count` lines of `input`, which is `length` bytes long.
+fn lines_start<R: Read + Seek>(input: &mut R, length: u64, count: u64) -> io::Result<u64> {
+    if count == 0 {
+        return Ok(length);
+    }
+
+    let mut end = length;
+    let mut block = vec![0; BLOCK_SIZE as usize];
+    let mut found = 0;
+    let mut first = true;
+    while end > 0 {
+        let start = end.saturating_sub(BLOCK_SIZE);
+        let block = &mut block[..(end - start) as usize];
+        input.seek(SeekFrom::Start(start))?;
+        input.read_exact(block)?;
+
+        // The terminator of the last line does not begin another.
+        let mut searched = &block[..];
+        if first && searched.last() == Some(&b'\n') {
+            searched = &searched[..searched.len() - 1];
+        }
+        first = false;
+
+        for (index, _) in searched
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, &byte)| byte == b'\n')
+        {
+            found += 1;
+            if found == count {
+                return Ok(start + index as u64 + 1);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Returns the tail of `input`.
+    fn tail_of(input: &str, tail: Tail) -> String {
+        let mut cursor = Cursor::new(input.as_bytes());
+        seek_to_tail(&mut cursor, tail).unwrap();
+        let mut output = String::new();
+        cursor.read_to_string(&mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_seek_to_tail() {
+        let input = "one\ntwo\nthree\n";
+
+        assert_eq!(tail_of(input, Tail::Lines(2)), "two\nthree\n");
+        assert_eq!(tail_of("one\ntwo\nthree", Tail::Lines(1)), "three");
+        assert_eq!(tail_of(input, Tail::Lines(5)), input);
+        assert_eq!(tail_of(input, Tail::Lines(0)), "");
+        assert_eq!(tail_of(input, Tail::Bytes(6)), "three\n");
+        assert_eq!(tail_of(input, Tail::Bytes(99)), input);
+
+        let long = format!("{}\n{}\n", "x".repeat(100_000), "y".repeat(70_000));
+        assert_eq!(
+            tail_of(&long, Tail::Lines(1)),
+            format!("{}\n", "y".repeat(70_000))
+        );
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::columns::Column;
+use wc_rs::i18n::Message;
+use wc_rs::Counts;
+
+/// A part of a template.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+enum Segment {
+    /// Literal text.
+    Text(String),
+    /// The value of a column for the current row, or for the totals of all rows.
+    Value { column: Column, total: bool },
+    /// Segments repeated for each row.
+    Rows(Vec<Segment>),
+}
+
+/// A report template in a subset of the Handlebars syntax.
+///
+/// `{{#each rows}}` ... `{{/each}}` repeats its contents for each output row, within which `{{words}}`
+/// and the other column names of `--columns` stand for the values of the row. Anywhere in the template
+/// `{{total.words}}` and so on stand for the sums over all rows, with `{{total.file}}` being `total`.
+/// Values are inserted verbatim, without escaping.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Template {
+    /// The parts of the template, in order.
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parses the template `text`.
+    pub fn parse(text: &str) -> Result<Self, Message> {
+        let mut segments = Vec::new();
+        let mut rows: Option<Vec<Segment>> = None;
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            let end = rest[start..]
+                .find("}}")
+                .map(|end| start + end)
+                .ok_or_else(|| Message::new("template-unterminated-expression"))?;
+            let text = Segment::Text(rest[..start].to_string());
+            let expression = rest[start + 2..end].trim();
+            rest = &rest[end + 2..];
+
+            let current = rows.as_mut().unwrap_or(&mut segments);
+            current.push(text);
+            match expression {
+                "#each rows" if rows.is_none() => rows = Some(Vec::new()),
+                "/each" => {
+                    let body = rows
+                        .take()
+                        .ok_or_else(|| Message::new("template-unopened-each"))?;
+                    segments.push(Segment::Rows(body));
+                }
+                _ => {
+                    let (name, total) = match expression.strip_prefix("total.") {
+                        Some(name) => (name, true),
+                        None if rows.is_some() => (expression, false),
+                        None => {
+                            return Err(Message::new("template-value-outside-each")
+                                .arg("expression", expression))
+                        }
+                    };
+                    let column = name.parse()?;
+                    rows.as_mut()
+                        .unwrap_or(&mut segments)
+                        .push(Segment::Value { column, total });
+                }
+            }
+        }
+
+        if rows.is_some() {
+            return Err(Message::new("template-unclosed-each"));
+        }
+        segments.push(Segment::Text(rest.to_string()));
+        Ok(Template { segments })
+    }
+
+    /// Returns the columns the receiver refers to.
+    pub fn columns(&self) -> Vec<Column> {
+        let mut columns = Vec::new();
+        collect_columns(&self.segments, &mut columns);
+        columns
+    }
+
+    /// Renders the receiver with `rows`, each the name of an input or a part of one and its counts, and
+    /// `total`, the sum of the inputs alone.
+    pub fn render(
+        &self,
+        rows: &[(String, Counts)],
+        total: &Counts,
+        precision: Option<usize>,
+    ) -> String {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Rows(body) => {
+                    for (name, counts) in rows {
+                        render_segments(body, (name, counts), total, precision, &mut output);
+                    }
+                }
+                _ => render_segments(
+                    std::slice::from_ref(segment),
+                    ("total", total),
+                    total,
+                    precision,
+                    &mut output,
+                ),
+            }
+        }
+        output
+    }
+}
+
+/// Appends the columns `segments` refer to to `columns`.
+fn collect_columns(segments: &[Segment], columns: &mut Vec<Column>) {
+    for segment in segments {
+        match segment {
+            Segment::Text(_) => {}
+            Segment::Value { column, .. } => columns.push(*column),
+            Segment::Rows(body) => collect_columns(body, columns),
+        }
+    }
+}
+
+/// Appends `segments` rendered for `row`, a name and its counts, and the totals `total` to `output`, with
+/// derived values to `precision` decimal places.
+fn render_segments(
+    segments: &[Segment],
+    row: (&str, &Counts),
+    total: &Counts,
+    precision: Option<usize>,
+    output: &mut String,
+) {
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Value {
+                column,
+                total: true,
+            } => output.push_str(&column.value_with_precision(total, total, "total", precision)),
+            Segment::Value {
+                column,
+                total: false,
+            } => output.push_str(&column.value_with_precision(row.1, total, row.0, precision)),
+            Segment::Rows(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        let template = Template::parse(
+            "| File | Words |\n{{#each rows}}| {{file}} | {{ words }} |\n{{/each}}**{{total.words}}** words\n",
+        )
+        .unwrap();
+        let rows = vec![
+            (
+                "a.md".to_string(),
+                Counts {
+                    words: 3,
+                    ..Counts::default()
+                },
+            ),
+            (
+                "b.md".to_string(),
+                Counts {
+                    words: 4,
+                    ..Counts::default()
+                },
+            ),
+        ];
+
+        let total = Counts {
+            words: 7,
+            ..Counts::default()
+        };
+        assert_eq!(
+            template.render(&rows, &total, None),
+            "| File | Words |\n| a.md | 3 |\n| b.md | 4 |\n**7** words\n"
+        );
+
+        // The rows of the parts of an input are not counted again in the total.
+        let parts = vec![
+            (
+                "a.zip".to_string(),
+                Counts {
+                    words: 3,
+                    ..Counts::default()
+                },
+            ),
+            (
+                "a.zip!a.md".to_string(),
+                Counts {
+                    words: 3,
+                    ..Counts::default()
+                },
+            ),
+        ];
+        assert_eq!(
+            template.render(&parts, &parts[0].1, None),
+            "| File | Words |\n| a.zip | 3 |\n| a.zip!a.md | 3 |\n**3** words\n"
+        );
+        assert_eq!(
+            template.columns(),
+            vec![Column::File, Column::Words, Column::Words]
+        );
+        assert!(Template::parse("{{words}}").is_err());
+        assert!(Template::parse("{{#each rows}}{{pages}}{{/each}}").is_err());
+        assert!(Template::parse("{{#each rows}}").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::{self, Read};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use wc_rs::i18n::Message;
+
+/// Parses `size`, a positive number of bytes with an optional binary `K`, `M` or `G` suffix such as `20M`.
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (digits, multiplier) = match size.char_indices().last() {
+        Some((index, 'K')) | Some((index, 'k')) => (&size[..index], 1 << 10),
+        Some((index, 'M')) | Some((index, 'm')) => (&size[..index], 1 << 20),
+        Some((index, 'G')) | Some((index, 'g')) => (&size[..index], 1 << 30),
+        _ => (size, 1),
+    };
+    match digits.trim().parse::<u64>() {
+        Ok(count) if count > 0 => count.checked_mul(multiplier),
+        _ => None,
+    }
+}
+
+/// Parses `rate`, a number of bytes per second written as a size, such as `20M`.
+pub fn parse_rate(rate: &str) -> Result<u64, Message> {
+    parse_size(rate).ok_or_else(|| Message::new("malformed-rate").arg("value", rate))
+}
+
+/// A reader which reads from another no faster than a given number of bytes per second.
+pub struct Throttle<R> {
+    /// The reader being throttled.
+    inner: R,
+    /// The greatest average rate of reading, in bytes per second, or `None` if reading is unthrottled.
+    rate: Option<u64>,
+    /// When the first read began.
+    start: Option<Instant>,
+    /// The number of bytes read so far.
+    read: u64,
+}
+
+impl<R: Read> Throttle<R> {
+    /// Returns a reader of `inner` limited to `rate` bytes per second, if given.
+    pub fn new(inner: R, rate: Option<u64>) -> Self {
+        Throttle {
+            inner,
+            rate,
+            start: None,
+            read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for Throttle<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let rate = match self.rate {
+            Some(rate) => rate,
+            None => return self.inner.read(buf),
+        };
+
+        // Wait until the bytes read so far are within the rate, then read no more than a tenth of a second's
+        // worth so that reads are spread out rather than made in bursts of a whole buffer.
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let due = Duration::from_secs_f64(self.read as f64 / rate as f64);
+        if let Some(wait) = due.checked_sub(start.elapsed()) {
+            thread::sleep(wait);
+        }
+        let limit = buf.len().min((rate / 10).max(1) as usize);
+        let length = self.inner.read(&mut buf[..limit])?;
+        self.read += length as u64;
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle() {
+        assert_eq!(parse_rate("512"), Ok(512));
+        assert_eq!(parse_rate("20M"), Ok(20 << 20));
+        assert_eq!(parse_rate("1k"), Ok(1024));
+        assert!(parse_rate("0").is_err());
+        assert!(parse_rate("fast").is_err());
+        assert_eq!(parse_size(" 4M"), Some(4 << 20));
+        assert_eq!(parse_size("99999999999G"), None);
+
+        let input = vec![b'x'; 300];
+        let start = Instant::now();
+        let mut output = Vec::new();
+        Throttle::new(&input[..], Some(1000))
+            .read_to_end(&mut output)
+            .unwrap();
+
+        assert_eq!(output, input);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+}
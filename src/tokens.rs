@@ -0,0 +1,341 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::ops::AddAssign;
+
+use rayon::prelude::*;
+
+/// Number of leading bytes of a token retained for classification.
+const MAX_TOKEN_LEN: usize = 4096;
+
+/// Size of the chunks a buffer is split into for parallel scanning.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// URL prefixes recognized at the start of a token, in lower case.
+const URL_PREFIXES: [&[u8]; 4] = [b"http://", b"https://", b"ftp://", b"www."];
+
+/// The class of a whitespace-delimited token.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+enum TokenClass {
+    Url,
+    Email,
+    Number,
+    Hashtag,
+    Mention,
+}
+
+/// The number of tokens of each class.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct TokenCounts {
+    pub urls: u64,
+    pub emails: u64,
+    pub numbers: u64,
+    pub hashtags: u64,
+    pub mentions: u64,
+}
+
+impl AddAssign for TokenCounts {
+    fn add_assign(&mut self, rhs: TokenCounts) {
+        self.urls = self.urls.saturating_add(rhs.urls);
+        self.emails = self.emails.saturating_add(rhs.emails);
+        self.numbers = self.numbers.saturating_add(rhs.numbers);
+        self.hashtags = self.hashtags.saturating_add(rhs.hashtags);
+        self.mentions = self.mentions.saturating_add(rhs.mentions);
+    }
+}
+
+impl TokenCounts {
+    /// Counts `token` under its class, if it has one.
+    fn count(&mut self, token: &Token) {
+        match token.class() {
+            Some(TokenClass::Url) => self.urls += 1,
+            Some(TokenClass::Email) => self.emails += 1,
+            Some(TokenClass::Number) => self.numbers += 1,
+            Some(TokenClass::Hashtag) => self.hashtags += 1,
+            Some(TokenClass::Mention) => self.mentions += 1,
+            None => {}
+        }
+    }
+}
+
+/// The leading bytes of a token, possibly spanning several chunks.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Token {
+    /// Up to `MAX_TOKEN_LEN` leading bytes of the token.
+    bytes: Vec<u8>,
+    /// The total length of the token.
+    len: usize,
+}
+
+impl Token {
+    /// Returns a new token consisting of `bytes`.
+    fn new(bytes: &[u8]) -> Self {
+        let mut token = Token::default();
+        token.append(bytes);
+        token
+    }
+
+    /// Appends `bytes` to the end of the token.
+    fn append(&mut self, bytes: &[u8]) {
+        let retained = bytes.len().min(MAX_TOKEN_LEN - self.bytes.len());
+        self.bytes.extend_from_slice(&bytes[..retained]);
+        self.len += bytes.len();
+    }
+
+    /// Returns the token formed by the receiver followed immediately by `rhs`.
+    fn join(mut self, rhs: Token) -> Self {
+        self.append(&rhs.bytes);
+        self.len += rhs.len - rhs.bytes.len();
+        self
+    }
+
+    /// Returns the class of the token, ignoring surrounding punctuation, if it has one.
+    fn class(&self) -> Option<TokenClass> {
+        let body = trim_punctuation(&self.bytes);
+        if URL_PREFIXES.iter().any(|prefix| {
+            body.len() > prefix.len() && body[..prefix.len()].eq_ignore_ascii_case(prefix)
+        }) {
+            return Some(TokenClass::Url);
+        }
+
+        // Tokens too long to have been retained in full can only be classified by their prefix.
+        if self.len > self.bytes.len() {
+            None
+        } else if is_email(body) {
+            Some(TokenClass::Email)
+        } else if is_number(body) {
+            Some(TokenClass::Number)
+        } else if is_tagged(body, b'#') {
+            Some(TokenClass::Hashtag)
+        } else if is_tagged(body, b'@') {
+            Some(TokenClass::Mention)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns `token` with leading opening and trailing closing punctuation removed.
+fn trim_punctuation(token: &[u8]) -> &[u8] {
+    let start = token
+        .iter()
+        .position(|byte| !b"([{\"'<".contains(byte))
+        .unwrap_or(token.len());
+    let end = token
+        .iter()
+        .rposition(|byte| !b".,;:!?)]}\"'>".contains(byte))
+        .map_or(start, |index| index + 1);
+    &token[start..end.max(start)]
+}
+
+/// Returns `true` if `token` has the form `local@domain.tld`.
+fn is_email(token: &[u8]) -> bool {
+    let mut parts = token.split(|&byte| byte == b'@');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(local), Some(domain), None) => {
+            !local.is_empty()
+                && domain.contains(&b'.')
+                && domain
+                    .split(|&byte| byte == b'.')
+                    .all(|label| !label.is_empty())
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if `token` is a decimal number with an optional sign and `.` or `,` separators.
+fn is_number(token: &[u8]) -> bool {
+    let digits = match token.first() {
+        Some(b'+') | Some(b'-') => &token[1..],
+        _ => token,
+    };
+    let starts_and_ends_with_digit = match (digits.first(), digits.last()) {
+        (Some(first), Some(last)) => first.is_ascii_digit() && last.is_ascii_digit(),
+        _ => false,
+    };
+    starts_and_ends_with_digit
+        && digits
+            .split(|&byte| byte == b'.' || byte == b',')
+            .all(|group| !group.is_empty() && group.iter().all(u8::is_ascii_digit))
+}
+
+/// Returns `true` if `token` is `sigil` followed by one or more letters, digits or underscores.
+///
+/// Bytes outside of the ASCII range are considered letters, so tags in any script are recognized.
+fn is_tagged(token: &[u8], sigil: u8) -> bool {
+    match token.split_first() {
+        Some((&first, name)) if first == sigil => {
+            !name.is_empty()
+                && name
+                    .iter()
+                    .all(|&byte| byte.is_ascii_alphanumeric() || byte == b'_' || !byte.is_ascii())
+        }
+        _ => false,
+    }
+}
+
+/// Representation of a chunk of text when classifying tokens.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum TokenFlux {
+    /// The chunk lies entirely within a single token.
+    Within(Token),
+    /// The chunk contains whitespace.
+    Across {
+        /// The token the chunk begins within, empty if it begins with whitespace.
+        head: Token,
+        /// The counts of the tokens lying entirely within the chunk.
+        counts: TokenCounts,
+        /// The token the chunk ends within, empty if it ends with whitespace.
+        tail: Token,
+    },
+}
+
+impl TokenFlux {
+    /// Returns a new TokenFlux over the chunk of text `chunk`.
+    fn from_chunk(chunk: &[u8]) -> Self {
+        let mut tokens = chunk.split(u8::is_ascii_whitespace);
+        let head = Token::new(tokens.next().unwrap_or_default());
+        let tail = match tokens.next_back() {
+            Some(tail) => Token::new(tail),
+            None => return TokenFlux::Within(head),
+        };
+
+        let mut counts = TokenCounts::default();
+        for token in tokens.filter(|token| !token.is_empty()) {
+            counts.count(&Token::new(token));
+        }
+
+        TokenFlux::Across { head, counts, tail }
+    }
+
+    /// Returns a new TokenFlux spanning the receiver on the left, and `rhs` on the right.
+    fn span(self, rhs: TokenFlux) -> Self {
+        match (self, rhs) {
+            (TokenFlux::Within(lhs), TokenFlux::Within(rhs)) => TokenFlux::Within(lhs.join(rhs)),
+            (TokenFlux::Within(lhs), TokenFlux::Across { head, counts, tail }) => {
+                TokenFlux::Across {
+                    head: lhs.join(head),
+                    counts,
+                    tail,
+                }
+            }
+            (TokenFlux::Across { head, counts, tail }, TokenFlux::Within(rhs)) => {
+                TokenFlux::Across {
+                    head,
+                    counts,
+                    tail: tail.join(rhs),
+                }
+            }
+            (
+                TokenFlux::Across { head, counts, tail },
+                TokenFlux::Across {
+                    head: rhs_head,
+                    counts: rhs_counts,
+                    tail: rhs_tail,
+                },
+            ) => {
+                // The tokens either side of the boundary form a single token.
+                let mut counts = counts;
+                counts.count(&tail.join(rhs_head));
+                counts += rhs_counts;
+                TokenFlux::Across {
+                    head,
+                    counts,
+                    tail: rhs_tail,
+                }
+            }
+        }
+    }
+
+    /// Returns the counts of all tokens in the chunk, treating it as the whole input.
+    pub fn counts(&self) -> TokenCounts {
+        let mut counts = TokenCounts::default();
+        match self {
+            TokenFlux::Within(token) => counts.count(token),
+            TokenFlux::Across {
+                head,
+                counts: interior,
+                tail,
+            } => {
+                counts.count(head);
+                counts += *interior;
+                counts.count(tail);
+            }
+        }
+        counts
+    }
+}
+
+/// Takes two optional TokenFlux instances and returns, where possible, the span of the two.
+pub fn span_opt(lhs: Option<TokenFlux>, rhs: Option<TokenFlux>) -> Option<TokenFlux> {
+    match (lhs, rhs) {
+        (Some(left_flux), Some(right_flux)) => Some(left_flux.span(right_flux)),
+        (flux, None) | (None, flux) => flux,
+    }
+}
+
+/// Computes the token flux over the provided input byte string.
+pub fn token_flux_over_byte_string<T>(input: T) -> Option<TokenFlux>
+where
+    T: AsRef<[u8]>,
+{
+    input
+        .as_ref()
+        .par_chunks(CHUNK_SIZE)
+        .map(TokenFlux::from_chunk)
+        .map(Some)
+        .reduce(|| None, span_opt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_classes() {
+        let flux = token_flux_over_byte_string(
+            "see https://example.com, mail (me@example.org) or call 555 -1,024.5 x1 a@b @c.d",
+        );
+
+        assert_eq!(
+            flux.map(|f| f.counts()),
+            Some(TokenCounts {
+                urls: 1,
+                emails: 1,
+                numbers: 2,
+                hashtags: 0,
+                mentions: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_token_flux_span_within_token() {
+        let flux_l = token_flux_over_byte_string("mail me@exa");
+        let flux_m = token_flux_over_byte_string("mple");
+        let flux_r = token_flux_over_byte_string(".org 42");
+
+        assert_eq!(
+            span_opt(span_opt(flux_l, flux_m), flux_r).map(|f| f.counts()),
+            Some(TokenCounts {
+                urls: 0,
+                emails: 1,
+                numbers: 1,
+                hashtags: 0,
+                mentions: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_social_token_classes() {
+        let flux = token_flux_over_byte_string("#rust and #日本 from @martin_m: #, @ and a#b");
+        let counts = flux.map(|f| f.counts()).unwrap_or_default();
+
+        assert_eq!((counts.hashtags, counts.mentions), (2, 1));
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// Returns `true` if `byte` is a UTF-8 continuation byte.
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+/// Returns the length of the UTF-8 sequence introduced by the lead byte `byte`.
+fn sequence_len(byte: u8) -> usize {
+    match byte {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    }
+}
+
+/// Returns the length of the longest prefix of `input` which does not end in an incomplete UTF-8 sequence.
+fn complete_prefix_len(input: &[u8]) -> usize {
+    let tail_start = input.len().saturating_sub(3);
+    for index in (tail_start..input.len()).rev() {
+        if !is_continuation(input[index]) {
+            let complete = index + sequence_len(input[index]) <= input.len();
+            return if complete { input.len() } else { index };
+        }
+    }
+    input.len()
+}
+
+/// Splits `input` into slices of at most `size` bytes, to be counted in parallel, which never split a UTF-8
+/// sequence unless `size` is too small to hold one.
+pub fn split_chunks(input: &[u8], size: usize) -> Vec<&[u8]> {
+    let mut chunks = Vec::with_capacity(input.len() / size + 1);
+    let mut rest = input;
+    while !rest.is_empty() {
+        let chunk = &rest[..size.min(rest.len())];
+        let split = match complete_prefix_len(chunk) {
+            0 => chunk.len(),
+            split => split,
+        };
+        chunks.push(&rest[..split]);
+        rest = &rest[split..];
+    }
+    chunks
+}
+
+/// Reassembles UTF-8 sequences split across the boundaries of consecutive buffers.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Utf8Carry {
+    /// The leading bytes of a sequence which began at the end of the previous buffer.
+    pending: Vec<u8>,
+}
+
+impl Utf8Carry {
+    /// Passes the contents of `buffer` to `f` in slices which never split a UTF-8 sequence.
+    ///
+    /// An incomplete sequence at the end of the buffer is held back and passed along with the
+    /// start of the next buffer.
+    pub fn feed<F>(&mut self, buffer: &[u8], mut f: F)
+    where
+        F: FnMut(&[u8]),
+    {
+        let mut rest = buffer;
+
+        if !self.pending.is_empty() {
+            let needed = sequence_len(self.pending[0]) - self.pending.len();
+            let taken = rest
+                .iter()
+                .take(needed)
+                .take_while(|&&byte| is_continuation(byte))
+                .count();
+            self.pending.extend_from_slice(&rest[..taken]);
+            rest = &rest[taken..];
+
+            // The buffer ran out before the sequence was either completed or found to be invalid.
+            if taken < needed && rest.is_empty() {
+                return;
+            }
+
+            f(&self.pending);
+            self.pending.clear();
+        }
+
+        let split = complete_prefix_len(rest);
+        if split > 0 {
+            f(&rest[..split]);
+        }
+        self.pending.extend_from_slice(&rest[split..]);
+    }
+
+    /// Passes any incomplete sequence held back at the end of the input to `f`.
+    pub fn finish<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&[u8]),
+    {
+        if !self.pending.is_empty() {
+            f(&self.pending);
+            self.pending.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reassemble(buffers: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut carry = Utf8Carry::default();
+        let mut slices = Vec::new();
+        for buffer in buffers {
+            carry.feed(buffer, |slice| slices.push(slice.to_vec()));
+        }
+        carry.finish(|slice| slices.push(slice.to_vec()));
+        slices
+    }
+
+    #[test]
+    fn test_utf8_carry_split_sequence() {
+        let dash = "\u{2014}".as_bytes();
+        assert_eq!(
+            reassemble(&[&[b'a', dash[0]], &[dash[1]], &[dash[2], b'b']]),
+            vec![b"a".to_vec(), dash.to_vec(), b"b".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let text = "a\u{3000}b\u{1F389}".as_bytes();
+        assert_eq!(
+            split_chunks(text, 4),
+            vec![&text[..4], &text[4..5], &text[5..]]
+        );
+        assert_eq!(split_chunks(&text[5..], 2), vec![&text[5..7], &text[7..]]);
+        assert!(split_chunks(b"", 3).is_empty());
+    }
+
+    #[test]
+    fn test_utf8_carry_invalid_sequence() {
+        assert_eq!(
+            reassemble(&[&[b'a', 0xE2], b"b"]),
+            vec![b"a".to_vec(), vec![0xE2], b"b".to_vec()]
+        );
+        assert_eq!(reassemble(&[&[0xE2, 0x80]]), vec![vec![0xE2, 0x80]]);
+    }
+}
@@ -0,0 +1,660 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use wc_rs::i18n::Message;
+
+use crate::glob;
+use crate::ignore::{self, Gitignore};
+
+/// How symbolic links found beneath a directory are treated. Links named by operands are always followed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum Symlinks {
+    /// Links are skipped.
+    Skip,
+    /// Links to files are followed, and links to directories skipped, so that cycles cannot be.
+    #[default]
+    Files,
+    /// Links are followed, other than those to directories already walked, which are reported as errors.
+    Follow,
+}
+
+impl FromStr for Symlinks {
+    type Err = Message;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Symlinks::Skip),
+            "files" => Ok(Symlinks::Files),
+            "follow" => Ok(Symlinks::Follow),
+            _ => Err(Message::new("unknown-symlink-policy").arg("value", s)),
+        }
+    }
+}
+
+/// The types of the files beneath a directory which are walked to. Devices never are, and neither are
+/// FIFOs and sockets by default, as reading them may block or never end.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct FileTypes {
+    /// Whether regular files are walked to.
+    pub regular: bool,
+    /// Whether symbolic links to files of the other types walked to are, as `Symlinks` allows.
+    pub symlink: bool,
+    /// Whether FIFOs are walked to.
+    pub fifo: bool,
+    /// Whether Unix domain sockets are walked to.
+    pub socket: bool,
+}
+
+impl Default for FileTypes {
+    fn default() -> Self {
+        FileTypes {
+            regular: true,
+            symlink: true,
+            fifo: false,
+            socket: false,
+        }
+    }
+}
+
+impl FromStr for FileTypes {
+    type Err = Message;
+
+    /// Parses a comma-separated list of the types `regular`, `symlink`, `fifo` and `socket`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut types = FileTypes {
+            regular: false,
+            symlink: false,
+            fifo: false,
+            socket: false,
+        };
+        for name in s.split(',') {
+            match name {
+                "regular" => types.regular = true,
+                "symlink" => types.symlink = true,
+                "fifo" => types.fifo = true,
+                "socket" => types.socket = true,
+                _ => return Err(Message::new("unknown-file-type").arg("value", name)),
+            }
+        }
+        Ok(types)
+    }
+}
+
+impl FileTypes {
+    /// Returns whether files of `file_type`, other than directories and symbolic links, are walked to.
+    fn includes(&self, file_type: fs::FileType) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                return self.fifo;
+            }
+            if file_type.is_socket() {
+                return self.socket;
+            }
+        }
+        file_type.is_file() && self.regular
+    }
+}
+
+/// Which of the files beneath a directory are walked to, by the wildcard patterns their names match, as
+/// `glob::matches` defines them, and how symbolic links are treated.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Walk {
+    /// The patterns one of which the name of each file must match, or empty to include every file.
+    pub include: Vec<String>,
+    /// The patterns the names of excluded files and directories match. Nothing beneath an excluded
+    /// directory is walked to.
+    pub exclude: Vec<String>,
+    /// How symbolic links are treated.
+    pub symlinks: Symlinks,
+    /// Whether the files and directories ignored by the `.gitignore` files of the repository walked, and
+    /// its `.git` directory, are skipped.
+    pub gitignore: bool,
+    /// Whether hidden files and directories, whose names start with a dot, are walked to when `.gitignore`
+    /// files are respected, as they always are otherwise.
+    pub hidden: bool,
+    /// Whether files of no bytes are skipped.
+    pub skip_empty: bool,
+    /// The time files must have been modified after, if any.
+    pub newer_than: Option<SystemTime>,
+    /// The types of files walked to.
+    pub file_types: FileTypes,
+}
+
+/// Returns the time of `text`, a date such as `2024-01-01` or a date and time such as
+/// `2024-01-01T12:30:00` or `2024-01-01 12:30`, in UTC.
+pub fn parse_time(text: &str) -> Result<SystemTime, Message> {
+    let invalid = || Message::new("malformed-date").arg("value", text);
+    let (date, time) = match text.find(['T', ' ']) {
+        Some(index) => (&text[..index], &text[index + 1..]),
+        None => (text, "00:00:00"),
+    };
+    let fields = |text: &str, separator| -> Option<Vec<u64>> {
+        text.split(separator)
+            .map(|field| {
+                // Signs, which `parse` would accept, are not.
+                if field.bytes().all(|byte| byte.is_ascii_digit()) {
+                    field.parse().ok()
+                } else {
+                    None
+                }
+            })
+            .collect()
+    };
+    let date = fields(date, '-')
+        .filter(|date| date.len() == 3)
+        .ok_or_else(invalid)?;
+    let time = fields(time, ':')
+        .filter(|time| (2..=3).contains(&time.len()))
+        .ok_or_else(invalid)?;
+    let (year, month, day) = (date[0], date[1], date[2]);
+    let (hour, minute, second) = (time[0], time[1], time.get(2).copied().unwrap_or(0));
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(invalid());
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(invalid());
+    }
+
+    // Days since the epoch of the civil date, counted in 400-year eras of the proleptic Gregorian calendar
+    // starting in March, so that leap days end each year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let seconds = ((days * 24 + hour) * 60 + minute) * 60 + second;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Why an empty file is skipped, for them to be told from the others skipped.
+pub const EMPTY: &str = "skipped-empty";
+
+/// The identity of a directory, the device and inode it is at, however many links lead to it.
+#[cfg(unix)]
+type DirectoryId = (u64, u64);
+
+/// The identity of a directory, its canonical path, however many links lead to it.
+#[cfg(not(unix))]
+type DirectoryId = PathBuf;
+
+/// Returns the identity of the directory at `path`.
+#[cfg(unix)]
+fn directory_id(path: &Path) -> io::Result<DirectoryId> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+/// Returns the identity of the directory at `path`.
+#[cfg(not(unix))]
+fn directory_id(path: &Path) -> io::Result<DirectoryId> {
+    fs::canonicalize(path)
+}
+
+/// The state of a walk beneath a directory.
+struct State {
+    /// The directory the walk began at.
+    root: PathBuf,
+    /// The path each directory walked was first walked at, by its identity, when links to directories are
+    /// followed.
+    visited: HashMap<DirectoryId, PathBuf>,
+    /// The ignore rules applying to the directory being walked, from the least to the most specific.
+    ignores: Vec<Gitignore>,
+    /// The files and directories passed over, and why.
+    skipped: Vec<(PathBuf, &'static str)>,
+}
+
+impl Walk {
+    /// Appends the paths of the files at or beneath `path` to `files`, in name order within each directory,
+    /// and the paths that cannot be read and why to `errors`. Only regular files are walked to beneath a
+    /// directory, while a `path` which is not a directory is a file of its own, whatever its name or type.
+    pub fn walk(
+        &self,
+        path: &Path,
+        files: &mut Vec<PathBuf>,
+        errors: &mut Vec<(PathBuf, io::Error)>,
+    ) {
+        self.walk_noting_skipped(path, files, errors, &mut Vec::new());
+    }
+
+    /// Returns whether the file of `metadata` was modified after the time files must be newer than, if any.
+    /// Files whose times are unknown are taken to be.
+    fn is_newer(&self, metadata: &fs::Metadata) -> bool {
+        match (self.newer_than, metadata.modified()) {
+            (Some(newer_than), Ok(modified)) => modified > newer_than,
+            _ => true,
+        }
+    }
+
+    /// Walks `path` as `walk` does, also appending the paths of the files and directories passed over
+    /// beneath it, and why, to `skipped`. Why each is passed over is the identifier of the message giving
+    /// the reason.
+    pub fn walk_noting_skipped(
+        &self,
+        path: &Path,
+        files: &mut Vec<PathBuf>,
+        errors: &mut Vec<(PathBuf, io::Error)>,
+        skipped: &mut Vec<(PathBuf, &'static str)>,
+    ) {
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => {
+                let mut state = State {
+                    root: path.to_path_buf(),
+                    visited: HashMap::new(),
+                    ignores: if self.gitignore {
+                        ignore::ancestors(path)
+                    } else {
+                        Vec::new()
+                    },
+                    skipped: Vec::new(),
+                };
+                self.walk_directory(path, &mut state, files, errors);
+                skipped.append(&mut state.skipped);
+            }
+            Ok(_) => files.push(path.to_path_buf()),
+            Err(error) => errors.push((path.to_path_buf(), error)),
+        }
+    }
+
+    /// Walks the entries of the directory at `path`, unless it has already been, as a link back to one of
+    /// its ancestors or a second link to it leads there, which is reported to `errors` instead.
+    fn walk_directory(
+        &self,
+        path: &Path,
+        state: &mut State,
+        files: &mut Vec<PathBuf>,
+        errors: &mut Vec<(PathBuf, io::Error)>,
+    ) {
+        if self.symlinks == Symlinks::Follow {
+            match directory_id(path).map(|id| state.visited.entry(id)) {
+                Ok(Entry::Occupied(walked)) => {
+                    let error = io::Error::other(
+                        Message::new("directory-already-walked")
+                            .arg("path", walked.get().display()),
+                    );
+                    return errors.push((path.to_path_buf(), error));
+                }
+                Ok(Entry::Vacant(entry)) => {
+                    entry.insert(path.to_path_buf());
+                }
+                Err(error) => return errors.push((path.to_path_buf(), error)),
+            }
+        }
+
+        let entries = fs::read_dir(path).and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()
+        });
+        let mut entries = match entries {
+            Ok(entries) => entries,
+            Err(error) => return errors.push((path.to_path_buf(), error)),
+        };
+        entries.sort();
+
+        // The rules of the directory's own `.gitignore` apply beneath it until it has been walked.
+        let base = path.strip_prefix(&state.root).unwrap_or(path).to_path_buf();
+        let gitignore = if self.gitignore {
+            Gitignore::read(&path.join(".gitignore"), base, PathBuf::new())
+        } else {
+            None
+        };
+        let pushed = gitignore.is_some();
+        state.ignores.extend(gitignore);
+
+        for entry in entries {
+            let name = entry
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if self
+                .exclude
+                .iter()
+                .any(|pattern| glob::matches(pattern, &name))
+            {
+                state.skipped.push((entry, "skipped-excluded"));
+                continue;
+            }
+
+            let metadata = match fs::symlink_metadata(&entry) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    if self.symlinks == Symlinks::Skip {
+                        state.skipped.push((entry, "skipped-symlink"));
+                        continue;
+                    }
+                    match fs::metadata(&entry) {
+                        Ok(metadata) if metadata.is_dir() && self.symlinks == Symlinks::Files => {
+                            state.skipped.push((entry, "skipped-symlink-to-directory"));
+                            continue;
+                        }
+                        Ok(metadata) if !metadata.is_dir() && !self.file_types.symlink => {
+                            state.skipped.push((entry, "skipped-symlink"));
+                            continue;
+                        }
+                        Ok(metadata) => metadata,
+                        Err(error) => {
+                            errors.push((entry, error));
+                            continue;
+                        }
+                    }
+                }
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    errors.push((entry, error));
+                    continue;
+                }
+            };
+
+            if self.gitignore && name.starts_with('.') && !self.hidden {
+                state.skipped.push((entry, "skipped-hidden"));
+                continue;
+            }
+            if self.gitignore
+                && (name == ".git"
+                    || ignore::is_ignored(
+                        &state.ignores,
+                        entry.strip_prefix(&state.root).unwrap_or(&entry),
+                        metadata.is_dir(),
+                    ))
+            {
+                state.skipped.push((entry, "skipped-gitignored"));
+                continue;
+            }
+
+            if metadata.is_dir() {
+                self.walk_directory(&entry, state, files, errors);
+            } else if !self.file_types.includes(metadata.file_type()) {
+                state.skipped.push((entry, "skipped-file-type"));
+            } else if self.include.is_empty()
+                || self
+                    .include
+                    .iter()
+                    .any(|pattern| glob::matches(pattern, &name))
+            {
+                if self.skip_empty && metadata.len() == 0 {
+                    state.skipped.push((entry, EMPTY));
+                } else if !self.is_newer(&metadata) {
+                    state.skipped.push((entry, "skipped-not-newer"));
+                } else {
+                    files.push(entry);
+                }
+            } else {
+                state.skipped.push((entry, "skipped-not-included"));
+            }
+        }
+
+        if pushed {
+            state.ignores.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the paths of the files beneath `root` walked to by `walk`, relative to the root.
+    fn walked(walk: &Walk, root: &Path) -> Vec<String> {
+        let (mut files, mut errors) = (Vec::new(), Vec::new());
+        walk.walk(root, &mut files, &mut errors);
+        assert!(errors.is_empty());
+        files
+            .iter()
+            .map(|file| {
+                let relative = file.strip_prefix(root).unwrap();
+                relative.to_string_lossy().replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_walk_hidden() {
+        let root = std::env::temp_dir().join(format!("wc_rs-walk-hidden-{}", std::process::id()));
+        fs::create_dir_all(root.join(".config")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        for file in [".config/settings", ".git/HEAD", ".gitignore", "README.md"] {
+            fs::write(root.join(file), "contents\n").unwrap();
+        }
+
+        let gitignore = Walk {
+            gitignore: true,
+            ..Walk::default()
+        };
+        let (mut files, mut skipped) = (Vec::new(), Vec::new());
+        gitignore.walk_noting_skipped(&root, &mut files, &mut Vec::new(), &mut skipped);
+        let (visible, hidden, all) = (
+            walked(&gitignore, &root),
+            walked(
+                &Walk {
+                    hidden: true,
+                    ..gitignore.clone()
+                },
+                &root,
+            ),
+            walked(&Walk::default(), &root),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(visible, vec!["README.md"]);
+        assert_eq!(
+            skipped,
+            vec![
+                (root.join(".config"), "skipped-hidden"),
+                (root.join(".git"), "skipped-hidden"),
+                (root.join(".gitignore"), "skipped-hidden"),
+            ]
+        );
+        assert_eq!(hidden, vec![".config/settings", ".gitignore", "README.md"]);
+        assert_eq!(
+            all,
+            vec![".config/settings", ".git/HEAD", ".gitignore", "README.md"]
+        );
+    }
+
+    #[test]
+    fn test_walk_skip_empty() {
+        let root = std::env::temp_dir().join(format!("wc_rs-walk-empty-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("empty.rs"), "").unwrap();
+        fs::write(root.join("empty.md"), "").unwrap();
+        fs::write(root.join("lib.rs"), "contents\n").unwrap();
+
+        let walk = Walk {
+            include: vec!["*.rs".to_string()],
+            skip_empty: true,
+            ..Walk::default()
+        };
+        let (mut files, mut skipped) = (Vec::new(), Vec::new());
+        walk.walk_noting_skipped(&root, &mut files, &mut Vec::new(), &mut skipped);
+        let all = walked(&Walk::default(), &root);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(files, vec![root.join("lib.rs")]);
+        assert_eq!(
+            skipped,
+            vec![
+                (root.join("empty.md"), "skipped-not-included"),
+                (root.join("empty.rs"), EMPTY),
+            ]
+        );
+        assert_eq!(all, vec!["empty.md", "empty.rs", "lib.rs"]);
+    }
+
+    #[test]
+    fn test_walk_newer_than() {
+        let root = std::env::temp_dir().join(format!("wc_rs-walk-newer-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        for (file, time) in [
+            ("old.md", "2023-12-31T23:59:59"),
+            ("new.md", "2024-01-01 00:00:01"),
+        ] {
+            let file = fs::File::create(root.join(file)).unwrap();
+            file.set_modified(parse_time(time).unwrap()).unwrap();
+        }
+
+        let walk = Walk {
+            newer_than: Some(parse_time("2024-01-01").unwrap()),
+            ..Walk::default()
+        };
+        let (mut files, mut skipped) = (Vec::new(), Vec::new());
+        walk.walk_noting_skipped(&root, &mut files, &mut Vec::new(), &mut skipped);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(files, vec![root.join("new.md")]);
+        assert_eq!(skipped, vec![(root.join("old.md"), "skipped-not-newer")]);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let seconds = |text| {
+            parse_time(text).map(|time| {
+                time.duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+            })
+        };
+        assert_eq!(seconds("1970-01-01"), Ok(0));
+        assert_eq!(seconds("2000-03-01"), Ok(951_868_800));
+        assert_eq!(seconds("2024-01-01"), Ok(1_704_067_200));
+        assert_eq!(seconds("2024-02-29T12:30"), Ok(1_709_209_800));
+        assert_eq!(seconds("2024-02-29 12:30:15"), Ok(1_709_209_815));
+        for invalid in [
+            "2024",
+            "2024-13-01",
+            "2024-01-01T25:00",
+            "1969-12-31",
+            "2024-01-0x",
+            "",
+        ] {
+            assert!(parse_time(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let root = std::env::temp_dir().join(format!("wc_rs-walk-{}", std::process::id()));
+        for directory in ["src/bin", "target/debug"] {
+            fs::create_dir_all(root.join(directory)).unwrap();
+        }
+        for file in [
+            "README.md",
+            "src/lib.rs",
+            "src/bin/main.rs",
+            "target/debug/out.rs",
+        ] {
+            fs::write(root.join(file), "contents\n").unwrap();
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("src"), root.join("src/bin/up")).unwrap();
+            std::os::unix::fs::symlink(root.join("README.md"), root.join("link.md")).unwrap();
+            // Neither sockets nor devices, even when linked to, are files to count.
+            std::os::unix::net::UnixListener::bind(root.join("socket")).unwrap();
+            std::os::unix::fs::symlink("/dev/null", root.join("null")).unwrap();
+        }
+
+        let rust = Walk {
+            include: vec!["*.rs".to_string()],
+            exclude: vec!["target".to_string()],
+            ..Walk::default()
+        };
+        let rust_files = walked(&rust, &root);
+        #[cfg(unix)]
+        let (all, skipped, (mut followed, mut loops)) = (
+            walked(&Walk::default(), &root),
+            walked(
+                &Walk {
+                    symlinks: Symlinks::Skip,
+                    ..Walk::default()
+                },
+                &root,
+            ),
+            (Vec::new(), Vec::new()),
+        );
+        #[cfg(unix)]
+        Walk {
+            symlinks: Symlinks::Follow,
+            ..rust.clone()
+        }
+        .walk(&root, &mut followed, &mut loops);
+        #[cfg(unix)]
+        let (sockets, links) = (
+            walked(
+                &Walk {
+                    file_types: "socket".parse().unwrap(),
+                    ..Walk::default()
+                },
+                &root,
+            ),
+            walked(
+                &Walk {
+                    file_types: "regular".parse().unwrap(),
+                    ..Walk::default()
+                },
+                &root,
+            ),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(rust_files, vec!["src/bin/main.rs", "src/lib.rs"]);
+        #[cfg(unix)]
+        {
+            assert_eq!(
+                all,
+                vec![
+                    "README.md",
+                    "link.md",
+                    "src/bin/main.rs",
+                    "src/lib.rs",
+                    "target/debug/out.rs"
+                ]
+            );
+            assert_eq!(
+                skipped,
+                vec![
+                    "README.md",
+                    "src/bin/main.rs",
+                    "src/lib.rs",
+                    "target/debug/out.rs"
+                ]
+            );
+            assert_eq!(sockets, vec!["socket"]);
+            assert_eq!(links, skipped);
+            // The link back up to `src` is followed, but `src` is not walked twice.
+            assert_eq!(
+                followed,
+                vec![root.join("src/bin/main.rs"), root.join("src/lib.rs")]
+            );
+            let loops: Vec<(PathBuf, String)> = loops
+                .into_iter()
+                .map(|(path, error)| (path, error.to_string()))
+                .collect();
+            assert_eq!(
+                loops,
+                vec![(
+                    root.join("src/bin/up"),
+                    format!("Directory already walked as {}", root.join("src").display())
+                )]
+            );
+        }
+        assert_eq!("follow".parse(), Ok(Symlinks::Follow));
+        assert_eq!("regular,symlink".parse(), Ok(FileTypes::default()));
+        assert!("device".parse::<FileTypes>().is_err());
+        assert!("always".parse::<Symlinks>().is_err());
+    }
+}
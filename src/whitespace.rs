@@ -0,0 +1,71 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::ops::AddAssign;
+
+/// The whitespace characters of a text, by kind.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct WhitespaceCounts {
+    /// The number of spaces, U+0020.
+    pub spaces: u64,
+    /// The number of horizontal tabs.
+    pub tabs: u64,
+    /// The number of line feeds.
+    pub newlines: u64,
+    /// The number of no-break spaces, U+00A0, as pasted from word processors.
+    pub nbsp: u64,
+    /// The number of other whitespace characters, such as carriage returns and the Unicode spaces.
+    pub other: u64,
+}
+
+impl WhitespaceCounts {
+    /// Counts the whitespace characters of `text`.
+    pub fn count(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                ' ' => self.spaces += 1,
+                '\t' => self.tabs += 1,
+                '\n' => self.newlines += 1,
+                '\u{A0}' => self.nbsp += 1,
+                _ if c.is_whitespace() => self.other += 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+impl AddAssign for WhitespaceCounts {
+    fn add_assign(&mut self, rhs: WhitespaceCounts) {
+        self.spaces = self.spaces.saturating_add(rhs.spaces);
+        self.tabs = self.tabs.saturating_add(rhs.tabs);
+        self.newlines = self.newlines.saturating_add(rhs.newlines);
+        self.nbsp = self.nbsp.saturating_add(rhs.nbsp);
+        self.other = self.other.saturating_add(rhs.other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count() {
+        let mut counts = WhitespaceCounts::default();
+        counts.count("a b\tc\r\n\u{A0}d\u{3000}e\n");
+
+        assert_eq!(
+            counts,
+            WhitespaceCounts {
+                spaces: 1,
+                tabs: 1,
+                newlines: 2,
+                nbsp: 1,
+                other: 2,
+            }
+        );
+    }
+}
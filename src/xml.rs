@@ -0,0 +1,215 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// Returns the text of the character or entity reference `name`, which excludes its `&` and `;`.
+fn entity(name: &[u8]) -> Option<char> {
+    match name {
+        b"amp" => Some('&'),
+        b"lt" => Some('<'),
+        b"gt" => Some('>'),
+        b"quot" => Some('"'),
+        b"apos" => Some('\''),
+        b"nbsp" => Some('\u{A0}'),
+        [b'#', b'x', digits @ ..] | [b'#', b'X', digits @ ..] => {
+            let digits = std::str::from_utf8(digits).ok()?;
+            std::char::from_u32(u32::from_str_radix(digits, 16).ok()?)
+        }
+        [b'#', digits @ ..] => std::char::from_u32(std::str::from_utf8(digits).ok()?.parse().ok()?),
+        _ => None,
+    }
+}
+
+/// Returns `text` with its character and entity references replaced by the text they refer to.
+///
+/// Unknown references are left as they are.
+pub fn unescape(text: &[u8]) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.iter().position(|&byte| byte == b'&') {
+        unescaped.extend_from_slice(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest
+            .iter()
+            .take(32)
+            .position(|&byte| byte == b';')
+            .and_then(|end| Some((end, entity(&rest[1..end])?)));
+        match reference {
+            Some((end, c)) => {
+                let mut encoded = [0; 4];
+                unescaped.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push(b'&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.extend_from_slice(rest);
+    unescaped
+}
+
+/// A start tag or empty-element tag.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Tag<'a> {
+    /// The qualified name of the element.
+    pub name: &'a [u8],
+    /// The attributes of the element, unparsed.
+    attributes: &'a [u8],
+    /// Whether the tag is an empty-element tag, which has no matching end tag.
+    pub empty: bool,
+}
+
+impl<'a> Tag<'a> {
+    /// Returns the unescaped value of the attribute with qualified name `name`, if the element has one.
+    pub fn attribute(&self, name: &[u8]) -> Option<Vec<u8>> {
+        let mut rest = self.attributes;
+        loop {
+            rest = &rest[rest.iter().position(|byte| !byte.is_ascii_whitespace())?..];
+            let equals = rest.iter().position(|&byte| byte == b'=')?;
+            let key = rest[..equals].strip_suffix(b" ").unwrap_or(&rest[..equals]);
+            rest = &rest[equals + 1..];
+            rest = &rest[rest.iter().position(|byte| !byte.is_ascii_whitespace())?..];
+            let quote = *rest.first()?;
+            let end = rest[1..].iter().position(|&byte| byte == quote)? + 1;
+            if key == name {
+                return Some(unescape(&rest[1..end]));
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+}
+
+/// An event produced while reading an XML document.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Event<'a> {
+    /// A start tag or empty-element tag.
+    Start(Tag<'a>),
+    /// An end tag, with the qualified name of its element.
+    End(&'a [u8]),
+    /// Character data, unescaped.
+    Text(Vec<u8>),
+}
+
+/// A non-validating reader of the elements and character data of an XML document.
+///
+/// The XML declaration, processing instructions, comments and the document type declaration are skipped.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Reader<'a> {
+    /// The document.
+    input: &'a [u8],
+    /// The offset of the next byte to be read.
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Returns a reader of the document `input`.
+    pub fn new(input: &'a [u8]) -> Self {
+        Reader { input, position: 0 }
+    }
+
+    /// Returns the offset of the first occurrence of `needle` at or after the current position.
+    fn offset_of(&self, needle: &[u8]) -> usize {
+        self.input[self.position..]
+            .windows(needle.len())
+            .position(|window| window == needle)
+            .map_or(self.input.len(), |index| self.position + index)
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        loop {
+            let rest = &self.input[self.position..];
+            if rest.is_empty() {
+                return None;
+            }
+
+            if rest[0] != b'<' {
+                let end = self.offset_of(b"<");
+                let text = &self.input[self.position..end];
+                self.position = end;
+                return Some(Event::Text(unescape(text)));
+            }
+
+            if rest.starts_with(b"<![CDATA[") {
+                let start = self.position + 9;
+                let end = self.offset_of(b"]]>");
+                self.position = (end + 3).min(self.input.len());
+                return Some(Event::Text(self.input[start..end].to_vec()));
+            }
+
+            let terminator: &[u8] = if rest.starts_with(b"<!--") {
+                b"-->"
+            } else if rest.starts_with(b"<?") {
+                b"?>"
+            } else {
+                b">"
+            };
+            let end = self.offset_of(terminator);
+            let markup = &self.input[self.position + 1..end];
+            self.position = (end + terminator.len()).min(self.input.len());
+
+            match markup.first() {
+                Some(b'!') | Some(b'?') | None => continue,
+                Some(b'/') => return Some(Event::End(markup[1..].trim_ascii_end())),
+                Some(_) => {
+                    let (markup, empty) = match markup.strip_suffix(b"/") {
+                        Some(markup) => (markup, true),
+                        None => (markup, false),
+                    };
+                    let name_len = markup
+                        .iter()
+                        .position(|byte| byte.is_ascii_whitespace())
+                        .unwrap_or(markup.len());
+                    return Some(Event::Start(Tag {
+                        name: &markup[..name_len],
+                        attributes: &markup[name_len..],
+                        empty,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader() {
+        let document = br#"<?xml version="1.0"?><!-- note --><a x = "1 &amp; 2" y='3'>A &lt;b&gt;<b/><![CDATA[<c>]]></a >"#;
+        let events: Vec<Event> = Reader::new(document).collect();
+
+        match &events[0] {
+            Event::Start(tag) => {
+                assert_eq!(tag.name, b"a");
+                assert_eq!(tag.attribute(b"x"), Some(b"1 & 2".to_vec()));
+                assert_eq!(tag.attribute(b"y"), Some(b"3".to_vec()));
+                assert_eq!(tag.attribute(b"z"), None);
+                assert!(!tag.empty);
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+        assert_eq!(events[1], Event::Text(b"A <b>".to_vec()));
+        assert!(matches!(&events[2], Event::Start(tag) if tag.name == b"b" && tag.empty));
+        assert_eq!(events[3], Event::Text(b"<c>".to_vec()));
+        assert_eq!(events[4], Event::End(b"a"));
+        assert_eq!(events.len(), 5);
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(
+            unescape(b"&#65;&#x42;&unknown; & &amp;"),
+            b"AB&unknown; & &"
+        );
+    }
+}
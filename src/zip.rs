@@ -0,0 +1,202 @@
+// Copyright 2019 wc_rs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::i18n::Message;
+use crate::inflate;
+
+/// Signature of the end of central directory record.
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4B50;
+
+/// Signature of a central directory file header.
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4B50;
+
+/// Signature of a local file header.
+const LOCAL_HEADER: u32 = 0x0403_4B50;
+
+/// Compression method of entries stored without compression.
+const STORED: u16 = 0;
+
+/// Compression method of entries compressed with DEFLATE.
+const DEFLATED: u16 = 8;
+
+/// Returns the little-endian 16-bit integer at `offset` in `data`.
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Returns the little-endian 32-bit integer at `offset` in `data`.
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// A file within a ZIP archive.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+struct Entry {
+    /// The path of the file within the archive.
+    name: String,
+    /// The compression method of the file.
+    method: u16,
+    /// The size of the file's compressed data.
+    compressed_size: usize,
+    /// The offset of the file's local header.
+    offset: usize,
+}
+
+/// A ZIP archive held in memory, as used by word processor documents and e-books.
+///
+/// Only stored and DEFLATE-compressed entries are supported, and neither ZIP64 nor encryption is.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Archive<'a> {
+    /// The archive.
+    data: &'a [u8],
+    /// The files of the archive, in central directory order.
+    entries: Vec<Entry>,
+}
+
+impl<'a> Archive<'a> {
+    /// Parses the central directory of the archive `data`.
+    pub fn parse(data: &'a [u8]) -> Result<Self, Message> {
+        let invalid = || Message::new("invalid-zip-archive");
+
+        // The end of central directory record is followed by a comment of at most 64 KiB.
+        let search_start = data.len().saturating_sub(22 + 0xFFFF);
+        let end = (search_start..data.len().saturating_sub(21))
+            .rev()
+            .find(|&offset| u32_at(data, offset) == Some(END_OF_CENTRAL_DIRECTORY))
+            .ok_or_else(invalid)?;
+        let count = u16_at(data, end + 10).ok_or_else(invalid)?;
+        let mut offset = u32_at(data, end + 16).ok_or_else(invalid)? as usize;
+
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            if u32_at(data, offset) != Some(CENTRAL_DIRECTORY_HEADER) {
+                return Err(invalid());
+            }
+            let field = |position| u16_at(data, offset + position).ok_or_else(invalid);
+            let (name_len, extra_len, comment_len) = (field(28)?, field(30)?, field(32)?);
+            let name_start = offset + 46;
+            let name = data
+                .get(name_start..name_start + name_len as usize)
+                .ok_or_else(invalid)?;
+            entries.push(Entry {
+                name: String::from_utf8_lossy(name).into_owned(),
+                method: field(10)?,
+                compressed_size: u32_at(data, offset + 20).ok_or_else(invalid)? as usize,
+                offset: u32_at(data, offset + 42).ok_or_else(invalid)? as usize,
+            });
+            offset = name_start + name_len as usize + extra_len as usize + comment_len as usize;
+        }
+
+        Ok(Archive { data, entries })
+    }
+
+    /// Returns the paths of the files and directories of the archive, in central directory order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    /// Returns the decompressed contents of the file at `name`.
+    pub fn read(&self, name: &str) -> Result<Vec<u8>, Message> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| Message::new("zip-no-entry").arg("name", name))?;
+
+        // The local header repeats the name, and may have a different extra field.
+        let invalid = || Message::new("invalid-zip-entry").arg("name", name);
+        if u32_at(self.data, entry.offset) != Some(LOCAL_HEADER) {
+            return Err(invalid());
+        }
+        let name_len = u16_at(self.data, entry.offset + 26).ok_or_else(invalid)?;
+        let extra_len = u16_at(self.data, entry.offset + 28).ok_or_else(invalid)?;
+        let start = entry.offset + 30 + name_len as usize + extra_len as usize;
+        let compressed = self
+            .data
+            .get(start..start + entry.compressed_size)
+            .ok_or_else(invalid)?;
+
+        match entry.method {
+            STORED => Ok(compressed.to_vec()),
+            DEFLATED => inflate::inflate(compressed).map_err(|error| {
+                Message::new("invalid-compressed-zip-entry")
+                    .arg("name", name)
+                    .arg("error", error)
+            }),
+            method => Err(Message::new("unsupported-zip-method")
+                .arg("method", method)
+                .arg("name", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an archive of `files`, each with its name, compression method and compressed data.
+    fn archive(files: &[(&str, u16, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        for (name, method, compressed) in files {
+            let offset = data.len() as u32;
+            data.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+            data.extend_from_slice(&[20, 0, 0, 0]);
+            data.extend_from_slice(&method.to_le_bytes());
+            data.extend_from_slice(&[0; 16]);
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(compressed);
+
+            directory.extend_from_slice(&CENTRAL_DIRECTORY_HEADER.to_le_bytes());
+            directory.extend_from_slice(&[20, 0, 20, 0, 0, 0]);
+            directory.extend_from_slice(&method.to_le_bytes());
+            directory.extend_from_slice(&[0; 8]);
+            directory.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&[0; 4]);
+            directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+
+        let directory_offset = data.len() as u32;
+        data.extend_from_slice(&directory);
+        data.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&directory_offset.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_archive() {
+        let data = archive(&[
+            ("mimetype", STORED, b"hello"),
+            (
+                "dir/text.txt",
+                DEFLATED,
+                b"\x4B\x4C\x4A\x56\x48\x44\xC5\x00",
+            ),
+        ]);
+        let archive = Archive::parse(&data).unwrap();
+
+        assert_eq!(archive.read("mimetype").unwrap(), b"hello");
+        assert_eq!(
+            archive.read("dir/text.txt").unwrap(),
+            b"abc abc abc abc abc"
+        );
+        assert!(archive.read("missing").is_err());
+        assert!(Archive::parse(b"not an archive").is_err());
+    }
+}